@@ -7,6 +7,7 @@ use std::{
     path::{Path, PathBuf},
 };
 
+pub use petr_effects::check_effects;
 pub use petr_fmt::{format_sources, Formattable, FormatterConfig, FormatterContext};
 pub use petr_ir::{Lowerer, LoweringError};
 pub use petr_parse::Parser;
@@ -14,7 +15,7 @@ pub use petr_parse::Parser;
 pub use petr_pkg::{manifest::find_manifest, BuildPlan};
 pub use petr_resolve::{resolve_symbols, Dependency};
 pub use petr_typecheck::type_check;
-pub use petr_utils::{render_error, Identifier, IndexMap, SourceId, SpannedItem};
+pub use petr_utils::{render_error, Diagnostics, Identifier, IndexMap, LintLevel, ReportedDiagnostic, Severity, SourceId, SpannedItem};
 pub use petr_vm::Vm;
 #[cfg(not(feature = "no_std"))]
 use termcolor::{ColorChoice, ColorSpec, StandardStream, WriteColor};
@@ -1,6 +1,6 @@
 use std::rc::Rc;
 
-use petr_utils::{Identifier, Path, SpannedItem};
+use petr_utils::{Identifier, IntegerType, Path, SpannedItem};
 
 use crate::comments::Commented;
 
@@ -22,11 +22,24 @@ impl std::fmt::Debug for Ast {
                 match node.item() {
                     AstNode::FunctionDeclaration(fun) => writeln!(f, "  Function: {}", fun.item().name.id)?,
                     AstNode::TypeDeclaration(ty) => writeln!(f, "  Type: {}", ty.item().name.id)?,
+                    AstNode::TypeAlias(alias) => writeln!(f, "  Type alias: {}", alias.item().name.id)?,
                     AstNode::ImportStatement(i) => writeln!(
                         f,
                         "  Import: {}",
                         i.item().path.iter().map(|x| format!("{}", x.id)).collect::<Vec<_>>().join(".")
                     )?,
+                    AstNode::InterfaceDeclaration(interface) => writeln!(f, "  Interface: {}", interface.item().name.id)?,
+                    AstNode::ImplementationDeclaration(implementation) => writeln!(
+                        f,
+                        "  Implementation: {}",
+                        implementation
+                            .item()
+                            .interface
+                            .iter()
+                            .map(|x| format!("{}", x.id))
+                            .collect::<Vec<_>>()
+                            .join(".")
+                    )?,
                 }
             }
         }
@@ -62,7 +75,10 @@ impl Ast {
 pub enum AstNode {
     FunctionDeclaration(Commented<FunctionDeclaration>),
     TypeDeclaration(Commented<TypeDeclaration>),
+    TypeAlias(Commented<TypeAlias>),
     ImportStatement(Commented<ImportStatement>),
+    InterfaceDeclaration(Commented<InterfaceDeclaration>),
+    ImplementationDeclaration(Commented<ImplementationDeclaration>),
 }
 
 pub struct ImportStatement {
@@ -78,17 +94,66 @@ impl ImportStatement {
 
 #[derive(Clone)]
 pub struct TypeDeclaration {
+    pub name:        Identifier,
+    /// The names of the type's generic parameters, like `'A` in `type Option 'A = ...`
+    pub type_params: Box<[Identifier]>,
+    pub variants:    Box<[SpannedItem<TypeVariantOrLiteral>]>,
+    pub visibility:  Visibility,
+}
+
+impl TypeDeclaration {
+    pub fn is_exported(&self) -> bool {
+        self.visibility == Visibility::Exported
+    }
+}
+
+/// Another name for an existing type, like `type alias Id = 'int`. Unlike a [`TypeDeclaration`],
+/// an alias doesn't introduce a new type, so its values don't need to be constructed.
+#[derive(Clone)]
+pub struct TypeAlias {
     pub name:       Identifier,
-    pub variants:   Box<[SpannedItem<TypeVariantOrLiteral>]>,
+    pub ty:         Ty,
     pub visibility: Visibility,
 }
 
-impl TypeDeclaration {
+impl TypeAlias {
     pub fn is_exported(&self) -> bool {
         self.visibility == Visibility::Exported
     }
 }
 
+/// A set of methods which types can implement, like `interface Show 'A with show(x in 'A) returns 'string`
+#[derive(Clone)]
+pub struct InterfaceDeclaration {
+    pub name:       Identifier,
+    /// The type implementing the interface, which the signatures of its methods refer to
+    pub type_param: Identifier,
+    pub methods:    Box<[InterfaceMethod]>,
+    pub visibility: Visibility,
+}
+
+impl InterfaceDeclaration {
+    pub fn is_exported(&self) -> bool {
+        self.visibility == Visibility::Exported
+    }
+}
+
+#[derive(Clone)]
+pub struct InterfaceMethod {
+    pub name:        Identifier,
+    pub parameters:  Box<[FunctionParameter]>,
+    pub return_type: Ty,
+}
+
+/// An implementation of an interface for a type, which names the function implementing each of
+/// the interface's methods, like `implement Show for 'int with show = show_int`
+#[derive(Clone)]
+pub struct ImplementationDeclaration {
+    pub interface: Path,
+    pub ty:        Ty,
+    pub methods:   Box<[(Identifier, Path)]>,
+}
+
 #[derive(Clone)]
 pub enum TypeVariantOrLiteral {
     Variant(TypeVariant),
@@ -127,9 +192,22 @@ pub struct FunctionDeclaration {
     pub name:        Identifier,
     pub parameters:  Box<[FunctionParameter]>,
     pub return_type: Ty,
+    /// The interfaces that the function's type parameters must implement, like `'A: Show` in
+    /// `where 'A: Show`
+    pub constraints: Box<[TypeConstraint]>,
+    /// The effects the function is allowed to have, like `io` in `uses io`. `pure` is an empty
+    /// list. Functions without an annotation may have any effects.
+    pub effects:     Option<Box<[Identifier]>>,
     pub body:        SpannedItem<Expression>,
     pub visibility:  Visibility,
 }
+
+#[derive(Clone)]
+pub struct TypeConstraint {
+    pub type_param: Identifier,
+    pub interface:  Path,
+}
+
 impl FunctionDeclaration {
     pub fn is_exported(&self) -> bool {
         self.visibility == Visibility::Exported
@@ -141,12 +219,26 @@ pub enum Expression {
     Literal(Literal),
     List(List),
     Operator(Box<OperatorExpression>),
+    UnaryOperator(Box<UnaryOperatorExpression>),
     FunctionCall(FunctionCall),
     Variable(Identifier),
     IntrinsicCall(IntrinsicCall),
     Binding(ExpressionWithBindings),
-    TypeConstructor(petr_utils::TypeId, Box<[SpannedItem<Expression>]>),
+    /// The `usize` is the index of the constructed variant among the type's non-literal variants
+    TypeConstructor(petr_utils::TypeId, usize, Box<[SpannedItem<Expression>]>),
+    /// The body of an interface method, which calls the implementation of the method with the
+    /// index `usize` for the type that the method is called with
+    InterfaceMethod(petr_utils::InterfaceId, usize),
     If(If),
+    Match(Match),
+    Lambda(Lambda),
+    FieldAccess(Box<FieldAccess>),
+    RecordUpdate(Box<RecordUpdate>),
+    /// An anonymous tuple, like `(1, "one")`
+    Tuple(Box<[SpannedItem<Expression>]>),
+    /// A placeholder for an expression that hasn't been written yet, either `_` or a named hole
+    /// like `?name`. The type checker reports the type that is expected in its place.
+    Hole(Option<Identifier>),
 }
 
 #[derive(Clone)]
@@ -161,8 +253,25 @@ pub struct ExprId(pub usize);
 
 #[derive(Clone)]
 pub struct Binding {
-    pub name: Identifier,
-    pub val:  SpannedItem<Expression>,
+    pub pattern: BindingPattern,
+    pub val:     SpannedItem<Expression>,
+}
+
+/// The names that a `let` binding binds
+#[derive(Clone, Debug)]
+pub enum BindingPattern {
+    Name(Identifier),
+    /// Destructures a tuple into its elements, like `let (a, b) = ~pair`
+    Tuple(Box<[Identifier]>),
+}
+
+impl BindingPattern {
+    pub fn names(&self) -> &[Identifier] {
+        match self {
+            BindingPattern::Name(name) => std::slice::from_ref(name),
+            BindingPattern::Tuple(names) => names,
+        }
+    }
 }
 
 #[derive(Clone)]
@@ -172,6 +281,87 @@ pub struct If {
     pub else_branch: Option<Box<SpannedItem<Expression>>>,
 }
 
+#[derive(Clone)]
+pub struct Match {
+    pub scrutinee: Box<SpannedItem<Expression>>,
+    pub arms:      Box<[SpannedItem<MatchArm>]>,
+}
+
+#[derive(Clone)]
+pub struct MatchArm {
+    pub pattern: SpannedItem<Pattern>,
+    pub body:    SpannedItem<Expression>,
+    /// each arm has its own scope, which contains the bindings introduced by its pattern
+    pub expr_id: ExprId,
+}
+
+/// Reading a field of a record, like `point.x`
+#[derive(Clone)]
+pub struct FieldAccess {
+    pub target: SpannedItem<Expression>,
+    pub field:  Identifier,
+}
+
+/// A copy of a record with some of its fields replaced, like `point with x = 1, y = 2`
+#[derive(Clone)]
+pub struct RecordUpdate {
+    pub target: SpannedItem<Expression>,
+    pub fields: Box<[(Identifier, SpannedItem<Expression>)]>,
+}
+
+/// An anonymous function, like `\(x in 'int) => + x y`. Any bindings from the surrounding scope
+/// that are referenced in the body are captured by value when the lambda is evaluated.
+#[derive(Clone)]
+pub struct Lambda {
+    pub parameters: Box<[FunctionParameter]>,
+    pub body:       Box<SpannedItem<Expression>>,
+    /// the lambda's parameters live in their own scope
+    pub expr_id:    ExprId,
+}
+
+#[derive(Clone)]
+pub enum Pattern {
+    /// `_`, which matches anything
+    Wildcard,
+    Literal(Literal),
+    Variant(VariantPattern),
+}
+
+/// A pattern which matches a single variant of a user-defined type, like `Sized address _`
+#[derive(Clone)]
+pub struct VariantPattern {
+    pub name:   Path,
+    pub fields: Box<[SpannedItem<FieldPattern>]>,
+}
+
+#[derive(Clone, Debug)]
+pub enum FieldPattern {
+    /// binds the field to a name in the arm's scope
+    Binding(Identifier),
+    Wildcard,
+    Literal(Literal),
+}
+
+impl VariantPattern {
+    /// The names bound by this pattern, in field order
+    pub fn bindings(&self) -> impl Iterator<Item = Identifier> + '_ {
+        self.fields.iter().filter_map(|field| match field.item() {
+            FieldPattern::Binding(name) => Some(*name),
+            _ => None,
+        })
+    }
+}
+
+impl Pattern {
+    /// The names bound by this pattern
+    pub fn bindings(&self) -> Vec<Identifier> {
+        match self {
+            Pattern::Variant(variant) => variant.bindings().collect(),
+            Pattern::Wildcard | Pattern::Literal(_) => vec![],
+        }
+    }
+}
+
 #[derive(Clone)]
 pub struct IntrinsicCall {
     pub intrinsic: Intrinsic,
@@ -192,6 +382,19 @@ impl std::fmt::Display for Intrinsic {
             Intrinsic::Malloc => write!(f, "malloc"),
             Intrinsic::SizeOf => write!(f, "size_of"),
             Intrinsic::Equals => write!(f, "eq"),
+            Intrinsic::NotEquals => write!(f, "not_equals"),
+            Intrinsic::LessThan => write!(f, "less_than"),
+            Intrinsic::GreaterThan => write!(f, "greater_than"),
+            Intrinsic::LessThanOrEquals => write!(f, "less_than_or_equals"),
+            Intrinsic::GreaterThanOrEquals => write!(f, "greater_than_or_equals"),
+            Intrinsic::And => write!(f, "and"),
+            Intrinsic::Or => write!(f, "or"),
+            Intrinsic::Not => write!(f, "not"),
+            Intrinsic::ConvertTo(IntegerType::I64) => write!(f, "to_int"),
+            Intrinsic::ConvertTo(ty) => write!(f, "to_{ty}"),
+            Intrinsic::ToFloat => write!(f, "to_float"),
+            Intrinsic::Sqrt => write!(f, "sqrt"),
+            Intrinsic::Floor => write!(f, "floor"),
         }
     }
 }
@@ -207,12 +410,29 @@ pub enum Intrinsic {
     Malloc,
     SizeOf,
     Equals,
+    NotEquals,
+    LessThan,
+    GreaterThan,
+    LessThanOrEquals,
+    GreaterThanOrEquals,
+    And,
+    Or,
+    Not,
+    /// Converts an integer to the given integer type, like `@to_u8`, or truncates a float to one
+    ConvertTo(IntegerType),
+    /// Converts an integer to a `'float`
+    ToFloat,
+    Sqrt,
+    Floor,
 }
 
 #[derive(Clone)]
 pub struct FunctionCall {
     pub func_name: Path,
     pub args: Box<[SpannedItem<Expression>]>,
+    /// if the args were passed by name, like `~Point x = 1, y = 2`, these are the names in the
+    /// order they were written
+    pub arg_names: Option<Box<[Identifier]>>,
     // used for the formatter, primarily
     pub args_were_parenthesized: bool,
 }
@@ -229,11 +449,60 @@ pub struct List {
 #[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
 pub enum Literal {
     Integer(i64),
+    Float(Float),
     Boolean(bool),
     // TODO intern these strings and use an ID
     String(String),
 }
 
+/// A float literal. Floats only have a partial order, so literals are compared with
+/// [`f64::total_cmp`], which lets them be used where literals need to be `Ord`.
+#[derive(Clone, Copy, Debug)]
+pub struct Float(pub f64);
+
+impl PartialEq for Float {
+    fn eq(
+        &self,
+        other: &Self,
+    ) -> bool {
+        self.cmp(other).is_eq()
+    }
+}
+
+impl Eq for Float {}
+
+impl PartialOrd for Float {
+    fn partial_cmp(
+        &self,
+        other: &Self,
+    ) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Float {
+    fn cmp(
+        &self,
+        other: &Self,
+    ) -> std::cmp::Ordering {
+        self.0.total_cmp(&other.0)
+    }
+}
+
+impl std::fmt::Display for Float {
+    fn fmt(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+    ) -> std::fmt::Result {
+        // always print a fractional part, so the literal reads back as a float
+        if self.0.fract() == 0.0 && self.0.is_finite() {
+            write!(f, "{:.1}", self.0)
+        } else {
+            write!(f, "{}", self.0)
+        }
+    }
+}
+
 impl std::fmt::Display for Literal {
     fn fmt(
         &self,
@@ -241,6 +510,7 @@ impl std::fmt::Display for Literal {
     ) -> std::fmt::Result {
         match self {
             Literal::Integer(i) => write!(f, "{}", i),
+            Literal::Float(x) => write!(f, "{}", x),
             Literal::Boolean(b) => write!(f, "{}", b),
             Literal::String(s) => write!(f, "\"{}\"", s),
         }
@@ -254,6 +524,12 @@ pub struct OperatorExpression {
     pub op:  SpannedItem<Operator>,
 }
 
+#[derive(Clone)]
+pub struct UnaryOperatorExpression {
+    pub operand: SpannedItem<Expression>,
+    pub op:      SpannedItem<UnaryOperator>,
+}
+
 #[derive(Clone, Debug)]
 pub struct FunctionParameter {
     pub name: Identifier,
@@ -263,12 +539,22 @@ pub struct FunctionParameter {
 #[derive(Clone, Debug)]
 pub enum Ty {
     Int,
+    /// A fixed-width integer type, like `'u8`. `'i64` is the same type as `'int`, so it is never
+    /// one of these.
+    SizedInt(IntegerType),
+    Float,
     Bool,
     Named(Identifier),
+    /// A generic user-defined type applied to type arguments, like `'Option 'int`
+    Applied(Identifier, Box<[Ty]>),
     String,
     Unit,
     Literal(Literal),
     Sum(Box<[Ty]>),
+    /// The type of a function value, like `'fn('int, 'int) returns 'bool`
+    Arrow(Box<[Ty]>, Box<Ty>),
+    /// An anonymous tuple type, like `'(int, string)`
+    Tuple(Box<[Ty]>),
 }
 
 #[derive(Clone)]
@@ -278,6 +564,13 @@ pub enum Operator {
     Star,
     Slash,
     Eq,
+    NotEq,
+    LessThan,
+    GreaterThan,
+    LessThanOrEq,
+    GreaterThanOrEq,
+    And,
+    Or,
 }
 
 impl Operator {
@@ -288,6 +581,26 @@ impl Operator {
             Operator::Star => "*",
             Operator::Slash => "/",
             Operator::Eq => "=",
+            Operator::NotEq => "!=",
+            Operator::LessThan => "<",
+            Operator::GreaterThan => ">",
+            Operator::LessThanOrEq => "<=",
+            Operator::GreaterThanOrEq => ">=",
+            Operator::And => "and",
+            Operator::Or => "or",
+        }
+    }
+}
+
+#[derive(Clone)]
+pub enum UnaryOperator {
+    Not,
+}
+
+impl UnaryOperator {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            UnaryOperator::Not => "not",
         }
     }
 }
@@ -19,6 +19,13 @@ impl<T> Commented<T> {
     pub fn comments(&self) -> &[Comment] {
         &self.1[..]
     }
+
+    pub fn map<B>(
+        self,
+        f: impl FnOnce(T) -> B,
+    ) -> Commented<B> {
+        Commented(f(self.0), self.1)
+    }
 }
 
 impl<T: Clone> Clone for Commented<T> {
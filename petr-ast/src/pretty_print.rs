@@ -61,7 +61,10 @@ impl PrettyPrint for AstNode {
         let mut string = match self {
             AstNode::FunctionDeclaration(node) => node.pretty_print(interner, indentation),
             AstNode::TypeDeclaration(ty) => ty.pretty_print(interner, indentation),
+            AstNode::TypeAlias(alias) => alias.pretty_print(interner, indentation),
             AstNode::ImportStatement(stmt) => stmt.pretty_print(interner, indentation),
+            AstNode::InterfaceDeclaration(interface) => interface.pretty_print(interner, indentation),
+            AstNode::ImplementationDeclaration(implementation) => implementation.pretty_print(interner, indentation),
         };
         let indentation_str = "  ".repeat(indentation);
         string = format!("{indentation_str}{string}");
@@ -77,12 +80,22 @@ impl PrettyPrint for TypeDeclaration {
         interner: &SymbolInterner,
         indentation: usize,
     ) -> String {
-        let TypeDeclaration { name, variants, visibility } = self;
+        let TypeDeclaration {
+            name,
+            type_params,
+            variants,
+            visibility,
+        } = self;
         format!(
-            "{}{}type {} =\n{}",
+            "{}{}type {}{} =\n{}",
             "  ".repeat(indentation),
             if *visibility == Visibility::Exported { "exported " } else { "" },
             name.pretty_print(interner, 0),
+            type_params
+                .iter()
+                .map(|param| format!(" '{}", param.pretty_print(interner, 0)))
+                .collect::<Vec<_>>()
+                .join(""),
             variants
                 .iter()
                 .map(|field| field.pretty_print(interner, indentation + 1))
@@ -92,6 +105,87 @@ impl PrettyPrint for TypeDeclaration {
     }
 }
 
+impl PrettyPrint for TypeAlias {
+    fn pretty_print(
+        &self,
+        interner: &SymbolInterner,
+        indentation: usize,
+    ) -> String {
+        let TypeAlias { name, ty, visibility } = self;
+        format!(
+            "{}{}type alias {} = {}\n",
+            "  ".repeat(indentation),
+            if *visibility == Visibility::Exported { "exported " } else { "" },
+            name.pretty_print(interner, 0),
+            ty.pretty_print(interner, 0),
+        )
+    }
+}
+
+impl PrettyPrint for InterfaceDeclaration {
+    fn pretty_print(
+        &self,
+        interner: &SymbolInterner,
+        indentation: usize,
+    ) -> String {
+        let InterfaceDeclaration {
+            name,
+            type_param,
+            methods,
+            visibility,
+        } = self;
+        format!(
+            "{}{}interface {} '{} with\n{}\n",
+            "  ".repeat(indentation),
+            if *visibility == Visibility::Exported { "exported " } else { "" },
+            name.pretty_print(interner, 0),
+            type_param.pretty_print(interner, 0),
+            methods
+                .iter()
+                .map(|method| {
+                    format!(
+                        "{}{}({}) -> {}",
+                        "  ".repeat(indentation + 1),
+                        method.name.pretty_print(interner, 0),
+                        method
+                            .parameters
+                            .iter()
+                            .map(|param| param.pretty_print(interner, 0))
+                            .collect::<Vec<_>>()
+                            .join(", "),
+                        method.return_type.pretty_print(interner, 0)
+                    )
+                })
+                .collect::<Vec<_>>()
+                .join(",\n"),
+        )
+    }
+}
+
+impl PrettyPrint for ImplementationDeclaration {
+    fn pretty_print(
+        &self,
+        interner: &SymbolInterner,
+        indentation: usize,
+    ) -> String {
+        format!(
+            "{}implement {} for {} with {}\n",
+            "  ".repeat(indentation),
+            self.interface.iter().map(|id| interner.get(id.id)).collect::<Vec<_>>().join("."),
+            self.ty.pretty_print(interner, 0),
+            self.methods
+                .iter()
+                .map(|(method, function)| format!(
+                    "{} = {}",
+                    method.pretty_print(interner, 0),
+                    function.iter().map(|id| interner.get(id.id)).collect::<Vec<_>>().join(".")
+                ))
+                .collect::<Vec<_>>()
+                .join(", "),
+        )
+    }
+}
+
 impl PrettyPrint for TypeVariantOrLiteral {
     fn pretty_print(
         &self,
@@ -137,11 +231,43 @@ impl PrettyPrint for Ty {
         let name = match self {
             Ty::Bool => "bool".to_string(),
             Ty::Int => "int".to_string(),
+            Ty::SizedInt(ty) => ty.to_string(),
+            Ty::Float => "float".to_string(),
             Ty::String => "string".to_string(),
             Ty::Unit => "unit".to_string(),
             Ty::Named(name) => name.pretty_print(interner, 0),
+            Ty::Applied(name, args) => format!(
+                "{} {}",
+                name.pretty_print(interner, 0),
+                args.iter()
+                    .map(|arg| match arg {
+                        Ty::Applied(..) => format!("({})", arg.pretty_print(interner, 0)),
+                        _ => arg.pretty_print(interner, 0),
+                    })
+                    .collect::<Vec<_>>()
+                    .join(" ")
+            ),
             Ty::Literal(lit) => format!("lit ty {}", lit.pretty_print(interner, 0)),
             Ty::Sum(tys) => tys.iter().map(|ty| ty.pretty_print(interner, 0)).collect::<Vec<_>>().join(" | "),
+            Ty::Arrow(params, return_ty) => format!(
+                "fn({}) returns {}",
+                params.iter().map(|ty| ty.pretty_print(interner, 0)).collect::<Vec<_>>().join(", "),
+                return_ty.pretty_print(interner, 0)
+            ),
+            // names don't need their `'` inside of a tuple type, like `'(int, 'Option 'int)`
+            Ty::Tuple(tys) => format!(
+                "({})",
+                tys.iter()
+                    .map(|ty| {
+                        let ty_str = ty.pretty_print(interner, 0);
+                        match ty {
+                            Ty::Applied(..) | Ty::Sum(..) | Ty::Arrow(..) | Ty::Literal(_) => ty_str,
+                            _ => ty_str.strip_prefix('\'').unwrap_or(&ty_str).to_string(),
+                        }
+                    })
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
         };
         format!("'{name}")
     }
@@ -172,12 +298,28 @@ impl PrettyPrint for Expression {
             Expression::Literal(l) => l.pretty_print(interner, indentation),
             Expression::List(list) => list.pretty_print(interner, indentation),
             Expression::Operator(op) => op.pretty_print(interner, indentation),
+            Expression::UnaryOperator(op) => op.pretty_print(interner, indentation),
             Expression::TypeConstructor(..) => "type constructor".to_string(),
+            Expression::InterfaceMethod(..) => "interface method".to_string(),
             Expression::FunctionCall(call) => call.pretty_print(interner, indentation),
             Expression::Variable(v) => format!("var({})", interner.get(v.id)),
             Expression::IntrinsicCall(call) => call.pretty_print(interner, indentation),
             Expression::Binding(binding) => binding.pretty_print(interner, indentation + 1),
             Expression::If(if_expr) => if_expr.pretty_print(interner, indentation),
+            Expression::Match(match_expr) => match_expr.pretty_print(interner, indentation),
+            Expression::Lambda(lambda) => lambda.pretty_print(interner, indentation),
+            Expression::FieldAccess(access) => access.pretty_print(interner, indentation),
+            Expression::RecordUpdate(update) => update.pretty_print(interner, indentation),
+            Expression::Tuple(elements) => format!(
+                "({})",
+                elements
+                    .iter()
+                    .map(|element| element.pretty_print(interner, indentation))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+            Expression::Hole(None) => "hole".to_string(),
+            Expression::Hole(Some(name)) => format!("hole({})", interner.get(name.id)),
         }
     }
 }
@@ -190,6 +332,7 @@ impl PrettyPrint for Literal {
     ) -> String {
         match self {
             Literal::Integer(i) => i.to_string(),
+            Literal::Float(x) => x.to_string(),
             Literal::Boolean(b) => b.to_string(),
             Literal::String(s) => format!("\"{s}\""),
         }
@@ -221,6 +364,96 @@ impl PrettyPrint for If {
     }
 }
 
+impl PrettyPrint for Match {
+    fn pretty_print(
+        &self,
+        interner: &SymbolInterner,
+        indentation: usize,
+    ) -> String {
+        let mut buf = format!("{}match {}", "  ".repeat(indentation), self.scrutinee.pretty_print(interner, indentation));
+        for arm in self.arms.iter() {
+            buf.push_str(&format!(
+                "\n{}| {} => {}",
+                "  ".repeat(indentation + 1),
+                arm.item().pattern.pretty_print(interner, indentation),
+                arm.item().body.pretty_print(interner, indentation)
+            ));
+        }
+        buf
+    }
+}
+
+impl PrettyPrint for Lambda {
+    fn pretty_print(
+        &self,
+        interner: &SymbolInterner,
+        indentation: usize,
+    ) -> String {
+        format!(
+            "lambda({}) => {}",
+            self.parameters
+                .iter()
+                .map(|param| param.pretty_print(interner, 0))
+                .collect::<Vec<_>>()
+                .join(", "),
+            self.body.pretty_print(interner, indentation)
+        )
+    }
+}
+
+impl PrettyPrint for FieldAccess {
+    fn pretty_print(
+        &self,
+        interner: &SymbolInterner,
+        indentation: usize,
+    ) -> String {
+        format!("{}.{}", self.target.pretty_print(interner, indentation), interner.get(self.field.id))
+    }
+}
+
+impl PrettyPrint for RecordUpdate {
+    fn pretty_print(
+        &self,
+        interner: &SymbolInterner,
+        indentation: usize,
+    ) -> String {
+        format!(
+            "{} with {}",
+            self.target.pretty_print(interner, indentation),
+            self.fields
+                .iter()
+                .map(|(name, value)| format!("{} = {}", interner.get(name.id), value.pretty_print(interner, indentation)))
+                .collect::<Vec<_>>()
+                .join(", ")
+        )
+    }
+}
+
+impl PrettyPrint for Pattern {
+    fn pretty_print(
+        &self,
+        interner: &SymbolInterner,
+        indentation: usize,
+    ) -> String {
+        match self {
+            Pattern::Wildcard => "_".to_string(),
+            Pattern::Literal(lit) => lit.pretty_print(interner, indentation),
+            Pattern::Variant(VariantPattern { name, fields }) => {
+                let mut buf = format!("variant({})", interner.get_path(name).join("."));
+                for field in fields.iter() {
+                    buf.push(' ');
+                    buf.push_str(&match field.item() {
+                        FieldPattern::Binding(name) => format!("bind({})", interner.get(name.id)),
+                        FieldPattern::Wildcard => "_".to_string(),
+                        FieldPattern::Literal(lit) => lit.pretty_print(interner, indentation),
+                    });
+                }
+                buf
+            },
+        }
+    }
+}
+
 impl PrettyPrint for ExpressionWithBindings {
     fn pretty_print(
         &self,
@@ -234,7 +467,7 @@ impl PrettyPrint for ExpressionWithBindings {
         let mut buf = format!(
             "\n{}let {} = {},",
             "  ".repeat(indentation),
-            first_binding.name.pretty_print(interner, indentation + 1),
+            first_binding.pattern.pretty_print(interner, indentation + 1),
             first_binding.val.pretty_print(interner, indentation + 1)
         );
         for (ix, binding) in bindings.enumerate() {
@@ -242,7 +475,7 @@ impl PrettyPrint for ExpressionWithBindings {
             buf.push_str(&format!(
                 "\n{}    {} = {}{}",
                 "  ".repeat(indentation),
-                binding.name.pretty_print(interner, indentation + 1),
+                binding.pattern.pretty_print(interner, indentation + 1),
                 binding.val.pretty_print(interner, indentation + 1),
                 if !is_last { "," } else { "" }
             ));
@@ -253,6 +486,26 @@ impl PrettyPrint for ExpressionWithBindings {
     }
 }
 
+impl PrettyPrint for BindingPattern {
+    fn pretty_print(
+        &self,
+        interner: &SymbolInterner,
+        indentation: usize,
+    ) -> String {
+        match self {
+            BindingPattern::Name(name) => name.pretty_print(interner, indentation),
+            BindingPattern::Tuple(names) => format!(
+                "({})",
+                names
+                    .iter()
+                    .map(|name| name.pretty_print(interner, indentation))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+        }
+    }
+}
+
 impl PrettyPrint for IntrinsicCall {
     fn pretty_print(
         &self,
@@ -284,7 +537,11 @@ impl PrettyPrint for FunctionCall {
             interner.get_path(&self.func_name).join("."),
             self.args
                 .iter()
-                .map(|arg| arg.pretty_print(interner, indentation))
+                .enumerate()
+                .map(|(ix, arg)| match &self.arg_names {
+                    Some(names) => format!("{} = {}", interner.get(names[ix].id), arg.pretty_print(interner, indentation)),
+                    None => arg.pretty_print(interner, indentation),
+                })
                 .collect::<Vec<_>>()
                 .join(", ")
         )
@@ -316,14 +573,18 @@ impl PrettyPrint for OperatorExpression {
     ) -> String {
         let lhs = self.lhs.pretty_print(interner, indentation);
         let rhs = self.rhs.pretty_print(interner, indentation);
-        let op = match self.op.item() {
-            Operator::Plus => "+",
-            Operator::Minus => "-",
-            Operator::Star => "*",
-            Operator::Slash => "/",
-            Operator::Eq => "=",
-        };
-        format!("{}({} {})", op, lhs, rhs)
+        format!("{}({} {})", self.op.item().as_str(), lhs, rhs)
+    }
+}
+
+impl PrettyPrint for UnaryOperatorExpression {
+    fn pretty_print(
+        &self,
+        interner: &SymbolInterner,
+        indentation: usize,
+    ) -> String {
+        let operand = self.operand.pretty_print(interner, indentation);
+        format!("{}({})", self.op.item().as_str(), operand)
     }
 }
 
@@ -337,11 +598,13 @@ impl PrettyPrint for FunctionDeclaration {
             name,
             parameters,
             return_type,
+            constraints,
+            effects,
             body,
             visibility,
         } = self;
         format!(
-            "{}{}Func {}({}{}{}) -> {} {}\n",
+            "{}{}Func {}({}{}{}) -> {}{}{} {}\n",
             "  ".repeat(indentation),
             if *visibility == Visibility::Exported { "exported " } else { "" },
             name.pretty_print(interner, 0),
@@ -353,6 +616,34 @@ impl PrettyPrint for FunctionDeclaration {
                 .join(",\n"),
             if parameters.is_empty() { "" } else { "\n" },
             return_type.pretty_print(interner, indentation),
+            if constraints.is_empty() {
+                String::new()
+            } else {
+                format!(
+                    " where {}",
+                    constraints
+                        .iter()
+                        .map(|constraint| format!(
+                            "'{}: {}",
+                            constraint.type_param.pretty_print(interner, 0),
+                            constraint.interface.iter().map(|id| interner.get(id.id)).collect::<Vec<_>>().join(".")
+                        ))
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                )
+            },
+            match effects {
+                None => String::new(),
+                Some(effects) if effects.is_empty() => " pure".to_string(),
+                Some(effects) => format!(
+                    " uses {}",
+                    effects
+                        .iter()
+                        .map(|effect| effect.pretty_print(interner, 0))
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                ),
+            },
             body.pretty_print(interner, indentation)
         )
     }
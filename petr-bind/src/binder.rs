@@ -1,7 +1,10 @@
 use std::{collections::BTreeMap, rc::Rc};
 
-use petr_ast::{dependency::Dependency, Ast, Binding, ExprId, Expression, FunctionDeclaration, Ty, TypeDeclaration};
-use petr_utils::{idx_map_key, Identifier, IndexMap, Path, Span, SpannedItem, SymbolId, SymbolInterner};
+use petr_ast::{
+    dependency::Dependency, Ast, Binding, ExprId, Expression, FunctionDeclaration, ImplementationDeclaration, InterfaceDeclaration, Ty, TypeAlias,
+    TypeConstraint, TypeDeclaration,
+};
+use petr_utils::{idx_map_key, Identifier, IndexMap, InterfaceId, Path, Span, SpannedItem, SymbolId, SymbolInterner};
 
 #[cfg(test)]
 mod tests;
@@ -35,35 +38,62 @@ idx_map_key!(
    ModuleId
 );
 
+idx_map_key!(
+    /// The ID type of a type alias.
+    TypeAliasId
+);
+
 #[derive(Clone)]
 pub enum Item {
     Binding(Binding),
     // the `ScopeId` is the scope of the function body
     Function(FunctionId, ScopeId),
     Type(petr_utils::TypeId),
+    TypeAlias(TypeAliasId),
+    Interface(InterfaceId),
     FunctionParameter(Ty),
     Module(ModuleId),
     Import { path: Path, alias: Option<Identifier> },
 }
 
 pub struct Binder {
-    scopes:      IndexMap<ScopeId, Scope>,
-    scope_chain: Vec<ScopeId>,
+    scopes:          IndexMap<ScopeId, Scope>,
+    scope_chain:     Vec<ScopeId>,
     /// Some expressions define their own scopes, like expressions with bindings
     // TODO rename to expr_scopes
     exprs: BTreeMap<ExprId, ScopeId>,
-    bindings:    IndexMap<BindingId, Binding>,
-    functions:   IndexMap<FunctionId, SpannedItem<FunctionDeclaration>>,
-    types:       IndexMap<petr_utils::TypeId, TypeDeclaration>,
-    modules:     IndexMap<ModuleId, Module>,
-    root_scope:  ScopeId,
+    bindings:        IndexMap<BindingId, Binding>,
+    functions:       IndexMap<FunctionId, SpannedItem<FunctionDeclaration>>,
+    types:           IndexMap<petr_utils::TypeId, TypeDeclaration>,
+    /// Each type alias is stored with the scope that it was declared in, which is where the type
+    /// it stands for is resolved.
+    type_aliases:    IndexMap<TypeAliasId, (SpannedItem<TypeAlias>, ScopeId)>,
+    interfaces:      IndexMap<InterfaceId, Interface>,
+    /// Implementations aren't named, so they aren't in any scope. Each is stored with the scope
+    /// that it was declared in.
+    implementations: Vec<(SpannedItem<ImplementationDeclaration>, ScopeId)>,
+    modules:         IndexMap<ModuleId, Module>,
+    root_scope:      ScopeId,
+    /// The lambdas that are currently being bound, innermost last, along with their scopes
+    lambda_chain:    Vec<(ExprId, ScopeId)>,
+    /// The local bindings and parameters from enclosing scopes that each lambda references, in
+    /// the order they are first referenced
+    captures:        BTreeMap<ExprId, Vec<Identifier>>,
+}
+
+pub struct Interface {
+    pub declaration: InterfaceDeclaration,
+    /// The functions which call each method of the interface, in the order they are declared
+    pub methods:     Box<[FunctionId]>,
 }
 
 #[derive(Debug)]
 pub struct Module {
-    pub root_scope:         ScopeId,
+    pub root_scope: ScopeId,
     pub exported_functions: BTreeMap<SymbolId, (FunctionId, ScopeId)>,
-    pub exported_types:     BTreeMap<SymbolId, petr_utils::TypeId>,
+    pub exported_types: BTreeMap<SymbolId, petr_utils::TypeId>,
+    pub exported_type_aliases: BTreeMap<SymbolId, TypeAliasId>,
+    pub exported_interfaces: BTreeMap<SymbolId, InterfaceId>,
 }
 
 #[derive(Default)]
@@ -77,6 +107,8 @@ pub struct Scope {
     bindings: BTreeMap<SymbolId, Binding>,
     function_params: BTreeMap<SymbolId, Ty>,
     types: BTreeMap<SymbolId, petr_utils::TypeId>,
+    type_aliases: BTreeMap<SymbolId, TypeAliasId>,
+    interfaces: BTreeMap<SymbolId, InterfaceId>,
     modules: BTreeMap<SymbolId, ModuleId>,
     imports: BTreeMap<SymbolId, ImportStatement>,
     spans: BTreeMap<SymbolId, petr_utils::Span>,
@@ -107,6 +139,11 @@ pub enum ScopeKind {
     TypeConstructor,
     /// For a let... expression, this is the scope of the expression and its bindings.
     ExpressionWithBindings,
+    /// The scope of a single arm of a match expression, which contains the bindings introduced by
+    /// its pattern.
+    MatchArm,
+    /// The scope of a lambda body, which is where the lambda's parameters are declared.
+    Lambda,
 }
 
 impl Default for ScopeKind {
@@ -143,6 +180,30 @@ impl Scope {
         self.spans.insert(k, span);
     }
 
+    pub fn insert_type_alias(
+        &mut self,
+        k: SymbolId,
+        span: Span,
+        v: TypeAliasId,
+    ) {
+        if self.type_aliases.insert(k, v).is_some() {
+            todo!("throw error for overriding symbol name {k}")
+        }
+        self.spans.insert(k, span);
+    }
+
+    pub fn insert_interface(
+        &mut self,
+        k: SymbolId,
+        span: Span,
+        v: InterfaceId,
+    ) {
+        if self.interfaces.insert(k, v).is_some() {
+            todo!("throw error for overriding symbol name {k}")
+        }
+        self.spans.insert(k, span);
+    }
+
     pub fn insert_binding(
         &mut self,
         k: SymbolId,
@@ -213,9 +274,14 @@ impl Binder {
             root_scope,
             functions: IndexMap::default(),
             types: IndexMap::default(),
+            type_aliases: IndexMap::default(),
+            interfaces: IndexMap::default(),
+            implementations: Vec::new(),
             bindings: IndexMap::default(),
             modules: IndexMap::default(),
             exprs: BTreeMap::new(),
+            lambda_chain: Vec::new(),
+            captures: BTreeMap::new(),
         }
     }
 
@@ -237,6 +303,26 @@ impl Binder {
         self.types.get(type_id)
     }
 
+    /// The type alias, along with the scope it was declared in
+    pub fn get_type_alias(
+        &self,
+        alias_id: TypeAliasId,
+    ) -> &(SpannedItem<TypeAlias>, ScopeId) {
+        self.type_aliases.get(alias_id)
+    }
+
+    pub fn get_interface(
+        &self,
+        interface_id: InterfaceId,
+    ) -> &Interface {
+        self.interfaces.get(interface_id)
+    }
+
+    /// All of the implementations of interfaces, along with the scopes they were declared in
+    pub fn implementations(&self) -> impl Iterator<Item = &(SpannedItem<ImplementationDeclaration>, ScopeId)> {
+        self.implementations.iter()
+    }
+
     /// does not check parent scopes
     pub fn find_module_in_single_scope(
         &self,
@@ -281,6 +367,38 @@ impl Binder {
         None
     }
 
+    pub fn find_type_alias_in_scope(
+        &self,
+        name: SymbolId,
+        scope_id: ScopeId,
+    ) -> Option<TypeAliasId> {
+        let scope = self.scopes.get(scope_id);
+        if let Some(id) = scope.type_aliases.get(&name) {
+            return Some(*id);
+        }
+
+        if let Some(parent_id) = scope.parent() {
+            return self.find_type_alias_in_scope(name, parent_id);
+        }
+        None
+    }
+
+    pub fn find_interface_in_scope(
+        &self,
+        name: SymbolId,
+        scope_id: ScopeId,
+    ) -> Option<InterfaceId> {
+        let scope = self.scopes.get(scope_id);
+        if let Some(id) = scope.interfaces.get(&name) {
+            return Some(*id);
+        }
+
+        if let Some(parent_id) = scope.parent() {
+            return self.find_interface_in_scope(name, parent_id);
+        }
+        None
+    }
+
     pub fn find_function_parameter_in_scope(
         &self,
         name: SymbolId,
@@ -359,6 +477,10 @@ impl Binder {
             return Some(Item::Type(id));
         }
 
+        if let Some(id) = self.find_interface_in_scope(name, scope_id) {
+            return Some(Item::Interface(id));
+        }
+
         if let Some(id) = self.find_binding_in_scope(name, scope_id) {
             return Some(Item::Binding(id));
         }
@@ -378,6 +500,30 @@ impl Binder {
     }
 
     /// Searches for a symbol in a scope or any of its parents
+    /// The local bindings and function parameters that are visible in `scope_id`, including
+    /// those of enclosing scopes, in the order they are declared. Where a name is shadowed, only
+    /// the innermost declaration is included.
+    pub fn locals_in_scope(
+        &self,
+        scope_id: ScopeId,
+    ) -> Vec<Identifier> {
+        let mut locals = BTreeMap::new();
+        let mut scope_id = Some(scope_id);
+        while let Some(id) = scope_id {
+            let scope = self.scopes.get(id);
+            for name in scope.bindings.keys().chain(scope.function_params.keys()) {
+                locals.entry(*name).or_insert_with(|| Identifier {
+                    id:   *name,
+                    span: scope.spans[name],
+                });
+            }
+            scope_id = scope.parent();
+        }
+        let mut locals = locals.into_values().collect::<Vec<_>>();
+        locals.sort_by_key(|local| local.span);
+        locals
+    }
+
     pub fn find_spanned_func_in_scope(
         &self,
         name: SymbolId,
@@ -449,6 +595,15 @@ impl Binder {
         self.scopes.get_mut(scope_id).insert_type(name, item.span(), *item.item());
     }
 
+    pub fn insert_interface_into_current_scope(
+        &mut self,
+        name: SymbolId,
+        item: SpannedItem<InterfaceId>,
+    ) {
+        let scope_id = self.current_scope_id();
+        self.scopes.get_mut(scope_id).insert_interface(name, item.span(), *item.item());
+    }
+
     fn push_scope(
         &mut self,
         kind: ScopeKind,
@@ -502,13 +657,25 @@ impl Binder {
         let type_id = self.types.insert((*ty_decl.item()).clone());
         self.insert_type_into_current_scope(ty_decl.item().name.id, ty_decl.span().with_item(type_id));
 
-        for variant in &ty_decl.item().variants {
+        // we don't need to create type constructor functions for constant literal types
+        let variants = ty_decl.item().variants.iter().filter_map(|variant| match variant.item() {
+            petr_ast::TypeVariantOrLiteral::Variant(v) => Some(variant.span().with_item(v)),
+            petr_ast::TypeVariantOrLiteral::Literal(_) => None,
+        });
+        // constructors of generic types are generic over the same type parameters
+        let constructed_type = if ty_decl.item().type_params.is_empty() {
+            Ty::Named(ty_decl.item().name)
+        } else {
+            Ty::Applied(
+                ty_decl.item().name,
+                ty_decl.item().type_params.iter().map(|param| Ty::Named(*param)).collect(),
+            )
+        };
+        let mut num_variants = 0;
+        for (variant_index, variant) in variants.enumerate() {
+            num_variants += 1;
             let span = variant.span();
-            let variant = match variant.item() {
-                petr_ast::TypeVariantOrLiteral::Variant(v) => v,
-                // we don't need to create type constructor functions for constant literal types
-                petr_ast::TypeVariantOrLiteral::Literal(_) => continue,
-            };
+            let variant = variant.item();
             let (fields_as_parameters, _func_scope) = self.with_scope(ScopeKind::TypeConstructor, |_, scope| {
                 (
                     variant
@@ -532,8 +699,14 @@ impl Binder {
             let function = FunctionDeclaration {
                 name:        variant.name,
                 parameters:  fields_as_parameters.into_boxed_slice(),
-                return_type: Ty::Named(ty_decl.item().name),
-                body:        span.with_item(Expression::TypeConstructor(type_id, type_constructor_exprs.into_boxed_slice())),
+                return_type: constructed_type.clone(),
+                constraints: Default::default(),
+                effects:     None,
+                body:        span.with_item(Expression::TypeConstructor(
+                    type_id,
+                    variant_index,
+                    type_constructor_exprs.into_boxed_slice(),
+                )),
                 visibility:  ty_decl.item().visibility,
             };
 
@@ -575,10 +748,15 @@ impl Binder {
                     ty:   constants_as_sum_type,
                 }]
                 .into_boxed_slice(),
-                return_type: Ty::Named(ty_decl.item().name),
-                body:        ty_decl
-                    .span()
-                    .with_item(Expression::TypeConstructor(type_id, vec![type_constructor_expr].into_boxed_slice())),
+                return_type: constructed_type.clone(),
+                constraints: Default::default(),
+                effects:     None,
+                // the constant literal constructor comes after all of the named variants
+                body:        ty_decl.span().with_item(Expression::TypeConstructor(
+                    type_id,
+                    num_variants,
+                    vec![type_constructor_expr].into_boxed_slice(),
+                )),
                 visibility:  ty_decl.item().visibility,
             };
 
@@ -592,6 +770,77 @@ impl Binder {
         }
     }
 
+    pub(crate) fn insert_type_alias(
+        &mut self,
+        alias: &SpannedItem<&TypeAlias>,
+    ) -> Option<(Identifier, TypeAliasId)> {
+        let scope_id = self.current_scope_id();
+        let decl = alias.item();
+        let alias_id = self.type_aliases.insert((alias.span().with_item((*decl).clone()), scope_id));
+        self.scopes.get_mut(scope_id).insert_type_alias(decl.name.id, alias.span(), alias_id);
+
+        if decl.is_exported() {
+            Some((decl.name, alias_id))
+        } else {
+            None
+        }
+    }
+
+    /// Inserts the interface, and a function for each of its methods. A method's function is
+    /// generic over the type implementing the interface, and calls the implementation for
+    /// whichever type it is called with.
+    pub(crate) fn insert_interface(
+        &mut self,
+        interface_decl: &SpannedItem<&InterfaceDeclaration>,
+    ) -> Option<(Identifier, InterfaceId)> {
+        let span = interface_decl.span();
+        let decl = interface_decl.item();
+        let interface_id = self.interfaces.insert(Interface {
+            declaration: (*decl).clone(),
+            methods:     Default::default(),
+        });
+        self.insert_interface_into_current_scope(decl.name.id, span.with_item(interface_id));
+
+        let mut methods = Vec::with_capacity(decl.methods.len());
+        for (method_index, method) in decl.methods.iter().enumerate() {
+            let function = FunctionDeclaration {
+                name:        method.name,
+                parameters:  method.parameters.clone(),
+                return_type: method.return_type.clone(),
+                // a method can only be called with types that implement the interface
+                constraints: vec![TypeConstraint {
+                    type_param: decl.type_param,
+                    interface:  Path::new(vec![decl.name]),
+                }]
+                .into_boxed_slice(),
+                effects:     None,
+                body:        span.with_item(Expression::InterfaceMethod(interface_id, method_index)),
+                visibility:  decl.visibility,
+            };
+            self.insert_function(&span.with_item(&function));
+            let (function_id, _) = self
+                .find_function_in_scope(method.name.id, self.current_scope_id())
+                .expect("method function was just inserted");
+            methods.push(function_id);
+        }
+        self.interfaces.get_mut(interface_id).methods = methods.into_boxed_slice();
+
+        if decl.is_exported() {
+            Some((decl.name, interface_id))
+        } else {
+            None
+        }
+    }
+
+    pub(crate) fn insert_implementation(
+        &mut self,
+        implementation: &SpannedItem<&ImplementationDeclaration>,
+    ) {
+        let scope_id = self.current_scope_id();
+        self.implementations
+            .push((implementation.span().with_item((*implementation.item()).clone()), scope_id));
+    }
+
     pub(crate) fn insert_function(
         &mut self,
         func: &SpannedItem<&FunctionDeclaration>,
@@ -625,6 +874,8 @@ impl Binder {
             let module_scope = binder.create_scope_from_path(&module.name);
             let mut exported_functions = BTreeMap::default();
             let mut exported_types = BTreeMap::default();
+            let mut exported_type_aliases = BTreeMap::default();
+            let mut exported_interfaces = BTreeMap::default();
             binder.with_specified_scope(module_scope, |binder, scope_id| {
                 for item in module.nodes.iter() {
                     match item.item() {
@@ -638,6 +889,17 @@ impl Binder {
                                 exported_types.insert(k.id, v);
                             }
                         },
+                        petr_ast::AstNode::TypeAlias(alias) => {
+                            if let Some((k, v)) = item.span().with_item(alias.item()).bind(binder) {
+                                exported_type_aliases.insert(k.id, v);
+                            }
+                        },
+                        petr_ast::AstNode::InterfaceDeclaration(decl) => {
+                            if let Some((k, v)) = item.span().with_item(decl.item()).bind(binder) {
+                                exported_interfaces.insert(k.id, v);
+                            }
+                        },
+                        petr_ast::AstNode::ImplementationDeclaration(decl) => item.span().with_item(decl.item()).bind(binder),
                         petr_ast::AstNode::ImportStatement(stmt) => {
                             println!("got an import");
                             if let Some(_item) = stmt.bind(binder) {
@@ -653,6 +915,8 @@ impl Binder {
                     root_scope: scope_id,
                     exported_functions,
                     exported_types,
+                    exported_type_aliases,
+                    exported_interfaces,
                 });
             });
         });
@@ -683,6 +947,8 @@ impl Binder {
                 for module in &dep_ast.modules {
                     let mut exported_functions = BTreeMap::default();
                     let mut exported_types = BTreeMap::default();
+                    let mut exported_type_aliases = BTreeMap::default();
+                    let mut exported_interfaces = BTreeMap::default();
                     let module_scope = binder.create_scope_from_path(&module.name);
                     binder.with_specified_scope(module_scope, |binder, scope_id| {
                         for item in module.nodes.iter() {
@@ -697,6 +963,17 @@ impl Binder {
                                         exported_types.insert(k.id, v);
                                     }
                                 },
+                                petr_ast::AstNode::TypeAlias(alias) => {
+                                    if let Some((k, v)) = item.span().with_item(alias.item()).bind(binder) {
+                                        exported_type_aliases.insert(k.id, v);
+                                    }
+                                },
+                                petr_ast::AstNode::InterfaceDeclaration(decl) => {
+                                    if let Some((k, v)) = item.span().with_item(decl.item()).bind(binder) {
+                                        exported_interfaces.insert(k.id, v);
+                                    }
+                                },
+                                petr_ast::AstNode::ImplementationDeclaration(decl) => item.span().with_item(decl.item()).bind(binder),
                                 petr_ast::AstNode::ImportStatement(stmt) => {
                                     if let Some(_item) = stmt.bind(binder) {
                                         todo!()
@@ -711,6 +988,8 @@ impl Binder {
                             root_scope: scope_id,
                             exported_functions,
                             exported_types,
+                            exported_type_aliases,
+                            exported_interfaces,
                         });
                     });
                 }
@@ -721,6 +1000,8 @@ impl Binder {
             let module_scope = binder.create_scope_from_path(&module.name);
             let mut exported_functions = BTreeMap::default();
             let mut exported_types = BTreeMap::default();
+            let mut exported_type_aliases = BTreeMap::default();
+            let mut exported_interfaces = BTreeMap::default();
             binder.with_specified_scope(module_scope, |binder, scope_id| {
                 for item in module.nodes.iter() {
                     match item.item() {
@@ -734,6 +1015,17 @@ impl Binder {
                                 exported_types.insert(k.id, v);
                             }
                         },
+                        petr_ast::AstNode::TypeAlias(alias) => {
+                            if let Some((k, v)) = item.span().with_item(alias.item()).bind(binder) {
+                                exported_type_aliases.insert(k.id, v);
+                            }
+                        },
+                        petr_ast::AstNode::InterfaceDeclaration(decl) => {
+                            if let Some((k, v)) = item.span().with_item(decl.item()).bind(binder) {
+                                exported_interfaces.insert(k.id, v);
+                            }
+                        },
+                        petr_ast::AstNode::ImplementationDeclaration(decl) => item.span().with_item(decl.item()).bind(binder),
                         petr_ast::AstNode::ImportStatement(stmt) => {
                             if let Some(_item) = stmt.bind(binder) {
                                 todo!()
@@ -748,6 +1040,8 @@ impl Binder {
                     root_scope: scope_id,
                     exported_functions,
                     exported_types,
+                    exported_type_aliases,
+                    exported_interfaces,
                 });
             });
         });
@@ -772,9 +1066,11 @@ impl Binder {
 
             let next_scope = self.create_scope(ScopeKind::Module(*segment));
             let module = Module {
-                root_scope:         next_scope,
+                root_scope: next_scope,
                 exported_functions: BTreeMap::default(),
-                exported_types:     BTreeMap::default(),
+                exported_types: BTreeMap::default(),
+                exported_type_aliases: BTreeMap::default(),
+                exported_interfaces: BTreeMap::default(),
             };
             let module_id = self.modules.insert(module);
             self.insert_module_into_specified_scope(current_scope_id, *segment, module_id);
@@ -849,7 +1145,17 @@ impl Binder {
             .iter()
             .map(|(k, v)| (k, scope.spans.get(k).unwrap().with_item(Item::Type(*v))));
 
-        func_items.into_iter().chain(type_items)
+        let type_alias_items = scope
+            .type_aliases
+            .iter()
+            .map(|(k, v)| (k, scope.spans.get(k).unwrap().with_item(Item::TypeAlias(*v))));
+
+        let interface_items = scope
+            .interfaces
+            .iter()
+            .map(|(k, v)| (k, scope.spans.get(k).unwrap().with_item(Item::Interface(*v))));
+
+        func_items.into_iter().chain(type_items).chain(type_alias_items).chain(interface_items)
     }
 
     pub fn insert_expression(
@@ -866,6 +1172,83 @@ impl Binder {
     ) -> Option<ScopeId> {
         self.exprs.get(&id).copied()
     }
+
+    /// Binds the body of a lambda in the lambda's own scope, tracking which locals from enclosing
+    /// scopes it references.
+    pub(crate) fn with_lambda_scope<F, R>(
+        &mut self,
+        id: ExprId,
+        f: F,
+    ) -> R
+    where
+        F: FnOnce(&mut Self, ScopeId) -> R,
+    {
+        self.with_scope(ScopeKind::Lambda, |binder, scope_id| {
+            binder.lambda_chain.push((id, scope_id));
+            binder.captures.entry(id).or_default();
+            let res = f(binder, scope_id);
+            binder.lambda_chain.pop();
+            binder.insert_expression(id, scope_id);
+            res
+        })
+    }
+
+    /// Records a reference to `name` from the current scope. If `name` is a local binding or
+    /// parameter that was declared outside of any of the lambdas currently being bound, those
+    /// lambdas capture it.
+    pub(crate) fn reference_local(
+        &mut self,
+        name: Identifier,
+    ) {
+        if self.lambda_chain.is_empty() {
+            return;
+        }
+        let current_scope = self.current_scope_id();
+        let Some(defining_scope) = self.find_local_declaration_scope(name.id, current_scope) else {
+            return;
+        };
+        for (lambda, lambda_scope) in self.lambda_chain.iter() {
+            if self.is_scope_within(defining_scope, *lambda_scope) {
+                continue;
+            }
+            let captures = self.captures.entry(*lambda).or_default();
+            if !captures.iter().any(|capture| capture.id == name.id) {
+                captures.push(name);
+            }
+        }
+    }
+
+    /// The locals from enclosing scopes that a lambda references, in the order they were first
+    /// referenced.
+    pub fn get_lambda_captures(
+        &self,
+        id: ExprId,
+    ) -> &[Identifier] {
+        self.captures.get(&id).map(|captures| &captures[..]).unwrap_or_default()
+    }
+
+    /// Finds the scope that declares a local binding or function parameter. Bindings shadow
+    /// parameters, mirroring the order that the resolver looks them up in.
+    fn find_local_declaration_scope(
+        &self,
+        name: SymbolId,
+        scope_id: ScopeId,
+    ) -> Option<ScopeId> {
+        let mut ancestors = std::iter::successors(Some(scope_id), |id| self.scopes.get(*id).parent());
+        if let Some(scope) = ancestors.clone().find(|id| self.scopes.get(*id).bindings.contains_key(&name)) {
+            return Some(scope);
+        }
+        ancestors.find(|id| self.scopes.get(*id).function_params.contains_key(&name))
+    }
+
+    /// whether `scope` is `ancestor` or one of its descendents
+    fn is_scope_within(
+        &self,
+        scope: ScopeId,
+        ancestor: ScopeId,
+    ) -> bool {
+        std::iter::successors(Some(scope), |id| self.scopes.get(*id).parent()).any(|id| id == ancestor)
+    }
 }
 
 pub trait Bind {
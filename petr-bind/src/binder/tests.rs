@@ -52,6 +52,8 @@ fn pretty_print_bindings(
                 ScopeKind::Root => "Root".into(),
                 ScopeKind::TypeConstructor => "Type Cons".into(),
                 ScopeKind::ExpressionWithBindings => "Expr w/ Bindings".into(),
+                ScopeKind::MatchArm => "Match Arm".into(),
+                ScopeKind::Lambda => "Lambda".into(),
             },
             scope.parent.map(|x| x.to_string()).unwrap_or_else(|| "none".into())
         ));
@@ -66,6 +68,11 @@ fn pretty_print_bindings(
             result.push_str(&format!("  {}: Type {:?}\n", ty_name, t));
         }
 
+        for (alias, id) in &scope.type_aliases {
+            let alias_name = interner.get(*alias);
+            result.push_str(&format!("  {}: TypeAlias {:?}\n", alias_name, id));
+        }
+
         for (id, module) in &scope.modules {
             let module_name = interner.get(*id);
             result.push_str(&format!("  {}: Module {:?}\n", module_name, module));
@@ -86,6 +93,13 @@ fn pretty_print_bindings(
             result.push_str(&format!("  {id}: Import {}\n", import_name));
         }
     }
+    if !binder.captures.is_empty() {
+        result.push_str("__Captures__\n");
+        for (expr_id, captures) in &binder.captures {
+            let captures = captures.iter().map(|name| interner.get(name.id).to_string()).collect::<Vec<_>>();
+            result.push_str(&format!("{}: [{}]\n", expr_id.0, captures.join(", ")));
+        }
+    }
     result
 }
 
@@ -153,59 +167,431 @@ fn imports_work() {
             __Scopes__
             0: Root (parent none):
               std: Module ModuleId(0)
-              test: Module ModuleId(7)
+              test: Module ModuleId(9)
             1: Module std (parent scopeid0):
               ops: Module ModuleId(1)
               io: Module ModuleId(3)
               mem: Module ModuleId(5)
+              math: Module ModuleId(7)
             2: Module ops (parent scopeid0):
               add: Function functionid0
               sub: Function functionid1
-              mult: Function functionid2
+              mul: Function functionid2
               div: Function functionid3
-              eq: Function functionid4
+              add_int: Function functionid4
+              sub_int: Function functionid5
+              mult: Function functionid6
+              div_int: Function functionid7
+              add_i8: Function functionid8
+              sub_i8: Function functionid9
+              mul_i8: Function functionid10
+              div_i8: Function functionid11
+              add_i16: Function functionid12
+              sub_i16: Function functionid13
+              mul_i16: Function functionid14
+              div_i16: Function functionid15
+              add_i32: Function functionid16
+              sub_i32: Function functionid17
+              mul_i32: Function functionid18
+              div_i32: Function functionid19
+              add_u8: Function functionid20
+              sub_u8: Function functionid21
+              mul_u8: Function functionid22
+              div_u8: Function functionid23
+              add_u16: Function functionid24
+              sub_u16: Function functionid25
+              mul_u16: Function functionid26
+              div_u16: Function functionid27
+              add_u32: Function functionid28
+              sub_u32: Function functionid29
+              mul_u32: Function functionid30
+              div_u32: Function functionid31
+              add_u64: Function functionid32
+              sub_u64: Function functionid33
+              mul_u64: Function functionid34
+              div_u64: Function functionid35
+              add_float: Function functionid36
+              sub_float: Function functionid37
+              mul_float: Function functionid38
+              div_float: Function functionid39
+              eq: Function functionid40
+              neq: Function functionid41
+              lt: Function functionid42
+              gt: Function functionid43
+              lte: Function functionid44
+              gte: Function functionid45
+              lt_int: Function functionid46
+              gt_int: Function functionid47
+              lte_int: Function functionid48
+              gte_int: Function functionid49
+              lt_i8: Function functionid50
+              gt_i8: Function functionid51
+              lte_i8: Function functionid52
+              gte_i8: Function functionid53
+              lt_i16: Function functionid54
+              gt_i16: Function functionid55
+              lte_i16: Function functionid56
+              gte_i16: Function functionid57
+              lt_i32: Function functionid58
+              gt_i32: Function functionid59
+              lte_i32: Function functionid60
+              gte_i32: Function functionid61
+              lt_u8: Function functionid62
+              gt_u8: Function functionid63
+              lte_u8: Function functionid64
+              gte_u8: Function functionid65
+              lt_u16: Function functionid66
+              gt_u16: Function functionid67
+              lte_u16: Function functionid68
+              gte_u16: Function functionid69
+              lt_u32: Function functionid70
+              gt_u32: Function functionid71
+              lte_u32: Function functionid72
+              gte_u32: Function functionid73
+              lt_u64: Function functionid74
+              gt_u64: Function functionid75
+              lte_u64: Function functionid76
+              gte_u64: Function functionid77
+              lt_float: Function functionid78
+              gt_float: Function functionid79
+              lte_float: Function functionid80
+              gte_float: Function functionid81
+              logical_not: Function functionid82
             3: Function (parent scopeid2):
+              lhs: FunctionParameter Named(Identifier { id: SymbolId(3), span: Span { source: SourceId(0), span: SourceSpan { offset: SourceOffset(41), length: 1 } } })
+              rhs: FunctionParameter Named(Identifier { id: SymbolId(3), span: Span { source: SourceId(0), span: SourceSpan { offset: SourceOffset(52), length: 1 } } })
+            4: Function (parent scopeid2):
+              lhs: FunctionParameter Named(Identifier { id: SymbolId(3), span: Span { source: SourceId(0), span: SourceSpan { offset: SourceOffset(108), length: 1 } } })
+              rhs: FunctionParameter Named(Identifier { id: SymbolId(3), span: Span { source: SourceId(0), span: SourceSpan { offset: SourceOffset(119), length: 1 } } })
+            5: Function (parent scopeid2):
+              lhs: FunctionParameter Named(Identifier { id: SymbolId(3), span: Span { source: SourceId(0), span: SourceSpan { offset: SourceOffset(175), length: 1 } } })
+              rhs: FunctionParameter Named(Identifier { id: SymbolId(3), span: Span { source: SourceId(0), span: SourceSpan { offset: SourceOffset(186), length: 1 } } })
+            6: Function (parent scopeid2):
+              lhs: FunctionParameter Named(Identifier { id: SymbolId(3), span: Span { source: SourceId(0), span: SourceSpan { offset: SourceOffset(242), length: 1 } } })
+              rhs: FunctionParameter Named(Identifier { id: SymbolId(3), span: Span { source: SourceId(0), span: SourceSpan { offset: SourceOffset(253), length: 1 } } })
+            7: Function (parent scopeid2):
               lhs: FunctionParameter Int
               rhs: FunctionParameter Int
-            4: Function (parent scopeid2):
+            8: Function (parent scopeid2):
               lhs: FunctionParameter Int
               rhs: FunctionParameter Int
-            5: Function (parent scopeid2):
+            9: Function (parent scopeid2):
               lhs: FunctionParameter Int
               rhs: FunctionParameter Int
-            6: Function (parent scopeid2):
+            10: Function (parent scopeid2):
               lhs: FunctionParameter Int
               rhs: FunctionParameter Int
-            7: Function (parent scopeid2):
-              lhs: FunctionParameter Named(Identifier { id: SymbolId(10), span: Span { source: SourceId(0), span: SourceSpan { offset: SourceOffset(307), length: 1 } } })
-              rhs: FunctionParameter Named(Identifier { id: SymbolId(10), span: Span { source: SourceId(0), span: SourceSpan { offset: SourceOffset(318), length: 1 } } })
-            8: Module io (parent scopeid0):
-              print: Function functionid5
-            9: Function (parent scopeid8):
+            11: Function (parent scopeid2):
+              lhs: FunctionParameter SizedInt(I8)
+              rhs: FunctionParameter SizedInt(I8)
+            12: Function (parent scopeid2):
+              lhs: FunctionParameter SizedInt(I8)
+              rhs: FunctionParameter SizedInt(I8)
+            13: Function (parent scopeid2):
+              lhs: FunctionParameter SizedInt(I8)
+              rhs: FunctionParameter SizedInt(I8)
+            14: Function (parent scopeid2):
+              lhs: FunctionParameter SizedInt(I8)
+              rhs: FunctionParameter SizedInt(I8)
+            15: Function (parent scopeid2):
+              lhs: FunctionParameter SizedInt(I16)
+              rhs: FunctionParameter SizedInt(I16)
+            16: Function (parent scopeid2):
+              lhs: FunctionParameter SizedInt(I16)
+              rhs: FunctionParameter SizedInt(I16)
+            17: Function (parent scopeid2):
+              lhs: FunctionParameter SizedInt(I16)
+              rhs: FunctionParameter SizedInt(I16)
+            18: Function (parent scopeid2):
+              lhs: FunctionParameter SizedInt(I16)
+              rhs: FunctionParameter SizedInt(I16)
+            19: Function (parent scopeid2):
+              lhs: FunctionParameter SizedInt(I32)
+              rhs: FunctionParameter SizedInt(I32)
+            20: Function (parent scopeid2):
+              lhs: FunctionParameter SizedInt(I32)
+              rhs: FunctionParameter SizedInt(I32)
+            21: Function (parent scopeid2):
+              lhs: FunctionParameter SizedInt(I32)
+              rhs: FunctionParameter SizedInt(I32)
+            22: Function (parent scopeid2):
+              lhs: FunctionParameter SizedInt(I32)
+              rhs: FunctionParameter SizedInt(I32)
+            23: Function (parent scopeid2):
+              lhs: FunctionParameter SizedInt(U8)
+              rhs: FunctionParameter SizedInt(U8)
+            24: Function (parent scopeid2):
+              lhs: FunctionParameter SizedInt(U8)
+              rhs: FunctionParameter SizedInt(U8)
+            25: Function (parent scopeid2):
+              lhs: FunctionParameter SizedInt(U8)
+              rhs: FunctionParameter SizedInt(U8)
+            26: Function (parent scopeid2):
+              lhs: FunctionParameter SizedInt(U8)
+              rhs: FunctionParameter SizedInt(U8)
+            27: Function (parent scopeid2):
+              lhs: FunctionParameter SizedInt(U16)
+              rhs: FunctionParameter SizedInt(U16)
+            28: Function (parent scopeid2):
+              lhs: FunctionParameter SizedInt(U16)
+              rhs: FunctionParameter SizedInt(U16)
+            29: Function (parent scopeid2):
+              lhs: FunctionParameter SizedInt(U16)
+              rhs: FunctionParameter SizedInt(U16)
+            30: Function (parent scopeid2):
+              lhs: FunctionParameter SizedInt(U16)
+              rhs: FunctionParameter SizedInt(U16)
+            31: Function (parent scopeid2):
+              lhs: FunctionParameter SizedInt(U32)
+              rhs: FunctionParameter SizedInt(U32)
+            32: Function (parent scopeid2):
+              lhs: FunctionParameter SizedInt(U32)
+              rhs: FunctionParameter SizedInt(U32)
+            33: Function (parent scopeid2):
+              lhs: FunctionParameter SizedInt(U32)
+              rhs: FunctionParameter SizedInt(U32)
+            34: Function (parent scopeid2):
+              lhs: FunctionParameter SizedInt(U32)
+              rhs: FunctionParameter SizedInt(U32)
+            35: Function (parent scopeid2):
+              lhs: FunctionParameter SizedInt(U64)
+              rhs: FunctionParameter SizedInt(U64)
+            36: Function (parent scopeid2):
+              lhs: FunctionParameter SizedInt(U64)
+              rhs: FunctionParameter SizedInt(U64)
+            37: Function (parent scopeid2):
+              lhs: FunctionParameter SizedInt(U64)
+              rhs: FunctionParameter SizedInt(U64)
+            38: Function (parent scopeid2):
+              lhs: FunctionParameter SizedInt(U64)
+              rhs: FunctionParameter SizedInt(U64)
+            39: Function (parent scopeid2):
+              lhs: FunctionParameter Float
+              rhs: FunctionParameter Float
+            40: Function (parent scopeid2):
+              lhs: FunctionParameter Float
+              rhs: FunctionParameter Float
+            41: Function (parent scopeid2):
+              lhs: FunctionParameter Float
+              rhs: FunctionParameter Float
+            42: Function (parent scopeid2):
+              lhs: FunctionParameter Float
+              rhs: FunctionParameter Float
+            43: Function (parent scopeid2):
+              lhs: FunctionParameter Named(Identifier { id: SymbolId(3), span: Span { source: SourceId(0), span: SourceSpan { offset: SourceOffset(4540), length: 1 } } })
+              rhs: FunctionParameter Named(Identifier { id: SymbolId(3), span: Span { source: SourceId(0), span: SourceSpan { offset: SourceOffset(4551), length: 1 } } })
+            44: Function (parent scopeid2):
+              lhs: FunctionParameter Named(Identifier { id: SymbolId(3), span: Span { source: SourceId(0), span: SourceSpan { offset: SourceOffset(4608), length: 1 } } })
+              rhs: FunctionParameter Named(Identifier { id: SymbolId(3), span: Span { source: SourceId(0), span: SourceSpan { offset: SourceOffset(4619), length: 1 } } })
+            45: Function (parent scopeid2):
+              lhs: FunctionParameter Named(Identifier { id: SymbolId(3), span: Span { source: SourceId(0), span: SourceSpan { offset: SourceOffset(4697), length: 1 } } })
+              rhs: FunctionParameter Named(Identifier { id: SymbolId(3), span: Span { source: SourceId(0), span: SourceSpan { offset: SourceOffset(4708), length: 1 } } })
+            46: Function (parent scopeid2):
+              lhs: FunctionParameter Named(Identifier { id: SymbolId(3), span: Span { source: SourceId(0), span: SourceSpan { offset: SourceOffset(4765), length: 1 } } })
+              rhs: FunctionParameter Named(Identifier { id: SymbolId(3), span: Span { source: SourceId(0), span: SourceSpan { offset: SourceOffset(4776), length: 1 } } })
+            47: Function (parent scopeid2):
+              lhs: FunctionParameter Named(Identifier { id: SymbolId(3), span: Span { source: SourceId(0), span: SourceSpan { offset: SourceOffset(4835), length: 1 } } })
+              rhs: FunctionParameter Named(Identifier { id: SymbolId(3), span: Span { source: SourceId(0), span: SourceSpan { offset: SourceOffset(4846), length: 1 } } })
+            48: Function (parent scopeid2):
+              lhs: FunctionParameter Named(Identifier { id: SymbolId(3), span: Span { source: SourceId(0), span: SourceSpan { offset: SourceOffset(4905), length: 1 } } })
+              rhs: FunctionParameter Named(Identifier { id: SymbolId(3), span: Span { source: SourceId(0), span: SourceSpan { offset: SourceOffset(4916), length: 1 } } })
+            49: Function (parent scopeid2):
+              lhs: FunctionParameter Int
+              rhs: FunctionParameter Int
+            50: Function (parent scopeid2):
+              lhs: FunctionParameter Int
+              rhs: FunctionParameter Int
+            51: Function (parent scopeid2):
+              lhs: FunctionParameter Int
+              rhs: FunctionParameter Int
+            52: Function (parent scopeid2):
+              lhs: FunctionParameter Int
+              rhs: FunctionParameter Int
+            53: Function (parent scopeid2):
+              lhs: FunctionParameter SizedInt(I8)
+              rhs: FunctionParameter SizedInt(I8)
+            54: Function (parent scopeid2):
+              lhs: FunctionParameter SizedInt(I8)
+              rhs: FunctionParameter SizedInt(I8)
+            55: Function (parent scopeid2):
+              lhs: FunctionParameter SizedInt(I8)
+              rhs: FunctionParameter SizedInt(I8)
+            56: Function (parent scopeid2):
+              lhs: FunctionParameter SizedInt(I8)
+              rhs: FunctionParameter SizedInt(I8)
+            57: Function (parent scopeid2):
+              lhs: FunctionParameter SizedInt(I16)
+              rhs: FunctionParameter SizedInt(I16)
+            58: Function (parent scopeid2):
+              lhs: FunctionParameter SizedInt(I16)
+              rhs: FunctionParameter SizedInt(I16)
+            59: Function (parent scopeid2):
+              lhs: FunctionParameter SizedInt(I16)
+              rhs: FunctionParameter SizedInt(I16)
+            60: Function (parent scopeid2):
+              lhs: FunctionParameter SizedInt(I16)
+              rhs: FunctionParameter SizedInt(I16)
+            61: Function (parent scopeid2):
+              lhs: FunctionParameter SizedInt(I32)
+              rhs: FunctionParameter SizedInt(I32)
+            62: Function (parent scopeid2):
+              lhs: FunctionParameter SizedInt(I32)
+              rhs: FunctionParameter SizedInt(I32)
+            63: Function (parent scopeid2):
+              lhs: FunctionParameter SizedInt(I32)
+              rhs: FunctionParameter SizedInt(I32)
+            64: Function (parent scopeid2):
+              lhs: FunctionParameter SizedInt(I32)
+              rhs: FunctionParameter SizedInt(I32)
+            65: Function (parent scopeid2):
+              lhs: FunctionParameter SizedInt(U8)
+              rhs: FunctionParameter SizedInt(U8)
+            66: Function (parent scopeid2):
+              lhs: FunctionParameter SizedInt(U8)
+              rhs: FunctionParameter SizedInt(U8)
+            67: Function (parent scopeid2):
+              lhs: FunctionParameter SizedInt(U8)
+              rhs: FunctionParameter SizedInt(U8)
+            68: Function (parent scopeid2):
+              lhs: FunctionParameter SizedInt(U8)
+              rhs: FunctionParameter SizedInt(U8)
+            69: Function (parent scopeid2):
+              lhs: FunctionParameter SizedInt(U16)
+              rhs: FunctionParameter SizedInt(U16)
+            70: Function (parent scopeid2):
+              lhs: FunctionParameter SizedInt(U16)
+              rhs: FunctionParameter SizedInt(U16)
+            71: Function (parent scopeid2):
+              lhs: FunctionParameter SizedInt(U16)
+              rhs: FunctionParameter SizedInt(U16)
+            72: Function (parent scopeid2):
+              lhs: FunctionParameter SizedInt(U16)
+              rhs: FunctionParameter SizedInt(U16)
+            73: Function (parent scopeid2):
+              lhs: FunctionParameter SizedInt(U32)
+              rhs: FunctionParameter SizedInt(U32)
+            74: Function (parent scopeid2):
+              lhs: FunctionParameter SizedInt(U32)
+              rhs: FunctionParameter SizedInt(U32)
+            75: Function (parent scopeid2):
+              lhs: FunctionParameter SizedInt(U32)
+              rhs: FunctionParameter SizedInt(U32)
+            76: Function (parent scopeid2):
+              lhs: FunctionParameter SizedInt(U32)
+              rhs: FunctionParameter SizedInt(U32)
+            77: Function (parent scopeid2):
+              lhs: FunctionParameter SizedInt(U64)
+              rhs: FunctionParameter SizedInt(U64)
+            78: Function (parent scopeid2):
+              lhs: FunctionParameter SizedInt(U64)
+              rhs: FunctionParameter SizedInt(U64)
+            79: Function (parent scopeid2):
+              lhs: FunctionParameter SizedInt(U64)
+              rhs: FunctionParameter SizedInt(U64)
+            80: Function (parent scopeid2):
+              lhs: FunctionParameter SizedInt(U64)
+              rhs: FunctionParameter SizedInt(U64)
+            81: Function (parent scopeid2):
+              lhs: FunctionParameter Float
+              rhs: FunctionParameter Float
+            82: Function (parent scopeid2):
+              lhs: FunctionParameter Float
+              rhs: FunctionParameter Float
+            83: Function (parent scopeid2):
+              lhs: FunctionParameter Float
+              rhs: FunctionParameter Float
+            84: Function (parent scopeid2):
+              lhs: FunctionParameter Float
+              rhs: FunctionParameter Float
+            85: Function (parent scopeid2):
+              b: FunctionParameter Bool
+            86: Module io (parent scopeid0):
+              print: Function functionid83
+            87: Function (parent scopeid86):
               content: FunctionParameter String
-            10: Module mem (parent scopeid0):
-              Unsized: Function functionid6
-              Sized: Function functionid7
-              malloc: Function functionid8
-              size_of: Function functionid9
+            88: Module mem (parent scopeid0):
+              Unsized: Function functionid84
+              Sized: Function functionid85
+              malloc: Function functionid86
+              size_of: Function functionid87
               Ptr: Type TypeId(0)
-            11: Type Cons (parent scopeid10):
-            12: Function (parent scopeid10):
+            89: Type Cons (parent scopeid88):
+            90: Function (parent scopeid88):
               address: FunctionParameter Int
-            13: Type Cons (parent scopeid10):
-            14: Function (parent scopeid10):
+            91: Type Cons (parent scopeid88):
+            92: Function (parent scopeid88):
               address: FunctionParameter Int
               size: FunctionParameter Int
-            15: Function (parent scopeid10):
+            93: Function (parent scopeid88):
               size: FunctionParameter Int
-            16: Expr w/ Bindings (parent scopeid15):
+            94: Expr w/ Bindings (parent scopeid93):
               allocated: Binding
-            17: Function (parent scopeid10):
-              expr: FunctionParameter Named(Identifier { id: SymbolId(10), span: Span { source: SourceId(2), span: SourceSpan { offset: SourceOffset(246), length: 1 } } })
-            18: Module test (parent scopeid0):
-              main: Function functionid10
-              symbolid2: Import add
-            19: Function (parent scopeid18):
+            95: Function (parent scopeid88):
+              expr: FunctionParameter Named(Identifier { id: SymbolId(3), span: Span { source: SourceId(2), span: SourceSpan { offset: SourceOffset(246), length: 1 } } })
+            96: Module math (parent scopeid0):
+              sqrt: Function functionid88
+              floor: Function functionid89
+              to_float: Function functionid90
+              to_int: Function functionid91
+            97: Function (parent scopeid96):
+              x: FunctionParameter Float
+            98: Function (parent scopeid96):
+              x: FunctionParameter Float
+            99: Function (parent scopeid96):
+              x: FunctionParameter Int
+            100: Function (parent scopeid96):
+              x: FunctionParameter Float
+            101: Module test (parent scopeid0):
+              main: Function functionid92
+              symbolid4: Import add
+            102: Function (parent scopeid101):
+        "#]],
+    );
+}
+
+#[test]
+fn lambdas_capture_locals_from_enclosing_scopes() {
+    check(
+        r#"
+        fn adder(a in 'int) returns 'fn('int) returns 'int
+          let b = 1;
+          \(x in 'int) => \(y in 'int) => + a + x + y b
+        "#,
+        expect![[r#"
+            __Scopes__
+            0: Root (parent none):
+              test: Module ModuleId(0)
+            1: Module test (parent scopeid0):
+              adder: Function functionid0
+            2: Function (parent scopeid1):
+              a: FunctionParameter Int
+            3: Expr w/ Bindings (parent scopeid2):
+              b: Binding
+            4: Lambda (parent scopeid3):
+              x: FunctionParameter Int
+            5: Lambda (parent scopeid4):
+              y: FunctionParameter Int
+            __Captures__
+            0: [a, x, b]
+            1: [a, b]
+        "#]],
+    );
+}
+
+#[test]
+fn bind_type_alias() {
+    check(
+        "type alias Id = 'int
+        fn next(id in 'Id) returns 'Id id",
+        expect![[r#"
+            __Scopes__
+            0: Root (parent none):
+              test: Module ModuleId(0)
+            1: Module test (parent scopeid0):
+              next: Function functionid0
+              Id: TypeAlias TypeAliasId(0)
+            2: Function (parent scopeid1):
+              id: FunctionParameter Named(Identifier { id: SymbolId(1), span: Span { source: SourceId(0), span: SourceSpan { offset: SourceOffset(44), length: 2 } } })
         "#]],
     );
 }
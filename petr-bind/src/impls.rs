@@ -1,7 +1,10 @@
-use petr_ast::{Commented, Expression, ExpressionWithBindings, FunctionDeclaration, ImportStatement, TypeDeclaration};
-use petr_utils::{Identifier, SpannedItem, TypeId};
+use petr_ast::{
+    Binding, BindingPattern, Commented, Expression, ExpressionWithBindings, FunctionDeclaration, ImplementationDeclaration, ImportStatement,
+    InterfaceDeclaration, Lambda, Match, TypeAlias, TypeDeclaration,
+};
+use petr_utils::{Identifier, InterfaceId, SpannedItem, TypeId};
 
-use crate::{binder::ScopeKind, Bind, Binder, FunctionId, ScopeId};
+use crate::{binder::ScopeKind, Bind, Binder, FunctionId, ScopeId, TypeAliasId};
 
 impl Bind for SpannedItem<&TypeDeclaration> {
     type Output = Option<(Identifier, TypeId)>;
@@ -25,6 +28,39 @@ impl Bind for SpannedItem<TypeDeclaration> {
     }
 }
 
+impl Bind for SpannedItem<&TypeAlias> {
+    type Output = Option<(Identifier, TypeAliasId)>;
+
+    fn bind(
+        &self,
+        binder: &mut Binder,
+    ) -> Self::Output {
+        binder.insert_type_alias(self)
+    }
+}
+
+impl Bind for SpannedItem<&InterfaceDeclaration> {
+    type Output = Option<(Identifier, InterfaceId)>;
+
+    fn bind(
+        &self,
+        binder: &mut Binder,
+    ) -> Self::Output {
+        binder.insert_interface(self)
+    }
+}
+
+impl Bind for SpannedItem<&ImplementationDeclaration> {
+    type Output = ();
+
+    fn bind(
+        &self,
+        binder: &mut Binder,
+    ) -> Self::Output {
+        binder.insert_implementation(self)
+    }
+}
+
 impl Bind for Expression {
     // the scope that the expression lives in
     type Output = ();
@@ -33,7 +69,8 @@ impl Bind for Expression {
         &self,
         binder: &mut Binder,
     ) -> Self::Output {
-        // only lists get their own scope for now
+        // only expressions with bindings, match arms, and lambdas get their own scope for now,
+        // but any expression can contain one of those
         match self {
             Expression::List(list) => {
                 list.bind(binder);
@@ -44,12 +81,79 @@ impl Bind for Expression {
                 expr_id,
             }) => binder.with_scope(ScopeKind::ExpressionWithBindings, |binder, scope_id| {
                 for binding in bindings.iter() {
-                    binder.insert_binding_into_current_scope(binding.name.id, binding.name.span().with_item(binding.clone()));
+                    binding.val.bind(binder);
+                    for name in binding.pattern.names() {
+                        binder.insert_binding_into_current_scope(name.id, name.span().with_item(binding.clone()));
+                    }
                 }
                 expression.bind(binder);
                 binder.insert_expression(*expr_id, scope_id);
             }),
-            _ => (),
+            Expression::Match(Match { scrutinee, arms }) => {
+                scrutinee.bind(binder);
+                for arm in arms.iter() {
+                    let arm = arm.item();
+                    binder.with_scope(ScopeKind::MatchArm, |binder, scope_id| {
+                        // pattern bindings are destructured out of the scrutinee, so that is the
+                        // expression they are bound to
+                        for name in arm.pattern.item().bindings() {
+                            let binding = Binding {
+                                pattern: BindingPattern::Name(name),
+                                val:     (**scrutinee).clone(),
+                            };
+                            binder.insert_binding_into_current_scope(name.id, name.span().with_item(binding));
+                        }
+                        arm.body.bind(binder);
+                        binder.insert_expression(arm.expr_id, scope_id);
+                    })
+                }
+            },
+            Expression::Operator(op) => {
+                op.lhs.bind(binder);
+                op.rhs.bind(binder);
+            },
+            Expression::UnaryOperator(op) => op.operand.bind(binder),
+            Expression::FieldAccess(access) => access.target.bind(binder),
+            Expression::RecordUpdate(update) => {
+                update.target.bind(binder);
+                for (_, value) in update.fields.iter() {
+                    value.bind(binder);
+                }
+            },
+            Expression::FunctionCall(call) => {
+                // calls through a local function value reference that local
+                if let [name] = &call.func_name.identifiers[..] {
+                    binder.reference_local(*name);
+                }
+                for arg in call.args.iter() {
+                    arg.bind(binder);
+                }
+            },
+            Expression::Lambda(Lambda { parameters, body, expr_id }) => binder.with_lambda_scope(*expr_id, |binder, _scope_id| {
+                for param in parameters.iter() {
+                    binder.insert_function_parameter_into_current_scope(param.name.id, param.name.span().with_item(param.ty.clone()));
+                }
+                body.bind(binder);
+            }),
+            Expression::Variable(name) => binder.reference_local(*name),
+            Expression::Tuple(elements) => {
+                for element in elements.iter() {
+                    element.bind(binder);
+                }
+            },
+            Expression::IntrinsicCall(call) => {
+                for arg in call.args.iter() {
+                    arg.bind(binder);
+                }
+            },
+            Expression::If(if_expr) => {
+                if_expr.condition.bind(binder);
+                if_expr.then_branch.bind(binder);
+                if let Some(else_branch) = &if_expr.else_branch {
+                    else_branch.bind(binder);
+                }
+            },
+            Expression::Literal(_) | Expression::Hole(_) | Expression::TypeConstructor(..) | Expression::InterfaceMethod(..) => (),
         }
     }
 }
@@ -0,0 +1,159 @@
+//! Long-form explanations of the codes of petr's diagnostics, like `E0202`, for `pete explain`.
+//! Each explanation is a markdown file in `petr-cli/explanations`. The petr examples in them are
+//! compiled by the tests below, so that they keep doing what the explanations say they do:
+//! - ` ```petr ` examples must compile and run without any diagnostics
+//! - ` ```petr,compile_fail ` examples must fail to compile, reporting the explained code
+//! - ` ```petr,warns ` examples must compile and run, reporting the explained code as a warning
+
+macro_rules! explanations {
+    ($($code:ident),* $(,)?) => {
+        /// Every explained code, and its explanation
+        const EXPLANATIONS: &[(&str, &str)] = &[$((stringify!($code), include_str!(concat!("../explanations/", stringify!($code), ".md")))),*];
+    };
+}
+
+explanations!(
+    E0101, E0102, E0103, E0104, E0105, E0106, E0107, E0108, E0109, E0201, E0202, E0203, E0204, E0205, E0206, E0207, E0208, E0209, E0210, E0211,
+    E0212, E0213, E0214, E0215, E0216, E0217, E0218, E0301, E0302, E0303, E0304, E0305, E0306, E0307, E0308, E0309, E0310, E0311, E0312, E0313,
+    E0314, E0315, E0316, E0317, E0318, E0319, E0320, E0401, E0402, E0403, E0501, E0502, E0503, E0504,
+);
+
+/// The explanation of a diagnostic code, like `E0202`. Codes aren't case sensitive.
+pub fn explanation(code: &str) -> Option<&'static str> {
+    EXPLANATIONS
+        .iter()
+        .find(|(explained, _)| explained.eq_ignore_ascii_case(code))
+        .map(|(_, explanation)| *explanation)
+}
+
+#[cfg(test)]
+mod tests {
+    use miette::Diagnostic;
+    use petr_api::*;
+
+    use super::*;
+
+    /// What happened when an example was compiled and run
+    #[derive(Debug)]
+    struct Outcome {
+        /// The codes of every diagnostic reported, errors and warnings alike
+        codes: Vec<String>,
+        ran:   bool,
+    }
+
+    fn codes<T: Diagnostic>(diagnostics: &[T]) -> Vec<String> {
+        diagnostics
+            .iter()
+            .map(|diagnostic| diagnostic.code().map(|code| code.to_string()).unwrap_or_default())
+            .collect()
+    }
+
+    /// Compiles `source` alongside the standard library, and runs it if it compiles. Compilation
+    /// stops at the first stage which reports errors.
+    fn compile_and_run(source: &str) -> Outcome {
+        let failed = |codes| Outcome { codes, ran: false };
+
+        let mut sources = petr_stdlib::stdlib();
+        sources.push(("main", source));
+        let (ast, parse_errs, interner, _) = Parser::new(sources).into_result();
+        if !parse_errs.is_empty() {
+            return failed(codes(&parse_errs));
+        }
+
+        let (resolution_errs, resolved) = resolve_symbols(ast, interner, Default::default());
+        if !resolution_errs.is_empty() {
+            return failed(codes(&resolution_errs));
+        }
+
+        let solution = match type_check(resolved) {
+            Ok(solution) => solution,
+            Err(type_errs) => return failed(codes(&type_errs)),
+        };
+        let mut reported = codes(solution.warnings());
+
+        if let Err(effect_errs) = check_effects(&solution) {
+            reported.extend(codes(&effect_errs));
+            return failed(reported);
+        }
+
+        let lowerer = match Lowerer::new(solution) {
+            Ok(lowerer) => lowerer,
+            Err(lowering_err) => {
+                reported.extend(codes(&[lowering_err]));
+                return failed(reported);
+            },
+        };
+        if !lowerer.errors().is_empty() {
+            reported.extend(codes(lowerer.errors()));
+            return failed(reported);
+        }
+
+        let mut diagnostics = Diagnostics::default();
+        let (data, instructions) = lowerer.finalize(&mut diagnostics);
+        reported.extend(
+            diagnostics
+                .reported()
+                .iter()
+                .map(|reported| reported.diagnostic().code().map(|code| code.to_string()).unwrap_or_default()),
+        );
+        Outcome {
+            codes: reported,
+            ran:   Vm::new(instructions, data).run().is_ok(),
+        }
+    }
+
+    /// The petr examples in an explanation, with the attributes of their code blocks
+    fn examples(explanation: &str) -> Vec<(&str, String)> {
+        let mut examples = Vec::new();
+        let mut lines = explanation.lines();
+        while let Some(line) = lines.next() {
+            let Some(attributes) = line.strip_prefix("```petr") else { continue };
+            let example = lines.by_ref().take_while(|line| *line != "```").collect::<Vec<_>>().join("\n");
+            examples.push((attributes.trim_start_matches(','), example));
+        }
+        examples
+    }
+
+    #[test]
+    fn explanations_start_with_their_code() {
+        for (code, explanation) in EXPLANATIONS {
+            assert!(
+                explanation.starts_with(&format!("# {code}: ")),
+                "the explanation of {code} should start with a `# {code}: ` heading"
+            );
+        }
+    }
+
+    #[test]
+    fn explanations_are_found_by_code() {
+        assert!(explanation("E0202").is_some_and(|explanation| explanation.starts_with("# E0202: ")));
+        assert!(explanation("e0202").is_some());
+        assert!(explanation("E9999").is_none());
+    }
+
+    #[test]
+    fn explanation_examples() {
+        let mut failures = Vec::new();
+        for (code, explanation) in EXPLANATIONS {
+            for (attributes, example) in examples(explanation) {
+                let outcome = compile_and_run(&example);
+                let reported = outcome.codes.iter().any(|reported| reported == code);
+                let expected = match attributes {
+                    "" => "run without diagnostics",
+                    "compile_fail" => "fail to compile with the explained code",
+                    "warns" => "run and warn with the explained code",
+                    other => panic!("unknown example attributes `{other}` in the explanation of {code}"),
+                };
+                let as_expected = match attributes {
+                    "" => outcome.ran && outcome.codes.is_empty(),
+                    "compile_fail" => !outcome.ran && reported,
+                    _ => outcome.ran && reported,
+                };
+                if !as_expected {
+                    failures.push(format!("this example of {code} should {expected}, but got {outcome:?}:\n{example}"));
+                }
+            }
+        }
+        assert!(failures.is_empty(), "{}", failures.join("\n\n"));
+    }
+}
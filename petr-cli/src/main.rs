@@ -10,9 +10,12 @@ use petr_pkg::BuildPlan;
 use petr_resolve::Dependency;
 use termcolor::{ColorChoice, ColorSpec, StandardStream, WriteColor};
 
+mod explain;
+
 pub mod error {
+    use miette::Diagnostic;
     use thiserror::Error;
-    #[derive(Error, Debug)]
+    #[derive(Error, Debug, Diagnostic)]
     pub enum PeteError {
         #[error(transparent)]
         Io(#[from] std::io::Error),
@@ -22,8 +25,16 @@ pub mod error {
         Pkg(#[from] petr_pkg::error::PkgError),
         #[error("Failed to lower code")]
         FailedToLower,
+        #[error("Program contained syntax or resolution errors")]
+        FailedToResolve,
         #[error("Program contained type errors")]
         FailedToTypeCheck,
+        #[error("Program contained effect errors")]
+        FailedToCheckEffects,
+        #[error("Program contained errors")]
+        FailedToCompile,
+        #[error("No diagnostic has the code {0}")]
+        UnknownDiagnosticCode(String),
     }
 }
 
@@ -39,15 +50,21 @@ enum Commands {
     #[command(about = "Run the program on a target")]
     Run {
         #[arg(short, long, help = "Target to run on", value_parser = ["vm", "native"], default_value = "vm")]
-        target: String,
+        target:        String,
         #[arg(
             long,
             help = "Path to the directory which contains the pete.toml manifest and src subdir",
             default_value = "."
         )]
-        path:   PathBuf,
+        path:          PathBuf,
         #[arg(short = 'm', long, help = "Print the timings table")]
-        time:   bool,
+        time:          bool,
+        #[arg(long, help = "Print what the type constraint solver did to stderr", value_parser = ["text", "json"])]
+        trace_types:   Option<String>,
+        #[arg(long, help = "Report warnings as errors")]
+        deny_warnings: bool,
+        #[arg(long, help = "How to print diagnostics", value_parser = ["human", "json"], default_value = "human")]
+        error_format:  String,
     },
     #[command(about = "Print the IR of the program to stdout")]
     Ir {
@@ -56,7 +73,13 @@ enum Commands {
             help = "Path to the directory which contains the pete.toml manifest and src subdir",
             default_value = "."
         )]
-        path: PathBuf,
+        path:          PathBuf,
+        #[arg(long, help = "Print what the type constraint solver did to stderr", value_parser = ["text", "json"])]
+        trace_types:   Option<String>,
+        #[arg(long, help = "Report warnings as errors")]
+        deny_warnings: bool,
+        #[arg(long, help = "How to print diagnostics", value_parser = ["human", "json"], default_value = "human")]
+        error_format:  String,
     },
     #[command(about = "Format all sources in the project")]
     Fmt {
@@ -78,17 +101,52 @@ enum Commands {
         )]
         path: PathBuf,
     },
+    #[command(about = "Explain a diagnostic code, like E0202, with examples")]
+    Explain {
+        #[arg(help = "The code of the diagnostic")]
+        code: String,
+    },
 }
 
 fn main() -> Result<(), error::PeteError> {
     let cli = Cli::parse();
+    let error_format = match &cli.command {
+        Commands::Run { error_format, .. } | Commands::Ir { error_format, .. } => error_format.clone(),
+        _ => "human".to_string(),
+    };
+    match run(cli) {
+        // the diagnostics before the error were printed as JSON objects, so it is printed as one too,
+        // instead of as a line of text after them
+        Err(error) if error_format == "json" => {
+            let mut diagnostics = Diagnostics::default();
+            diagnostics.unspanned_error(error);
+            render_diagnostics(&mut diagnostics, &Default::default(), &error_format);
+            std::process::exit(1);
+        },
+        result => result,
+    }
+}
 
+fn run(cli: Cli) -> Result<(), error::PeteError> {
     match cli.command {
-        Commands::Run { target, path, time } => {
+        Commands::Run {
+            target,
+            path,
+            time,
+            trace_types,
+            deny_warnings,
+            error_format,
+        } => {
             let mut timings = petr_profiling::Timings::default();
-            let lowerer = compile(path, &mut timings)?;
-
-            let (data, instructions) = lowerer.finalize();
+            let mut diagnostics = diagnostics_for_project(&path, deny_warnings);
+            let (lowerer, source_map) = compile(path, &mut timings, trace_types.as_deref(), &error_format, &mut diagnostics)?;
+
+            let return_ty = lowerer.entry_point_return_type().cloned();
+            let (data, instructions) = lowerer.finalize(&mut diagnostics);
+            render_diagnostics(&mut diagnostics, &source_map, &error_format);
+            if diagnostics.has_errors() {
+                return Err(PeteError::FailedToCompile);
+            }
 
             timings.end("full compile");
 
@@ -96,8 +154,13 @@ fn main() -> Result<(), error::PeteError> {
             match target.to_lowercase().as_str() {
                 "vm" => {
                     let vm = Vm::new(instructions, data);
-                    let result = vm.run().expect("Failed to run vm");
-                    println!("VM terminated with stack:\n{:#?}", result);
+                    let (value, stack, logs) = vm.run().expect("Failed to run vm");
+                    // without an entry point, or one whose return type is unknown, the value is printed as it is
+                    let value = match return_ty {
+                        Some(ty) => value.render(&ty),
+                        None => value.inner().to_string(),
+                    };
+                    println!("VM terminated with value {value} and stack:\n{:#?}", (stack, logs));
                 },
                 "native" => todo!(),
                 _ => {
@@ -132,19 +195,75 @@ fn main() -> Result<(), error::PeteError> {
                 println!("{}", path.to_string_lossy());
             }
         },
-        Commands::Ir { path } => {
-            let lowerer = compile(path, &mut petr_profiling::Timings::default())?;
+        Commands::Ir {
+            path,
+            trace_types,
+            deny_warnings,
+            error_format,
+        } => {
+            let mut diagnostics = diagnostics_for_project(&path, deny_warnings);
+            let (lowerer, _) = compile(
+                path,
+                &mut petr_profiling::Timings::default(),
+                trace_types.as_deref(),
+                &error_format,
+                &mut diagnostics,
+            )?;
 
             println!("{}", lowerer.pretty_print());
         },
+        Commands::Explain { code } => match explain::explanation(&code) {
+            Some(explanation) => print!("{explanation}"),
+            None => return Err(PeteError::UnknownDiagnosticCode(code)),
+        },
     }
     Ok(())
 }
 
+/// A diagnostic sink which reports warnings at the levels configured in the `[lints]` table of
+/// the project's manifest
+fn diagnostics_for_project(
+    path: &Path,
+    deny_warnings: bool,
+) -> Diagnostics {
+    let manifest = petr_pkg::manifest::find_manifest(Some(path.to_path_buf())).expect("Failed to find manifest");
+    let lint_levels = manifest.lints.into_iter().map(|(lint, level)| (lint, level.into())).collect();
+    Diagnostics::new(lint_levels, deny_warnings)
+}
+
+/// Renders the diagnostics reported since they were last rendered. If `error_format` is `json`,
+/// each diagnostic is printed as a JSON object on its own line instead.
+fn render_diagnostics(
+    diagnostics: &mut Diagnostics,
+    sources: &IndexMap<SourceId, (&'static str, &'static str)>,
+    error_format: &str,
+) {
+    match error_format {
+        "json" => {
+            for reported in diagnostics.take() {
+                eprintln!("{}", reported.to_json(sources));
+            }
+        },
+        _ => {
+            for report in diagnostics.take_reports(sources) {
+                eprintln!("{:?}", report);
+            }
+        },
+    }
+}
+
+/// Compiles the project at `path`, reporting diagnostics to `diagnostics` and rendering them in
+/// `error_format`. Returns the lowered program and the sources it was compiled from. If
+/// `trace_types` is `text` or `json`, what the type constraint solver did is printed to stderr in
+/// that format, so it doesn't mix with the output of the program.
+#[allow(clippy::type_complexity)]
 pub fn compile(
     path: PathBuf,
     timings: &mut petr_profiling::Timings,
-) -> Result<Lowerer, crate::error::PeteError> {
+    trace_types: Option<&str>,
+    error_format: &str,
+    diagnostics: &mut Diagnostics,
+) -> Result<(Lowerer, IndexMap<SourceId, (&'static str, &'static str)>), crate::error::PeteError> {
     timings.start("full compile");
     timings.start("load project and dependencies");
     let (lockfile, buf, build_plan) = load_project_and_dependencies(&path)?;
@@ -153,10 +272,14 @@ pub fn compile(
     fs::write(lockfile_path, lockfile_toml).expect("Failed to write lockfile");
     timings.end("load project and dependencies");
 
-    // convert pathbufs into strings for the parser
-    let buf = buf
+    // convert pathbufs into strings for the parser. The stdlib is parsed along with the user's
+    // code, like the playground does. As a dependency, its modules would be nested under a second
+    // `std`, and its expression ids, which each parser numbers from zero, would clash with the
+    // user's in the binder.
+    let buf = petr_stdlib::stdlib()
         .into_iter()
-        .map(|(pathbuf, s)| (pathbuf.to_string_lossy().to_string(), s))
+        .map(|(name, source)| (name.to_string(), source.to_string()))
+        .chain(buf.into_iter().map(|(pathbuf, s)| (pathbuf.to_string_lossy().to_string(), s)))
         .collect::<Vec<_>>();
 
     timings.start("parsing stage");
@@ -164,24 +287,12 @@ pub fn compile(
     // parse
     // construct an interner for symbols, which will be used throughout the whole compilation.
     let parser = Parser::new(buf);
-    let (ast, mut parse_errs, interner, source_map) = parser.into_result();
+    let (ast, mut parse_errs, mut interner, mut source_map) = parser.into_result();
 
     timings.end("parse user code");
     timings.start("parse dependencies");
 
-    let mut dependencies = Vec::with_capacity(build_plan.items.len() + 1);
-
-    // add the stdlib
-    let parser = Parser::new_with_existing_interner_and_source_map(petr_stdlib::stdlib(), interner, source_map);
-    let (dep_ast, mut new_parse_errs, mut interner, mut source_map) = parser.into_result();
-    parse_errs.append(&mut new_parse_errs);
-
-    dependencies.push(Dependency {
-        key:          "stdlib".to_string(),
-        name:         "std".into(),
-        dependencies: vec![],
-        ast:          dep_ast,
-    });
+    let mut dependencies = Vec::with_capacity(build_plan.items.len());
 
     for item in build_plan.items {
         let (lockfile, buf, _build_plan) = load_project_and_dependencies(&item.path_to_source)?;
@@ -214,39 +325,71 @@ pub fn compile(
 
     timings.end("parse dependencies");
     timings.end("parsing stage");
+    diagnostics.errors(parse_errs);
 
     // resolve symbols
     timings.start("symbol resolution");
     let (resolution_errs, resolved) = petr_resolve::resolve_symbols(ast, interner, dependencies);
     timings.end("symbol resolution");
+    diagnostics.errors(resolution_errs);
+    // type checking a program which didn't parse or resolve would only report errors caused by the
+    // earlier ones, and lowering it could fail in ways which look like compiler bugs
+    if diagnostics.has_errors() {
+        render_diagnostics(diagnostics, &source_map, error_format);
+        return Err(PeteError::FailedToResolve);
+    }
 
     timings.start("type check");
     // type check
-    let res = petr_typecheck::type_check(resolved);
+    let res = match trace_types {
+        Some(format) => {
+            let (res, trace) = petr_typecheck::type_check_with_trace(resolved);
+            match format {
+                "json" => eprintln!("{}", trace.render_json(&source_map)),
+                _ => eprint!("{}", trace.render_text(&source_map)),
+            }
+            res
+        },
+        None => petr_typecheck::type_check(resolved),
+    };
 
     timings.end("type check");
     let type_solution = match res {
         Ok(o) => o,
         Err(e) => {
-            render_errors(parse_errs, &source_map);
-            render_errors(e, &source_map);
+            diagnostics.errors(e);
+            render_diagnostics(diagnostics, &source_map, error_format);
             return Err(PeteError::FailedToTypeCheck);
         },
     };
+    type_solution.report_warnings(diagnostics);
+
+    timings.start("effect check");
+    let effects = petr_effects::check_effects(&type_solution);
+    timings.end("effect check");
+    if let Err(e) = effects {
+        diagnostics.errors(e);
+        render_diagnostics(diagnostics, &source_map, error_format);
+        return Err(PeteError::FailedToCheckEffects);
+    }
 
     timings.start("lowering");
     let lowerer: Lowerer = match Lowerer::new(type_solution) {
         Ok(l) => l,
         Err(e) => {
-            eprintln!("Failed to lower: {:?}", e);
+            diagnostics.error(e);
+            render_diagnostics(diagnostics, &source_map, error_format);
             return Err(PeteError::FailedToLower);
         },
     };
     timings.end("lowering");
+    diagnostics.errors(lowerer.errors().to_vec());
 
-    render_errors(parse_errs, &source_map);
-    render_errors(resolution_errs, &source_map);
-    Ok(lowerer)
+    render_diagnostics(diagnostics, &source_map, error_format);
+    if diagnostics.has_errors() {
+        return Err(PeteError::FailedToCompile);
+    }
+    Ok((lowerer, source_map))
 }
 
 #[allow(clippy::type_complexity)]
@@ -302,3 +445,50 @@ pub fn load_files(path: &Path) -> Vec<(PathBuf, String)> {
     read_petr_files(&path.join("src"), &mut buf);
     buf
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Writes a project whose only source is `src/main.pt` to a fresh directory
+    fn write_project(
+        name: &str,
+        main: &str,
+    ) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("petr-cli-{name}-{}", std::process::id()));
+        fs::create_dir_all(path.join("src")).expect("Failed to create project");
+        let manifest = format!("author = \"test\"\nlicense = \"MIT\"\nname = \"{name}\"\nformatter = {{}}\ndependencies = {{}}\n");
+        fs::write(path.join("pete.toml"), manifest).expect("Failed to write manifest");
+        fs::write(path.join("src").join("main.pt"), main).expect("Failed to write source");
+        path
+    }
+
+    #[test]
+    fn compiled_programs_can_use_the_stdlib() {
+        let path = write_project(
+            "stdlib",
+            "fn main() returns 'int\n  let pointer = ~std.mem.malloc(2)\n  + ~std.ops.add(1, 2) 3\n",
+        );
+        let mut diagnostics = diagnostics_for_project(&path, false);
+        let (lowerer, _) =
+            compile(path.clone(), &mut petr_profiling::Timings::default(), None, "human", &mut diagnostics).expect("Failed to compile");
+        let (data, instructions) = lowerer.finalize(&mut diagnostics);
+        assert!(!diagnostics.has_errors());
+
+        let (value, ..) = Vm::new(instructions, data).run().expect("Failed to run vm");
+        assert_eq!(value.inner(), 6);
+        fs::remove_dir_all(path).expect("Failed to remove project");
+    }
+
+    #[test]
+    fn resolution_errors_stop_compilation_before_type_checking() {
+        let path = write_project(
+            "missing_argument",
+            "type Point = Point x 'int y 'int\n\nfn main() returns 'int\n  let point = ~Point x = 1\n  point.x\n",
+        );
+        let mut diagnostics = diagnostics_for_project(&path, false);
+        let result = compile(path.clone(), &mut petr_profiling::Timings::default(), None, "human", &mut diagnostics);
+        assert!(matches!(result, Err(PeteError::FailedToResolve)));
+        fs::remove_dir_all(path).expect("Failed to remove project");
+    }
+}
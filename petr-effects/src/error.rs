@@ -0,0 +1,15 @@
+use miette::Diagnostic;
+use thiserror::Error;
+
+#[derive(Error, Debug, Diagnostic, PartialEq, Clone)]
+pub enum EffectError {
+    #[error("unknown effect `{0}`")]
+    #[diagnostic(code(E0401), help("the known effects are `io` and `alloc`"))]
+    UnknownEffect(String),
+    #[error("function `{function}` is declared pure, but {cause} has the `{effect}` effect")]
+    #[diagnostic(code(E0402), help("declare the effect with `uses {effect}` instead of `pure`"))]
+    EffectInPureFunction { function: String, effect: String, cause: String },
+    #[error("{cause} has the `{effect}` effect, which function `{function}` does not declare")]
+    #[diagnostic(code(E0403), help("add `{effect}` to the `uses` annotation of `{function}`"))]
+    UndeclaredEffect { function: String, effect: String, cause: String },
+}
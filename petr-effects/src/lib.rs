@@ -0,0 +1,295 @@
+//! Infers the effects of every function, after type checking. An expression's effects are those
+//! of its subexpressions, plus its own: `@puts` performs IO, `@malloc` allocates, and a call has
+//! the effects of the function it calls. Functions with an effect annotation, like `pure` or
+//! `uses io`, are checked against the effects they actually have.
+
+use std::{
+    collections::{btree_map::Entry, BTreeMap, BTreeSet},
+    fmt,
+};
+
+pub use error::EffectError;
+use petr_typecheck::{FunctionId, TypeSolution, TypedExpr, TypedExprKind};
+use petr_utils::{Identifier, Span, SpannedItem};
+
+mod error;
+#[cfg(test)]
+mod tests;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Effect {
+    /// Reading from or writing to the outside world, like `@puts`
+    Io,
+    /// Allocating memory on the heap, like `@malloc`
+    Alloc,
+}
+
+impl Effect {
+    /// The effect with the name used in `uses` annotations, if there is one
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "io" => Some(Effect::Io),
+            "alloc" => Some(Effect::Alloc),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Effect {
+    fn fmt(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+    ) -> fmt::Result {
+        match self {
+            Effect::Io => write!(f, "io"),
+            Effect::Alloc => write!(f, "alloc"),
+        }
+    }
+}
+
+/// Where a function first picks up an effect
+#[derive(Debug, Clone, Copy)]
+enum Cause {
+    Intrinsic(&'static str, Span),
+    Call(FunctionId, Span),
+}
+
+impl Cause {
+    fn span(&self) -> Span {
+        match self {
+            Cause::Intrinsic(_, span) | Cause::Call(_, span) => *span,
+        }
+    }
+}
+
+pub struct EffectSolution {
+    effects: BTreeMap<FunctionId, BTreeSet<Effect>>,
+}
+
+impl EffectSolution {
+    /// The effects that calling `function` may have
+    pub fn effects_of(
+        &self,
+        function: FunctionId,
+    ) -> impl Iterator<Item = Effect> + '_ {
+        self.effects.get(&function).into_iter().flatten().copied()
+    }
+}
+
+/// Infers the effects of every function in the solution, and checks them against the functions'
+/// effect annotations.
+pub fn check_effects(solution: &TypeSolution) -> Result<EffectSolution, Vec<SpannedItem<EffectError>>> {
+    let mut checker = EffectChecker {
+        solution,
+        effects: Default::default(),
+        calls: Default::default(),
+    };
+    for (id, function) in solution.functions() {
+        let mut effects = BTreeMap::new();
+        let mut calls = Vec::new();
+        checker.collect(&function.body, &mut effects, &mut calls);
+        checker.effects.insert(*id, effects);
+        checker.calls.insert(*id, calls);
+    }
+    checker.propagate();
+
+    let errors = checker.check_annotations();
+    if !errors.is_empty() {
+        return Err(errors);
+    }
+
+    Ok(EffectSolution {
+        effects: checker
+            .effects
+            .into_iter()
+            .map(|(id, effects)| (id, effects.into_keys().collect()))
+            .collect(),
+    })
+}
+
+struct EffectChecker<'a> {
+    solution: &'a TypeSolution,
+    /// The effects of each function, and what causes them
+    effects:  BTreeMap<FunctionId, BTreeMap<Effect, Cause>>,
+    /// The functions each function calls, or uses as a value
+    calls:    BTreeMap<FunctionId, Vec<(FunctionId, Span)>>,
+}
+
+impl EffectChecker<'_> {
+    /// Collects the effects that `expr` has itself, and the functions it calls.
+    fn collect(
+        &self,
+        expr: &TypedExpr,
+        effects: &mut BTreeMap<Effect, Cause>,
+        calls: &mut Vec<(FunctionId, Span)>,
+    ) {
+        use TypedExprKind::*;
+        match &expr.kind {
+            FunctionCall { func, args, .. } => {
+                calls.push((*func, expr.span));
+                for (_, arg) in args {
+                    self.collect(arg, effects, calls);
+                }
+            },
+            // a function that is used as a value could be called anywhere the value ends up, so
+            // the function using it takes on its effects
+            FunctionValue { signature, .. } => calls.push((signature.0, expr.span)),
+            // the body of an interface method runs one of the implementations of the method
+            InterfaceMethod { interface, method, .. } => {
+                calls.extend(self.solution.implementations_of(*interface, *method).map(|func| (func, expr.span)))
+            },
+            Intrinsic { intrinsic, .. } => {
+                use petr_typecheck::Intrinsic::*;
+                match intrinsic {
+                    Puts(arg) => {
+                        effects.entry(Effect::Io).or_insert(Cause::Intrinsic("@puts", expr.span));
+                        self.collect(arg, effects, calls);
+                    },
+                    Malloc(arg) => {
+                        effects.entry(Effect::Alloc).or_insert(Cause::Intrinsic("@malloc", expr.span));
+                        self.collect(arg, effects, calls);
+                    },
+                    SizeOf(arg) | Not(arg) | ConvertTo(_, arg) | ToFloat(arg) | Sqrt(arg) | Floor(arg) => self.collect(arg, effects, calls),
+                    Add(lhs, rhs)
+                    | Multiply(lhs, rhs)
+                    | Divide(lhs, rhs)
+                    | Subtract(lhs, rhs)
+                    | Equals(lhs, rhs)
+                    | NotEquals(lhs, rhs)
+                    | LessThan(lhs, rhs)
+                    | GreaterThan(lhs, rhs)
+                    | LessThanOrEquals(lhs, rhs)
+                    | GreaterThanOrEquals(lhs, rhs)
+                    | And(lhs, rhs)
+                    | Or(lhs, rhs) => {
+                        self.collect(lhs, effects, calls);
+                        self.collect(rhs, effects, calls);
+                    },
+                }
+            },
+            List { elements, .. } | Tuple { elements, .. } => {
+                for element in elements {
+                    self.collect(element, effects, calls);
+                }
+            },
+            ExprWithBindings { bindings, expression } => {
+                for (_, binding) in bindings {
+                    self.collect(binding, effects, calls);
+                }
+                self.collect(expression, effects, calls);
+            },
+            TypeConstructor { args, .. } => {
+                for arg in args.iter() {
+                    self.collect(arg, effects, calls);
+                }
+            },
+            If {
+                condition,
+                then_branch,
+                else_branch,
+            } => {
+                self.collect(condition, effects, calls);
+                self.collect(then_branch, effects, calls);
+                self.collect(else_branch, effects, calls);
+            },
+            Match { scrutinee, arms, .. } => {
+                self.collect(scrutinee, effects, calls);
+                for arm in arms {
+                    self.collect(&arm.body, effects, calls);
+                }
+            },
+            // like function values, a lambda's effects belong to the function that creates it
+            Lambda { body, .. } => self.collect(body, effects, calls),
+            GeneralizedLambda { lambda } => self.collect(lambda, effects, calls),
+            // an instance has the same effects as the lambda it was instantiated from
+            Instantiation { .. } => (),
+            ClosureCall { callee, args, .. } => {
+                self.collect(callee, effects, calls);
+                for arg in args {
+                    self.collect(arg, effects, calls);
+                }
+            },
+            FieldAccess { target, .. } => self.collect(target, effects, calls),
+            RecordUpdate { target, fields, .. } => {
+                self.collect(target, effects, calls);
+                for (_, value) in fields {
+                    self.collect(value, effects, calls);
+                }
+            },
+            Literal { .. } | Unit | Variable { .. } | Hole { .. } | ErrorRecovery(_) => (),
+        }
+    }
+
+    /// Gives each function the effects of the functions it calls, until no function gains any
+    /// more effects. Functions may call each other recursively, so this can take several passes.
+    fn propagate(&mut self) {
+        loop {
+            let mut changed = false;
+            for (caller, calls) in &self.calls {
+                for (callee, span) in calls {
+                    let callee_effects = self.effects[callee].keys().copied().collect::<Vec<_>>();
+                    let caller_effects = self.effects.get_mut(caller).expect("every function has effects");
+                    for effect in callee_effects {
+                        if let Entry::Vacant(entry) = caller_effects.entry(effect) {
+                            entry.insert(Cause::Call(*callee, *span));
+                            changed = true;
+                        }
+                    }
+                }
+            }
+            if !changed {
+                break;
+            }
+        }
+    }
+
+    fn check_annotations(&self) -> Vec<SpannedItem<EffectError>> {
+        let mut errors = Vec::new();
+        for (id, function) in self.solution.functions() {
+            let Some(ref annotation) = function.effects else { continue };
+            let declared = annotation
+                .iter()
+                .filter_map(|name| match Effect::from_name(&self.name(name)) {
+                    Some(effect) => Some(effect),
+                    None => {
+                        errors.push(name.span.with_item(EffectError::UnknownEffect(self.name(name))));
+                        None
+                    },
+                })
+                .collect::<BTreeSet<_>>();
+
+            for (effect, cause) in &self.effects[id] {
+                if declared.contains(effect) {
+                    continue;
+                }
+                let function_name = self.name(&function.name);
+                let cause_description = match cause {
+                    Cause::Intrinsic(intrinsic, _) => format!("`{intrinsic}`"),
+                    Cause::Call(callee, _) => format!("`{}`", self.name(&self.solution.get_function(callee).name)),
+                };
+                let error = if annotation.is_empty() {
+                    EffectError::EffectInPureFunction {
+                        function: function_name,
+                        effect:   effect.to_string(),
+                        cause:    cause_description,
+                    }
+                } else {
+                    EffectError::UndeclaredEffect {
+                        function: function_name,
+                        effect:   effect.to_string(),
+                        cause:    cause_description,
+                    }
+                };
+                errors.push(cause.span().with_item(error));
+            }
+        }
+        errors
+    }
+
+    fn name(
+        &self,
+        identifier: &Identifier,
+    ) -> String {
+        self.solution.interner().get(identifier.id).to_string()
+    }
+}
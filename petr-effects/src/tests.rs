@@ -0,0 +1,177 @@
+use expect_test::{expect, Expect};
+use petr_resolve::resolve_symbols;
+use petr_typecheck::type_check;
+use petr_utils::render_error;
+
+use crate::check_effects;
+
+fn check(
+    input: impl Into<String>,
+    expect: Expect,
+) {
+    let input = input.into();
+    let mut sources = petr_stdlib::stdlib();
+    sources.push(("test", &input));
+    let parser = petr_parse::Parser::new(sources);
+    let (ast, errs, interner, source_map) = parser.into_result();
+    if !errs.is_empty() {
+        errs.into_iter().for_each(|err| eprintln!("{:?}", render_error(&source_map, err)));
+        panic!("test failed: code didn't parse");
+    }
+    let (errs, resolved) = resolve_symbols(ast, interner, Default::default());
+    if !errs.is_empty() {
+        errs.into_iter().for_each(|err| eprintln!("{:?}", render_error(&source_map, err)));
+        panic!("unresolved symbols in test");
+    }
+    let solution = match type_check(resolved) {
+        Ok(solution) => solution,
+        Err(errs) => {
+            errs.into_iter().for_each(|err| eprintln!("{:?}", render_error(&source_map, err)));
+            panic!("test failed: code didn't type check");
+        },
+    };
+
+    let res = match check_effects(&solution) {
+        Ok(effects) => solution
+            .functions()
+            .filter_map(|(id, function)| {
+                let effects = effects.effects_of(*id).map(|effect| effect.to_string()).collect::<Vec<_>>();
+                if effects.is_empty() {
+                    None
+                } else {
+                    Some(format!("fn {}: {}", solution.interner().get(function.name.id), effects.join(", ")))
+                }
+            })
+            .collect::<Vec<_>>()
+            .join("\n"),
+        Err(errs) => errs.into_iter().map(|err| format!("{:?}", err)).collect::<Vec<_>>().join("\n"),
+    };
+
+    expect.assert_eq(&res);
+}
+
+#[test]
+fn intrinsics_have_effects() {
+    check(
+        r#"
+fn greet() returns 'unit @puts "hello"
+
+fn allocate() returns 'int @malloc 8
+
+fn add(a in 'int, b in 'int) returns 'int + a b
+"#,
+        expect![[r#"
+            fn print: io
+            fn malloc: alloc
+            fn greet: io
+            fn allocate: alloc"#]],
+    )
+}
+
+#[test]
+fn effects_propagate_through_calls() {
+    check(
+        r#"
+fn greet() returns 'unit ~std.io.print "hello"
+
+fn twice() returns 'unit
+    let a = ~greet;
+        b = ~std.mem.malloc(8)
+    ~greet
+
+fn three() returns 'int
+    let a = ~twice;
+        b = 3
+    b
+"#,
+        expect![[r#"
+            fn print: io
+            fn malloc: alloc
+            fn greet: io
+            fn twice: io, alloc
+            fn three: io, alloc"#]],
+    )
+}
+
+#[test]
+fn effects_propagate_to_functions_declared_earlier() {
+    check(
+        r#"
+fn first() returns 'unit ~second
+
+fn second() returns 'unit ~third
+
+fn third() returns 'unit @puts "hello"
+"#,
+        expect![[r#"
+            fn print: io
+            fn malloc: alloc
+            fn first: io
+            fn second: io
+            fn third: io"#]],
+    )
+}
+
+#[test]
+fn pure_function_prints() {
+    check(
+        r#"
+fn greet() returns 'unit pure ~std.io.print "hello"
+"#,
+        expect![[
+            r#"SpannedItem EffectInPureFunction { function: "greet", effect: "io", cause: "`print`" } [Span { source: SourceId(4), span: SourceSpan { offset: SourceOffset(30), length: 22 } }]"#
+        ]],
+    )
+}
+
+#[test]
+fn declared_effects_are_allowed() {
+    check(
+        r#"
+fn greet() returns 'unit uses io ~std.io.print "hello"
+
+fn add(a in 'int, b in 'int) returns 'int pure + a b
+"#,
+        expect![[r#"
+            fn print: io
+            fn malloc: alloc
+            fn greet: io"#]],
+    )
+}
+
+#[test]
+fn undeclared_effect() {
+    check(
+        r#"
+fn greet() returns 'int uses io
+    let a = ~std.io.print "hello"
+    @malloc 8
+"#,
+        expect![[
+            r#"SpannedItem UndeclaredEffect { function: "greet", effect: "alloc", cause: "`@malloc`" } [Span { source: SourceId(4), span: SourceSpan { offset: SourceOffset(71), length: 9 } }]"#
+        ]],
+    )
+}
+
+#[test]
+fn unknown_effect() {
+    check(
+        r#"
+fn greet() returns 'unit uses io, network ~std.io.print "hello"
+"#,
+        expect![[r#"SpannedItem UnknownEffect("network") [Span { source: SourceId(4), span: SourceSpan { offset: SourceOffset(35), length: 7 } }]"#]],
+    )
+}
+
+#[test]
+fn lambda_effects_belong_to_enclosing_function() {
+    check(
+        r#"
+fn make_printer() returns 'fn('string) returns 'unit pure
+    \(s in 'string) => ~std.io.print s
+"#,
+        expect![[
+            r#"SpannedItem EffectInPureFunction { function: "make_printer", effect: "io", cause: "`print`" } [Span { source: SourceId(4), span: SourceSpan { offset: SourceOffset(81), length: 16 } }]"#
+        ]],
+    )
+}
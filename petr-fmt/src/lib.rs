@@ -143,6 +143,24 @@ impl Formattable for FunctionDeclaration {
 
         buf.push_str(&self.return_type.pretty_print(&ctx.interner, ctx.indentation()));
 
+        if !self.constraints.is_empty() {
+            let constraints = self
+                .constraints
+                .iter()
+                .map(|constraint| constraint.format(ctx).into_single_line().content.to_string())
+                .collect::<Vec<_>>();
+            buf.push_str(&format!(" where {}", constraints.join(", ")));
+        }
+
+        match &self.effects {
+            None => (),
+            Some(effects) if effects.is_empty() => buf.push_str(" pure"),
+            Some(effects) => {
+                let effects = effects.iter().map(|effect| ctx.interner.get(effect.id).to_string()).collect::<Vec<_>>();
+                buf.push_str(&format!(" uses {}", effects.join(", ")));
+            },
+        }
+
         lines.push(ctx.new_line(buf));
 
         let mut body = ctx.indented(|ctx| self.body.format(ctx));
@@ -159,6 +177,64 @@ impl Formattable for FunctionDeclaration {
     }
 }
 
+impl Formattable for TypeConstraint {
+    fn format(
+        &self,
+        ctx: &mut FormatterContext,
+    ) -> FormattedLines {
+        let buf = format!(
+            "'{}: {}",
+            ctx.interner.get(self.type_param.id),
+            ctx.interner.get_path(&self.interface).join(".")
+        );
+        FormattedLines::new(vec![ctx.new_line(buf)])
+    }
+}
+
+impl Formattable for InterfaceDeclaration {
+    fn format(
+        &self,
+        ctx: &mut FormatterContext,
+    ) -> FormattedLines {
+        let mut buf: String = if self.is_exported() { "export interface " } else { "interface " }.to_string();
+        buf.push_str(&ctx.interner.get(self.name.id));
+        buf.push_str(&format!(" '{} with ", ctx.interner.get(self.type_param.id)));
+        let methods = self
+            .methods
+            .iter()
+            .map(|method| {
+                let params = method
+                    .parameters
+                    .iter()
+                    .map(|param| param.format(ctx).into_single_line().content.to_string())
+                    .collect::<Vec<_>>();
+                let return_ty = method.return_type.format(ctx).into_single_line().content.to_string();
+                format!("{}({}) returns {return_ty}", ctx.interner.get(method.name.id), params.join(", "))
+            })
+            .collect::<Vec<_>>();
+        buf.push_str(&methods.join(", "));
+        FormattedLines::new(vec![ctx.new_line(buf)])
+    }
+}
+
+impl Formattable for ImplementationDeclaration {
+    fn format(
+        &self,
+        ctx: &mut FormatterContext,
+    ) -> FormattedLines {
+        let mut buf = format!("implement {} for ", ctx.interner.get_path(&self.interface).join("."));
+        buf.push_str(&self.ty.format(ctx).into_single_line().content);
+        buf.push_str(" with ");
+        let methods = self
+            .methods
+            .iter()
+            .map(|(name, function)| format!("{} = {}", ctx.interner.get(name.id), ctx.interner.get_path(function).join(".")))
+            .collect::<Vec<_>>();
+        buf.push_str(&methods.join(", "));
+        FormattedLines::new(vec![ctx.new_line(buf)])
+    }
+}
+
 impl Formattable for FunctionParameter {
     fn format(
         &self,
@@ -205,21 +281,179 @@ impl Formattable for Expression {
                     FormattedLines::new(lines)
                 }
             },
+            Expression::UnaryOperator(op) => {
+                let mut buf = op.op.item().as_str().to_string();
+                buf.push(' ');
+                let mut operand = ctx.indented(|ctx| op.operand.item().format(ctx).lines);
+                if operand.len() == 1 {
+                    buf.push_str(&operand[0].content);
+                    FormattedLines::new(vec![ctx.new_line(buf)])
+                } else {
+                    let mut lines = Vec::new();
+                    buf.push_str(" (");
+                    lines.push(ctx.new_line(buf));
+                    lines.append(&mut operand);
+                    FormattedLines::new(lines)
+                }
+            },
             Expression::Literal(lit) => FormattedLines::new(vec![ctx.new_line(lit.to_string())]),
             Expression::Variable(var) => {
                 let ident_as_string = ctx.interner.get(var.id);
                 FormattedLines::new(vec![ctx.new_line(ident_as_string)])
             },
+            Expression::Hole(None) => FormattedLines::new(vec![ctx.new_line("_")]),
+            Expression::Hole(Some(name)) => {
+                let name = format!("?{}", ctx.interner.get(name.id));
+                FormattedLines::new(vec![ctx.new_line(name)])
+            },
             Expression::List(list) => list.format(ctx),
-            Expression::TypeConstructor(..) => unreachable!("this is only constructed after binding, which the formatter doesn't do"),
+            Expression::Tuple(elements) => {
+                let elements = elements
+                    .iter()
+                    .map(|element| element.item().format(ctx).into_single_line().content.to_string())
+                    .collect::<Vec<_>>();
+                FormattedLines::new(vec![ctx.new_line(format!("({})", elements.join(", ")))])
+            },
+            Expression::TypeConstructor(..) | Expression::InterfaceMethod(..) => {
+                unreachable!("this is only constructed after binding, which the formatter doesn't do")
+            },
             Expression::FunctionCall(f) => f.format(ctx),
             Expression::IntrinsicCall(i) => i.format(ctx),
             Expression::Binding(binding) => binding.format(ctx),
-            Expression::If(_) => todo!(),
+            Expression::If(if_expression) => if_expression.format(ctx),
+            Expression::Match(match_expression) => match_expression.format(ctx),
+            Expression::Lambda(lambda) => lambda.format(ctx),
+            Expression::FieldAccess(access) => {
+                let target = access.target.item().format(ctx).into_single_line().content.to_string();
+                FormattedLines::new(vec![ctx.new_line(format!("{target}.{}", ctx.interner.get(access.field.id)))])
+            },
+            Expression::RecordUpdate(update) => {
+                let mut buf = update.target.item().format(ctx).into_single_line().content.to_string();
+                buf.push_str(" with ");
+                let fields = update
+                    .fields
+                    .iter()
+                    .map(|(name, value)| {
+                        let value = value.item().format(ctx).into_single_line().content.to_string();
+                        format!("{} = {value}", ctx.interner.get(name.id))
+                    })
+                    .collect::<Vec<_>>();
+                buf.push_str(&fields.join(", "));
+                FormattedLines::new(vec![ctx.new_line(buf)])
+            },
+        }
+    }
+}
+
+impl Formattable for Lambda {
+    fn format(
+        &self,
+        ctx: &mut FormatterContext,
+    ) -> FormattedLines {
+        let params = self
+            .parameters
+            .iter()
+            .map(|param| param.format(ctx).into_single_line().content.to_string())
+            .collect::<Vec<_>>()
+            .join(", ");
+        let mut buf = format!("\\({params}) => ");
+        let mut body = ctx.indented(|ctx| self.body.item().format(ctx).lines);
+        if body.len() == 1 {
+            buf.push_str(&body[0].content);
+            FormattedLines::new(vec![ctx.new_line(buf)])
+        } else {
+            let mut lines = vec![ctx.new_line(buf.trim_end())];
+            lines.append(&mut body);
+            FormattedLines::new(lines)
         }
     }
 }
 
+impl Formattable for If {
+    fn format(
+        &self,
+        ctx: &mut FormatterContext,
+    ) -> FormattedLines {
+        // ifs whose branches fit on one line stay on one line, otherwise each branch is indented
+        // below its keyword:
+        // if x then
+        //   a
+        // else
+        //   b
+        let condition = self.condition.item().format(ctx).into_single_line().content.to_string();
+        let mut then_branch = ctx.indented(|ctx| self.then_branch.item().format(ctx).lines);
+        let mut else_branch = self
+            .else_branch
+            .as_ref()
+            .map(|else_branch| ctx.indented(|ctx| else_branch.item().format(ctx).lines));
+        if then_branch.len() == 1 && else_branch.as_ref().map_or(true, |lines| lines.len() == 1) {
+            let mut buf = format!("if {condition} then {}", then_branch[0].content);
+            if let Some(else_branch) = else_branch {
+                buf.push_str(&format!(" else {}", else_branch[0].content));
+            }
+            return FormattedLines::new(vec![ctx.new_line(buf)]);
+        }
+        let mut lines = vec![ctx.new_line(format!("if {condition} then"))];
+        lines.append(&mut then_branch);
+        if let Some(else_branch) = else_branch.as_mut() {
+            lines.push(ctx.new_line("else"));
+            lines.append(else_branch);
+        }
+        FormattedLines::new(lines)
+    }
+}
+
+impl Formattable for Match {
+    fn format(
+        &self,
+        ctx: &mut FormatterContext,
+    ) -> FormattedLines {
+        // matches look like this, with each arm on its own line:
+        // match x
+        // | Some y => y
+        // | None => 0
+        let scrutinee = self.scrutinee.item().format(ctx).into_single_line().content.to_string();
+        let mut lines = vec![ctx.new_line(format!("match {scrutinee}"))];
+        for arm in self.arms.iter() {
+            let pattern = arm.item().pattern.item().format(ctx).into_single_line().content.to_string();
+            let buf = format!("| {pattern} => ");
+            let mut body = ctx.indented(|ctx| arm.item().body.item().format(ctx).lines);
+            if body.len() == 1 {
+                lines.push(ctx.new_line(format!("{buf}{}", body[0].content)));
+            } else {
+                lines.push(ctx.new_line(buf.trim_end()));
+                lines.append(&mut body);
+            }
+        }
+        FormattedLines::new(lines)
+    }
+}
+
+impl Formattable for Pattern {
+    fn format(
+        &self,
+        ctx: &mut FormatterContext,
+    ) -> FormattedLines {
+        let pattern = match self {
+            Pattern::Wildcard => "_".to_string(),
+            Pattern::Literal(lit) => lit.to_string(),
+            Pattern::Variant(VariantPattern { name, fields }) => {
+                let mut buf = ctx.interner.get_path(name).join(".");
+                for field in fields.iter() {
+                    buf.push(' ');
+                    match field.item() {
+                        FieldPattern::Binding(name) => buf.push_str(&ctx.interner.get(name.id)),
+                        FieldPattern::Wildcard => buf.push('_'),
+                        FieldPattern::Literal(lit) => buf.push_str(&lit.to_string()),
+                    }
+                }
+                buf
+            },
+        };
+        FormattedLines::new(vec![ctx.new_line(pattern)])
+    }
+}
+
 impl Formattable for ExpressionWithBindings {
     fn format(
         &self,
@@ -233,8 +467,13 @@ impl Formattable for ExpressionWithBindings {
                 let is_last = ix == self.bindings.len() - 1;
                 let mut buf = if is_first { "let ".to_string() } else { "    ".to_string() };
 
-                let name = ctx.interner.get(binding.name.id);
-                buf.push_str(&name);
+                match &binding.pattern {
+                    BindingPattern::Name(name) => buf.push_str(&ctx.interner.get(name.id)),
+                    BindingPattern::Tuple(names) => {
+                        let names = names.iter().map(|name| ctx.interner.get(name.id)).collect::<Vec<_>>();
+                        buf.push_str(&format!("({})", names.join(", ")));
+                    },
+                }
                 buf.push_str(" = ");
                 let expr_lines = ctx.indented(|ctx| binding.val.format(ctx).lines);
 
@@ -342,6 +581,9 @@ impl Formattable for FunctionCall {
         ctx.indented(|ctx| {
             for (ix, arg) in self.args.iter().enumerate() {
                 let mut arg = (arg).format(ctx).into_single_line().content.to_string();
+                if let Some(names) = &self.arg_names {
+                    arg = format!("{} = {arg}", ctx.interner.get(names[ix].id));
+                }
                 let is_last = ix == self.args.len() - 1;
 
                 if !is_last || ctx.config.put_fn_args_on_new_lines() {
@@ -414,7 +656,10 @@ impl Formattable for AstNode {
         match self {
             AstNode::FunctionDeclaration(fd) => fd.format(ctx),
             AstNode::TypeDeclaration(ty) => ty.format(ctx),
+            AstNode::TypeAlias(alias) => alias.format(ctx),
             AstNode::ImportStatement(_) => todo!(),
+            AstNode::InterfaceDeclaration(interface) => interface.format(ctx),
+            AstNode::ImplementationDeclaration(implementation) => implementation.format(ctx),
         }
     }
 }
@@ -439,6 +684,9 @@ impl Formattable for TypeDeclaration {
         let mut lines = Vec::new();
         let mut buf: String = if self.visibility == Visibility::Exported { "Type " } else { "type " }.to_string();
         buf.push_str(&ctx.interner.get(self.name.id));
+        for param in &*self.type_params {
+            buf.push_str(&format!(" '{}", ctx.interner.get(param.id)));
+        }
         let mut variants = self.variants.iter();
         if let Some(first_variant) = variants.next() {
             buf.push_str(" = ");
@@ -478,6 +726,24 @@ impl Formattable for TypeDeclaration {
     }
 }
 
+impl Formattable for TypeAlias {
+    fn format(
+        &self,
+        ctx: &mut FormatterContext,
+    ) -> FormattedLines {
+        let mut buf: String = if self.visibility == Visibility::Exported {
+            "Type alias "
+        } else {
+            "type alias "
+        }
+        .to_string();
+        buf.push_str(&ctx.interner.get(self.name.id));
+        buf.push_str(" = ");
+        buf.push_str(&self.ty.format(ctx).into_single_line().content);
+        FormattedLines::new(vec![ctx.new_line(buf)])
+    }
+}
+
 impl Formattable for TypeVariant {
     fn format(
         &self,
@@ -526,9 +792,25 @@ impl Formattable for Ty {
         let name = match self {
             Ty::Bool => "'bool".to_string(),
             Ty::Int => "'int".to_string(),
+            Ty::SizedInt(ty) => format!("'{ty}"),
+            Ty::Float => "'float".to_string(),
             Ty::String => "'string".to_string(),
             Ty::Unit => "'unit".to_string(),
             Ty::Named(name) => format!("'{}", ctx.interner.get(name.id)),
+            Ty::Applied(name, args) => format!(
+                "'{} {}",
+                ctx.interner.get(name.id),
+                args.iter()
+                    .map(|arg| {
+                        let arg_str = arg.format(ctx).into_single_line().content;
+                        match arg {
+                            Ty::Applied(..) => format!("({arg_str})"),
+                            _ => arg_str.to_string(),
+                        }
+                    })
+                    .collect::<Vec<_>>()
+                    .join(" ")
+            ),
             Ty::Literal(lit) => lit.to_string(),
             Ty::Sum(tys) => format!(
                 "'Σ {}",
@@ -537,6 +819,29 @@ impl Formattable for Ty {
                     .collect::<Vec<_>>()
                     .join(" | ")
             ),
+            Ty::Arrow(params, return_ty) => format!(
+                "'fn({}) returns {}",
+                params
+                    .iter()
+                    .map(|ty| ty.format(ctx).into_single_line().content)
+                    .collect::<Vec<_>>()
+                    .join(", "),
+                return_ty.format(ctx).into_single_line().content
+            ),
+            // names don't need their `'` inside of a tuple type, like `'(int, 'Option 'int)`
+            Ty::Tuple(tys) => format!(
+                "'({})",
+                tys.iter()
+                    .map(|ty| {
+                        let ty_str = ty.format(ctx).into_single_line().content;
+                        match ty {
+                            Ty::Applied(..) | Ty::Sum(..) | Ty::Arrow(..) | Ty::Literal(_) => ty_str.to_string(),
+                            _ => ty_str.strip_prefix('\'').unwrap_or(&ty_str).to_string(),
+                        }
+                    })
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
         };
         FormattedLines::new(vec![ctx.new_line(name)])
     }
@@ -11,6 +11,25 @@ fn check(
     input: impl Into<String>,
     expect: Expect,
 ) {
+    let result = format_source(config, input);
+    expect.assert_eq(&result);
+}
+
+/// Like [`check`], but also checks that formatting the formatted code doesn't change it
+fn check_round_trip(
+    config: FormatterConfig,
+    input: impl Into<String>,
+    expect: Expect,
+) {
+    let result = format_source(config, input);
+    expect.assert_eq(&result);
+    assert_eq!(format_source(config, result.clone()), result, "formatted code did not round-trip");
+}
+
+fn format_source(
+    config: FormatterConfig,
+    input: impl Into<String>,
+) -> String {
     let input = input.into();
     let parser = petr_parse::Parser::new(vec![("test", input)]);
     let (ast, errs, interner, source_map) = parser.into_result();
@@ -19,8 +38,7 @@ fn check(
         panic!("fmt failed: code didn't parse");
     }
     let mut ctx = FormatterContext::from_interner(interner).with_config(config);
-    let result = ast.line_length_aware_format(&mut ctx).render();
-    expect.assert_eq(&result);
+    ast.line_length_aware_format(&mut ctx).render()
 }
 
 #[test]
@@ -647,3 +665,206 @@ fn sum_ty_formatting() {
     "#]],
     )
 }
+
+#[test]
+fn comparison_and_boolean_operators() {
+    check(
+        Default::default(),
+        "fn in_range(x in 'int) returns 'bool and >= x 10 not != x 20",
+        expect![[r#"
+            fn in_range(
+              x ∈ 'int,
+            ) → 'bool
+              and >= x 10 not != x 20
+        "#]],
+    )
+}
+
+#[test]
+fn records_named_args_field_access_and_update() {
+    check(
+        Default::default(),
+        "type Point = Point x 'int y 'int
+fn flip(p in 'Point) returns 'Point p with x = p.y, y = p.x
+fn origin() returns 'Point ~Point y = 0, x = 0",
+        expect![[r#"
+            type Point = Point x 'int y 'int
+
+
+            fn flip(
+              p ∈ 'Point,
+            ) → 'Point
+              p with x = p.y, y = p.x
+
+            fn origin() → 'Point
+              ~Point y = 0, x = 0
+        "#]],
+    )
+}
+
+#[test]
+fn generic_type_declarations() {
+    check(
+        Default::default(),
+        "type Option 'A = None | Some value 'A
+fn nested(x in 'Option ('Option 'int)) returns 'Option 'int ~None",
+        expect![[r#"
+            type Option 'A = None
+                           | Some value 'A
+
+            fn nested(
+              x ∈ 'Option ('Option 'int),
+            ) → 'Option 'int
+              ~None
+        "#]],
+    )
+}
+
+#[test]
+fn interfaces_implementations_and_where_clauses() {
+    check(
+        Default::default(),
+        "export interface Size 'A with size(x in 'A) returns 'int, empty() returns 'A
+implement Size for 'bool with size = bool_size, empty = no
+fn measure(x in 'A) returns 'int where 'A: Size, 'A: std.ops.Add ~size x",
+        expect![[r#"
+            export interface Size 'A with size(x ∈ 'A) returns 'int, empty() returns 'A
+
+            implement Size for 'bool with size = bool_size, empty = no
+
+            fn measure(
+              x ∈ 'A,
+            ) → 'int where 'A: Size, 'A: std.ops.Add
+              ~size x
+        "#]],
+    )
+}
+
+#[test]
+fn effect_annotations() {
+    check(
+        Default::default(),
+        "fn greet() returns 'unit uses io, alloc ~print \"hi\"
+fn add(a in 'int, b in 'int) returns 'int where 'A: Add pure + a b",
+        expect![[r#"
+            fn greet() → 'unit uses io, alloc
+              ~print "hi"
+
+            fn add(
+              a ∈ 'int,
+              b ∈ 'int,
+            ) → 'int where 'A: Add pure
+              + a b
+        "#]],
+    )
+}
+
+#[test]
+fn tuples() {
+    check(
+        Default::default(),
+        "fn pair() returns '(int, 'Option 'string) (1, ~none)
+fn first() returns 'int let (a, b) = ~pair(); c = a c",
+        expect![[r#"
+            fn pair() → '(int, 'Option 'string)
+              (1, ~none)
+
+            fn first() → 'int
+              let (a, b) = ~pair();
+                  c = a
+              c
+        "#]],
+    )
+}
+
+#[test]
+fn type_aliases() {
+    check(
+        Default::default(),
+        "type alias Id = 'int
+Type alias Small = 'sum 1 | 2 | 3
+type alias Pair = '(Id, Small)
+fn first(pair in 'Pair) returns 'Id let (a, b) = pair a",
+        expect![[r#"
+            type alias Id = 'int
+
+            Type alias Small = 'Σ 1 | 2 | 3
+
+            type alias Pair = '(Id, Small)
+
+            fn first(
+              pair ∈ 'Pair,
+            ) → 'Id
+              let (a, b) = pair
+              a
+        "#]],
+    )
+}
+
+#[test]
+fn holes() {
+    check(
+        Default::default(),
+        "fn foo(a in 'int) returns 'int + ?rest _",
+        expect![[r#"
+            fn foo(
+              a ∈ 'int,
+            ) → 'int
+              + ?rest _
+        "#]],
+    )
+}
+
+#[test]
+fn match_expressions() {
+    check_round_trip(
+        FCB::default().use_symbol_for_fn_return_type(false).build(),
+        r#"type Shape = Circle radius 'int | Rect width 'int height 'int
+fn area(s in 'Shape) returns 'int match s | Circle r => * r r | Rect w h => let a = * w h; a
+fn describe(n in 'int) returns 'string match n | 0 => "zero" | _ => "many""#,
+        expect![[r#"
+            type Shape = Circle radius 'int
+                       | Rect width 'int height 'int
+
+            fn area(
+              s ∈ 'Shape,
+            ) returns 'int
+              match s
+              | Circle r => * r r
+              | Rect w h =>
+                let a = * w h
+                a
+
+            fn describe(
+              n ∈ 'int,
+            ) returns 'string
+              match n
+              | 0 => "zero"
+              | _ => "many"
+        "#]],
+    )
+}
+
+#[test]
+fn if_expressions() {
+    check_round_trip(
+        FCB::default().use_symbol_for_fn_return_type(false).build(),
+        r#"fn sign(n in 'int) returns 'int if < n 0 then - 0 1 else if > n 0 then 1 else 0
+fn clamped(n in 'int) returns 'int if > n 10 then let m = 10; m else n"#,
+        expect![[r#"
+            fn sign(
+              n ∈ 'int,
+            ) returns 'int
+              if < n 0 then - 0 1 else if > n 0 then 1 else 0
+
+            fn clamped(
+              n ∈ 'int,
+            ) returns 'int
+              if > n 10 then
+                let m = 10
+                m
+              else
+                n
+        "#]],
+    )
+}
@@ -4,7 +4,15 @@ use thiserror::Error;
 #[derive(Debug, Error, Diagnostic, Clone)]
 pub enum LoweringError {
     #[error("Internal compiler error: {0}")]
+    #[diagnostic(code(E0501))]
     Internal(String),
     #[error("Unable to infer type")]
+    #[diagnostic(code(E0502))]
     UnableToInferType,
+    #[error("this hole must be filled in before the program can run")]
+    #[diagnostic(code(E0503), help("replace the hole with an expression of the type it expects"))]
+    UnfilledHole,
+    #[error("the program has no entry point, so it won't do anything")]
+    #[diagnostic(code(E0504), help("declare a function called `main` to run when the program starts"))]
+    NoEntryPoint,
 }
@@ -9,19 +9,22 @@
 
 use std::{collections::BTreeMap, rc::Rc};
 
-use petr_typecheck::{FunctionSignature, SpecificType, TypeSolution, TypeVariable, TypedExpr, TypedExprKind};
-use petr_utils::{idx_map_key, Identifier, IndexMap, SpannedItem, SymbolId};
+use petr_typecheck::{BindingPattern, FunctionSignature, GeneralType, SpecificType, TypeSolution, TypeVariable, TypedExpr, TypedExprKind};
+use petr_utils::{idx_map_key, Diagnostics, Identifier, IndexMap, IntegerType, SpannedItem, SymbolId};
 
 mod error;
 mod opcodes;
 
 pub use error::LoweringError;
 use opcodes::*;
-pub use opcodes::{DataLabel, Intrinsic, IrOpcode, LabelId, Reg, ReservedRegister};
+pub use opcodes::{DataLabel, Intrinsic, IrOpcode, IrTy, LabelId, Reg, ReservedRegister};
 
-pub fn lower(solution: TypeSolution) -> Result<(DataSection, Vec<IrOpcode>)> {
+pub fn lower(
+    solution: TypeSolution,
+    diagnostics: &mut Diagnostics,
+) -> Result<(DataSection, Vec<IrOpcode>)> {
     let lowerer = Lowerer::new(solution)?;
-    Ok(lowerer.finalize())
+    Ok(lowerer.finalize(diagnostics))
 }
 
 pub struct Function {
@@ -37,17 +40,38 @@ pub type DataSection = IndexMap<DataLabel, DataSectionEntry>;
 pub struct Lowerer {
     data_section: DataSection,
     entry_point: Option<MonomorphizedFunctionId>,
+    /// The type of the value that the entry point returns, which is how the value a program
+    /// terminates with is read
+    entry_point_return_ty: Option<IrTy>,
     reg_assigner: usize,
     type_solution: TypeSolution,
     variables_in_scope: Vec<BTreeMap<SymbolId, Reg>>,
-    monomorphized_functions: IndexMap<MonomorphizedFunctionId, (FunctionSignature, Function)>,
+    monomorphized_functions: IndexMap<MonomorphizedFunctionId, (FunctionOrigin, Function)>,
     errors: Vec<SpannedItem<LoweringError>>,
     label_assigner: usize,
+    /// The concrete types of the type parameters of the function being lowered, by the id of the
+    /// type variable that they are still inferred as after type checking
+    type_parameters: BTreeMap<usize, GeneralType>,
 }
 
+/// What the code of a function in the IR was generated from
+enum FunctionOrigin {
+    /// A monomorphization of a declared function, for the concrete types of its arguments and of
+    /// the value it returns
+    Declaration(FunctionSignature, GeneralType),
+    /// The body of a lambda
+    Lambda,
+    /// A wrapper which lets a monomorphized declared function be called as a closure
+    FunctionValue(FunctionSignature),
+}
+
+/// The size of each slot in a closure's memory, see [`IrTy::Closure`]
+const CLOSURE_SLOT_SIZE: u64 = 8;
+
 #[derive(Debug, Clone)]
 pub enum DataSectionEntry {
     Int64(i64),
+    Float64(f64),
     String(Rc<str>),
     Bool(bool),
 }
@@ -61,39 +85,60 @@ impl Lowerer {
         let mut lowerer = Self {
             data_section: IndexMap::default(),
             entry_point: None,
+            entry_point_return_ty: None,
             reg_assigner: 0,
             type_solution,
             variables_in_scope: Default::default(),
             monomorphized_functions: Default::default(),
             label_assigner: 0,
             errors: Default::default(),
+            type_parameters: Default::default(),
         };
 
         let monomorphized_entry_point_id = match entry_point {
             None => None,
             Some((id, _func)) => {
-                let monomorphized_entry_point_id = lowerer.monomorphize_function((id, vec![].into_boxed_slice()))?;
+                let monomorphized_entry_point_id = lowerer.monomorphize_function((id, vec![].into_boxed_slice()), None)?;
                 Some(monomorphized_entry_point_id)
             },
         };
 
         lowerer.entry_point = monomorphized_entry_point_id;
+        // a return type which was never inferred doesn't stop the program from running, so it
+        // isn't an error here: the value just can't be read as any particular type
+        lowerer.entry_point_return_ty = monomorphized_entry_point_id.and_then(|id| {
+            let return_ty = match lowerer.monomorphized_functions.get(id) {
+                (FunctionOrigin::Declaration(_, return_ty), _) => return_ty.clone(),
+                _ => unreachable!("the entry point is monomorphized from a declaration"),
+            };
+            (!return_ty.contains_infer()).then(|| lowerer.lower_general_type(return_ty))
+        });
         Ok(lowerer)
     }
 
-    pub fn finalize(self) -> (DataSection, Vec<IrOpcode>) {
+    /// The type of the value that the entry point returns, if there is an entry point and its
+    /// return type is known
+    pub fn entry_point_return_type(&self) -> Option<&IrTy> {
+        self.entry_point_return_ty.as_ref()
+    }
+
+    /// Produces the data section and the program. A program without an entry point is reported
+    /// to `diagnostics` as a `no_entry_point` warning, and returns 0 without doing anything.
+    pub fn finalize(
+        self,
+        diagnostics: &mut Diagnostics,
+    ) -> (DataSection, Vec<IrOpcode>) {
         let mut program_section = vec![];
 
         // insert jump to entry point as first instr
         if let Some(entry_point) = self.entry_point {
             program_section.push(IrOpcode::JumpImmediateFunction(entry_point));
         } else {
-            // TODO use diagnostics here
-            eprintln!("Warning: Generating IR for program with no entry point");
+            diagnostics.unspanned_warning("no_entry_point", LoweringError::NoEntryPoint);
             program_section.push(IrOpcode::ReturnImmediate(0));
         }
 
-        for (label, (_signature, mut function)) in self.monomorphized_functions.into_iter() {
+        for (label, (_origin, mut function)) in self.monomorphized_functions.into_iter() {
             program_section.push(IrOpcode::FunctionLabel(label));
             program_section.append(&mut function.body);
         }
@@ -102,18 +147,57 @@ impl Lowerer {
     }
 
     /// this lowers a function declaration.
+    /// If the function is generic, the concrete types of its type parameters are determined by
+    /// the types of its arguments, and by `return_ty` for type parameters which only appear in
+    /// the type that it returns.
     fn monomorphize_function(
         &mut self,
         func: FunctionSignature,
+        return_ty: Option<GeneralType>,
     ) -> Result<MonomorphizedFunctionId> {
-        if let Some(previously_monomorphized_definition) = self.monomorphized_functions.iter().find(|(_id, (sig, _))| *sig == func) {
+        let declaration = self.type_solution.get_function(&func.0).clone();
+        let mut type_parameters = BTreeMap::new();
+        for ((_, param_ty), concrete_ty) in declaration.params.iter().zip(func.1.iter()) {
+            let param_ty = self.type_solution.generalize(&self.type_solution.get_latest_type(*param_ty));
+            bind_type_parameters(&param_ty, concrete_ty, &mut type_parameters);
+        }
+        let declared_return_ty = self.type_solution.generalize(&self.type_solution.get_latest_type(declaration.return_ty));
+        if let Some(ref return_ty) = return_ty {
+            bind_type_parameters(&declared_return_ty, return_ty, &mut type_parameters);
+        }
+        let return_ty = substitute_type_parameters(declared_return_ty, &type_parameters);
+
+        // an interface method is lowered to the implementation of the method for the type that
+        // it was called with
+        if let TypedExprKind::InterfaceMethod {
+            interface, method, self_ty, ..
+        } = declaration.body.kind
+        {
+            let self_ty = substitute_type_parameters(
+                self.type_solution.generalize(&self.type_solution.get_latest_type(self_ty)),
+                &type_parameters,
+            );
+            let Some(implementation) = self.type_solution.find_implementation(interface, method, &self_ty) else {
+                return Err(declaration.body.span().with_item(LoweringError::Internal(
+                    "interface method was called with a type which doesn't implement the interface".into(),
+                )));
+            };
+            return self.monomorphize_function((implementation, func.1), Some(return_ty));
+        }
+
+        if let Some(previously_monomorphized_definition) = self
+            .monomorphized_functions
+            .iter()
+            .find(|(_id, (origin, _))| matches!(origin, FunctionOrigin::Declaration(sig, ret) if *sig == func && *ret == return_ty))
+        {
             return Ok(previously_monomorphized_definition.0);
         }
 
-        let func_def = self.type_solution.get_monomorphized_function(&func).clone();
+        let func_def = self.type_solution.find_monomorphized_function(&func).cloned().unwrap_or(declaration);
 
+        let enclosing_type_parameters = std::mem::replace(&mut self.type_parameters, type_parameters);
         let mut buf = vec![];
-        self.with_variable_context(|ctx| -> Result<_> {
+        let res = self.with_variable_context(|ctx| -> Result<_> {
             // Pop parameters off the stack in reverse order -- the last parameter for the function
             // will be the first thing popped off the stack
             // When we lower a function call, we push them onto the stack from first to last. Since
@@ -121,21 +205,7 @@ impl Lowerer {
 
             for (param_ty, (param_name, _)) in func.1.iter().zip(func_def.params).rev() {
                 let param_ty = ctx.lower_type(param_ty.clone());
-                // in order, assign parameters to registers
-                if param_ty.is_copy_type() {
-                    // load from stack into register
-                    let param_reg = ctx.fresh_reg();
-                    let ty_reg = TypedReg {
-                        ty:  param_ty.clone(),
-                        reg: param_reg,
-                    };
-                    buf.push(IrOpcode::StackPop(ty_reg));
-                    // insert param into mapping
-
-                    ctx.insert_var(&param_name, param_reg);
-                } else {
-                    todo!("make reg a ptr to the value")
-                }
+                buf.push(ctx.lower_param_pop(&param_name, param_ty));
             }
 
             let return_reg = ctx.fresh_reg();
@@ -149,8 +219,173 @@ impl Lowerer {
             // jump back to caller
             buf.push(IrOpcode::Return());
 
-            Ok(ctx.monomorphized_functions.insert((func, Function { body: buf })))
-        })
+            Ok(ctx
+                .monomorphized_functions
+                .insert((FunctionOrigin::Declaration(func, return_ty), Function { body: buf })))
+        });
+        self.type_parameters = enclosing_type_parameters;
+        res
+    }
+
+    /// Pops a parameter off of the stack into a fresh register, and inserts it into the current
+    /// variable context.
+    fn lower_param_pop(
+        &mut self,
+        param_name: &Identifier,
+        param_ty: IrTy,
+    ) -> IrOpcode {
+        // values which don't fit in a register are already represented by a pointer to
+        // their memory, so either way, the parameter is loaded into a register
+        let param_ty = if param_ty.is_copy_type() {
+            param_ty
+        } else {
+            IrTy::Ptr(Box::new(param_ty))
+        };
+        // load from stack into register
+        let param_reg = self.fresh_reg();
+        let ty_reg = TypedReg {
+            ty:  param_ty,
+            reg: param_reg,
+        };
+        // insert param into mapping
+        self.insert_var(param_name, param_reg);
+        IrOpcode::StackPop(ty_reg)
+    }
+
+    /// Lowers the body of a lambda into its own function. Closures are called with their
+    /// arguments on the stack like any other function, followed by the closure itself, which the
+    /// function loads its captured values from.
+    fn lower_lambda(
+        &mut self,
+        params: &[(Identifier, TypeVariable)],
+        captures: &[Identifier],
+        body: &TypedExpr,
+    ) -> Result<MonomorphizedFunctionId> {
+        // the body can only refer to its own parameters and the values it captured, not to the
+        // variables of the function it is defined in
+        let enclosing_variables = std::mem::take(&mut self.variables_in_scope);
+        let res = self.with_variable_context(|ctx| -> Result<_> {
+            let mut buf = vec![];
+            let closure_reg = ctx.fresh_reg();
+            buf.push(IrOpcode::StackPop(TypedReg {
+                ty:  IrTy::Closure,
+                reg: closure_reg,
+            }));
+
+            let slot_reg = ctx.fresh_reg();
+            for (ix, capture) in captures.iter().enumerate() {
+                let capture_reg = ctx.fresh_reg();
+                buf.push(IrOpcode::LoadImmediate(slot_reg, (ix as u64 + 1) * CLOSURE_SLOT_SIZE));
+                buf.push(IrOpcode::Add(slot_reg, slot_reg, closure_reg));
+                buf.push(IrOpcode::ReadMemoryToRegister(capture_reg, slot_reg));
+                ctx.insert_var(capture, capture_reg);
+            }
+
+            // see `monomorphize_function` for why parameters are popped in reverse
+            for (param_name, param_ty) in params.iter().rev() {
+                let param_ty = ctx.to_ir_type(*param_ty);
+                buf.push(ctx.lower_param_pop(param_name, param_ty));
+            }
+
+            let return_reg = ctx.fresh_reg();
+            buf.append(&mut ctx.lower_expr(body, ReturnDestination::Reg(return_reg))?);
+            buf.push(IrOpcode::Copy(Reg::Reserved(ReservedRegister::ReturnValueRegister), return_reg));
+            buf.push(IrOpcode::Return());
+
+            Ok(ctx.monomorphized_functions.insert((FunctionOrigin::Lambda, Function { body: buf })))
+        });
+        self.variables_in_scope = enclosing_variables;
+        res
+    }
+
+    /// Declared functions don't expect a closure on the stack when they are called, so a function
+    /// value calls a wrapper which discards the closure before jumping to the function.
+    fn function_value_wrapper(
+        &mut self,
+        signature: FunctionSignature,
+    ) -> Result<MonomorphizedFunctionId> {
+        if let Some(previous_wrapper) = self
+            .monomorphized_functions
+            .iter()
+            .find(|(_id, (origin, _))| matches!(origin, FunctionOrigin::FunctionValue(sig) if *sig == signature))
+        {
+            return Ok(previous_wrapper.0);
+        }
+
+        let function = self.monomorphize_function(signature.clone(), None)?;
+        let closure_reg = self.fresh_reg();
+        // the caller already pushed the return address, so the function will return straight to
+        // it
+        let body = vec![
+            IrOpcode::StackPop(TypedReg {
+                ty:  IrTy::Closure,
+                reg: closure_reg,
+            }),
+            IrOpcode::JumpImmediateFunction(function),
+        ];
+        Ok(self
+            .monomorphized_functions
+            .insert((FunctionOrigin::FunctionValue(signature), Function { body })))
+    }
+
+    /// Allocates a closure which calls `function`, see [`IrTy::Closure`] for its layout. Without
+    /// a function, the closure only holds the captured values.
+    fn lower_closure(
+        &mut self,
+        function: Option<MonomorphizedFunctionId>,
+        captures: &[Identifier],
+        return_destination: ReturnDestination,
+    ) -> Vec<IrOpcode> {
+        let ReturnDestination::Reg(closure_reg) = return_destination;
+        let size = (captures.len() as u64 + 1) * CLOSURE_SLOT_SIZE;
+        let mut buf = vec![IrOpcode::MallocImmediate(closure_reg, (size as usize).into())];
+        if let Some(function) = function {
+            let function_reg = self.fresh_reg();
+            buf.push(IrOpcode::LoadFunction(function_reg, function));
+            buf.push(IrOpcode::WriteRegisterToMemory(function_reg, closure_reg));
+        }
+
+        let slot_reg = self.fresh_reg();
+        for (ix, capture) in captures.iter().enumerate() {
+            let capture_reg = self
+                .get_variable(capture.id)
+                .unwrap_or_else(|| panic!("captured var {} did not exist TODO err", capture.id));
+            buf.push(IrOpcode::LoadImmediate(slot_reg, (ix as u64 + 1) * CLOSURE_SLOT_SIZE));
+            buf.push(IrOpcode::Add(slot_reg, slot_reg, closure_reg));
+            buf.push(IrOpcode::WriteRegisterToMemory(capture_reg, slot_reg));
+        }
+        buf
+    }
+
+    /// Allocates a closure which calls `function`, an instance of a generalized lambda, with the
+    /// values captured by `binding_reg`, the closure that the lambda was bound to
+    fn lower_instantiation(
+        &mut self,
+        function: MonomorphizedFunctionId,
+        binding_reg: Reg,
+        num_captures: usize,
+        return_destination: ReturnDestination,
+    ) -> Vec<IrOpcode> {
+        let ReturnDestination::Reg(closure_reg) = return_destination;
+        let size = (num_captures as u64 + 1) * CLOSURE_SLOT_SIZE;
+        let function_reg = self.fresh_reg();
+        let mut buf = vec![
+            IrOpcode::MallocImmediate(closure_reg, (size as usize).into()),
+            IrOpcode::LoadFunction(function_reg, function),
+            IrOpcode::WriteRegisterToMemory(function_reg, closure_reg),
+        ];
+
+        let slot_reg = self.fresh_reg();
+        let capture_reg = self.fresh_reg();
+        for ix in 1..=num_captures as u64 {
+            buf.push(IrOpcode::LoadImmediate(slot_reg, ix * CLOSURE_SLOT_SIZE));
+            buf.push(IrOpcode::Add(slot_reg, slot_reg, binding_reg));
+            buf.push(IrOpcode::ReadMemoryToRegister(capture_reg, slot_reg));
+            buf.push(IrOpcode::LoadImmediate(slot_reg, ix * CLOSURE_SLOT_SIZE));
+            buf.push(IrOpcode::Add(slot_reg, slot_reg, closure_reg));
+            buf.push(IrOpcode::WriteRegisterToMemory(capture_reg, slot_reg));
+        }
+        buf
     }
 
     fn fresh_reg(&mut self) -> Reg {
@@ -173,7 +408,7 @@ impl Lowerer {
                     ReturnDestination::Reg(reg) => vec![IrOpcode::LoadData(reg, data_label)],
                 })
             },
-            FunctionCall { func, args, ty: _ty } => {
+            FunctionCall { func, args, ty } => {
                 let mut buf = Vec::new();
                 // push all args onto the stack in order
 
@@ -192,9 +427,16 @@ impl Lowerer {
                 // push current PC onto the stack
                 buf.push(IrOpcode::PushPc());
 
-                let arg_petr_types = arg_types.iter().map(|(_name, ty)| self.type_solution.generalize(ty)).collect();
+                let arg_petr_types = arg_types
+                    .iter()
+                    .map(|(_name, ty)| substitute_type_parameters(self.type_solution.generalize(ty), &self.type_parameters))
+                    .collect();
+                let return_ty = substitute_type_parameters(
+                    self.type_solution.generalize(&self.type_solution.get_latest_type(*ty)),
+                    &self.type_parameters,
+                );
 
-                let monomorphized_func_id = self.monomorphize_function((*func, arg_petr_types))?;
+                let monomorphized_func_id = self.monomorphize_function((*func, arg_petr_types), Some(return_ty))?;
 
                 // jump to the function
                 buf.push(IrOpcode::JumpImmediateFunction(monomorphized_func_id));
@@ -250,52 +492,36 @@ impl Lowerer {
             },
             Intrinsic { ty: _ty, intrinsic } => self.lower_intrinsic(intrinsic, return_destination),
             ErrorRecovery(span) => Err(span.with_item(LoweringError::Internal("Lowering should not be performed on an AST with errors".into()))),
+            Hole { .. } => Err(body.span().with_item(LoweringError::UnfilledHole)),
+            InterfaceMethod { .. } => Err(body
+                .span()
+                .with_item(LoweringError::Internal("interface methods are lowered to their implementations".into()))),
             ExprWithBindings { bindings, expression } => self.with_variable_context(|ctx| -> Result<_> {
                 let mut buf = vec![];
-                for (name, expr) in bindings {
+                for (pattern, expr) in bindings {
                     let reg = ctx.fresh_reg();
-                    let mut expr = ctx.lower_expr(expr, ReturnDestination::Reg(reg))?;
-                    buf.append(&mut expr);
-                    ctx.insert_var(name, reg);
+                    let mut lowered = ctx.lower_expr(expr, ReturnDestination::Reg(reg))?;
+                    buf.append(&mut lowered);
+                    match pattern {
+                        BindingPattern::Name(name) => ctx.insert_var(name, reg),
+                        BindingPattern::Tuple(names) => {
+                            let tuple_ty = ctx.to_ir_type(ctx.type_solution.expr_ty(expr));
+                            buf.append(&mut ctx.lower_destructuring(reg, &tuple_ty, names));
+                        },
+                    }
                 }
                 let mut expr = ctx.lower_expr(expression, return_destination)?;
                 buf.append(&mut expr);
                 Ok(buf)
             }),
-            TypeConstructor { ty, args } => {
-                let mut buf = vec![];
+            TypeConstructor { ty, variant, args } => {
                 let ir_ty = self.to_ir_type(*ty);
-                if ir_ty.is_copy_type() && args.len() == 1 {
-                    // if it's a copy type, then the args should be 1, as any
-                    // more args would make it non-copy due to size.
-                    //
-                    // If it's a copy type, just copy the value into the register
-                    buf.append(&mut self.lower_expr(&args[0], return_destination)?);
-                    return Ok(buf);
-                }
-
-                // the memory model for types is currently not finalized,
-                // but for now, it is just sequential memory that is word-aligned
-                println!("lowering type constructor for ir type {ir_ty:?}");
-                let size_of_aggregate_type = ir_ty.size();
-                let ReturnDestination::Reg(return_destination) = return_destination;
-
-                buf.push(IrOpcode::MallocImmediate(return_destination, size_of_aggregate_type));
-                // for each arg, lower it and store it in memory
-                let mut current_size_offset = 0;
-                let current_size_offset_reg = self.fresh_reg();
-                for arg in args.iter() {
-                    let reg = self.fresh_reg();
-                    buf.append(&mut self.lower_expr(arg, ReturnDestination::Reg(reg))?);
-                    buf.push(IrOpcode::LoadImmediate(current_size_offset_reg, current_size_offset));
-                    buf.push(IrOpcode::Add(current_size_offset_reg, current_size_offset_reg, return_destination));
-                    buf.push(IrOpcode::WriteRegisterToMemory(reg, current_size_offset_reg));
-
-                    let arg_ty = self.type_solution.expr_ty(arg);
-
-                    current_size_offset += self.to_ir_type(arg_ty).size().num_bytes() as u64;
-                }
-                Ok(buf)
+                self.lower_aggregate(&ir_ty, *variant, args, return_destination)
+            },
+            // tuples have the same layout as a user-defined type with one variant
+            Tuple { elements, ty } => {
+                let ir_ty = self.to_ir_type(*ty);
+                self.lower_aggregate(&ir_ty, 0, elements, return_destination)
             },
             If {
                 condition,
@@ -315,7 +541,295 @@ impl Lowerer {
                 buf.push(IrOpcode::Label(end_label));
                 Ok(buf)
             },
+            Match { scrutinee, arms, ty: _ } => {
+                let mut buf = vec![];
+                let scrutinee_reg = self.fresh_reg();
+                buf.append(&mut self.lower_expr(scrutinee, ReturnDestination::Reg(scrutinee_reg))?);
+                let scrutinee_ty = self.to_ir_type(self.type_solution.expr_ty(scrutinee));
+                let end_label = self.new_label();
+                // each arm checks its pattern, and jumps to the next arm if it doesn't match
+                for arm in arms {
+                    let next_arm_label = self.new_label();
+                    let mut arm_buf = self.with_variable_context(|ctx| -> Result<_> {
+                        let mut buf = ctx.lower_pattern(arm.pattern.item(), scrutinee_reg, &scrutinee_ty, next_arm_label)?;
+                        buf.append(&mut ctx.lower_expr(&arm.body, return_destination.clone())?);
+                        Ok(buf)
+                    })?;
+                    buf.append(&mut arm_buf);
+                    buf.push(IrOpcode::JumpImmediate(end_label));
+                    buf.push(IrOpcode::Label(next_arm_label));
+                }
+                buf.push(IrOpcode::Label(end_label));
+                Ok(buf)
+            },
+            FunctionValue { signature, ty: _ } => {
+                let wrapper = self.function_value_wrapper(signature.clone())?;
+                Ok(self.lower_closure(Some(wrapper), &[], return_destination))
+            },
+            Lambda {
+                params,
+                captures,
+                body,
+                ty: _,
+            } => {
+                let function = self.lower_lambda(params, captures, body)?;
+                Ok(self.lower_closure(Some(function), captures, return_destination))
+            },
+            GeneralizedLambda { lambda } => {
+                let Lambda { captures, .. } = &lambda.kind else {
+                    return Err(lambda.span().with_item(LoweringError::Internal("only lambdas are generalized".into())));
+                };
+                // the lambda's code is generated for each instantiation of it, as the types it is
+                // used at can differ, so the binding just holds the values it captures
+                Ok(self.lower_closure(None, captures, return_destination))
+            },
+            Instantiation { name, instance } => {
+                let Lambda { params, captures, body, .. } = &instance.kind else {
+                    return Err(instance.span().with_item(LoweringError::Internal("only lambdas are generalized".into())));
+                };
+                let Some(binding_reg) = self.get_variable(name.id) else {
+                    return Err(name.span.with_item(LoweringError::Internal("generalized binding was not lowered".into())));
+                };
+                let function = self.lower_lambda(params, captures, body)?;
+                Ok(self.lower_instantiation(function, binding_reg, captures.len(), return_destination))
+            },
+            ClosureCall { callee, args, ty: _ } => {
+                let mut buf = vec![];
+                let closure_reg = self.fresh_reg();
+                buf.append(&mut self.lower_expr(callee, ReturnDestination::Reg(closure_reg))?);
+                for arg in args {
+                    let reg = self.fresh_reg();
+                    buf.append(&mut self.lower_expr(arg, ReturnDestination::Reg(reg))?);
+                    let ty = self.to_ir_type(self.type_solution.expr_ty(arg));
+                    buf.push(IrOpcode::StackPush(TypedReg { ty, reg }));
+                }
+                // the closure goes on top of the arguments, so the function can load its captures
+                buf.push(IrOpcode::StackPush(TypedReg {
+                    ty:  IrTy::Closure,
+                    reg: closure_reg,
+                }));
+                let function_reg = self.fresh_reg();
+                buf.push(IrOpcode::ReadMemoryToRegister(function_reg, closure_reg));
+                buf.push(IrOpcode::PushPc());
+                buf.push(IrOpcode::JumpFunction(function_reg));
+                let ReturnDestination::Reg(reg) = return_destination;
+                buf.push(IrOpcode::Copy(reg, Reg::Reserved(ReservedRegister::ReturnValueRegister)));
+                Ok(buf)
+            },
+            FieldAccess { target, field_index, ty: _ } => {
+                let mut buf = vec![];
+                let record_ty = self.to_ir_type(self.type_solution.expr_ty(target));
+                let ReturnDestination::Reg(return_reg) = return_destination;
+                // see `TypeConstructor` lowering for the layout of user-defined types. Records only
+                // have one variant, so they are never tagged.
+                let offsets = record_ty.field_offsets(0);
+                if record_ty.is_copy_type() && offsets.len() == 1 {
+                    // the only field is the whole value
+                    buf.append(&mut self.lower_expr(target, ReturnDestination::Reg(return_reg))?);
+                    return Ok(buf);
+                }
+                let record_reg = self.fresh_reg();
+                buf.append(&mut self.lower_expr(target, ReturnDestination::Reg(record_reg))?);
+                let field_ptr_reg = self.fresh_reg();
+                buf.push(IrOpcode::LoadImmediate(field_ptr_reg, offsets[*field_index].num_bytes() as u64));
+                buf.push(IrOpcode::Add(field_ptr_reg, field_ptr_reg, record_reg));
+                buf.push(IrOpcode::ReadMemoryToRegister(return_reg, field_ptr_reg));
+                Ok(buf)
+            },
+            RecordUpdate { target, fields, ty: _ } => {
+                let mut buf = vec![];
+                let record_ty = self.to_ir_type(self.type_solution.expr_ty(target));
+                let ReturnDestination::Reg(return_reg) = return_destination;
+                let record_reg = self.fresh_reg();
+                buf.append(&mut self.lower_expr(target, ReturnDestination::Reg(record_reg))?);
+                // the new values are evaluated in the order they were written
+                let mut updated_field_regs = BTreeMap::new();
+                for (field_index, value) in fields {
+                    let reg = self.fresh_reg();
+                    buf.append(&mut self.lower_expr(value, ReturnDestination::Reg(reg))?);
+                    updated_field_regs.insert(*field_index, reg);
+                }
+
+                let offsets = record_ty.field_offsets(0);
+                if record_ty.is_copy_type() && offsets.len() == 1 {
+                    // the only field is the whole value
+                    let value_reg = updated_field_regs.get(&0).copied().unwrap_or(record_reg);
+                    buf.push(IrOpcode::Copy(return_reg, value_reg));
+                    return Ok(buf);
+                }
+
+                // copy the record, replacing the updated fields
+                buf.push(IrOpcode::MallocImmediate(return_reg, record_ty.size()));
+                let field_ptr_reg = self.fresh_reg();
+                for (field_index, offset) in offsets.into_iter().enumerate() {
+                    let value_reg = match updated_field_regs.get(&field_index) {
+                        Some(reg) => *reg,
+                        None => {
+                            let reg = self.fresh_reg();
+                            buf.push(IrOpcode::LoadImmediate(field_ptr_reg, offset.num_bytes() as u64));
+                            buf.push(IrOpcode::Add(field_ptr_reg, field_ptr_reg, record_reg));
+                            buf.push(IrOpcode::ReadMemoryToRegister(reg, field_ptr_reg));
+                            reg
+                        },
+                    };
+                    buf.push(IrOpcode::LoadImmediate(field_ptr_reg, offset.num_bytes() as u64));
+                    buf.push(IrOpcode::Add(field_ptr_reg, field_ptr_reg, return_reg));
+                    buf.push(IrOpcode::WriteRegisterToMemory(value_reg, field_ptr_reg));
+                }
+                Ok(buf)
+            },
+        }
+    }
+
+    /// Lowers a value of a user-defined type, or a tuple, constructed from `args` with the given
+    /// variant. Values which don't fit in a register are stored on the heap.
+    fn lower_aggregate(
+        &mut self,
+        ir_ty: &IrTy,
+        variant: usize,
+        args: &[TypedExpr],
+        return_destination: ReturnDestination,
+    ) -> Result<Vec<IrOpcode>> {
+        let mut buf = vec![];
+        if ir_ty.is_copy_type() && ir_ty.is_tagged() {
+            // none of the variants have any data, so the value is just the tag
+            let ReturnDestination::Reg(return_destination) = return_destination;
+            buf.push(IrOpcode::LoadImmediate(return_destination, variant as u64));
+            return Ok(buf);
+        }
+        if ir_ty.is_copy_type() && args.len() == 1 {
+            // if it's a copy type, then the args should be 1, as any
+            // more args would make it non-copy due to size.
+            //
+            // If it's a copy type, just copy the value into the register
+            buf.append(&mut self.lower_expr(&args[0], return_destination)?);
+            return Ok(buf);
+        }
+
+        // the memory model for types is currently not finalized,
+        // but for now, it is just sequential memory that is word-aligned
+        println!("lowering type constructor for ir type {ir_ty:?}");
+        let size_of_aggregate_type = ir_ty.size();
+        let ReturnDestination::Reg(return_destination) = return_destination;
+
+        buf.push(IrOpcode::MallocImmediate(return_destination, size_of_aggregate_type));
+        if ir_ty.is_tagged() {
+            // the tag goes first, so the variant can be checked before reading any fields
+            let tag_reg = self.fresh_reg();
+            buf.push(IrOpcode::LoadImmediate(tag_reg, variant as u64));
+            buf.push(IrOpcode::WriteRegisterToMemory(tag_reg, return_destination));
+        }
+        // for each arg, lower it and store it in memory
+        let current_size_offset_reg = self.fresh_reg();
+        for (arg, offset) in args.iter().zip(ir_ty.field_offsets(variant)) {
+            let reg = self.fresh_reg();
+            buf.append(&mut self.lower_expr(arg, ReturnDestination::Reg(reg))?);
+            buf.push(IrOpcode::LoadImmediate(current_size_offset_reg, offset.num_bytes() as u64));
+            buf.push(IrOpcode::Add(current_size_offset_reg, current_size_offset_reg, return_destination));
+            buf.push(IrOpcode::WriteRegisterToMemory(reg, current_size_offset_reg));
+        }
+        Ok(buf)
+    }
+
+    /// Reads each element of the tuple in `tuple` into a new variable with the corresponding name,
+    /// see `lower_aggregate` for the layout of tuples.
+    fn lower_destructuring(
+        &mut self,
+        tuple: Reg,
+        tuple_ty: &IrTy,
+        names: &[Identifier],
+    ) -> Vec<IrOpcode> {
+        let mut buf = vec![];
+        let element_ptr_reg = self.fresh_reg();
+        for (name, offset) in names.iter().zip(tuple_ty.field_offsets(0)) {
+            let reg = self.fresh_reg();
+            buf.push(IrOpcode::LoadImmediate(element_ptr_reg, offset.num_bytes() as u64));
+            buf.push(IrOpcode::Add(element_ptr_reg, element_ptr_reg, tuple));
+            buf.push(IrOpcode::ReadMemoryToRegister(reg, element_ptr_reg));
+            self.insert_var(name, reg);
+        }
+        buf
+    }
+
+    /// Lowers the checks for whether the value in `scrutinee` matches `pattern`, jumping to
+    /// `no_match` if it doesn't. Any fields which the pattern binds are inserted into the
+    /// current variable context.
+    fn lower_pattern(
+        &mut self,
+        pattern: &petr_typecheck::Pattern,
+        scrutinee: Reg,
+        scrutinee_ty: &IrTy,
+        no_match: LabelId,
+    ) -> Result<Vec<IrOpcode>> {
+        use petr_typecheck::{FieldPattern, Pattern};
+        let mut buf = vec![];
+        match pattern {
+            Pattern::Wildcard => (),
+            Pattern::Literal(lit) => buf.append(&mut self.lower_literal_check(lit, scrutinee, no_match)),
+            Pattern::Variant { ty: _, variant, fields } => {
+                // see `TypeConstructor` lowering for the layout of user-defined types
+                let value_is_in_register = scrutinee_ty.is_copy_type() && (scrutinee_ty.is_tagged() || fields.len() == 1);
+                if scrutinee_ty.is_tagged() {
+                    let tag_reg = if value_is_in_register {
+                        scrutinee
+                    } else {
+                        let tag_reg = self.fresh_reg();
+                        buf.push(IrOpcode::ReadMemoryToRegister(tag_reg, scrutinee));
+                        tag_reg
+                    };
+                    let expected_tag_reg = self.fresh_reg();
+                    let is_variant_reg = self.fresh_reg();
+                    buf.push(IrOpcode::LoadImmediate(expected_tag_reg, *variant as u64));
+                    buf.push(IrOpcode::Equal(is_variant_reg, tag_reg, expected_tag_reg));
+                    buf.push(IrOpcode::JumpIfFalseImmediate(is_variant_reg, no_match));
+                }
+
+                for (field, offset) in fields.iter().zip(scrutinee_ty.field_offsets(*variant)) {
+                    if let FieldPattern::Wildcard = field.item() {
+                        continue;
+                    }
+                    let field_reg = if value_is_in_register {
+                        scrutinee
+                    } else {
+                        let field_ptr_reg = self.fresh_reg();
+                        let field_reg = self.fresh_reg();
+                        buf.push(IrOpcode::LoadImmediate(field_ptr_reg, offset.num_bytes() as u64));
+                        buf.push(IrOpcode::Add(field_ptr_reg, field_ptr_reg, scrutinee));
+                        buf.push(IrOpcode::ReadMemoryToRegister(field_reg, field_ptr_reg));
+                        field_reg
+                    };
+                    match field.item() {
+                        FieldPattern::Binding(name) => self.insert_var(name, field_reg),
+                        FieldPattern::Literal(lit) => buf.append(&mut self.lower_literal_check(lit, field_reg, no_match)),
+                        FieldPattern::Wildcard => unreachable!("wildcards were skipped above"),
+                    }
+                }
+            },
         }
+        Ok(buf)
+    }
+
+    /// Jumps to `no_match` if the value in `reg` is not equal to `lit`
+    fn lower_literal_check(
+        &mut self,
+        lit: &petr_typecheck::Literal,
+        reg: Reg,
+        no_match: LabelId,
+    ) -> Vec<IrOpcode> {
+        let data_label = self.insert_literal_data(lit);
+        let lit_reg = self.fresh_reg();
+        let is_equal_reg = self.fresh_reg();
+        let equal = match lit {
+            petr_typecheck::Literal::Float(_) => IrOpcode::FloatEqual(is_equal_reg, reg, lit_reg),
+            // a string is a pointer, so it is only equal to the literal if their contents are
+            petr_typecheck::Literal::String(_) => IrOpcode::StringEqual(is_equal_reg, reg, lit_reg),
+            _ => IrOpcode::Equal(is_equal_reg, reg, lit_reg),
+        };
+        vec![
+            IrOpcode::LoadData(lit_reg, data_label),
+            equal,
+            IrOpcode::JumpIfFalseImmediate(is_equal_reg, no_match),
+        ]
     }
 
     fn new_label(&mut self) -> LabelId {
@@ -332,6 +846,7 @@ impl Lowerer {
 
         self.data_section.insert(match lit {
             Integer(val) => DataSectionEntry::Int64(*val),
+            Float(val) => DataSectionEntry::Float64(val.0),
             Boolean(val) => DataSectionEntry::Bool(*val),
             String(val) => DataSectionEntry::String(Rc::from(val.as_str())),
         })
@@ -345,11 +860,21 @@ impl Lowerer {
         // Generalize the type. These general types are much more useful for codegen.
         // Specific types include extra information about constant literal value types,
         // data flow analysis, effects tracking, etc., that codegen does not care about.
-        let ty = self.type_solution.generalize(&ty.as_specific_ty());
+        let ty = substitute_type_parameters(self.type_solution.generalize(&ty.as_specific_ty()), &self.type_parameters);
+        self.lower_general_type(ty)
+    }
+
+    /// Lowers a type which is already generalized, without looking up any of its type variables
+    fn lower_general_type(
+        &mut self,
+        ty: GeneralType,
+    ) -> IrTy {
         use petr_typecheck::GeneralType::*;
         match ty {
             Unit => IrTy::Unit,
             Integer => IrTy::Int64,
+            SizedInteger(ty) => IrTy::SizedInt(ty),
+            Float => IrTy::Float64,
             Boolean => IrTy::Boolean,
             String => IrTy::String,
             UserDefined {
@@ -363,7 +888,7 @@ impl Lowerer {
                 for variant in variants {
                     let mut fields_buf = Vec::with_capacity(variant.fields.len());
                     for field in &variant.fields {
-                        fields_buf.push(self.lower_type(field.clone()));
+                        fields_buf.push(self.lower_general_type(field.clone()));
                     }
                     variants_buf.push(IrUserDefinedTypeVariant { fields: fields_buf });
                 }
@@ -378,17 +903,28 @@ impl Lowerer {
                     constant_literal_types,
                 }
             },
-            Arrow(_) => todo!(),
+            Arrow(_) => IrTy::Closure,
+            // values of recursive types are always larger than a register, so they are on the heap,
+            // and the fields which refer back to them hold pointers. The layout of the type they
+            // point to isn't needed to store them.
+            Recursive(_) => IrTy::Ptr(Box::new(IrTy::Unit)),
             ErrorRecovery => todo!(),
-            List(ty) => IrTy::List(Box::new(self.lower_type(*ty))),
+            List(ty) => IrTy::List(Box::new(self.lower_general_type(*ty))),
             Infer(_, span) => {
                 self.errors.push(span.with_item(LoweringError::UnableToInferType));
                 IrTy::Unit
             },
+            Tuple(tys) => {
+                let fields = tys.into_iter().map(|ty| self.lower_general_type(ty)).collect();
+                IrTy::UserDefinedType {
+                    variants: vec![IrUserDefinedTypeVariant { fields }],
+                    constant_literal_types: vec![],
+                }
+            },
             Sum(a) => {
                 let mut variants_buf = Vec::with_capacity(a.len());
                 for variant in a {
-                    let ir_ty = self.lower_type(variant);
+                    let ir_ty = self.lower_general_type(variant);
                     variants_buf.push(ir_ty);
                 }
 
@@ -430,10 +966,31 @@ impl Lowerer {
                 }
                 Ok(buf)
             },
-            Add(lhs, rhs) => self.lower_arithmetic_op(lhs, rhs, return_destination, IrOpcode::Add),
-            Multiply(lhs, rhs) => self.lower_arithmetic_op(lhs, rhs, return_destination, IrOpcode::Multiply),
-            Divide(lhs, rhs) => self.lower_arithmetic_op(lhs, rhs, return_destination, IrOpcode::Divide),
-            Subtract(lhs, rhs) => self.lower_arithmetic_op(lhs, rhs, return_destination, IrOpcode::Subtract),
+            Add(lhs, rhs) => self.lower_numeric_op(lhs, rhs, return_destination, IrOpcode::Add, IrOpcode::SizedAdd, IrOpcode::FloatAdd),
+            Multiply(lhs, rhs) => self.lower_numeric_op(
+                lhs,
+                rhs,
+                return_destination,
+                IrOpcode::Multiply,
+                IrOpcode::SizedMultiply,
+                IrOpcode::FloatMultiply,
+            ),
+            Divide(lhs, rhs) => self.lower_numeric_op(
+                lhs,
+                rhs,
+                return_destination,
+                IrOpcode::Divide,
+                IrOpcode::SizedDivide,
+                IrOpcode::FloatDivide,
+            ),
+            Subtract(lhs, rhs) => self.lower_numeric_op(
+                lhs,
+                rhs,
+                return_destination,
+                IrOpcode::Subtract,
+                IrOpcode::SizedSubtract,
+                IrOpcode::FloatSubtract,
+            ),
             Malloc(size) => {
                 let size_reg = self.fresh_reg();
                 let ptr_dest = self.fresh_reg();
@@ -459,6 +1016,8 @@ impl Lowerer {
                 }
                 Ok(buf)
             },
+            // floats are compared by value, not by their bits, as `0.0` and `-0.0` are equal
+            Equals(lhs, rhs) if self.is_float(lhs) => self.lower_arithmetic_op(lhs, rhs, return_destination, IrOpcode::FloatEqual),
             Equals(lhs, rhs) => {
                 let lhs_reg = self.fresh_reg();
                 let rhs_reg = self.fresh_reg();
@@ -468,15 +1027,160 @@ impl Lowerer {
                 buf.push(IrOpcode::Equal(return_reg, lhs_reg, rhs_reg));
                 Ok(buf)
             },
+            NotEquals(lhs, rhs) if self.is_float(lhs) => self.lower_arithmetic_op(lhs, rhs, return_destination, IrOpcode::FloatNotEqual),
+            NotEquals(lhs, rhs) => self.lower_arithmetic_op(lhs, rhs, return_destination, IrOpcode::NotEqual),
+            LessThan(lhs, rhs) => self.lower_numeric_op(
+                lhs,
+                rhs,
+                return_destination,
+                IrOpcode::LessThan,
+                IrOpcode::SizedLessThan,
+                IrOpcode::FloatLessThan,
+            ),
+            GreaterThan(lhs, rhs) => self.lower_numeric_op(
+                lhs,
+                rhs,
+                return_destination,
+                IrOpcode::GreaterThan,
+                IrOpcode::SizedGreaterThan,
+                IrOpcode::FloatGreaterThan,
+            ),
+            LessThanOrEquals(lhs, rhs) => self.lower_numeric_op(
+                lhs,
+                rhs,
+                return_destination,
+                IrOpcode::LessThanOrEqual,
+                IrOpcode::SizedLessThanOrEqual,
+                IrOpcode::FloatLessThanOrEqual,
+            ),
+            GreaterThanOrEquals(lhs, rhs) => self.lower_numeric_op(
+                lhs,
+                rhs,
+                return_destination,
+                IrOpcode::GreaterThanOrEqual,
+                IrOpcode::SizedGreaterThanOrEqual,
+                IrOpcode::FloatGreaterThanOrEqual,
+            ),
+            And(lhs, rhs) => {
+                // if the lhs is false, it is also the result, and the rhs is skipped
+                let ReturnDestination::Reg(return_reg) = return_destination;
+                let end_label = self.new_label();
+                buf.append(&mut self.lower_expr(lhs, ReturnDestination::Reg(return_reg))?);
+                buf.push(IrOpcode::JumpIfFalseImmediate(return_reg, end_label));
+                buf.append(&mut self.lower_expr(rhs, ReturnDestination::Reg(return_reg))?);
+                buf.push(IrOpcode::Label(end_label));
+                Ok(buf)
+            },
+            Or(lhs, rhs) => {
+                // if the lhs is true, it is also the result, and the rhs is skipped
+                let ReturnDestination::Reg(return_reg) = return_destination;
+                let rhs_label = self.new_label();
+                let end_label = self.new_label();
+                buf.append(&mut self.lower_expr(lhs, ReturnDestination::Reg(return_reg))?);
+                buf.push(IrOpcode::JumpIfFalseImmediate(return_reg, rhs_label));
+                buf.push(IrOpcode::JumpImmediate(end_label));
+                buf.push(IrOpcode::Label(rhs_label));
+                buf.append(&mut self.lower_expr(rhs, ReturnDestination::Reg(return_reg))?);
+                buf.push(IrOpcode::Label(end_label));
+                Ok(buf)
+            },
+            Not(arg) => {
+                let arg_reg = self.fresh_reg();
+                buf.append(&mut self.lower_expr(arg, ReturnDestination::Reg(arg_reg))?);
+                let ReturnDestination::Reg(return_reg) = return_destination;
+                buf.push(IrOpcode::Not(return_reg, arg_reg));
+                Ok(buf)
+            },
+            ConvertTo(to, arg) => {
+                let is_float = self.is_float(arg);
+                let from = self.integer_type(arg);
+                let arg_reg = self.fresh_reg();
+                buf.append(&mut self.lower_expr(arg, ReturnDestination::Reg(arg_reg))?);
+                let ReturnDestination::Reg(return_reg) = return_destination;
+                buf.push(if is_float {
+                    IrOpcode::FloatToInt(*to, return_reg, arg_reg)
+                } else {
+                    IrOpcode::Convert(from, *to, return_reg, arg_reg)
+                });
+                Ok(buf)
+            },
+            ToFloat(arg) => {
+                let is_float = self.is_float(arg);
+                let from = self.integer_type(arg);
+                let arg_reg = self.fresh_reg();
+                buf.append(&mut self.lower_expr(arg, ReturnDestination::Reg(arg_reg))?);
+                let ReturnDestination::Reg(return_reg) = return_destination;
+                buf.push(if is_float {
+                    IrOpcode::Copy(return_reg, arg_reg)
+                } else {
+                    IrOpcode::IntToFloat(from, return_reg, arg_reg)
+                });
+                Ok(buf)
+            },
+            Sqrt(arg) | Floor(arg) => {
+                let arg_reg = self.fresh_reg();
+                buf.append(&mut self.lower_expr(arg, ReturnDestination::Reg(arg_reg))?);
+                let ReturnDestination::Reg(return_reg) = return_destination;
+                buf.push(match intrinsic {
+                    Sqrt(_) => IrOpcode::FloatSqrt(return_reg, arg_reg),
+                    _ => IrOpcode::FloatFloor(return_reg, arg_reg),
+                });
+                Ok(buf)
+            },
         }
     }
 
-    fn lower_arithmetic_op(
+    /// Lowers an arithmetic or comparison intrinsic with `op`, with `sized_op` if its operands
+    /// are fixed-width integers other than `'int`, or with `float_op` if they are floats
+    fn lower_numeric_op(
         &mut self,
         lhs: &TypedExpr,
         rhs: &TypedExpr,
         return_destination: ReturnDestination,
         op: fn(Reg, Reg, Reg) -> IrOpcode,
+        sized_op: fn(IntegerType, Reg, Reg, Reg) -> IrOpcode,
+        float_op: fn(Reg, Reg, Reg) -> IrOpcode,
+    ) -> Result<Vec<IrOpcode>> {
+        if self.is_float(lhs) {
+            return self.lower_arithmetic_op(lhs, rhs, return_destination, float_op);
+        }
+        // a literal operand is only known to be an integer, so the other operand's type is used
+        let ty = match self.integer_type(lhs) {
+            IntegerType::I64 => self.integer_type(rhs),
+            ty => ty,
+        };
+        match ty {
+            IntegerType::I64 => self.lower_arithmetic_op(lhs, rhs, return_destination, op),
+            ty => self.lower_arithmetic_op(lhs, rhs, return_destination, |dest, lhs, rhs| sized_op(ty, dest, lhs, rhs)),
+        }
+    }
+
+    fn is_float(
+        &mut self,
+        expr: &TypedExpr,
+    ) -> bool {
+        let ty = self.type_solution.expr_ty(expr);
+        self.to_ir_type(ty) == IrTy::Float64
+    }
+
+    /// The integer type of `expr`, which is `'int` unless it is known to be a fixed-width integer
+    fn integer_type(
+        &mut self,
+        expr: &TypedExpr,
+    ) -> IntegerType {
+        let ty = self.type_solution.expr_ty(expr);
+        match self.to_ir_type(ty) {
+            IrTy::SizedInt(ty) => ty,
+            _ => IntegerType::I64,
+        }
+    }
+
+    fn lower_arithmetic_op(
+        &mut self,
+        lhs: &TypedExpr,
+        rhs: &TypedExpr,
+        return_destination: ReturnDestination,
+        op: impl FnOnce(Reg, Reg, Reg) -> IrOpcode,
     ) -> Result<Vec<IrOpcode>> {
         let mut buf = vec![];
         let lhs_reg = self.fresh_reg();
@@ -536,7 +1240,7 @@ impl Lowerer {
         } else {
             result.push_str("\tNO ENTRY POINT\n");
         }
-        for (id, (_sig, func)) in self.monomorphized_functions.iter() {
+        for (id, (_origin, func)) in self.monomorphized_functions.iter() {
             result.push_str(&format!(
                 "{}function {}:\n",
                 if Some(id) == self.entry_point { "ENTRY: " } else { "" },
@@ -567,11 +1271,79 @@ enum ReturnDestination {
     Reg(Reg),
 }
 
+/// Binds the type parameters in `generic`, which are still uninferred after type checking, to the
+/// corresponding parts of `concrete`
+fn bind_type_parameters(
+    generic: &GeneralType,
+    concrete: &GeneralType,
+    bindings: &mut BTreeMap<usize, GeneralType>,
+) {
+    use GeneralType::*;
+    match (generic, concrete) {
+        (_, Infer(..)) => (),
+        (Infer(id, _), concrete) => {
+            bindings.entry(*id).or_insert_with(|| concrete.clone());
+        },
+        (UserDefined { variants: generic, .. }, UserDefined { variants: concrete, .. }) => {
+            for (generic, concrete) in generic
+                .iter()
+                .zip(concrete.iter())
+                .flat_map(|(a, b)| a.fields.iter().zip(b.fields.iter()))
+            {
+                bind_type_parameters(generic, concrete, bindings);
+            }
+        },
+        (List(generic), List(concrete)) => bind_type_parameters(generic, concrete, bindings),
+        (Tuple(generic), Tuple(concrete)) => {
+            for (generic, concrete) in generic.iter().zip(concrete.iter()) {
+                bind_type_parameters(generic, concrete, bindings);
+            }
+        },
+        _ => (),
+    }
+}
+
+/// Replaces the type parameters in `ty` which have concrete types in `bindings`
+fn substitute_type_parameters(
+    ty: GeneralType,
+    bindings: &BTreeMap<usize, GeneralType>,
+) -> GeneralType {
+    use GeneralType::*;
+    match ty {
+        Infer(id, span) => bindings.get(&id).cloned().unwrap_or(Infer(id, span)),
+        UserDefined {
+            name,
+            variants,
+            constant_literal_types,
+        } => UserDefined {
+            name,
+            variants: variants
+                .into_vec()
+                .into_iter()
+                .map(|variant| petr_typecheck::GeneralizedTypeVariant {
+                    fields: variant
+                        .fields
+                        .into_vec()
+                        .into_iter()
+                        .map(|field| substitute_type_parameters(field, bindings))
+                        .collect(),
+                })
+                .collect(),
+            constant_literal_types,
+        },
+        List(ty) => List(Box::new(substitute_type_parameters(*ty, bindings))),
+        Sum(tys) => Sum(tys.into_iter().map(|ty| substitute_type_parameters(ty, bindings)).collect()),
+        Tuple(tys) => Tuple(tys.into_iter().map(|ty| substitute_type_parameters(ty, bindings)).collect()),
+        ty @ (Unit | Integer | SizedInteger(_) | Float | Boolean | String | Arrow(_) | Recursive(_) | ErrorRecovery) => ty,
+    }
+}
+
 #[allow(dead_code)]
 fn literal_to_ir_ty(param_ty: petr_typecheck::Literal) -> IrTy {
     use petr_typecheck::Literal::*;
     match param_ty {
         Integer(_) => todo!(),
+        Float(_) => IrTy::Float64,
         Boolean(_) => todo!(),
         String(_) => todo!(),
     }
@@ -592,7 +1364,12 @@ mod tests {
     ) {
         let input = input.into();
         let parser = petr_parse::Parser::new(vec![
-            ("std/ops.pt", "fn add(lhs in 'int, rhs in 'int) returns 'int @add lhs, rhs"),
+            (
+                "std/ops.pt",
+                "export interface Add 'A with add(lhs in 'A, rhs in 'A) returns 'A
+                 implement Add for 'int with add = add_int
+                 fn add_int(lhs in 'int, rhs in 'int) returns 'int @add lhs, rhs",
+            ),
             ("test", &input),
         ]);
         let (ast, errs, interner, source_map) = parser.into_result();
@@ -930,4 +1707,413 @@ mod tests {
             "#]],
         );
     }
+
+    #[test]
+    fn match_on_tagged_type() {
+        check(
+            r#"
+            type Shape = Square side 'int | Rect width 'int height 'int
+
+            fn area(s in 'Shape) returns 'int
+                match s
+                | Square side => side
+                | Rect w 0 => w
+                | _ => 1
+
+            fn main() returns 'int ~area ~Square 5
+            "#,
+            expect![[r#"
+                ; DATA_SECTION
+                0: Int64(5)
+                1: Int64(0)
+                2: Int64(1)
+
+                ; PROGRAM_SECTION
+                	ENTRY: 2
+                function 0:
+                 0	pop v3
+                 1	malloci v4 24 bytes
+                 2	imm v5 0
+                 3	sri v5 v4
+                 4	cp v7 v3
+                 5	imm v6 8
+                 6	add v6 v6 v4
+                 7	sri v7 v6
+                 8	cp rr(func return value) v4
+                 9	ret
+                function 1:
+                 10	pop v8
+                 11	cp v10 v8
+                 12	lri v11 v10
+                 13	imm v12 0
+                 14	eq v13 v11 v12
+                 15	cjump v13 labelid1
+                 16	imm v14 8
+                 17	add v14 v14 v10
+                 18	lri v15 v14
+                 19	cp v9 v15
+                 20	jumpi labelid0
+                 21	label labelid1
+                 22	lri v16 v10
+                 23	imm v17 1
+                 24	eq v18 v16 v17
+                 25	cjump v18 labelid2
+                 26	imm v19 8
+                 27	add v19 v19 v10
+                 28	lri v20 v19
+                 29	imm v21 16
+                 30	add v21 v21 v10
+                 31	lri v22 v21
+                 32	ld v23 datalabel1
+                 33	eq v24 v22 v23
+                 34	cjump v24 labelid2
+                 35	cp v9 v20
+                 36	jumpi labelid0
+                 37	label labelid2
+                 38	ld v9 datalabel2
+                 39	jumpi labelid0
+                 40	label labelid3
+                 41	label labelid0
+                 42	cp rr(func return value) v9
+                 43	ret
+                ENTRY: function 2:
+                 44	ld v2 datalabel0
+                 45	push v2
+                 46	ppc
+                 47	fjumpi monomorphizedfunctionid0
+                 48	cp v1 rr(func return value)
+                 49	push v1
+                 50	ppc
+                 51	fjumpi monomorphizedfunctionid1
+                 52	cp v0 rr(func return value)
+                 53	cp rr(func return value) v0
+                 54	ret
+            "#]],
+        );
+    }
+
+    #[test]
+    fn lambda_closure_conversion() {
+        check(
+            r#"
+            fn main() returns 'int
+                let a = 1;
+                    f = \(x in 'int) => + a x
+                ~f 2
+            "#,
+            expect![[r#"
+                ; DATA_SECTION
+                0: Int64(1)
+                1: Int64(2)
+
+                ; PROGRAM_SECTION
+                	ENTRY: 2
+                function 0:
+                 0	pop v12
+                 1	pop v13
+                 2	cp v15 v13
+                 3	cp v16 v12
+                 4	add v14 v15 v16
+                 5	cp rr(func return value) v14
+                 6	ret
+                function 1:
+                 7	pop v5
+                 8	imm v6 8
+                 9	add v6 v6 v5
+                 10	lri v7 v6
+                 11	pop v8
+                 12	cp v10 v7
+                 13	push v10
+                 14	cp v11 v8
+                 15	push v11
+                 16	ppc
+                 17	fjumpi monomorphizedfunctionid0
+                 18	cp v9 rr(func return value)
+                 19	cp rr(func return value) v9
+                 20	ret
+                ENTRY: function 2:
+                 21	ld v1 datalabel0
+                 22	malloci v2 16 bytes
+                 23	imm v3 8
+                 24	add v3 v3 v2
+                 25	sri v1 v3
+                 26	malloci v4 16 bytes
+                 27	ldf v17 monomorphizedfunctionid1
+                 28	sri v17 v4
+                 29	imm v18 8
+                 30	add v18 v18 v2
+                 31	lri v19 v18
+                 32	imm v18 8
+                 33	add v18 v18 v4
+                 34	sri v19 v18
+                 35	ld v20 datalabel1
+                 36	push v20
+                 37	push v4
+                 38	lri v21 v4
+                 39	ppc
+                 40	fjump v21
+                 41	cp v0 rr(func return value)
+                 42	cp rr(func return value) v0
+                 43	ret
+            "#]],
+        );
+    }
+
+    #[test]
+    fn short_circuiting_and_or() {
+        check(
+            r#"
+            fn main() returns 'bool or and true false true
+            "#,
+            expect![[r#"
+                ; DATA_SECTION
+                0: Bool(true)
+                1: Bool(false)
+                2: Bool(true)
+
+                ; PROGRAM_SECTION
+                	ENTRY: 0
+                ENTRY: function 0:
+                 0	ld v0 datalabel0
+                 1	cjump v0 labelid2
+                 2	ld v0 datalabel1
+                 3	label labelid2
+                 4	cjump v0 labelid0
+                 5	jumpi labelid1
+                 6	label labelid0
+                 7	ld v0 datalabel2
+                 8	label labelid1
+                 9	cp rr(func return value) v0
+                 10	ret
+            "#]],
+        );
+    }
+
+    #[test]
+    fn generic_type_monomorphized_per_instance() {
+        check(
+            r#"
+            type Box 'A = Box value 'A
+
+            fn unbox(b in 'Box 'A) returns 'A b.value
+
+            fn main() returns 'bool
+              let x = ~unbox(~Box(1))
+              ~unbox(~Box(true))
+            "#,
+            expect![[r#"
+                ; DATA_SECTION
+                0: Int64(1)
+                1: Bool(true)
+
+                ; PROGRAM_SECTION
+                	ENTRY: 4
+                function 0:
+                 0	pop v4
+                 1	cp v5 v4
+                 2	cp rr(func return value) v5
+                 3	ret
+                function 1:
+                 4	pop v6
+                 5	cp v7 v6
+                 6	cp rr(func return value) v7
+                 7	ret
+                function 2:
+                 8	pop v10
+                 9	cp v11 v10
+                 10	cp rr(func return value) v11
+                 11	ret
+                function 3:
+                 12	pop v12
+                 13	cp v13 v12
+                 14	cp rr(func return value) v13
+                 15	ret
+                ENTRY: function 4:
+                 16	ld v3 datalabel0
+                 17	push v3
+                 18	ppc
+                 19	fjumpi monomorphizedfunctionid0
+                 20	cp v2 rr(func return value)
+                 21	push v2
+                 22	ppc
+                 23	fjumpi monomorphizedfunctionid1
+                 24	cp v1 rr(func return value)
+                 25	ld v9 datalabel1
+                 26	push v9
+                 27	ppc
+                 28	fjumpi monomorphizedfunctionid2
+                 29	cp v8 rr(func return value)
+                 30	push v8
+                 31	ppc
+                 32	fjumpi monomorphizedfunctionid3
+                 33	cp v0 rr(func return value)
+                 34	cp rr(func return value) v0
+                 35	ret
+            "#]],
+        );
+    }
+
+    #[test]
+    fn constrained_generic_dispatches_to_implementation() {
+        check(
+            r#"
+            fn double(x in 'A) returns 'A where 'A: std.ops.Add
+              + x x
+
+            fn main() returns 'int ~double 2
+            "#,
+            expect![[r#"
+                ; DATA_SECTION
+                0: Int64(2)
+
+                ; PROGRAM_SECTION
+                	ENTRY: 2
+                function 0:
+                 0	pop v6
+                 1	pop v7
+                 2	cp v9 v7
+                 3	cp v10 v6
+                 4	add v8 v9 v10
+                 5	cp rr(func return value) v8
+                 6	ret
+                function 1:
+                 7	pop v2
+                 8	cp v4 v2
+                 9	push v4
+                 10	cp v5 v2
+                 11	push v5
+                 12	ppc
+                 13	fjumpi monomorphizedfunctionid0
+                 14	cp v3 rr(func return value)
+                 15	cp rr(func return value) v3
+                 16	ret
+                ENTRY: function 2:
+                 17	ld v1 datalabel0
+                 18	push v1
+                 19	ppc
+                 20	fjumpi monomorphizedfunctionid1
+                 21	cp v0 rr(func return value)
+                 22	cp rr(func return value) v0
+                 23	ret
+            "#]],
+        );
+    }
+
+    #[test]
+    fn sized_integer_ops() {
+        check(
+            r#"
+            fn add(a in 'u8, b in 'u8) returns 'u8 @add a, b
+            fn main() returns 'int @to_int ~add(200, 100)
+            "#,
+            expect![[r#"
+                ; DATA_SECTION
+                0: Int64(200)
+                1: Int64(100)
+
+                ; PROGRAM_SECTION
+                	ENTRY: 1
+                function 0:
+                 0	pop v4
+                 1	pop v5
+                 2	cp v7 v5
+                 3	cp v8 v4
+                 4	adds u8 v6 v7 v8
+                 5	cp rr(func return value) v6
+                 6	ret
+                ENTRY: function 1:
+                 7	ld v2 datalabel0
+                 8	push v2
+                 9	ld v3 datalabel1
+                 10	push v3
+                 11	ppc
+                 12	fjumpi monomorphizedfunctionid0
+                 13	cp v1 rr(func return value)
+                 14	conv u8 i64 v0 v1
+                 15	cp rr(func return value) v0
+                 16	ret
+            "#]],
+        );
+    }
+
+    #[test]
+    fn float_ops() {
+        check(
+            r#"
+            fn scale(x in 'float) returns 'float @floor @multiply x, 2.5
+            fn main() returns 'int @to_int ~scale(@to_float 3)
+            "#,
+            expect![[r#"
+                ; DATA_SECTION
+                0: Int64(3)
+                1: Float64(2.5)
+
+                ; PROGRAM_SECTION
+                	ENTRY: 1
+                function 0:
+                 0	pop v4
+                 1	cp v7 v4
+                 2	ld v8 datalabel1
+                 3	fmult v6 v7 v8
+                 4	ffloor v5 v6
+                 5	cp rr(func return value) v5
+                 6	ret
+                ENTRY: function 1:
+                 7	ld v3 datalabel0
+                 8	itof i64 v2 v3
+                 9	push v2
+                 10	ppc
+                 11	fjumpi monomorphizedfunctionid0
+                 12	cp v1 rr(func return value)
+                 13	ftoi i64 v0 v1
+                 14	cp rr(func return value) v0
+                 15	ret
+            "#]],
+        );
+    }
+
+    #[test]
+    fn tuples() {
+        check(
+            r#"
+            fn pair() returns '(int, bool) (7, true)
+            fn main() returns 'int
+              let (a, b) = ~pair()
+              a
+            "#,
+            expect![[r#"
+                ; DATA_SECTION
+                0: Int64(7)
+                1: Bool(true)
+
+                ; PROGRAM_SECTION
+                	ENTRY: 1
+                function 0:
+                 0	malloci v2 9 bytes
+                 1	ld v4 datalabel0
+                 2	imm v3 0
+                 3	add v3 v3 v2
+                 4	sri v4 v3
+                 5	ld v5 datalabel1
+                 6	imm v3 8
+                 7	add v3 v3 v2
+                 8	sri v5 v3
+                 9	cp rr(func return value) v2
+                 10	ret
+                ENTRY: function 1:
+                 11	ppc
+                 12	fjumpi monomorphizedfunctionid0
+                 13	cp v1 rr(func return value)
+                 14	imm v6 0
+                 15	add v6 v6 v1
+                 16	lri v7 v6
+                 17	imm v6 8
+                 18	add v6 v6 v1
+                 19	lri v8 v6
+                 20	cp v0 v7
+                 21	cp rr(func return value) v0
+                 22	ret
+            "#]],
+        );
+    }
 }
@@ -1,4 +1,4 @@
-use petr_utils::idx_map_key;
+use petr_utils::{idx_map_key, IntegerType};
 
 use crate::MonomorphizedFunctionId;
 
@@ -42,6 +42,10 @@ macro_rules! ir_ops {
 
 ir_ops! {
     JumpImmediateFunction "fjumpi" MonomorphizedFunctionId: imm;
+    /// Jump to the function whose id is in register `function`
+    JumpFunction "fjump" Reg: function;
+    /// Load the id of a function into register `dest`, so it can be jumped to with `fjump`
+    LoadFunction "ldf" Reg: dest, MonomorphizedFunctionId: function;
     Jump "jump" Reg:  dest;
     Add "add" Reg: dest, Reg: lhs, Reg: rhs;
     Multiply "mult" Reg: dest, Reg: lhs, Reg: rhs;
@@ -63,10 +67,53 @@ ir_ops! {
     MallocImmediate "malloci" Reg: ptr_dest, Size<Bytes>: imm;
     /// Register `src` will itself have its value written to the memory pointed to by `dest_ptr`
     WriteRegisterToMemory "sri" Reg: src, Reg: dest_ptr;
+    /// Register `dest` will have the value in the memory pointed to by `src_ptr` written to it
+    ReadMemoryToRegister "lri" Reg: dest, Reg: src_ptr;
     Comment "comment" String: comment;
     JumpIfFalseImmediate "cjump" Reg: cond, LabelId: dest;
     JumpImmediate "jumpi" LabelId: dest;
-    Equal "eq" Reg: dest, Reg: lhs, Reg: rhs
+    Equal "eq" Reg: dest, Reg: lhs, Reg: rhs;
+    /// Compares the contents of the strings that `lhs` and `rhs` point to, rather than the pointers
+    StringEqual "streq" Reg: dest, Reg: lhs, Reg: rhs;
+    NotEqual "neq" Reg: dest, Reg: lhs, Reg: rhs;
+    /// Signed integer comparisons
+    LessThan "lt" Reg: dest, Reg: lhs, Reg: rhs;
+    GreaterThan "gt" Reg: dest, Reg: lhs, Reg: rhs;
+    LessThanOrEqual "lte" Reg: dest, Reg: lhs, Reg: rhs;
+    GreaterThanOrEqual "gte" Reg: dest, Reg: lhs, Reg: rhs;
+    Not "not" Reg: dest, Reg: src;
+    /// Arithmetic on fixed-width integers, which wraps around at the bounds of `ty` with its
+    /// signed or unsigned semantics. The ops without a type operate on `'int`s.
+    SizedAdd "adds" IntegerType: ty, Reg: dest, Reg: lhs, Reg: rhs;
+    SizedSubtract "subs" IntegerType: ty, Reg: dest, Reg: lhs, Reg: rhs;
+    SizedMultiply "mults" IntegerType: ty, Reg: dest, Reg: lhs, Reg: rhs;
+    SizedDivide "divs" IntegerType: ty, Reg: dest, Reg: lhs, Reg: rhs;
+    /// Comparisons of fixed-width integers, with the signed or unsigned semantics of `ty`
+    SizedLessThan "lts" IntegerType: ty, Reg: dest, Reg: lhs, Reg: rhs;
+    SizedGreaterThan "gts" IntegerType: ty, Reg: dest, Reg: lhs, Reg: rhs;
+    SizedLessThanOrEqual "ltes" IntegerType: ty, Reg: dest, Reg: lhs, Reg: rhs;
+    SizedGreaterThanOrEqual "gtes" IntegerType: ty, Reg: dest, Reg: lhs, Reg: rhs;
+    /// Converts `src`, an integer of type `from`, to an integer of type `to`, wrapping around if
+    /// it doesn't fit
+    Convert "conv" IntegerType: from, IntegerType: to, Reg: dest, Reg: src;
+    /// Arithmetic and comparisons of `'float`s, whose registers hold the bits of 64-bit floats
+    FloatAdd "fadd" Reg: dest, Reg: lhs, Reg: rhs;
+    FloatSubtract "fsub" Reg: dest, Reg: lhs, Reg: rhs;
+    FloatMultiply "fmult" Reg: dest, Reg: lhs, Reg: rhs;
+    FloatDivide "fdiv" Reg: dest, Reg: lhs, Reg: rhs;
+    FloatEqual "feq" Reg: dest, Reg: lhs, Reg: rhs;
+    FloatNotEqual "fneq" Reg: dest, Reg: lhs, Reg: rhs;
+    FloatLessThan "flt" Reg: dest, Reg: lhs, Reg: rhs;
+    FloatGreaterThan "fgt" Reg: dest, Reg: lhs, Reg: rhs;
+    FloatLessThanOrEqual "flte" Reg: dest, Reg: lhs, Reg: rhs;
+    FloatGreaterThanOrEqual "fgte" Reg: dest, Reg: lhs, Reg: rhs;
+    FloatSqrt "fsqrt" Reg: dest, Reg: src;
+    FloatFloor "ffloor" Reg: dest, Reg: src;
+    /// Converts `src`, an integer of type `from`, to the nearest float
+    IntToFloat "itof" IntegerType: from, Reg: dest, Reg: src;
+    /// Converts `src`, a float, to an integer of type `to`, rounding towards zero and wrapping
+    /// around if it doesn't fit
+    FloatToInt "ftoi" IntegerType: to, Reg: dest, Reg: src
 }
 
 idx_map_key!(LabelId);
@@ -102,6 +149,11 @@ pub struct TypedReg {
 pub enum IrTy {
     Ptr(Box<IrTy>),
     Int64,
+    /// A fixed-width integer other than `'int`. In a register, signed integers are sign-extended
+    /// and unsigned integers are zero-extended to 64 bits.
+    SizedInt(IntegerType),
+    /// A 64-bit float. In a register, it is stored as its bits.
+    Float64,
     Unit,
     String,
     Boolean,
@@ -110,6 +162,9 @@ pub enum IrTy {
         constant_literal_types: Vec<IrTy>,
     },
     List(Box<IrTy>),
+    /// A function value, which is a pointer to the closure's memory. The first word of a closure
+    /// is the id of the function to call, and the values it captured follow.
+    Closure,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
@@ -128,28 +183,38 @@ impl IrTy {
     pub fn size(&self) -> Size<Bytes> {
         match self {
             IrTy::Int64 => 8,
+            IrTy::SizedInt(ty) => ty.bits() as usize / 8,
+            IrTy::Float64 => 8,
             IrTy::Ptr(_) => 8,
             IrTy::Unit => 0,
             // size of the pointer to the string
             IrTy::String => 8,
             IrTy::Boolean => 1,
-            // the size of a sum type is the size of the largest variant
+            // the size of a sum type is the size of the largest variant, plus the tag if there is
+            // one
             IrTy::UserDefinedType {
                 variants,
                 constant_literal_types,
             } => {
-                return variants
+                let largest_variant = variants
                     .iter()
                     .map(|v| v.size())
                     .chain(constant_literal_types.iter().map(|t| t.size()))
                     .max()
-                    .expect("user defined type should have at least one variant")
+                    .expect("user defined type should have at least one variant");
+                if self.is_tagged() {
+                    TAG_SIZE + largest_variant.num_bytes()
+                } else {
+                    largest_variant.num_bytes()
+                }
             },
             IrTy::List(ty) => {
                 // the size of a list is the size of a pointer
                 // to the first element
                 return IrTy::Ptr(ty.clone()).size();
             },
+            // the size of the pointer to the closure
+            IrTy::Closure => 8,
         }
         .into()
     }
@@ -157,8 +222,49 @@ impl IrTy {
     pub(crate) fn is_copy_type(&self) -> bool {
         self.size().num_bytes() <= 8
     }
+
+    /// Values of user-defined types with more than one variant begin with a tag, which is the
+    /// index of the variant they were constructed with. All of the constant literal types of a
+    /// type count as one variant. If the whole value fits in a register, then the register just
+    /// holds the tag, as none of the variants can have any data.
+    pub(crate) fn is_tagged(&self) -> bool {
+        match self {
+            IrTy::UserDefinedType {
+                variants,
+                constant_literal_types,
+            } => variants.len() + usize::from(!constant_literal_types.is_empty()) > 1,
+            _ => false,
+        }
+    }
+
+    /// The offset of each field of the given variant, from the start of a value of this type.
+    pub(crate) fn field_offsets(
+        &self,
+        variant: usize,
+    ) -> Vec<Size<Bytes>> {
+        let IrTy::UserDefinedType { variants, .. } = self else {
+            return vec![];
+        };
+        let mut offset = if self.is_tagged() { TAG_SIZE } else { 0 };
+        let Some(variant) = variants.get(variant) else {
+            // constant literal variants have a single field, the literal itself
+            return vec![offset.into()];
+        };
+        variant
+            .fields
+            .iter()
+            .map(|field| {
+                let field_offset = offset;
+                offset += field.size().num_bytes();
+                field_offset.into()
+            })
+            .collect()
+    }
 }
 
+/// the size of the tag which tells tagged user-defined types apart, see [`IrTy::is_tagged`]
+const TAG_SIZE: usize = 8;
+
 #[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
 pub struct Size<T>(T)
 where
@@ -0,0 +1,580 @@
+//! A lossless concrete syntax tree (CST) for petr sources.
+//! Unlike [`petr_ast::Ast`], which throws away whitespace, newlines, and most comments, the CST
+//! records every byte of the source as a token. This means it can be turned back into the exact
+//! source it was built from, which makes it suitable for tooling like the formatter or
+//! refactoring tools, which need to edit code without destroying comments inside expressions.
+//!
+//! A file is a list of top-level items, each of which starts with its leading trivia. Below the
+//! item level, the tree has a node for every expression, match arm, pattern, type variant,
+//! parameter, interface method, and implemented method that the parser produced, as well as for
+//! aliased and implementing types and imported and implemented paths. Trivia such as a comment
+//! inside an expression is a child of the innermost node around it. Parts of the source which failed to parse are kept as flat tokens in their item.
+
+#[cfg(test)]
+mod tests;
+
+use std::{cmp::Reverse, iter::Peekable, ops::Range};
+
+use logos::Logos;
+use miette::SourceSpan;
+use petr_ast::{AstNode, Expression, FunctionParameter, Pattern, TypeVariantOrLiteral};
+use petr_utils::{Path, SourceId, Span, SpannedItem};
+
+use crate::parser::{Parser, Token};
+
+/// The kind of a single [`CstToken`].
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum CstTokenKind {
+    /// A token produced by the lexer, including [`Token::Comment`] and [`Token::Newline`].
+    Token(Token),
+    /// Whitespace which the lexer skips, i.e. spaces and tabs.
+    Whitespace,
+    /// Bytes which the lexer could not turn into a token.
+    Invalid,
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct CstToken {
+    kind: CstTokenKind,
+    text: &'static str,
+    span: Span,
+}
+
+impl CstToken {
+    pub fn kind(&self) -> CstTokenKind {
+        self.kind
+    }
+
+    pub fn text(&self) -> &'static str {
+        self.text
+    }
+
+    pub fn span(&self) -> Span {
+        self.span
+    }
+
+    fn start(&self) -> usize {
+        self.span.span().offset()
+    }
+
+    /// Trivia are tokens which carry no meaning for the parser: whitespace, newlines, and comments.
+    pub fn is_trivia(&self) -> bool {
+        matches!(
+            self.kind,
+            CstTokenKind::Whitespace | CstTokenKind::Token(Token::Newline) | CstTokenKind::Token(Token::Comment)
+        )
+    }
+}
+
+/// The kind of a [`CstNode`].
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum CstNodeKind {
+    File,
+    FunctionDeclaration,
+    /// A type declaration or a type alias, which both begin with `type`
+    TypeDeclaration,
+    ImportStatement,
+    InterfaceDeclaration,
+    ImplementationDeclaration,
+    /// Tokens which do not begin a top-level item, e.g. a stray `)` at the top level of a file.
+    Error,
+    /// A variant of a type declaration, like `Circle radius 'int`
+    TypeVariant,
+    /// A field of a type variant, like `radius 'int`
+    TypeField,
+    /// A parameter of a function or interface method, like `x in 'int`
+    Parameter,
+    /// A method of an interface declaration, like `show(x in 'A) returns 'string`
+    InterfaceMethod,
+    /// A method of an implementation declaration and the function implementing it, like
+    /// `show = show_int`
+    ImplementedMethod,
+    /// The type of a type alias, or the type an interface is implemented for
+    Type,
+    /// A path to an item, like `std.io` in an import statement
+    Path,
+    Literal,
+    List,
+    /// A binary operator and its operands, like `+ a 1`
+    Operator,
+    UnaryOperator,
+    FunctionCall,
+    Variable,
+    IntrinsicCall,
+    /// A `let` expression, including its bindings and the expression they are bound in
+    Let,
+    /// A single binding of a `let` expression, like `a = 1`
+    Binding,
+    If,
+    Match,
+    MatchArm,
+    Pattern,
+    Lambda,
+    FieldAccess,
+    RecordUpdate,
+    Tuple,
+    Hole,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum CstElement {
+    Node(CstNode),
+    Token(CstToken),
+}
+
+impl CstElement {
+    pub fn span(&self) -> Span {
+        match self {
+            CstElement::Node(node) => node.span,
+            CstElement::Token(token) => token.span,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct CstNode {
+    kind:     CstNodeKind,
+    span:     Span,
+    children: Vec<CstElement>,
+}
+
+impl CstNode {
+    pub fn kind(&self) -> CstNodeKind {
+        self.kind
+    }
+
+    pub fn span(&self) -> Span {
+        self.span
+    }
+
+    pub fn children(&self) -> &[CstElement] {
+        &self.children
+    }
+
+    /// All tokens in this node, in source order.
+    pub fn tokens(&self) -> Box<dyn Iterator<Item = &CstToken> + '_> {
+        Box::new(self.children.iter().flat_map(|child| -> Box<dyn Iterator<Item = &CstToken>> {
+            match child {
+                CstElement::Node(node) => node.tokens(),
+                CstElement::Token(token) => Box::new(std::iter::once(token)),
+            }
+        }))
+    }
+
+    fn new(
+        kind: CstNodeKind,
+        source: SourceId,
+        children: Vec<CstElement>,
+    ) -> Self {
+        let span = match (children.first(), children.last()) {
+            (Some(first), Some(last)) => first.span().join(last.span()),
+            // only an empty file has no children
+            _ => Span::new(source, SourceSpan::new(0.into(), 0.into())),
+        };
+        Self { kind, span, children }
+    }
+}
+
+/// A lossless concrete syntax tree for a single source file.
+#[derive(Debug, PartialEq, Clone)]
+pub struct ConcreteSyntaxTree {
+    source: SourceId,
+    root:   CstNode,
+}
+
+impl ConcreteSyntaxTree {
+    pub fn new(
+        source: SourceId,
+        text: &'static str,
+    ) -> Self {
+        let (ast, _errors, _interner, _source_map) = Parser::new(vec![("cst", text)]).into_result();
+        // types have no spans in the AST, so their extent is found among the tokens
+        let tokens = lex_losslessly(source, text)
+            .into_iter()
+            .filter(|token| !token.is_trivia())
+            .collect::<Vec<_>>();
+        let mut syntax_nodes = Vec::new();
+        for node in ast.modules.iter().flat_map(|module| module.nodes.iter()) {
+            collect_item(node, &tokens, &mut syntax_nodes);
+        }
+        // parents come before their children, and `sort_by_key` is stable, so a child with the
+        // same range as its parent stays after it
+        syntax_nodes.sort_by_key(|(_, range)| (range.start, Reverse(range.end)));
+        let mut syntax_nodes = syntax_nodes.into_iter().peekable();
+
+        let children = top_level_items(source, text)
+            .into_iter()
+            .map(|element| match element {
+                CstElement::Node(item) => {
+                    let mut tokens = item.tokens().copied().collect::<Vec<_>>().into_iter().peekable();
+                    CstElement::Node(nest(item.kind, source, &mut tokens, &mut syntax_nodes, usize::MAX))
+                },
+                token => token,
+            })
+            .collect();
+
+        Self {
+            source,
+            root: CstNode::new(CstNodeKind::File, source, children),
+        }
+    }
+
+    pub fn source(&self) -> SourceId {
+        self.source
+    }
+
+    pub fn root(&self) -> &CstNode {
+        &self.root
+    }
+
+    /// All tokens in the file, in source order.
+    pub fn tokens(&self) -> impl Iterator<Item = &CstToken> {
+        self.root.tokens()
+    }
+}
+
+/// Writes the exact source text that this tree was built from.
+impl std::fmt::Display for ConcreteSyntaxTree {
+    fn fmt(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+    ) -> std::fmt::Result {
+        for token in self.tokens() {
+            write!(f, "{}", token.text)?;
+        }
+        Ok(())
+    }
+}
+
+/// Groups the tokens of `text` into top-level items, each of which is a node holding the flat list
+/// of its tokens, starting with its leading trivia. Trivia at the end of the file is not part of any
+/// item.
+pub(crate) fn top_level_items(
+    source: SourceId,
+    text: &'static str,
+) -> Vec<CstElement> {
+    let mut children = Vec::new();
+    let mut current_item: Option<(CstNodeKind, Vec<CstElement>)> = None;
+    // trivia is held back until we know whether it leads into another item, or trails at the end of the file
+    let mut pending_trivia = Vec::new();
+
+    for token in lex_losslessly(source, text) {
+        if token.is_trivia() {
+            pending_trivia.push(CstElement::Token(token));
+            continue;
+        }
+
+        let kind = match token.kind {
+            CstTokenKind::Token(Token::FunctionKeyword | Token::ExportFunctionKeyword) => Some(CstNodeKind::FunctionDeclaration),
+            CstTokenKind::Token(Token::TypeKeyword | Token::ExportTypeKeyword) => Some(CstNodeKind::TypeDeclaration),
+            CstTokenKind::Token(Token::Import) => Some(CstNodeKind::ImportStatement),
+            CstTokenKind::Token(Token::InterfaceKeyword | Token::ExportInterfaceKeyword) => Some(CstNodeKind::InterfaceDeclaration),
+            CstTokenKind::Token(Token::ImplementKeyword) => Some(CstNodeKind::ImplementationDeclaration),
+            _ => None,
+        };
+
+        match (kind, current_item.as_mut()) {
+            // the start of a new item, which takes the pending trivia as its leading trivia
+            (Some(kind), _) => {
+                if let Some((kind, item_children)) = current_item.take() {
+                    children.push(CstElement::Node(CstNode::new(kind, source, item_children)));
+                }
+                let mut item_children = std::mem::take(&mut pending_trivia);
+                item_children.push(CstElement::Token(token));
+                current_item = Some((kind, item_children));
+            },
+            // the continuation of the current item
+            (None, Some((_, item_children))) => {
+                item_children.append(&mut pending_trivia);
+                item_children.push(CstElement::Token(token));
+            },
+            // tokens before the first item
+            (None, None) => {
+                let mut item_children = std::mem::take(&mut pending_trivia);
+                item_children.push(CstElement::Token(token));
+                current_item = Some((CstNodeKind::Error, item_children));
+            },
+        }
+    }
+
+    if let Some((kind, item_children)) = current_item.take() {
+        children.push(CstElement::Node(CstNode::new(kind, source, item_children)));
+    }
+    children.append(&mut pending_trivia);
+    children
+}
+
+/// Builds a node of `kind` out of the tokens before `end`, nesting them under the syntax nodes whose
+/// ranges cover them. A syntax node begins at the first non-trivia token in its range, so trivia
+/// between two nodes stays in their parent.
+fn nest(
+    kind: CstNodeKind,
+    source: SourceId,
+    tokens: &mut Peekable<std::vec::IntoIter<CstToken>>,
+    syntax_nodes: &mut Peekable<std::vec::IntoIter<(CstNodeKind, Range<usize>)>>,
+    end: usize,
+) -> CstNode {
+    let mut children = Vec::new();
+    while let Some(token) = tokens.peek().copied().filter(|token| token.start() < end) {
+        if !token.is_trivia() {
+            // nodes which end before this token can no longer contain anything
+            while syntax_nodes.next_if(|(_, range)| range.end <= token.start()).is_some() {}
+            if let Some((kind, range)) = syntax_nodes.next_if(|(_, range)| range.start <= token.start()) {
+                children.push(CstElement::Node(nest(kind, source, tokens, syntax_nodes, range.end.min(end))));
+                continue;
+            }
+        }
+        children.push(CstElement::Token(token));
+        tokens.next();
+    }
+    CstNode::new(kind, source, children)
+}
+
+/// Collects the kind and byte range of the syntax nodes inside of a top-level item. `tokens` are
+/// the non-trivia tokens of the file.
+fn collect_item(
+    node: &SpannedItem<AstNode>,
+    tokens: &[CstToken],
+    syntax_nodes: &mut Vec<(CstNodeKind, Range<usize>)>,
+) {
+    let end = range(node.span()).end;
+    match node.item() {
+        AstNode::FunctionDeclaration(function) => {
+            collect_parameters(&function.item().parameters, tokens, end, syntax_nodes);
+            collect_expression(&function.item().body, syntax_nodes);
+        },
+        AstNode::TypeDeclaration(declaration) => {
+            for variant in declaration.item().variants.iter() {
+                syntax_nodes.push((CstNodeKind::TypeVariant, range(variant.span())));
+                if let TypeVariantOrLiteral::Variant(variant) = variant.item() {
+                    for field in variant.fields.iter() {
+                        syntax_nodes.push((CstNodeKind::TypeField, range(field.span())));
+                    }
+                }
+            }
+        },
+        AstNode::TypeAlias(alias) => {
+            let start = start_after(tokens, range(alias.item().name.span).end, Token::Equals);
+            syntax_nodes.push((CstNodeKind::Type, extent(tokens, start, end, &[])));
+        },
+        AstNode::ImportStatement(import) => syntax_nodes.push((CstNodeKind::Path, path_range(&import.item().path))),
+        AstNode::InterfaceDeclaration(interface) => {
+            for method in interface.item().methods.iter() {
+                let start = range(method.name.span).start;
+                syntax_nodes.push((CstNodeKind::InterfaceMethod, extent(tokens, start, end, &[Token::Comma])));
+                collect_parameters(&method.parameters, tokens, end, syntax_nodes);
+            }
+        },
+        AstNode::ImplementationDeclaration(implementation) => {
+            let implementation = implementation.item();
+            let interface = path_range(&implementation.interface);
+            syntax_nodes.push((CstNodeKind::Path, interface.clone()));
+            let start = start_after(tokens, interface.end, Token::ForKeyword);
+            syntax_nodes.push((CstNodeKind::Type, extent(tokens, start, end, &[Token::With])));
+            for (method, function) in implementation.methods.iter() {
+                let function = path_range(function);
+                syntax_nodes.push((CstNodeKind::ImplementedMethod, range(method.span).start..function.end));
+                syntax_nodes.push((CstNodeKind::Path, function));
+            }
+        },
+    }
+}
+
+fn collect_parameters(
+    parameters: &[FunctionParameter],
+    tokens: &[CstToken],
+    end: usize,
+    syntax_nodes: &mut Vec<(CstNodeKind, Range<usize>)>,
+) {
+    for parameter in parameters.iter() {
+        let start = range(parameter.name.span).start;
+        syntax_nodes.push((CstNodeKind::Parameter, extent(tokens, start, end, &[Token::Comma])));
+    }
+}
+
+/// The range of the tokens from `start` up to the first of `separators` or closing delimiter
+/// which isn't nested in delimiters of its own, or up to `end`.
+fn extent(
+    tokens: &[CstToken],
+    start: usize,
+    end: usize,
+    separators: &[Token],
+) -> Range<usize> {
+    let mut extent = start..start;
+    let mut depth = 0usize;
+    for token in tokens
+        .iter()
+        .skip_while(|token| token.start() < start)
+        .take_while(|token| token.start() < end)
+    {
+        match token.kind {
+            CstTokenKind::Token(Token::OpenParen | Token::OpenBracket) => depth += 1,
+            CstTokenKind::Token(Token::CloseParen | Token::CloseBracket) if depth == 0 => break,
+            CstTokenKind::Token(Token::CloseParen | Token::CloseBracket) => depth -= 1,
+            CstTokenKind::Token(tok) if depth == 0 && separators.contains(&tok) => break,
+            _ => (),
+        }
+        extent.end = range(token.span).end;
+    }
+    extent
+}
+
+/// Where the token after the first `token` at or after `start` begins
+fn start_after(
+    tokens: &[CstToken],
+    start: usize,
+    token: Token,
+) -> usize {
+    let mut rest = tokens
+        .iter()
+        .skip_while(|tok| tok.start() < start)
+        .skip_while(|tok| tok.kind != CstTokenKind::Token(token));
+    rest.nth(1).map(CstToken::start).unwrap_or(start)
+}
+
+fn path_range(path: &Path) -> Range<usize> {
+    match (path.identifiers.first(), path.identifiers.last()) {
+        (Some(first), Some(last)) => range(first.span).start..range(last.span).end,
+        _ => unreachable!("paths have at least one identifier"),
+    }
+}
+
+fn collect_expression(
+    expression: &SpannedItem<Expression>,
+    syntax_nodes: &mut Vec<(CstNodeKind, Range<usize>)>,
+) {
+    let kind = match expression.item() {
+        Expression::Literal(_) => CstNodeKind::Literal,
+        Expression::List(_) => CstNodeKind::List,
+        Expression::Operator(_) => CstNodeKind::Operator,
+        Expression::UnaryOperator(_) => CstNodeKind::UnaryOperator,
+        Expression::FunctionCall(_) => CstNodeKind::FunctionCall,
+        Expression::Variable(_) => CstNodeKind::Variable,
+        Expression::IntrinsicCall(_) => CstNodeKind::IntrinsicCall,
+        Expression::Binding(_) => CstNodeKind::Let,
+        Expression::If(_) => CstNodeKind::If,
+        Expression::Match(_) => CstNodeKind::Match,
+        Expression::Lambda(_) => CstNodeKind::Lambda,
+        Expression::FieldAccess(_) => CstNodeKind::FieldAccess,
+        Expression::RecordUpdate(_) => CstNodeKind::RecordUpdate,
+        Expression::Tuple(_) => CstNodeKind::Tuple,
+        Expression::Hole(_) => CstNodeKind::Hole,
+        // these are generated by the parser for declarations, and have no source of their own
+        Expression::TypeConstructor(..) | Expression::InterfaceMethod(..) => return,
+    };
+    syntax_nodes.push((kind, range(expression.span())));
+
+    match expression.item() {
+        Expression::List(list) => {
+            for element in list.elements.iter() {
+                collect_expression(element.item(), syntax_nodes);
+            }
+        },
+        Expression::Operator(operator) => {
+            collect_expression(&operator.lhs, syntax_nodes);
+            collect_expression(&operator.rhs, syntax_nodes);
+        },
+        Expression::UnaryOperator(operator) => collect_expression(&operator.operand, syntax_nodes),
+        Expression::FunctionCall(call) => {
+            for arg in call.args.iter() {
+                collect_expression(arg, syntax_nodes);
+            }
+        },
+        Expression::IntrinsicCall(call) => {
+            for arg in call.args.iter() {
+                collect_expression(arg, syntax_nodes);
+            }
+        },
+        Expression::Tuple(elements) => {
+            for element in elements.iter() {
+                collect_expression(element, syntax_nodes);
+            }
+        },
+        Expression::Binding(binding) => {
+            for binding in binding.bindings.iter() {
+                if let Some(name) = binding.pattern.names().first() {
+                    let start = range(name.span).start;
+                    syntax_nodes.push((CstNodeKind::Binding, start..range(binding.val.span()).end));
+                }
+                collect_expression(&binding.val, syntax_nodes);
+            }
+            collect_expression(&binding.expression, syntax_nodes);
+        },
+        Expression::If(if_expression) => {
+            collect_expression(&if_expression.condition, syntax_nodes);
+            collect_expression(&if_expression.then_branch, syntax_nodes);
+            if let Some(else_branch) = &if_expression.else_branch {
+                collect_expression(else_branch, syntax_nodes);
+            }
+        },
+        Expression::Match(match_expression) => {
+            collect_expression(&match_expression.scrutinee, syntax_nodes);
+            for arm in match_expression.arms.iter() {
+                syntax_nodes.push((CstNodeKind::MatchArm, range(arm.span())));
+                syntax_nodes.push((CstNodeKind::Pattern, range(arm.item().pattern.span())));
+                if let Pattern::Variant(variant) = arm.item().pattern.item() {
+                    for field in variant.fields.iter() {
+                        syntax_nodes.push((CstNodeKind::Pattern, range(field.span())));
+                    }
+                }
+                collect_expression(&arm.item().body, syntax_nodes);
+            }
+        },
+        Expression::Lambda(lambda) => collect_expression(&lambda.body, syntax_nodes),
+        Expression::FieldAccess(access) => collect_expression(&access.target, syntax_nodes),
+        Expression::RecordUpdate(update) => {
+            collect_expression(&update.target, syntax_nodes);
+            for (_, value) in update.fields.iter() {
+                collect_expression(value, syntax_nodes);
+            }
+        },
+        Expression::Literal(_)
+        | Expression::Variable(_)
+        | Expression::Hole(_)
+        | Expression::TypeConstructor(..)
+        | Expression::InterfaceMethod(..) => (),
+    }
+}
+
+fn range(span: Span) -> Range<usize> {
+    let span = span.span();
+    span.offset()..span.offset() + span.len()
+}
+
+/// Lexes `text`, but unlike [`crate::parser::Parser::advance`], does not discard anything.
+/// The gaps between lexed tokens are exactly the whitespace that the lexer skips.
+fn lex_losslessly(
+    source: SourceId,
+    text: &'static str,
+) -> Vec<CstToken> {
+    let mut tokens = Vec::new();
+    let mut lexer = Token::lexer(text);
+    let mut last_end = 0;
+
+    let mut push = |kind, range: std::ops::Range<usize>| {
+        tokens.push(CstToken {
+            kind,
+            text: &text[range.clone()],
+            span: Span::new(source, SourceSpan::new(range.start.into(), range.len().into())),
+        })
+    };
+
+    while let Some(result) = lexer.next() {
+        let range = lexer.span();
+        if range.start > last_end {
+            push(CstTokenKind::Whitespace, last_end..range.start);
+        }
+        let kind = match result {
+            Ok(tok) => CstTokenKind::Token(tok),
+            Err(_) => CstTokenKind::Invalid,
+        };
+        last_end = range.end;
+        push(kind, range);
+    }
+
+    if text.len() > last_end {
+        push(CstTokenKind::Whitespace, last_end..text.len());
+    }
+
+    tokens
+}
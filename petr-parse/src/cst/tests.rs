@@ -0,0 +1,377 @@
+use expect_test::expect;
+
+use super::*;
+
+fn check(
+    source: &'static str,
+    expected: expect_test::Expect,
+) {
+    let cst = ConcreteSyntaxTree::new(0.into(), source);
+    assert_eq!(cst.to_string(), source, "CST did not round-trip to the original source");
+    let mut buf = String::new();
+    pretty_print_node(cst.root(), 0, &mut buf);
+    expected.assert_eq(&buf);
+}
+
+fn pretty_print_node(
+    node: &CstNode,
+    indentation: usize,
+    buf: &mut String,
+) {
+    buf.push_str(&format!("{}{:?}\n", "  ".repeat(indentation), node.kind()));
+    for child in node.children() {
+        match child {
+            CstElement::Node(node) => pretty_print_node(node, indentation + 1, buf),
+            CstElement::Token(token) => buf.push_str(&format!("{}{:?} {:?}\n", "  ".repeat(indentation + 1), token.kind(), token.text())),
+        }
+    }
+}
+
+#[test]
+fn round_trips_comments_in_expressions() {
+    check(
+        "fn foo(a in 'int) returns 'int\n  + a {- the other one -} 1\n",
+        expect![[r#"
+            File
+              FunctionDeclaration
+                Token(FunctionKeyword) "fn"
+                Whitespace " "
+                Token(Identifier) "foo"
+                Token(OpenParen) "("
+                Parameter
+                  Token(Identifier) "a"
+                  Whitespace " "
+                  Token(InKeyword) "in"
+                  Whitespace " "
+                  Token(TyMarker) "'"
+                  Token(Identifier) "int"
+                Token(CloseParen) ")"
+                Whitespace " "
+                Token(ReturnsKeyword) "returns"
+                Whitespace " "
+                Token(TyMarker) "'"
+                Token(Identifier) "int"
+                Token(Newline) "\n"
+                Whitespace "  "
+                Operator
+                  Token(Plus) "+"
+                  Whitespace " "
+                  Variable
+                    Token(Identifier) "a"
+                  Whitespace " "
+                  Token(Comment) "{- the other one -}"
+                  Whitespace " "
+                  Literal
+                    Token(Integer) "1"
+              Token(Newline) "\n"
+        "#]],
+    )
+}
+
+#[test]
+fn leading_trivia_belongs_to_next_item() {
+    check(
+        "import std.io\n\n{- a type -}\nType Foo = A | B\n\t\nexport fn bar() returns 'Foo ~A  \n",
+        expect![[r#"
+            File
+              ImportStatement
+                Token(Import) "import"
+                Whitespace " "
+                Path
+                  Token(Identifier) "std"
+                  Token(Dot) "."
+                  Token(Identifier) "io"
+              TypeDeclaration
+                Token(Newline) "\n"
+                Token(Newline) "\n"
+                Token(Comment) "{- a type -}"
+                Token(Newline) "\n"
+                Token(ExportTypeKeyword) "Type"
+                Whitespace " "
+                Token(Identifier) "Foo"
+                Whitespace " "
+                Token(Equals) "="
+                Whitespace " "
+                TypeVariant
+                  Token(Identifier) "A"
+                Whitespace " "
+                Token(Pipe) "|"
+                Whitespace " "
+                TypeVariant
+                  Token(Identifier) "B"
+              FunctionDeclaration
+                Token(Newline) "\n"
+                Whitespace "\t"
+                Token(Newline) "\n"
+                Token(ExportFunctionKeyword) "export fn"
+                Whitespace " "
+                Token(Identifier) "bar"
+                Token(OpenParen) "("
+                Token(CloseParen) ")"
+                Whitespace " "
+                Token(ReturnsKeyword) "returns"
+                Whitespace " "
+                Token(TyMarker) "'"
+                Token(Identifier) "Foo"
+                Whitespace " "
+                FunctionCall
+                  Token(Tilde) "~"
+                  Token(Identifier) "A"
+              Whitespace "  "
+              Token(Newline) "\n"
+        "#]],
+    )
+}
+
+#[test]
+fn invalid_tokens_are_kept() {
+    check(
+        "$ fn foo() returns 'int 1 $",
+        expect![[r#"
+            File
+              Error
+                Invalid "$"
+              FunctionDeclaration
+                Whitespace " "
+                Token(FunctionKeyword) "fn"
+                Whitespace " "
+                Token(Identifier) "foo"
+                Token(OpenParen) "("
+                Token(CloseParen) ")"
+                Whitespace " "
+                Token(ReturnsKeyword) "returns"
+                Whitespace " "
+                Token(TyMarker) "'"
+                Token(Identifier) "int"
+                Whitespace " "
+                Literal
+                  Token(Integer) "1"
+                Whitespace " "
+                Invalid "$"
+        "#]],
+    )
+}
+
+#[test]
+fn empty_file() {
+    check(
+        "",
+        expect![[r#"
+        File
+    "#]],
+    )
+}
+
+#[test]
+fn comments_belong_to_the_innermost_node() {
+    check(
+        "fn foo(b in 'bool) returns 'int\n  let a = ~bar {- first -} 1;\n  match b\n  | true => + a {- second -} 2\n  | false => a\n",
+        expect![[r#"
+            File
+              FunctionDeclaration
+                Token(FunctionKeyword) "fn"
+                Whitespace " "
+                Token(Identifier) "foo"
+                Token(OpenParen) "("
+                Parameter
+                  Token(Identifier) "b"
+                  Whitespace " "
+                  Token(InKeyword) "in"
+                  Whitespace " "
+                  Token(TyMarker) "'"
+                  Token(Identifier) "bool"
+                Token(CloseParen) ")"
+                Whitespace " "
+                Token(ReturnsKeyword) "returns"
+                Whitespace " "
+                Token(TyMarker) "'"
+                Token(Identifier) "int"
+                Token(Newline) "\n"
+                Whitespace "  "
+                Let
+                  Token(Let) "let"
+                  Whitespace " "
+                  Binding
+                    Token(Identifier) "a"
+                    Whitespace " "
+                    Token(Equals) "="
+                    Whitespace " "
+                    FunctionCall
+                      Token(Tilde) "~"
+                      Token(Identifier) "bar"
+                      Whitespace " "
+                      Token(Comment) "{- first -}"
+                      Whitespace " "
+                      Literal
+                        Token(Integer) "1"
+                  Token(Semicolon) ";"
+                  Token(Newline) "\n"
+                  Whitespace "  "
+                  Match
+                    Token(Match) "match"
+                    Whitespace " "
+                    Variable
+                      Token(Identifier) "b"
+                    Token(Newline) "\n"
+                    Whitespace "  "
+                    Token(Pipe) "|"
+                    Whitespace " "
+                    MatchArm
+                      Pattern
+                        Token(True) "true"
+                      Whitespace " "
+                      Token(FatArrow) "=>"
+                      Whitespace " "
+                      Operator
+                        Token(Plus) "+"
+                        Whitespace " "
+                        Variable
+                          Token(Identifier) "a"
+                        Whitespace " "
+                        Token(Comment) "{- second -}"
+                        Whitespace " "
+                        Literal
+                          Token(Integer) "2"
+                    Token(Newline) "\n"
+                    Whitespace "  "
+                    Token(Pipe) "|"
+                    Whitespace " "
+                    MatchArm
+                      Pattern
+                        Token(False) "false"
+                      Whitespace " "
+                      Token(FatArrow) "=>"
+                      Whitespace " "
+                      Variable
+                        Token(Identifier) "a"
+              Token(Newline) "\n"
+        "#]],
+    )
+}
+
+#[test]
+fn declarations_have_nodes_for_their_parts() {
+    check(
+        "import std.io as io\ntype alias Pair = '(int, string)\ninterface Show 'A with show(x in 'A) returns 'string, both(x in 'A, y in 'Pair) returns 'string\nimplement Show for 'int with show = std.show_int, both = both_ints\n",
+        expect![[r#"
+            File
+              ImportStatement
+                Token(Import) "import"
+                Whitespace " "
+                Path
+                  Token(Identifier) "std"
+                  Token(Dot) "."
+                  Token(Identifier) "io"
+                Whitespace " "
+                Token(As) "as"
+                Whitespace " "
+                Token(Identifier) "io"
+              TypeDeclaration
+                Token(Newline) "\n"
+                Token(TypeKeyword) "type"
+                Whitespace " "
+                Token(AliasKeyword) "alias"
+                Whitespace " "
+                Token(Identifier) "Pair"
+                Whitespace " "
+                Token(Equals) "="
+                Whitespace " "
+                Type
+                  Token(TyMarker) "'"
+                  Token(OpenParen) "("
+                  Token(Identifier) "int"
+                  Token(Comma) ","
+                  Whitespace " "
+                  Token(Identifier) "string"
+                  Token(CloseParen) ")"
+              InterfaceDeclaration
+                Token(Newline) "\n"
+                Token(InterfaceKeyword) "interface"
+                Whitespace " "
+                Token(Identifier) "Show"
+                Whitespace " "
+                Token(TyMarker) "'"
+                Token(Identifier) "A"
+                Whitespace " "
+                Token(With) "with"
+                Whitespace " "
+                InterfaceMethod
+                  Token(Identifier) "show"
+                  Token(OpenParen) "("
+                  Parameter
+                    Token(Identifier) "x"
+                    Whitespace " "
+                    Token(InKeyword) "in"
+                    Whitespace " "
+                    Token(TyMarker) "'"
+                    Token(Identifier) "A"
+                  Token(CloseParen) ")"
+                  Whitespace " "
+                  Token(ReturnsKeyword) "returns"
+                  Whitespace " "
+                  Token(TyMarker) "'"
+                  Token(Identifier) "string"
+                Token(Comma) ","
+                Whitespace " "
+                InterfaceMethod
+                  Token(Identifier) "both"
+                  Token(OpenParen) "("
+                  Parameter
+                    Token(Identifier) "x"
+                    Whitespace " "
+                    Token(InKeyword) "in"
+                    Whitespace " "
+                    Token(TyMarker) "'"
+                    Token(Identifier) "A"
+                  Token(Comma) ","
+                  Whitespace " "
+                  Parameter
+                    Token(Identifier) "y"
+                    Whitespace " "
+                    Token(InKeyword) "in"
+                    Whitespace " "
+                    Token(TyMarker) "'"
+                    Token(Identifier) "Pair"
+                  Token(CloseParen) ")"
+                  Whitespace " "
+                  Token(ReturnsKeyword) "returns"
+                  Whitespace " "
+                  Token(TyMarker) "'"
+                  Token(Identifier) "string"
+              ImplementationDeclaration
+                Token(Newline) "\n"
+                Token(ImplementKeyword) "implement"
+                Whitespace " "
+                Path
+                  Token(Identifier) "Show"
+                Whitespace " "
+                Token(ForKeyword) "for"
+                Whitespace " "
+                Type
+                  Token(TyMarker) "'"
+                  Token(Identifier) "int"
+                Whitespace " "
+                Token(With) "with"
+                Whitespace " "
+                ImplementedMethod
+                  Token(Identifier) "show"
+                  Whitespace " "
+                  Token(Equals) "="
+                  Whitespace " "
+                  Path
+                    Token(Identifier) "std"
+                    Token(Dot) "."
+                    Token(Identifier) "show_int"
+                Token(Comma) ","
+                Whitespace " "
+                ImplementedMethod
+                  Token(Identifier) "both"
+                  Whitespace " "
+                  Token(Equals) "="
+                  Whitespace " "
+                  Path
+                    Token(Identifier) "both_ints"
+              Token(Newline) "\n"
+        "#]],
+    )
+}
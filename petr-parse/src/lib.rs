@@ -1,7 +1,8 @@
 #![allow(dead_code)]
 
+pub mod cst;
 mod parse_to_ast;
 mod parser;
 
-pub use parser::Parser;
+pub use parser::{InvalidEdit, InvalidEditKind, ParsedSources, Parser, Token};
 use petr_utils::IndexMap;
@@ -2,7 +2,7 @@ use std::rc::Rc;
 
 // using this crate's Parser, parse an AST.
 use petr_ast::*;
-use petr_utils::{Identifier, Path, SpannedItem};
+use petr_utils::{Identifier, IntegerType, Path, SpannedItem};
 
 use crate::{
     parser::{Parse, ParseErrorKind, Token},
@@ -17,19 +17,59 @@ impl Parse for FunctionCall {
             // optionally, args can be in parens to resolve ambiguity
             // like if they're in a list
             let open = p.try_token(Token::OpenParen);
-            let args = p.sequence_zero_or_more(Token::Comma)?;
+            // args are either all passed by name, like `~Point x = 1, y = 2`, or all positionally
+            let (args, arg_names) = match p.with_backtrack(|p| p.parse::<NamedArgument>()) {
+                Ok(first) => {
+                    let (names, args): (Vec<_>, Vec<_>) = parse_remaining_named_arguments(p, first)?.into_iter().unzip();
+                    (args, Some(names.into_boxed_slice()))
+                },
+                Err(_) => (p.sequence_zero_or_more(Token::Comma)?, None),
+            };
             if open.is_some() {
                 p.token(Token::CloseParen)?;
             }
             Some(Self {
                 func_name,
                 args: args.into_boxed_slice(),
+                arg_names,
                 args_were_parenthesized: open.is_some(),
             })
         })
     }
 }
 
+/// `name = expr`, as used in named function arguments and record updates
+struct NamedArgument(Identifier, SpannedItem<Expression>);
+
+impl Parse for NamedArgument {
+    fn parse(p: &mut Parser) -> Option<Self> {
+        let name: Identifier = p.parse()?;
+        p.token(Token::Equals)?;
+        let value = p.parse()?;
+        Some(NamedArgument(name, value))
+    }
+}
+
+/// Parses the comma-separated named arguments which follow `first`. A comma is only consumed if
+/// a named argument follows it, so a list of named arguments can itself be an item in a
+/// comma-separated sequence.
+fn parse_remaining_named_arguments(
+    p: &mut Parser,
+    first: NamedArgument,
+) -> Option<Vec<(Identifier, SpannedItem<Expression>)>> {
+    let mut args = vec![(first.0, first.1)];
+    while *p.peek().item() == Token::Comma {
+        let Ok(NamedArgument(name, value)) = p.with_backtrack(|p| {
+            p.token(Token::Comma)?;
+            p.parse::<NamedArgument>()
+        }) else {
+            break;
+        };
+        args.push((name, value));
+    }
+    Some(args)
+}
+
 impl Parse for Path {
     fn parse(p: &mut Parser) -> Option<Self> {
         p.with_help("path", |p| -> Option<Self> {
@@ -51,10 +91,15 @@ impl Parse for TypeDeclaration {
                 _ => unreachable!(),
             };
             let name = p.parse()?;
+            let mut type_params = vec![];
+            while p.try_token(Token::TyMarker).is_some() {
+                type_params.push(p.parse()?);
+            }
             if *p.peek().item() != Token::Equals {
                 // if there's no equals, then this is a type with no variants
                 return Some(Self {
                     name,
+                    type_params: type_params.into_boxed_slice(),
                     variants: vec![].into_boxed_slice(),
                     visibility,
                 });
@@ -63,6 +108,7 @@ impl Parse for TypeDeclaration {
             let variants = p.sequence(Token::Pipe)?;
             Some(Self {
                 name,
+                type_params: type_params.into_boxed_slice(),
                 variants: variants.into_boxed_slice(),
                 visibility,
             })
@@ -70,6 +116,24 @@ impl Parse for TypeDeclaration {
     }
 }
 
+impl Parse for TypeAlias {
+    fn parse(p: &mut Parser) -> Option<Self> {
+        p.with_help("type alias", |p| -> Option<Self> {
+            let tok = p.one_of([Token::TypeKeyword, Token::ExportTypeKeyword])?;
+            let visibility = match tok.item() {
+                Token::TypeKeyword => Visibility::Local,
+                Token::ExportTypeKeyword => Visibility::Exported,
+                _ => unreachable!(),
+            };
+            p.token(Token::AliasKeyword)?;
+            let name = p.parse()?;
+            p.token(Token::Equals)?;
+            let ty = p.parse()?;
+            Some(Self { name, ty, visibility })
+        })
+    }
+}
+
 impl Parse for TypeVariantOrLiteral {
     fn parse(p: &mut Parser) -> Option<Self> {
         p.with_help("type variant or literal", |p| -> Option<Self> {
@@ -113,13 +177,23 @@ impl Parse for AstNode {
     fn parse(p: &mut Parser) -> Option<Self> {
         match p.peek().item() {
             Token::FunctionKeyword | Token::ExportFunctionKeyword => Some(AstNode::FunctionDeclaration(p.parse()?)),
+            Token::TypeKeyword | Token::ExportTypeKeyword if *p.peek_second().item() == Token::AliasKeyword => Some(AstNode::TypeAlias(p.parse()?)),
             Token::TypeKeyword | Token::ExportTypeKeyword => Some(AstNode::TypeDeclaration(p.parse()?)),
             Token::Eof | Token::NewFile(..) => None,
             Token::Import => Some(AstNode::ImportStatement(p.parse()?)),
+            Token::InterfaceKeyword | Token::ExportInterfaceKeyword => Some(AstNode::InterfaceDeclaration(p.parse()?)),
+            Token::ImplementKeyword => Some(AstNode::ImplementationDeclaration(p.parse()?)),
             a => {
                 let span = p.peek().span();
                 p.push_error(span.with_item(ParseErrorKind::ExpectedOneOf(
-                    vec![Token::FunctionKeyword, Token::TypeKeyword, Token::Eof],
+                    vec![
+                        Token::FunctionKeyword,
+                        Token::TypeKeyword,
+                        Token::InterfaceKeyword,
+                        Token::ImplementKeyword,
+                        Token::Import,
+                        Token::Eof,
+                    ],
                     *a,
                 )));
                 None
@@ -167,17 +241,123 @@ impl Parse for FunctionDeclaration {
             };
             p.token(Token::ReturnsKeyword)?;
             let return_type = p.parse()?;
+            let constraints = if p.try_token(Token::WhereKeyword).is_some() {
+                p.sequence(Token::Comma)?.into_boxed_slice()
+            } else {
+                vec![].into_boxed_slice()
+            };
+            let effects = if p.try_token(Token::PureKeyword).is_some() {
+                Some(vec![].into_boxed_slice())
+            } else if p.try_token(Token::UsesKeyword).is_some() {
+                Some(p.sequence_one_or_more(Token::Comma)?.into_boxed_slice())
+            } else {
+                None
+            };
             let body = p.parse()?;
             Some(Self {
                 name,
                 parameters,
                 return_type,
+                constraints,
+                effects,
                 body,
                 visibility,
             })
         })
     }
 }
+
+impl Parse for TypeConstraint {
+    fn parse(p: &mut Parser) -> Option<Self> {
+        p.with_help("type constraint", |p| -> Option<Self> {
+            p.token(Token::TyMarker)?;
+            let type_param = p.parse()?;
+            p.token(Token::Colon)?;
+            let interface = p.parse()?;
+            Some(Self { type_param, interface })
+        })
+    }
+}
+
+impl Parse for InterfaceDeclaration {
+    fn parse(p: &mut Parser) -> Option<Self> {
+        p.with_help("interface declaration", |p| -> Option<Self> {
+            let tok = p.one_of([Token::InterfaceKeyword, Token::ExportInterfaceKeyword])?;
+            let visibility = match tok.item() {
+                Token::InterfaceKeyword => Visibility::Local,
+                Token::ExportInterfaceKeyword => Visibility::Exported,
+                _ => unreachable!(),
+            };
+            let name = p.parse()?;
+            p.token(Token::TyMarker)?;
+            let type_param = p.parse()?;
+            p.token(Token::With)?;
+            let methods = p.sequence(Token::Comma)?;
+            Some(Self {
+                name,
+                type_param,
+                methods: methods.into_boxed_slice(),
+                visibility,
+            })
+        })
+    }
+}
+
+impl Parse for InterfaceMethod {
+    fn parse(p: &mut Parser) -> Option<Self> {
+        p.with_help("interface method", |p| -> Option<Self> {
+            let name = p.parse()?;
+            p.token(Token::OpenParen)?;
+            let parameters = if p.try_token(Token::CloseParen).is_some() {
+                vec![].into_boxed_slice()
+            } else {
+                let seq = p.sequence(Token::Comma)?.into_boxed_slice();
+                p.token(Token::CloseParen)?;
+                seq
+            };
+            p.token(Token::ReturnsKeyword)?;
+            let return_type = p.parse()?;
+            Some(Self {
+                name,
+                parameters,
+                return_type,
+            })
+        })
+    }
+}
+
+impl Parse for ImplementationDeclaration {
+    fn parse(p: &mut Parser) -> Option<Self> {
+        p.with_help("implementation declaration", |p| -> Option<Self> {
+            p.token(Token::ImplementKeyword)?;
+            let interface = p.parse()?;
+            p.token(Token::ForKeyword)?;
+            let ty = p.parse()?;
+            p.token(Token::With)?;
+            let methods = p.sequence::<ImplementedMethod>(Token::Comma)?;
+            Some(Self {
+                interface,
+                ty,
+                methods: methods
+                    .into_iter()
+                    .map(|ImplementedMethod(method, function)| (method, function))
+                    .collect(),
+            })
+        })
+    }
+}
+
+/// `method = function` in an implementation declaration
+struct ImplementedMethod(Identifier, Path);
+
+impl Parse for ImplementedMethod {
+    fn parse(p: &mut Parser) -> Option<Self> {
+        let method = p.parse()?;
+        p.token(Token::Equals)?;
+        let function = p.parse()?;
+        Some(Self(method, function))
+    }
+}
 impl Parse for Literal {
     fn parse(p: &mut Parser) -> Option<Self> {
         let tok = p.advance();
@@ -185,10 +365,11 @@ impl Parse for Literal {
             Token::True => Some(Literal::Boolean(true)),
             Token::False => Some(Literal::Boolean(false)),
             Token::Integer => Some(Literal::Integer(p.slice().parse().expect("lexer should have verified this"))),
+            Token::Float => Some(Literal::Float(Float(p.slice().parse().expect("lexer should have verified this")))),
             Token::String => Some(Literal::String(p.slice()[1..p.slice().len() - 1].to_string())),
             _ => {
                 p.push_error(p.span().with_item(ParseErrorKind::ExpectedOneOf(
-                    vec![Token::Integer, Token::True, Token::False, Token::String],
+                    vec![Token::Integer, Token::Float, Token::True, Token::False, Token::String],
                     *tok.item(),
                 )));
                 None
@@ -215,17 +396,56 @@ impl Parse for Ty {
                     let tys = p.sequence_one_or_more(Token::Pipe)?;
                     Some(Ty::Sum(tys.into_boxed_slice()))
                 })
+            } else if let Some(_tok) = p.try_token(Token::FunctionTypeKeyword) {
+                p.with_help("function type", |p| {
+                    p.token(Token::OpenParen)?;
+                    let params: Vec<Ty> = if p.try_token(Token::CloseParen).is_some() {
+                        vec![]
+                    } else {
+                        let params = p.sequence(Token::Comma)?;
+                        p.token(Token::CloseParen)?;
+                        params
+                    };
+                    p.token(Token::ReturnsKeyword)?;
+                    let return_ty: Ty = p.parse()?;
+                    Some(Ty::Arrow(params.into_boxed_slice(), Box::new(return_ty)))
+                })
             } else if let Some(_tok) = p.try_token(Token::TyMarker) {
-                let next: Identifier = p.parse()?;
-                let ty = match p.slice() {
-                    "int" => Ty::Int,
-                    "bool" => Ty::Bool,
-                    "string" => Ty::String,
-                    "unit" => Ty::Unit,
-                    _ => Ty::Named(next),
+                if p.try_token(Token::OpenParen).is_some() {
+                    return parse_tuple_type(p);
+                }
+                let name = match parse_type_name(p)? {
+                    Ty::Named(name) => name,
+                    primitive => return Some(primitive),
                 };
-
-                Some(ty)
+                // generic types are applied to their type arguments, like `'Option 'int`. An
+                // argument which has arguments of its own is parenthesized, like
+                // `'Option ('Option 'int)`.
+                let mut args = vec![];
+                loop {
+                    if p.try_token(Token::TyMarker).is_some() {
+                        args.push(parse_type_name(p)?);
+                    } else if *p.peek().item() == Token::OpenParen {
+                        // a parenthesis which doesn't hold a type starts whatever follows the
+                        // type, like a function body which is a tuple
+                        let Ok(arg) = p.with_backtrack(|p| {
+                            p.token(Token::OpenParen)?;
+                            let arg = p.parse()?;
+                            p.token(Token::CloseParen)?;
+                            Some(arg)
+                        }) else {
+                            break;
+                        };
+                        args.push(arg);
+                    } else {
+                        break;
+                    }
+                }
+                if args.is_empty() {
+                    Some(Ty::Named(name))
+                } else {
+                    Some(Ty::Applied(name, args.into_boxed_slice()))
+                }
             } else {
                 // TODO: Better error message on failed type parse
                 // Currently just throws "expected literal" which is very wrong
@@ -240,6 +460,51 @@ impl Parse for Ty {
     }
 }
 
+/// Parses the element types of a tuple type, like `'(int, string)`, after its `(`. The elements are
+/// type names, tuples, or any other type, like `'(int, 'Option 'int)`.
+fn parse_tuple_type(p: &mut Parser) -> Option<Ty> {
+    p.with_help("tuple type", |p| {
+        let mut elements = vec![];
+        loop {
+            let element = match p.peek().item() {
+                Token::OpenParen => {
+                    p.advance();
+                    parse_tuple_type(p)?
+                },
+                Token::Identifier => parse_type_name(p)?,
+                _ => p.parse()?,
+            };
+            elements.push(element);
+            if p.try_token(Token::Comma).is_none() {
+                break;
+            }
+        }
+        p.token(Token::CloseParen)?;
+        // a single parenthesized type is just that type
+        Some(match <[Ty; 1]>::try_from(elements) {
+            Ok([ty]) => ty,
+            Err(elements) => Ty::Tuple(elements.into_boxed_slice()),
+        })
+    })
+}
+
+/// Parses the name of a type which follows a `'`, like `int` or `Option`
+fn parse_type_name(p: &mut Parser) -> Option<Ty> {
+    let next: Identifier = p.parse()?;
+    Some(match p.slice() {
+        // `'int` is a signed 64-bit integer already
+        "int" | "i64" => Ty::Int,
+        "bool" => Ty::Bool,
+        "string" => Ty::String,
+        "unit" => Ty::Unit,
+        "float" => Ty::Float,
+        name => match IntegerType::from_name(name) {
+            Some(ty) => Ty::SizedInt(ty),
+            None => Ty::Named(next),
+        },
+    })
+}
+
 impl Parse for Operator {
     fn parse(p: &mut Parser) -> Option<Self> {
         let tok = p.advance();
@@ -249,6 +514,13 @@ impl Parse for Operator {
             Token::Star => Some(Operator::Star),
             Token::Slash => Some(Operator::Slash),
             Token::Equals => Some(Operator::Eq),
+            Token::NotEquals => Some(Operator::NotEq),
+            Token::LessThan => Some(Operator::LessThan),
+            Token::GreaterThan => Some(Operator::GreaterThan),
+            Token::LessThanOrEquals => Some(Operator::LessThanOrEq),
+            Token::GreaterThanOrEquals => Some(Operator::GreaterThanOrEq),
+            Token::And => Some(Operator::And),
+            Token::Or => Some(Operator::Or),
             _ => {
                 p.push_error(p.span().with_item(ParseErrorKind::ExpectedOneOf(
                     vec![Token::Plus, Token::Minus, Token::Star, Token::Slash],
@@ -260,11 +532,25 @@ impl Parse for Operator {
     }
 }
 
+impl Parse for UnaryOperator {
+    fn parse(p: &mut Parser) -> Option<Self> {
+        let tok = p.advance();
+        match tok.item() {
+            Token::Not => Some(UnaryOperator::Not),
+            _ => {
+                p.push_error(p.span().with_item(ParseErrorKind::ExpectedToken(Token::Not, *tok.item())));
+                None
+            },
+        }
+    }
+}
+
 impl Parse for Identifier {
     fn parse(p: &mut Parser) -> Option<Self> {
         let identifier = p.advance();
         if *identifier.item() != Token::Identifier {
             p.push_error(p.span().with_item(ParseErrorKind::ExpectedIdentifier(p.slice().to_string())));
+            return None;
         }
         let span = p.span();
         let slice = Rc::from(p.slice());
@@ -308,13 +594,61 @@ impl Parse for Expression {
                     let rhs: SpannedItem<Expression> = p.parse()?;
                     Some(Expression::Operator(Box::new(OperatorExpression { lhs, rhs, op })))
                 },
-                Token::Identifier => Some(Expression::Variable(p.parse()?)),
+                Token::Not => {
+                    let op: SpannedItem<UnaryOperator> = p.parse()?;
+                    let operand: SpannedItem<Expression> = p.parse()?;
+                    Some(Expression::UnaryOperator(Box::new(UnaryOperatorExpression { operand, op })))
+                },
+                Token::Identifier => {
+                    let var: Identifier = p.parse()?;
+                    let mut expr = var.span.with_item(Expression::Variable(var));
+                    // field accesses, like `a.b.c`
+                    // the lookahead is backtracked so a plain variable's span doesn't extend to the next token
+                    while p.with_backtrack(|p| p.try_token(Token::Dot)).is_ok() {
+                        let field: Identifier = p.parse()?;
+                        let span = expr.span().join(field.span);
+                        expr = span.with_item(Expression::FieldAccess(Box::new(FieldAccess { target: expr, field })));
+                    }
+                    if p.with_backtrack(|p| p.try_token(Token::With)).is_err() {
+                        return Some(expr.into_item());
+                    }
+                    let first: NamedArgument = p.parse()?;
+                    let fields = parse_remaining_named_arguments(p, first)?;
+                    Some(Expression::RecordUpdate(Box::new(RecordUpdate {
+                        target: expr,
+                        fields: fields.into_boxed_slice(),
+                    })))
+                },
                 Token::If => Some(Expression::If(p.parse()?)),
+                Token::Match => Some(Expression::Match(p.parse()?)),
                 Token::OpenBracket => Some(Expression::List(p.parse()?)),
                 Token::Tilde => Some(Expression::FunctionCall(p.parse()?)),
-                Token::True | Token::False | Token::String | Token::Integer => Some(Expression::Literal(p.parse()?)),
+                Token::True | Token::False | Token::String | Token::Integer | Token::Float => Some(Expression::Literal(p.parse()?)),
                 Token::Intrinsic => Some(Expression::IntrinsicCall(p.parse()?)),
+                Token::Underscore => {
+                    p.advance();
+                    Some(Expression::Hole(None))
+                },
+                Token::Hole => {
+                    p.advance();
+                    // the name of the hole is everything after the `?`
+                    let span = p.span();
+                    let name = Rc::from(&p.slice()[1..]);
+                    let id = p.intern(name);
+                    Some(Expression::Hole(Some(Identifier { id, span })))
+                },
                 Token::Let => Some(Expression::Binding(p.parse()?)),
+                Token::Backslash => Some(Expression::Lambda(p.parse()?)),
+                Token::OpenParen => {
+                    p.advance();
+                    let elements: Vec<SpannedItem<Expression>> = p.sequence_one_or_more(Token::Comma)?;
+                    p.token(Token::CloseParen)?;
+                    // a single parenthesized expression is just that expression
+                    Some(match <[_; 1]>::try_from(elements) {
+                        Ok([expr]) => expr.into_item(),
+                        Err(elements) => Expression::Tuple(elements.into_boxed_slice()),
+                    })
+                },
                 otherwise => {
                     p.push_error(p.span().with_item(ParseErrorKind::ExpectedOneOf(
                         vec![
@@ -325,8 +659,13 @@ impl Parse for Expression {
                             Token::False,
                             Token::String,
                             Token::Integer,
+                            Token::Float,
                             Token::Intrinsic,
                             Token::Let,
+                            Token::Backslash,
+                            Token::OpenParen,
+                            Token::Underscore,
+                            Token::Hole,
                         ],
                         *otherwise,
                     )));
@@ -355,6 +694,125 @@ impl Parse for If {
     }
 }
 
+impl Parse for Lambda {
+    fn parse(p: &mut Parser) -> Option<Self> {
+        p.with_help("lambda", |p| -> Option<Self> {
+            p.token(Token::Backslash)?;
+            p.token(Token::OpenParen)?;
+            let parameters = if p.try_token(Token::CloseParen).is_some() {
+                vec![].into_boxed_slice()
+            } else {
+                let seq = p.sequence(Token::Comma)?.into_boxed_slice();
+                p.token(Token::CloseParen)?;
+                seq
+            };
+            p.token(Token::FatArrow)?;
+            let body = p.parse()?;
+            Some(Lambda {
+                parameters,
+                body: Box::new(body),
+                expr_id: p.new_expr_id(),
+            })
+        })
+    }
+}
+
+impl Parse for Match {
+    fn parse(p: &mut Parser) -> Option<Self> {
+        p.with_help("match expression", |p| -> Option<Self> {
+            p.token(Token::Match)?;
+            let scrutinee = p.parse()?;
+            // every arm begins with a pipe, so the match ends at the first arm body that isn't
+            // followed by one. This means a match nested inside of an arm body will consume
+            // all of the arms after it.
+            p.token(Token::Pipe)?;
+            let mut arms = vec![p.parse()?];
+            while p.try_token(Token::Pipe).is_some() {
+                arms.push(p.parse()?);
+            }
+            Some(Match {
+                scrutinee: Box::new(scrutinee),
+                arms:      arms.into_boxed_slice(),
+            })
+        })
+    }
+}
+
+impl Parse for MatchArm {
+    fn parse(p: &mut Parser) -> Option<Self> {
+        p.with_help("match arm", |p| -> Option<Self> {
+            let pattern = p.parse()?;
+            p.token(Token::FatArrow)?;
+            let body = p.parse()?;
+            Some(MatchArm {
+                pattern,
+                body,
+                expr_id: p.new_expr_id(),
+            })
+        })
+    }
+}
+
+impl Parse for Pattern {
+    fn parse(p: &mut Parser) -> Option<Self> {
+        p.with_help("pattern", |p| -> Option<Self> {
+            match p.peek().item() {
+                Token::Underscore => {
+                    p.advance();
+                    Some(Pattern::Wildcard)
+                },
+                Token::Identifier => Some(Pattern::Variant(p.parse()?)),
+                Token::True | Token::False | Token::String | Token::Integer | Token::Float => Some(Pattern::Literal(p.parse()?)),
+                otherwise => {
+                    p.push_error(p.span().with_item(ParseErrorKind::ExpectedOneOf(
+                        vec![
+                            Token::Underscore,
+                            Token::Identifier,
+                            Token::True,
+                            Token::False,
+                            Token::String,
+                            Token::Integer,
+                            Token::Float,
+                        ],
+                        *otherwise,
+                    )));
+                    None
+                },
+            }
+        })
+    }
+}
+
+impl Parse for VariantPattern {
+    fn parse(p: &mut Parser) -> Option<Self> {
+        let name = p.parse()?;
+        let mut fields = vec![];
+        while matches!(
+            p.peek().item(),
+            Token::Identifier | Token::Underscore | Token::True | Token::False | Token::String | Token::Integer | Token::Float
+        ) {
+            fields.push(p.parse()?);
+        }
+        Some(VariantPattern {
+            name,
+            fields: fields.into_boxed_slice(),
+        })
+    }
+}
+
+impl Parse for FieldPattern {
+    fn parse(p: &mut Parser) -> Option<Self> {
+        match p.peek().item() {
+            Token::Underscore => {
+                p.advance();
+                Some(FieldPattern::Wildcard)
+            },
+            Token::Identifier => Some(FieldPattern::Binding(p.parse()?)),
+            _ => Some(FieldPattern::Literal(p.parse()?)),
+        }
+    }
+}
+
 impl Parse for ExpressionWithBindings {
     /// parse an expression that is prefaced with symbol bindings
     fn parse(p: &mut Parser) -> Option<Self> {
@@ -374,10 +832,28 @@ impl Parse for ExpressionWithBindings {
 impl Parse for Binding {
     fn parse(p: &mut Parser) -> Option<Self> {
         p.with_help("let binding", |p| {
-            let name: Identifier = p.parse()?;
+            let pattern = if p.try_token(Token::OpenParen).is_some() {
+                let mut names = vec![];
+                loop {
+                    // a sequence would only report this error for the first element
+                    if *p.peek().item() == Token::OpenParen {
+                        let span = p.peek().span();
+                        p.push_error(span.with_item(ParseErrorKind::NestedTuplePattern));
+                        return None;
+                    }
+                    names.push(p.parse()?);
+                    if p.try_token(Token::Comma).is_none() {
+                        break;
+                    }
+                }
+                p.token(Token::CloseParen)?;
+                BindingPattern::Tuple(names.into_boxed_slice())
+            } else {
+                BindingPattern::Name(p.parse()?)
+            };
             p.token(Token::Equals)?;
             let expr = p.parse()?;
-            Some(Binding { name, val: expr })
+            Some(Binding { pattern, val: expr })
         })
     }
 }
@@ -395,7 +871,22 @@ impl Parse for IntrinsicCall {
                 "malloc" => Intrinsic::Malloc,
                 "size_of" => Intrinsic::SizeOf,
                 "equals" => Intrinsic::Equals,
-                a => todo!("unrecognized intrinsic error: {a:?}"),
+                "not_equals" => Intrinsic::NotEquals,
+                "less_than" => Intrinsic::LessThan,
+                "greater_than" => Intrinsic::GreaterThan,
+                "less_than_or_equals" => Intrinsic::LessThanOrEquals,
+                "greater_than_or_equals" => Intrinsic::GreaterThanOrEquals,
+                "and" => Intrinsic::And,
+                "or" => Intrinsic::Or,
+                "not" => Intrinsic::Not,
+                "to_int" => Intrinsic::ConvertTo(IntegerType::I64),
+                "to_float" => Intrinsic::ToFloat,
+                "sqrt" => Intrinsic::Sqrt,
+                "floor" => Intrinsic::Floor,
+                a => match a.strip_prefix("to_").and_then(IntegerType::from_name) {
+                    Some(ty) => Intrinsic::ConvertTo(ty),
+                    None => todo!("unrecognized intrinsic error: {a:?}"),
+                },
             };
             p.token(Token::Intrinsic)?;
             let open = p.try_token(Token::OpenParen);
@@ -410,12 +901,26 @@ impl Parse for IntrinsicCall {
     }
 }
 
+/// Tokens which can only appear at the start of a top-level item. These are the points at which
+/// the parser recovers from errors, so a top-level item can always be parsed independently of
+/// its neighbors.
+pub(crate) const TOP_LEVEL_ITEM_KEYWORDS: [Token; 8] = [
+    Token::FunctionKeyword,
+    Token::ExportFunctionKeyword,
+    Token::TypeKeyword,
+    Token::ExportTypeKeyword,
+    Token::Import,
+    Token::InterfaceKeyword,
+    Token::ExportInterfaceKeyword,
+    Token::ImplementKeyword,
+];
+
 impl Parse for Module {
     fn parse(p: &mut Parser) -> Option<Self> {
         let module_name = p.advance();
         match module_name.item() {
-            Token::NewFile(name) => {
-                let name = p.source_map().get(*name).0;
+            Token::NewFile(source_id) => {
+                let name = p.source_map().get(*source_id).0;
                 let name = match file_name_to_module_name(name) {
                     Ok(o) => o,
                     Err(e) => {
@@ -434,7 +939,9 @@ impl Parse for Module {
                     .into_boxed_slice();
                 let name = Path { identifiers };
 
-                let nodes: Vec<_> = p.many::<SpannedItem<AstNode>>();
+                // if a top-level item fails to parse, resume at the start of the next one
+                let nodes: Vec<_> = p.many_with_recovery::<SpannedItem<AstNode>>(&TOP_LEVEL_ITEM_KEYWORDS);
+                p.record_module_source(*source_id);
 
                 Some(Module { name, nodes })
             },
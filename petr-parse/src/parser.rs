@@ -1,6 +1,7 @@
 #[cfg(test)]
 mod tests;
 
+mod incremental;
 mod lexer;
 use std::rc::Rc;
 
@@ -11,6 +12,9 @@ use petr_ast::{Ast, Comment, ExprId, List, Module};
 use petr_utils::{IndexMap, SourceId, Span, SpannedItem, SymbolId, SymbolInterner};
 use thiserror::Error;
 
+pub use self::incremental::{InvalidEdit, InvalidEditKind, ParsedSources};
+use crate::cst::ConcreteSyntaxTree;
+
 #[derive(Error, Debug, PartialEq)]
 pub struct ParseError {
     kind: ParseErrorKind,
@@ -79,25 +83,36 @@ impl std::fmt::Display for ParseError {
 #[derive(Error, Debug, Diagnostic, PartialEq)]
 pub enum ParseErrorKind {
     #[error("Unmatched parenthesis")]
+    #[diagnostic(code(E0101))]
     UnmatchedParenthesis,
     #[error("Expected identifier, found {0}")]
+    #[diagnostic(code(E0102))]
     ExpectedIdentifier(String),
     #[error("Expected token {0}, found {1}")]
+    #[diagnostic(code(E0103))]
     ExpectedToken(Token, Token),
     #[error("Expected one of tokens {}; found {1}", format_toks(.0))]
+    #[diagnostic(code(E0104))]
     ExpectedOneOf(Vec<Token>, Token),
     #[error("Internal error in parser. Please file an issue on GitHub. {0}")]
+    #[diagnostic(code(E0105))]
     InternalError(String),
     #[error("File name could not be converted to module name. petr source names should be a valid identifier.")]
+    #[diagnostic(code(E0106))]
     #[help(
         "Identifiers cannot begin with numbers, contain spaces, or contain symbols other than an underscore or hyphen.\
      Hyphens in file names are transformed into underscores."
     )]
     InvalidIdentifier(String),
     #[error("Internal error in parser. Please file an issue on GitHub. A span was joined with a span from another file.")]
+    #[diagnostic(code(E0107))]
     InternalSpanError(#[label] SourceSpan, #[label] SourceSpan),
     #[error("Invalid token encountered")]
+    #[diagnostic(code(E0108))]
     LexerError,
+    #[error("Nested tuple patterns are not supported. Bind the inner tuple to a name, and destructure that name in a binding of its own.")]
+    #[diagnostic(code(E0109))]
+    NestedTuplePattern,
 }
 
 impl ParseErrorKind {
@@ -131,9 +146,13 @@ pub struct Parser {
     errors: Vec<SpannedItem<ParseError>>,
     comments: Vec<SpannedItem<Comment>>,
     peek: Option<SpannedItem<Token>>,
+    /// the last token returned by [`Parser::advance`], which is where a spanned item ends
+    previous_token: Option<Span>,
     // the tuple is the file name and content
     source_map: IndexMap<SourceId, (&'static str, &'static str)>,
     help: Vec<String>,
+    /// the source each parsed module came from, in the same order as the modules
+    module_sources: Vec<SourceId>,
 }
 
 impl Parser {
@@ -178,12 +197,21 @@ impl Parser {
         if let Some(ref peek) = self.peek {
             *peek
         } else {
-            let item = self.advance();
+            let item = self.next_token();
             self.peek = Some(item);
             item
         }
     }
 
+    /// Peeks at the token after the next one, without consuming either of them
+    pub fn peek_second(&mut self) -> SpannedItem<Token> {
+        let checkpoint = self.checkpoint();
+        self.advance();
+        let second = self.advance();
+        let _ = self.restore_checkpoint(checkpoint);
+        second
+    }
+
     pub fn new_with_existing_interner_and_source_map(
         sources: impl IntoIterator<Item = (impl Into<String>, impl Into<String>)>,
         interner: SymbolInterner,
@@ -214,9 +242,11 @@ impl Parser {
             errors: Default::default(),
             comments: Default::default(),
             peek: None,
+            previous_token: None,
             source_map,
             help: Default::default(),
             expr_id_assigner: 0,
+            module_sources: Default::default(),
         }
     }
 
@@ -238,16 +268,36 @@ impl Parser {
     /// consume tokens until a node is produced
     #[allow(clippy::type_complexity)]
     pub fn into_result(
-        mut self
+        self
     ) -> (
         Ast,
         Vec<SpannedItem<ParseError>>,
         SymbolInterner,
         IndexMap<SourceId, (&'static str, &'static str)>,
     ) {
+        self.into_parsed_sources().into_result()
+    }
+
+    /// Like [`Parser::into_result`], but keeps the state needed to incrementally reparse the
+    /// sources later with [`Parser::reparse`].
+    pub fn into_parsed_sources(mut self) -> ParsedSources {
         let nodes: Vec<Module> = self.many::<Module>();
-        // drop the lexers from the source map
-        (Ast::new(nodes), self.errors, self.interner, self.source_map)
+        ParsedSources {
+            ast: Ast::new(nodes),
+            errors: self.errors,
+            interner: self.interner,
+            source_map: self.source_map,
+            edited_texts: Default::default(),
+            module_sources: self.module_sources,
+            next_expr_id: self.expr_id_assigner,
+        }
+    }
+
+    pub(crate) fn record_module_source(
+        &mut self,
+        source: SourceId,
+    ) {
+        self.module_sources.push(source);
     }
 
     pub fn interner(&self) -> &SymbolInterner {
@@ -269,6 +319,57 @@ impl Parser {
         buf
     }
 
+    /// Like [`Parser::many`], but does not stop at the first item that fails to parse.
+    /// Each item is parsed with a backtracking parse. When one fails, its errors are kept, and
+    /// tokens are skipped until one of `recovery_points` (or the end of the current file) is
+    /// reached, at which point parsing resumes. This lets us report every independent error in a
+    /// file while still producing the items that did parse.
+    pub fn many_with_recovery<P: Parse>(
+        &mut self,
+        recovery_points: &[Token],
+    ) -> Vec<P> {
+        let mut buf = Vec::new();
+        loop {
+            if matches!(self.peek().item(), Token::Eof | Token::NewFile(_)) {
+                break;
+            }
+            match self.with_backtrack(|p| P::parse(p)) {
+                Ok(parsed_item) => buf.push(parsed_item),
+                Err(errs) => {
+                    let errs_before_skip = self.errors.len();
+                    // always consume at least one token, so recovery makes progress
+                    self.advance();
+                    self.skip_until(recovery_points);
+                    // tokens we skipped over may have been lexed during the failed parse already,
+                    // in which case their lexer errors were reported in `errs`
+                    let skipped_errs = self.errors.split_off(errs_before_skip);
+                    let skipped_errs = skipped_errs
+                        .into_iter()
+                        .filter(|skipped| !errs.iter().any(|err| err.span() == skipped.span()))
+                        .collect::<Vec<_>>();
+                    self.errors.extend(errs);
+                    self.errors.extend(skipped_errs);
+                },
+            }
+        }
+        buf
+    }
+
+    /// advances until the next token is one of `toks`, a new file, or the end of input.
+    /// does not consume the token it stops at.
+    fn skip_until(
+        &mut self,
+        toks: &[Token],
+    ) {
+        loop {
+            let tok = *self.peek().item();
+            if toks.contains(&tok) || matches!(tok, Token::Eof | Token::NewFile(_)) {
+                return;
+            }
+            self.advance();
+        }
+    }
+
     /// parses a sequence separated by `separator`
     /// e.g. if separator is `Token::Comma`, can parse `a, b, c, d`
     /// NOTE: this parses zero or more items. Will not reject zero items.
@@ -356,24 +457,32 @@ impl Parser {
     }
 
     pub fn advance(&mut self) -> SpannedItem<Token> {
-        if let Some(tok) = self.peek.take() {
-            return tok;
-        }
+        let tok = match self.peek.take() {
+            Some(tok) => tok,
+            None => self.next_token(),
+        };
+        self.previous_token = Some(tok.span());
+        tok
+    }
+
+    /// Lexes the next token, skipping newlines and collecting comments along the way.
+    fn next_token(&mut self) -> SpannedItem<Token> {
         let next_tok = match self.lexer.advance() {
             Ok(o) => o,
             Err(span) => {
                 let span = span.span();
                 self.push_error(span.with_item(ParseErrorKind::LexerError));
-                return span.with_item(Token::Eof);
+                // skip the invalid token so that the rest of the source is still parsed
+                return self.next_token();
             },
         };
         match *next_tok.item() {
-            Token::Newline => self.advance(),
+            Token::Newline => self.next_token(),
             Token::Comment => {
                 if let Some(comment) = self.parse::<SpannedItem<Comment>>() {
                     self.comments.push(comment);
                 }
-                self.advance()
+                self.next_token()
             },
             _ => next_tok,
         }
@@ -495,9 +604,11 @@ impl Parser {
 
     fn checkpoint(&self) -> Checkpoint {
         Checkpoint {
-            errors: self.errors.len(),
-            lexer:  self.lexer.clone(),
-            peek:   self.peek,
+            errors:         self.errors.len(),
+            comments:       self.comments.len(),
+            lexer:          self.lexer.clone(),
+            peek:           self.peek,
+            previous_token: self.previous_token,
         }
     }
 
@@ -507,18 +618,31 @@ impl Parser {
     ) -> Vec<SpannedItem<ParseError>> {
         self.lexer = checkpoint.lexer;
         self.peek = checkpoint.peek;
+        self.previous_token = checkpoint.previous_token;
+        // comments will be re-lexed after backtracking, so drop the ones we already collected
+        self.comments.truncate(checkpoint.comments);
         self.errors.split_off(checkpoint.errors)
     }
 
     pub fn source_map(&self) -> &IndexMap<SourceId, (&'static str, &'static str)> {
         &self.source_map
     }
+
+    /// Builds a lossless [`ConcreteSyntaxTree`] for every source known to this parser.
+    pub fn concrete_syntax_trees(&self) -> Vec<ConcreteSyntaxTree> {
+        self.source_map
+            .iter()
+            .map(|(id, (_name, source))| ConcreteSyntaxTree::new(id, source))
+            .collect()
+    }
 }
 
 struct Checkpoint {
-    errors: usize,
-    lexer:  Lexer,
-    peek:   Option<SpannedItem<Token>>,
+    errors:         usize,
+    comments:       usize,
+    lexer:          Lexer,
+    peek:           Option<SpannedItem<Token>>,
+    previous_token: Option<Span>,
 }
 
 pub trait Parse: Sized {
@@ -531,9 +655,21 @@ where
 {
     fn parse(p: &mut Parser) -> Option<Self> {
         let before_span = p.lexer.span();
+        // if the first token of the item was already peeked, then the lexer is past it, and the
+        // item starts at that token rather than after the last lexed one
+        let peeked_span = p.peek.map(|tok| tok.span());
         let result = T::parse(p)?;
 
-        let mut after_span = p.lexer.span();
+        let mut after_span = match (p.peek, p.previous_token) {
+            // the parser looked ahead at the token after the item, so the lexer is past the end of
+            // the item and the item ends at the last token it consumed instead
+            (Some(_), Some(previous_token))
+                if previous_token.source() == before_span.source() && previous_token.span().offset() >= before_span.span().offset() =>
+            {
+                previous_token
+            },
+            _ => p.lexer.span(),
+        };
         if after_span.source() != before_span.source() && after_span.span().offset() == 0 {
             // this is acceptable only if after_span.pos == 0, 0 OR if after_span.pos <= the first
             // whitespace of the next file, which is less likely to be hit and this function
@@ -553,6 +689,10 @@ where
             return None;
         }
 
+        if let Some(peeked_span) = peeked_span.filter(|span| span.source() == after_span.source()) {
+            return Some(peeked_span.join(after_span).with_item(result));
+        }
+
         // i think this should be `hi` to `hi`, not 100% though
         Some(before_span.hi_to_hi(after_span).with_item(result))
     }
@@ -0,0 +1,719 @@
+//! Incremental reparsing of sources after text edits.
+//! Top-level items can always be parsed independently of their neighbors (see
+//! [`TOP_LEVEL_ITEM_KEYWORDS`]), so an edit only requires reparsing the items it touches. Every other
+//! item is kept as-is, including its [`ExprId`]s and interned symbols, and only has its spans
+//! shifted if it comes after the edit in the same file. If the file has parse errors before or after
+//! the edit, or the edit changes how the text after the touched items is lexed, like by opening a
+//! string or a comment, the whole file is reparsed instead.
+
+#[cfg(test)]
+mod tests;
+
+use std::collections::BTreeMap;
+
+use logos::Logos;
+use miette::SourceSpan;
+use petr_ast::*;
+use petr_utils::{Identifier, IndexMap, Path, SourceId, Span, SpannedItem, SymbolInterner};
+use thiserror::Error;
+
+use super::{
+    lexer::{Lexer, Token},
+    ParseError, Parser,
+};
+use crate::{
+    cst::{top_level_items, CstElement},
+    parse_to_ast::TOP_LEVEL_ITEM_KEYWORDS,
+};
+
+/// The output of [`Parser::into_parsed_sources`], which can be updated with [`Parser::reparse`].
+pub struct ParsedSources {
+    pub ast: Ast,
+    pub errors: Vec<SpannedItem<ParseError>>,
+    pub interner: SymbolInterner,
+    /// The name and text of each source. The texts of edited sources point into `edited_texts`,
+    /// so they don't actually live forever, and are only lent out for as long as `self` is borrowed.
+    pub(super) source_map: IndexMap<SourceId, (&'static str, &'static str)>,
+    /// the current text of each edited source, by source id. Each edit replaces the previous text of
+    /// its source, instead of leaking it.
+    pub(super) edited_texts: BTreeMap<usize, Box<str>>,
+    /// the source each module in `ast` was parsed from, in the same order as `ast.modules`
+    pub(super) module_sources: Vec<SourceId>,
+    pub(super) next_expr_id: usize,
+}
+
+impl ParsedSources {
+    /// Converts this into the same shape as [`Parser::into_result`].
+    #[allow(clippy::type_complexity)]
+    pub fn into_result(
+        self
+    ) -> (
+        Ast,
+        Vec<SpannedItem<ParseError>>,
+        SymbolInterner,
+        IndexMap<SourceId, (&'static str, &'static str)>,
+    ) {
+        // the returned source map outlives `self`, so the edited texts have to live forever from now on
+        for text in self.edited_texts.into_values() {
+            let _ = Box::leak(text);
+        }
+        (self.ast, self.errors, self.interner, self.source_map)
+    }
+
+    /// The name and text of the source `id`
+    pub fn source(
+        &self,
+        id: SourceId,
+    ) -> (&str, &str) {
+        *self.source_map.get(id)
+    }
+
+    /// The name and text of each source, in the order of their ids
+    pub fn sources(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.source_map.iter().map(|(_, (name, text))| (*name, *text))
+    }
+
+    /// Checks that `range` is a valid range of bytes to replace in the source it points into,
+    /// and returns the start and end of that range
+    fn check_edit(
+        &self,
+        range: Span,
+    ) -> Result<(usize, usize), InvalidEditKind> {
+        if usize::from(range.source()) >= self.source_map.len() {
+            return Err(InvalidEditKind::UnknownSource(range.source()));
+        }
+        let (_, text) = self.source_map.get(range.source());
+        let start = range.span().offset();
+        let end = start + range.span().len();
+        if end > text.len() {
+            return Err(InvalidEditKind::OutOfBounds { end, len: text.len() });
+        }
+        match [start, end].into_iter().find(|offset| !text.is_char_boundary(*offset)) {
+            Some(offset) => Err(InvalidEditKind::NotACharBoundary(offset)),
+            None => Ok((start, end)),
+        }
+    }
+
+    fn apply_edit(
+        mut self,
+        range: Span,
+        replacement: String,
+    ) -> Result<Self, InvalidEdit> {
+        let (edit_start, edit_end) = match self.check_edit(range) {
+            Ok(bounds) => bounds,
+            Err(kind) => {
+                return Err(InvalidEdit {
+                    edit: range,
+                    kind,
+                    sources: Box::new(self),
+                })
+            },
+        };
+        let source_id = range.source();
+        let (name, old_text) = *self.source_map.get(source_id);
+
+        let mut new_text = String::with_capacity(old_text.len() + replacement.len());
+        new_text.push_str(&old_text[..edit_start]);
+        new_text.push_str(&replacement);
+        new_text.push_str(&old_text[edit_end..]);
+        let new_buffer = new_text.into_boxed_str();
+        // SAFETY: moving the box into `edited_texts` doesn't move the text. The text is only dropped
+        // when this source is edited again or `self` is dropped, and until then it is only lent out
+        // by borrowing `self`, or to the parser below, which doesn't outlive this function.
+        let new_text: &'static str = unsafe { &*(&*new_buffer as *const str) };
+        // keep the previous text alive until the end of this function, since `old_text` may point into it
+        let _old_buffer = self.edited_texts.insert(source_id.into(), new_buffer);
+        *self.source_map.get_mut(source_id) = (name, new_text);
+
+        // if this source didn't produce a module, e.g. because its file name is invalid, there
+        // is nothing to reparse.
+        let Some(module_index) = self.module_sources.iter().position(|source| *source == source_id) else {
+            return Ok(self);
+        };
+
+        let delta = new_text.len() as isize - old_text.len() as isize;
+        let old_region = affected_region(&top_level_items(source_id, old_text), old_text.len(), edit_start..edit_end);
+        let new_region = old_region.start..shift_offset(old_region.end, delta);
+        // an item with errors may have been cut short by the end of the region, or have stopped
+        // somewhere else than a full parse would, and comments before it carry over to the next item,
+        // which all changes how the items around it parse. So the whole file is reparsed if it had or
+        // has errors, or if the region can't be lexed on its own.
+        let had_errors = self.errors.iter().any(|error| error.span().source() == source_id);
+        let region_parse = (!had_errors && lexes_on_its_own(new_text, new_region.clone()))
+            .then(|| self.parse_region(source_id, new_text, new_region.clone()))
+            .filter(|(_, errors)| errors.is_empty());
+        let (old_region, (new_nodes, mut new_errors)) = match region_parse {
+            Some(parsed) => (old_region, parsed),
+            None => (0..old_text.len(), self.parse_region(source_id, new_text, 0..new_text.len())),
+        };
+
+        // splice the new nodes in place of the ones in the affected region
+        let in_region = |span: Span| {
+            let offset = span.span().offset();
+            old_region.contains(&offset) || (offset == old_text.len() && old_region.end == old_text.len())
+        };
+        let module = &mut self.ast.modules[module_index];
+        let old_nodes = std::mem::take(&mut module.nodes);
+        let mut splice_index = None;
+        for node in old_nodes {
+            if in_region(node.span()) {
+                splice_index.get_or_insert(module.nodes.len());
+            } else if node.span().span().offset() >= old_region.end {
+                splice_index.get_or_insert(module.nodes.len());
+                module.nodes.push(node.shift_spans(delta));
+            } else {
+                module.nodes.push(node);
+            }
+        }
+        let splice_index = splice_index.unwrap_or(module.nodes.len());
+        module.nodes.splice(splice_index..splice_index, new_nodes);
+
+        let old_errors = std::mem::take(&mut self.errors);
+        for error in old_errors {
+            if error.span().source() != source_id {
+                self.errors.push(error);
+            } else if in_region(error.span()) {
+                continue;
+            } else if error.span().span().offset() >= old_region.end {
+                let span = shift_span(error.span(), delta);
+                self.errors.push(span.with_item(error.into_item()));
+            } else {
+                self.errors.push(error);
+            }
+        }
+        self.errors.append(&mut new_errors);
+
+        Ok(self)
+    }
+
+    /// Parses the top-level items in `region` of the source `source_id`, picking up where the last
+    /// parse left off
+    fn parse_region(
+        &mut self,
+        source_id: SourceId,
+        text: &'static str,
+        region: std::ops::Range<usize>,
+    ) -> (Vec<SpannedItem<AstNode>>, Vec<SpannedItem<ParseError>>) {
+        let mut parser = Parser {
+            interner: std::mem::take(&mut self.interner),
+            expr_id_assigner: self.next_expr_id,
+            lexer: Lexer::new_for_range(source_id, text, region),
+            errors: Default::default(),
+            comments: Default::default(),
+            peek: None,
+            previous_token: None,
+            source_map: std::mem::take(&mut self.source_map),
+            help: Default::default(),
+            module_sources: Default::default(),
+        };
+        let nodes = parser.many_with_recovery::<SpannedItem<AstNode>>(&TOP_LEVEL_ITEM_KEYWORDS);
+        self.interner = parser.interner;
+        self.source_map = parser.source_map;
+        self.next_expr_id = parser.expr_id_assigner;
+        (nodes, parser.errors)
+    }
+}
+
+/// An edit which couldn't be applied, because its span doesn't point into the sources
+pub struct InvalidEdit {
+    pub edit:    Span,
+    pub kind:    InvalidEditKind,
+    /// The sources with every edit before the invalid one applied, and none after it
+    pub sources: Box<ParsedSources>,
+}
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum InvalidEditKind {
+    #[error("there is no source with id {0:?}")]
+    UnknownSource(SourceId),
+    #[error("the edit ends at byte {end}, but the source is only {len} bytes long")]
+    OutOfBounds { end: usize, len: usize },
+    #[error("byte {0} is inside of a multibyte character")]
+    NotACharBoundary(usize),
+}
+
+impl Parser {
+    /// Applies `edits` to the sources of a previous parse, and reparses only the top-level items
+    /// they touch. Each edit replaces the bytes covered by the span with the replacement text, and
+    /// edits are applied in order, so the span of each edit refers to the source as it is after
+    /// all of the previous edits. Applying stops at the first edit whose span is out of the bounds
+    /// of its source, or starts or ends inside of a multibyte character.
+    ///
+    /// [`SourceId`]s, [`ExprId`]s, and interned symbols of all untouched items are preserved.
+    pub fn reparse(
+        previous: ParsedSources,
+        edits: impl IntoIterator<Item = (Span, impl Into<String>)>,
+    ) -> Result<ParsedSources, InvalidEdit> {
+        edits
+            .into_iter()
+            .try_fold(previous, |parsed, (range, replacement)| parsed.apply_edit(range, replacement.into()))
+    }
+}
+
+/// Finds the byte range of the top-level items which are touched by `edit`. An edit touches an
+/// item if it overlaps it or is directly adjacent to it. The range always begins at the start of
+/// an item (including its leading trivia), and ends either at the start of an untouched item or at
+/// the end of the file.
+fn affected_region(
+    items: &[CstElement],
+    file_len: usize,
+    edit: std::ops::Range<usize>,
+) -> std::ops::Range<usize> {
+    let items = items
+        .iter()
+        .filter_map(|child| match child {
+            CstElement::Node(node) => {
+                let span = node.span().span();
+                Some(span.offset()..span.offset() + span.len())
+            },
+            CstElement::Token(_) => None,
+        })
+        .collect::<Vec<_>>();
+    let end_of_items = items.last().map(|item| item.end).unwrap_or(0);
+
+    let mut touched = items
+        .iter()
+        .enumerate()
+        .filter(|(_, item)| item.start <= edit.end && edit.start <= item.end);
+    let Some((first_index, first)) = touched.next() else {
+        // the edit is entirely in the trailing trivia of the file
+        return end_of_items.min(edit.start)..file_len;
+    };
+    let (last_index, last) = touched.last().unwrap_or((first_index, first));
+
+    let end = if last_index == items.len() - 1 { file_len } else { last.end };
+    first.start..end
+}
+
+/// Whether lexing `region` of `text` on its own gives the same tokens as lexing all of `text`. That
+/// isn't the case if a token runs past the end of the region, like a string or a comment which an
+/// edit opened but didn't close, or if some of the region can't be lexed at all.
+fn lexes_on_its_own(
+    text: &str,
+    region: std::ops::Range<usize>,
+) -> bool {
+    let region_len = region.end - region.start;
+    let mut lexer = Token::lexer(&text[region.start..]);
+    while let Some(token) = lexer.next() {
+        let span = lexer.span();
+        if span.start >= region_len {
+            return true;
+        }
+        if token.is_err() || span.end > region_len {
+            return false;
+        }
+    }
+    true
+}
+
+fn shift_offset(
+    offset: usize,
+    delta: isize,
+) -> usize {
+    (offset as isize + delta) as usize
+}
+
+fn shift_span(
+    span: Span,
+    delta: isize,
+) -> Span {
+    Span::new(
+        span.source(),
+        SourceSpan::new(shift_offset(span.span().offset(), delta).into(), span.span().len().into()),
+    )
+}
+
+/// Moves every span in an AST node by `delta` bytes, for nodes which come after an edit.
+trait ShiftSpans {
+    fn shift_spans(
+        self,
+        delta: isize,
+    ) -> Self;
+}
+
+impl<T: ShiftSpans> ShiftSpans for SpannedItem<T> {
+    fn shift_spans(
+        self,
+        delta: isize,
+    ) -> Self {
+        let span = shift_span(self.span(), delta);
+        span.with_item(self.into_item().shift_spans(delta))
+    }
+}
+
+impl<T: ShiftSpans> ShiftSpans for Commented<T> {
+    fn shift_spans(
+        self,
+        delta: isize,
+    ) -> Self {
+        self.map(|item| item.shift_spans(delta))
+    }
+}
+
+impl<T: ShiftSpans> ShiftSpans for Box<T> {
+    fn shift_spans(
+        self,
+        delta: isize,
+    ) -> Self {
+        Box::new((*self).shift_spans(delta))
+    }
+}
+
+impl<T: ShiftSpans> ShiftSpans for Box<[T]> {
+    fn shift_spans(
+        self,
+        delta: isize,
+    ) -> Self {
+        self.into_vec().into_iter().map(|item| item.shift_spans(delta)).collect()
+    }
+}
+
+impl<T: ShiftSpans> ShiftSpans for Option<T> {
+    fn shift_spans(
+        self,
+        delta: isize,
+    ) -> Self {
+        self.map(|item| item.shift_spans(delta))
+    }
+}
+
+impl ShiftSpans for Identifier {
+    fn shift_spans(
+        self,
+        delta: isize,
+    ) -> Self {
+        Identifier {
+            id:   self.id,
+            span: shift_span(self.span, delta),
+        }
+    }
+}
+
+impl ShiftSpans for Path {
+    fn shift_spans(
+        self,
+        delta: isize,
+    ) -> Self {
+        Path {
+            identifiers: self.identifiers.shift_spans(delta),
+        }
+    }
+}
+
+impl ShiftSpans for AstNode {
+    fn shift_spans(
+        self,
+        delta: isize,
+    ) -> Self {
+        match self {
+            AstNode::FunctionDeclaration(decl) => AstNode::FunctionDeclaration(decl.shift_spans(delta)),
+            AstNode::TypeDeclaration(decl) => AstNode::TypeDeclaration(decl.shift_spans(delta)),
+            AstNode::TypeAlias(alias) => AstNode::TypeAlias(alias.shift_spans(delta)),
+            AstNode::ImportStatement(stmt) => AstNode::ImportStatement(stmt.shift_spans(delta)),
+            AstNode::InterfaceDeclaration(decl) => AstNode::InterfaceDeclaration(decl.shift_spans(delta)),
+            AstNode::ImplementationDeclaration(decl) => AstNode::ImplementationDeclaration(decl.shift_spans(delta)),
+        }
+    }
+}
+
+impl ShiftSpans for InterfaceDeclaration {
+    fn shift_spans(
+        self,
+        delta: isize,
+    ) -> Self {
+        InterfaceDeclaration {
+            name:       self.name.shift_spans(delta),
+            type_param: self.type_param.shift_spans(delta),
+            methods:    self.methods.shift_spans(delta),
+            visibility: self.visibility,
+        }
+    }
+}
+
+impl ShiftSpans for InterfaceMethod {
+    fn shift_spans(
+        self,
+        delta: isize,
+    ) -> Self {
+        InterfaceMethod {
+            name:        self.name.shift_spans(delta),
+            parameters:  self.parameters.shift_spans(delta),
+            return_type: self.return_type.shift_spans(delta),
+        }
+    }
+}
+
+impl ShiftSpans for ImplementationDeclaration {
+    fn shift_spans(
+        self,
+        delta: isize,
+    ) -> Self {
+        ImplementationDeclaration {
+            interface: self.interface.shift_spans(delta),
+            ty:        self.ty.shift_spans(delta),
+            methods:   self
+                .methods
+                .into_vec()
+                .into_iter()
+                .map(|(method, function)| (method.shift_spans(delta), function.shift_spans(delta)))
+                .collect(),
+        }
+    }
+}
+
+impl ShiftSpans for TypeConstraint {
+    fn shift_spans(
+        self,
+        delta: isize,
+    ) -> Self {
+        TypeConstraint {
+            type_param: self.type_param.shift_spans(delta),
+            interface:  self.interface.shift_spans(delta),
+        }
+    }
+}
+
+impl ShiftSpans for ImportStatement {
+    fn shift_spans(
+        self,
+        delta: isize,
+    ) -> Self {
+        ImportStatement {
+            path:       self.path.shift_spans(delta),
+            alias:      self.alias.shift_spans(delta),
+            visibility: self.visibility,
+        }
+    }
+}
+
+impl ShiftSpans for TypeDeclaration {
+    fn shift_spans(
+        self,
+        delta: isize,
+    ) -> Self {
+        TypeDeclaration {
+            name:        self.name.shift_spans(delta),
+            type_params: self.type_params.shift_spans(delta),
+            variants:    self.variants.shift_spans(delta),
+            visibility:  self.visibility,
+        }
+    }
+}
+
+impl ShiftSpans for TypeAlias {
+    fn shift_spans(
+        self,
+        delta: isize,
+    ) -> Self {
+        TypeAlias {
+            name:       self.name.shift_spans(delta),
+            ty:         self.ty.shift_spans(delta),
+            visibility: self.visibility,
+        }
+    }
+}
+
+impl ShiftSpans for TypeVariantOrLiteral {
+    fn shift_spans(
+        self,
+        delta: isize,
+    ) -> Self {
+        match self {
+            TypeVariantOrLiteral::Variant(variant) => TypeVariantOrLiteral::Variant(TypeVariant {
+                name:   variant.name.shift_spans(delta),
+                fields: variant.fields.shift_spans(delta),
+            }),
+            literal @ TypeVariantOrLiteral::Literal(_) => literal,
+        }
+    }
+}
+
+impl ShiftSpans for TypeField {
+    fn shift_spans(
+        self,
+        delta: isize,
+    ) -> Self {
+        TypeField {
+            name: self.name.shift_spans(delta),
+            ty:   self.ty.shift_spans(delta),
+        }
+    }
+}
+
+impl ShiftSpans for Ty {
+    fn shift_spans(
+        self,
+        delta: isize,
+    ) -> Self {
+        match self {
+            Ty::Named(name) => Ty::Named(name.shift_spans(delta)),
+            Ty::Applied(name, args) => Ty::Applied(name.shift_spans(delta), args.shift_spans(delta)),
+            Ty::Sum(tys) => Ty::Sum(tys.shift_spans(delta)),
+            Ty::Tuple(tys) => Ty::Tuple(tys.shift_spans(delta)),
+            Ty::Arrow(params, return_ty) => Ty::Arrow(params.shift_spans(delta), return_ty.shift_spans(delta)),
+            ty @ (Ty::Int | Ty::SizedInt(_) | Ty::Float | Ty::Bool | Ty::String | Ty::Unit | Ty::Literal(_)) => ty,
+        }
+    }
+}
+
+impl ShiftSpans for BindingPattern {
+    fn shift_spans(
+        self,
+        delta: isize,
+    ) -> Self {
+        match self {
+            BindingPattern::Name(name) => BindingPattern::Name(name.shift_spans(delta)),
+            BindingPattern::Tuple(names) => BindingPattern::Tuple(names.shift_spans(delta)),
+        }
+    }
+}
+
+impl ShiftSpans for FunctionDeclaration {
+    fn shift_spans(
+        self,
+        delta: isize,
+    ) -> Self {
+        FunctionDeclaration {
+            name:        self.name.shift_spans(delta),
+            parameters:  self.parameters.shift_spans(delta),
+            return_type: self.return_type.shift_spans(delta),
+            constraints: self.constraints.shift_spans(delta),
+            effects:     self.effects.shift_spans(delta),
+            body:        self.body.shift_spans(delta),
+            visibility:  self.visibility,
+        }
+    }
+}
+
+impl ShiftSpans for FunctionParameter {
+    fn shift_spans(
+        self,
+        delta: isize,
+    ) -> Self {
+        FunctionParameter {
+            name: self.name.shift_spans(delta),
+            ty:   self.ty.shift_spans(delta),
+        }
+    }
+}
+
+impl ShiftSpans for Expression {
+    fn shift_spans(
+        self,
+        delta: isize,
+    ) -> Self {
+        match self {
+            Expression::Literal(literal) => Expression::Literal(literal),
+            Expression::List(list) => Expression::List(List {
+                elements: list.elements.shift_spans(delta),
+            }),
+            Expression::Operator(op) => Expression::Operator(Box::new(OperatorExpression {
+                lhs: op.lhs.shift_spans(delta),
+                rhs: op.rhs.shift_spans(delta),
+                op:  shift_span(op.op.span(), delta).with_item(op.op.into_item()),
+            })),
+            Expression::UnaryOperator(op) => Expression::UnaryOperator(Box::new(UnaryOperatorExpression {
+                operand: op.operand.shift_spans(delta),
+                op:      shift_span(op.op.span(), delta).with_item(op.op.into_item()),
+            })),
+            Expression::FunctionCall(call) => Expression::FunctionCall(FunctionCall {
+                func_name: call.func_name.shift_spans(delta),
+                args: call.args.shift_spans(delta),
+                arg_names: call.arg_names.shift_spans(delta),
+                args_were_parenthesized: call.args_were_parenthesized,
+            }),
+            Expression::Variable(name) => Expression::Variable(name.shift_spans(delta)),
+            Expression::Tuple(elements) => Expression::Tuple(elements.shift_spans(delta)),
+            Expression::Hole(name) => Expression::Hole(name.map(|name| name.shift_spans(delta))),
+            Expression::IntrinsicCall(call) => Expression::IntrinsicCall(IntrinsicCall {
+                intrinsic: call.intrinsic,
+                args:      call.args.shift_spans(delta),
+            }),
+            Expression::Binding(bindings) => Expression::Binding(ExpressionWithBindings {
+                bindings:   bindings
+                    .bindings
+                    .into_iter()
+                    .map(|binding| Binding {
+                        pattern: binding.pattern.shift_spans(delta),
+                        val:     binding.val.shift_spans(delta),
+                    })
+                    .collect(),
+                expression: bindings.expression.shift_spans(delta),
+                expr_id:    bindings.expr_id,
+            }),
+            Expression::TypeConstructor(ty, variant, args) => Expression::TypeConstructor(ty, variant, args.shift_spans(delta)),
+            Expression::InterfaceMethod(interface, method) => Expression::InterfaceMethod(interface, method),
+            Expression::If(If {
+                condition,
+                then_branch,
+                else_branch,
+            }) => Expression::If(If {
+                condition:   condition.shift_spans(delta),
+                then_branch: then_branch.shift_spans(delta),
+                else_branch: else_branch.shift_spans(delta),
+            }),
+            Expression::Match(Match { scrutinee, arms }) => Expression::Match(Match {
+                scrutinee: scrutinee.shift_spans(delta),
+                arms:      arms.shift_spans(delta),
+            }),
+            Expression::Lambda(Lambda { parameters, body, expr_id }) => Expression::Lambda(Lambda {
+                parameters: parameters.shift_spans(delta),
+                body: body.shift_spans(delta),
+                expr_id,
+            }),
+            Expression::FieldAccess(access) => Expression::FieldAccess(Box::new(FieldAccess {
+                target: access.target.shift_spans(delta),
+                field:  access.field.shift_spans(delta),
+            })),
+            Expression::RecordUpdate(update) => Expression::RecordUpdate(Box::new(RecordUpdate {
+                target: update.target.shift_spans(delta),
+                fields: update
+                    .fields
+                    .into_vec()
+                    .into_iter()
+                    .map(|(name, value)| (name.shift_spans(delta), value.shift_spans(delta)))
+                    .collect(),
+            })),
+        }
+    }
+}
+
+impl ShiftSpans for MatchArm {
+    fn shift_spans(
+        self,
+        delta: isize,
+    ) -> Self {
+        MatchArm {
+            pattern: self.pattern.shift_spans(delta),
+            body:    self.body.shift_spans(delta),
+            expr_id: self.expr_id,
+        }
+    }
+}
+
+impl ShiftSpans for Pattern {
+    fn shift_spans(
+        self,
+        delta: isize,
+    ) -> Self {
+        match self {
+            Pattern::Variant(variant) => Pattern::Variant(VariantPattern {
+                name:   variant.name.shift_spans(delta),
+                fields: variant.fields.shift_spans(delta),
+            }),
+            pattern @ (Pattern::Wildcard | Pattern::Literal(_)) => pattern,
+        }
+    }
+}
+
+impl ShiftSpans for FieldPattern {
+    fn shift_spans(
+        self,
+        delta: isize,
+    ) -> Self {
+        match self {
+            FieldPattern::Binding(name) => FieldPattern::Binding(name.shift_spans(delta)),
+            pattern @ (FieldPattern::Wildcard | FieldPattern::Literal(_)) => pattern,
+        }
+    }
+}
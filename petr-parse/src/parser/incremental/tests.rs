@@ -0,0 +1,319 @@
+use expect_test::expect;
+use miette::SourceSpan;
+use petr_ast::{AstNode, ExprId, Expression};
+use petr_utils::{PrettyPrint, SourceId, Span, SymbolId};
+use proptest::prelude::*;
+
+use crate::{parser::ParsedSources, InvalidEditKind, Parser};
+
+fn edit(
+    source: usize,
+    start: usize,
+    end: usize,
+    replacement: &str,
+) -> (Span, String) {
+    (
+        Span::new(SourceId::from(source), SourceSpan::new(start.into(), (end - start).into())),
+        replacement.to_string(),
+    )
+}
+
+fn render(parsed: &ParsedSources) -> String {
+    let ast = parsed.ast.pretty_print(&parsed.interner, 0);
+    let node_spans = parsed
+        .ast
+        .modules
+        .iter()
+        .flat_map(|module| module.nodes.iter().map(|node| format!("{:?}", node.span())))
+        .collect::<Vec<_>>()
+        .join("\n");
+    let mut errs = parsed.errors.iter().map(|err| format!("{err:?}")).collect::<Vec<_>>();
+    errs.sort();
+    format!("{ast}\n{node_spans}\n{}", errs.join("\n"))
+}
+
+/// Applies `edits` incrementally, and checks that the result is the same as parsing the edited
+/// sources from scratch.
+fn check(
+    sources: Vec<&str>,
+    edits: Vec<(Span, String)>,
+    expected: expect_test::Expect,
+) -> ParsedSources {
+    let parsed = Parser::new(sources.into_iter().map(|s| ("test", s))).into_parsed_sources();
+    let Ok(reparsed) = Parser::reparse(parsed, edits) else {
+        panic!("edits should be valid")
+    };
+
+    let edited_sources = reparsed.sources().collect::<Vec<_>>();
+    let from_scratch = Parser::new(edited_sources).into_parsed_sources();
+    assert_eq!(render(&reparsed), render(&from_scratch));
+
+    let sources = reparsed.sources().map(|(_, source)| source).collect::<Vec<_>>().join("\n____\n");
+    expected.assert_eq(&format!("{sources}\n\n{}", reparsed.ast.pretty_print(&reparsed.interner, 0)));
+    reparsed
+}
+
+fn function_name_and_body_expr_id(
+    parsed: &ParsedSources,
+    function_index: usize,
+) -> (SymbolId, ExprId) {
+    let AstNode::FunctionDeclaration(func) = parsed.ast.modules[0].nodes[function_index].item() else {
+        panic!("expected a function")
+    };
+    let Expression::Binding(ref bindings) = func.item().body.item() else {
+        panic!("expected a let expression")
+    };
+    (func.item().name.id, bindings.expr_id)
+}
+
+#[test]
+fn edit_inside_function_body() {
+    check(
+        vec!["fn a() returns 'int 1\nfn b() returns 'int 2\nfn c() returns 'int 3"],
+        vec![edit(0, 42, 43, "+ 30 3")],
+        expect![[r#"
+            fn a() returns 'int 1
+            fn b() returns 'int + 30 3
+            fn c() returns 'int 3
+
+            module test =
+            Func a() -> 'int 1
+            Func b() -> 'int +(30 3)
+            Func c() -> 'int 3
+        "#]],
+    );
+}
+
+#[test]
+fn edit_shifts_later_items_and_errors() {
+    check(
+        vec!["fn a() returns 'int 1\nfn b( returns 'int 2\nfn c() returns 'int 3\n) fn d() returns 'int 4"],
+        vec![edit(0, 20, 21, "100")],
+        expect![[r#"
+            fn a() returns 'int 100
+            fn b( returns 'int 2
+            fn c() returns 'int 3
+            ) fn d() returns 'int 4
+
+            module test =
+            Func a() -> 'int 100
+            Func c() -> 'int 3
+            Func d() -> 'int 4
+        "#]],
+    );
+}
+
+#[test]
+fn fixing_an_error_removes_it() {
+    check(
+        vec!["fn a() returns 'int 1\nfn b( returns 'int 2\nfn c() returns 'int 3"],
+        vec![edit(0, 27, 27, ")")],
+        expect![[r#"
+            fn a() returns 'int 1
+            fn b() returns 'int 2
+            fn c() returns 'int 3
+
+            module test =
+            Func a() -> 'int 1
+            Func b() -> 'int 2
+            Func c() -> 'int 3
+        "#]],
+    );
+}
+
+#[test]
+fn splitting_and_appending_items() {
+    check(
+        vec!["fn a() returns 'int 1\nfn b() returns 'int 2"],
+        vec![edit(0, 21, 21, "\nimport std.io"), edit(0, 57, 57, "\n{- new -} type T = A | B")],
+        expect![[r#"
+            fn a() returns 'int 1
+            import std.io
+            fn b() returns 'int 2
+            {- new -} type T = A | B
+
+            module test =
+            Func a() -> 'int 1
+            import std.io
+            Func b() -> 'int 2
+            {- new -}
+            type T =
+              A() |
+              B()"#]],
+    );
+}
+
+#[test]
+fn edits_in_other_files_are_isolated() {
+    check(
+        vec!["fn a() returns 'int 1", "fn b() returns 'int 2", "fn c() returns 'int 3"],
+        vec![edit(1, 0, 2, "export fn")],
+        expect![[r#"
+            fn a() returns 'int 1
+            ____
+            export fn b() returns 'int 2
+            ____
+            fn c() returns 'int 3
+
+            module test =
+            Func a() -> 'int 1
+            module test =
+            exported Func b() -> 'int 2
+            module test =
+            Func c() -> 'int 3
+        "#]],
+    );
+}
+
+#[test]
+fn opening_a_string_reparses_the_whole_file() {
+    check(
+        vec!["fn a() returns 'int 1\nfn b() returns 'int 2\nfn c() returns 'string \"c\""],
+        vec![edit(0, 42, 42, "\"")],
+        expect![[r#"
+            fn a() returns 'int 1
+            fn b() returns 'int "2
+            fn c() returns 'string "c"
+
+            module test =
+            Func a() -> 'int 1
+            Func b() -> 'int "2
+            fn c() returns 'string "
+        "#]],
+    );
+}
+
+#[test]
+fn opening_a_comment_reparses_the_whole_file() {
+    check(
+        vec!["fn a() returns 'int 1\nfn b() returns 'int 2\n{- c -} fn c() returns 'int 3"],
+        vec![edit(0, 20, 20, "{- ")],
+        expect![[r#"
+            fn a() returns 'int {- 1
+            fn b() returns 'int 2
+            {- c -} fn c() returns 'int 3
+
+            module test =
+            Func c() -> 'int 3
+        "#]],
+    );
+}
+
+#[test]
+fn untouched_items_keep_expr_ids_and_symbols() {
+    let source = "fn a() returns 'int let x = 1 x\nfn b() returns 'int let y = 2 y\nfn c() returns 'int let z = 3 z";
+    let parsed = Parser::new(vec![("test", source)]).into_parsed_sources();
+    let before = [0, 1, 2].map(|ix| function_name_and_body_expr_id(&parsed, ix));
+
+    let reparsed = check(
+        vec![source],
+        vec![edit(0, 60, 61, "5")],
+        expect![[r#"
+        fn a() returns 'int let x = 1 x
+        fn b() returns 'int let y = 5 y
+        fn c() returns 'int let z = 3 z
+
+        module test =
+        Func a() -> 'int 
+          let x = 1,
+        var(x)
+
+
+        Func b() -> 'int 
+          let y = 5,
+        var(y)
+
+
+        Func c() -> 'int 
+          let z = 3,
+        var(z)
+
+
+    "#]],
+    );
+    let after = [0, 1, 2].map(|ix| function_name_and_body_expr_id(&reparsed, ix));
+
+    assert_eq!(after[0], before[0]);
+    assert_eq!(after[2], before[2]);
+    // the edited function keeps its interned name, but gets a fresh expr id
+    assert_eq!(after[1].0, before[1].0);
+    assert!(before.iter().all(|(_, expr_id)| *expr_id != after[1].1));
+}
+
+#[test]
+fn invalid_edits_are_rejected() {
+    let source = "fn a() returns 'int 1\nfn b() returns 'string \"λ\"";
+    let invalid_edit = |edits: Vec<(Span, String)>| {
+        let parsed = Parser::new(vec![("test", source)]).into_parsed_sources();
+        match Parser::reparse(parsed, edits) {
+            Ok(_) => panic!("the edit should be rejected"),
+            Err(invalid) => {
+                // the edits before the invalid one are still applied
+                assert_eq!(invalid.sources.source(SourceId::from(0)).1, source.replacen('1', "2", 1));
+                invalid.kind
+            },
+        }
+    };
+    assert_eq!(
+        invalid_edit(vec![edit(0, 20, 21, "2"), edit(0, 40, 60, "")]),
+        InvalidEditKind::OutOfBounds { end: 60, len: 49 }
+    );
+    // `λ` is two bytes long, starting at byte 46
+    assert_eq!(
+        invalid_edit(vec![edit(0, 20, 21, "2"), edit(0, 47, 48, "x")]),
+        InvalidEditKind::NotACharBoundary(47)
+    );
+    assert_eq!(
+        invalid_edit(vec![edit(0, 20, 21, "2"), edit(1, 0, 0, "x")]),
+        InvalidEditKind::UnknownSource(SourceId::from(1))
+    );
+}
+
+/// Pieces of code which edits insert, including ones which change how the rest of the file is lexed
+const SNIPPETS: &[&str] = &[
+    "",
+    " ",
+    "\n",
+    "\"",
+    "{-",
+    "-}",
+    "(",
+    ")",
+    "1",
+    "x",
+    "fn",
+    "fn f() returns 'int 1",
+    "type",
+    "import std.io",
+    "let",
+    "=",
+    "'int",
+    "export",
+];
+
+const PROGRAM: &str =
+    "fn a() returns 'int 1\n{- b -} fn b(x in 'int) returns 'int + x 2\ntype T = A | B x 'int\nimport std.io\nfn c() returns 'string \"c\"";
+
+fn arbitrary_edit() -> impl Strategy<Value = (usize, usize, &'static str)> {
+    (0..=PROGRAM.len(), 0..=8usize, proptest::sample::select(SNIPPETS))
+}
+
+proptest! {
+    #[test]
+    fn incremental_and_full_parses_agree(edits in proptest::collection::vec(arbitrary_edit(), 1..6)) {
+        let mut parsed = Parser::new(vec![("test", PROGRAM)]).into_parsed_sources();
+        for (start, len, replacement) in edits {
+            // the program is ascii, so every offset is a char boundary
+            let text_len = parsed.source(SourceId::from(0)).1.len();
+            let start = start.min(text_len);
+            let end = (start + len).min(text_len);
+            let Ok(reparsed) = Parser::reparse(parsed, vec![edit(0, start, end, replacement)]) else {
+                panic!("edits should be valid")
+            };
+            parsed = reparsed;
+
+            let from_scratch = Parser::new(parsed.sources().collect::<Vec<_>>()).into_parsed_sources();
+            prop_assert_eq!(render(&parsed), render(&from_scratch));
+        }
+    }
+}
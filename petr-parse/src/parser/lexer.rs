@@ -27,14 +27,40 @@ pub enum Token {
     Slash,
     #[token("*")]
     Star,
+    #[token("!=")]
+    NotEquals,
+    #[token("<")]
+    LessThan,
+    #[token(">")]
+    GreaterThan,
+    #[token("<=")]
+    LessThanOrEquals,
+    #[token(">=")]
+    GreaterThanOrEquals,
+    #[token("and")]
+    And,
+    #[token("or")]
+    Or,
+    #[token("not")]
+    Not,
+    #[token("with")]
+    With,
     #[regex("[0-9]+")]
     Integer,
+    #[regex("[0-9]+\\.[0-9]+")]
+    Float,
     #[token("if")]
     If,
     #[token("then")]
     Then,
     #[token("else")]
     Else,
+    #[token("match")]
+    Match,
+    #[token("=>")]
+    FatArrow,
+    #[token("_", priority = 3)]
+    Underscore,
     #[regex("[_a-zA-Z][_a-zA-Z0-9]{0,30}")]
     Identifier,
     #[regex(r#""([^"\\]|\\["\\bnfrt]|u[a-fA-F0-9]{4})*""#)]
@@ -47,9 +73,13 @@ pub enum Token {
     InKeyword,
     #[token("∈")]
     IsInSymbol,
-    #[token("'sum")]
+    /// `'sum`, lexed by [`type_marker`]
     SumKeyword,
-    #[token("'")]
+    /// `'fn`, lexed by [`type_marker`]
+    FunctionTypeKeyword,
+    #[token("\\")]
+    Backslash,
+    #[token("'", type_marker)]
     TyMarker,
     #[token(",")]
     Comma,
@@ -61,6 +91,8 @@ pub enum Token {
     SumSymbol,
     #[token("type")]
     TypeKeyword,
+    #[token("alias")]
+    AliasKeyword,
     #[token("\n")]
     Newline,
     #[token("=")]
@@ -73,6 +105,22 @@ pub enum Token {
     ExportFunctionKeyword,
     #[token("Type")]
     ExportTypeKeyword,
+    #[token("interface")]
+    InterfaceKeyword,
+    #[regex(r#"export\s+interface"#)]
+    ExportInterfaceKeyword,
+    #[token("implement")]
+    ImplementKeyword,
+    #[token("for")]
+    ForKeyword,
+    #[token("where")]
+    WhereKeyword,
+    #[token("uses")]
+    UsesKeyword,
+    #[token("pure")]
+    PureKeyword,
+    #[token(":")]
+    Colon,
     #[token("~")]
     Tilde,
     #[token("true")]
@@ -89,16 +137,34 @@ pub enum Token {
     As,
     #[regex(r#"\@[_a-zA-Z][_a-zA-Z0-9]{0,30}"#)]
     Intrinsic,
+    #[regex(r#"\?[_a-zA-Z][_a-zA-Z0-9]{0,30}"#)]
+    Hole,
     #[token(";")]
     Semicolon,
     NewFile(SourceId),
     Eof,
 }
 
+/// Lexes `'sum` and `'fn` as keywords only when they are whole words, so that type names which
+/// start with them, like `'fnord` or `'summary`, are lexed as a type marker and an identifier
+fn type_marker(lex: &mut logos::Lexer<Token>) -> Token {
+    for (keyword, token) in [("sum", Token::SumKeyword), ("fn", Token::FunctionTypeKeyword)] {
+        let Some(rest) = lex.remainder().strip_prefix(keyword) else { continue };
+        if !rest.starts_with(|c: char| c == '_' || c.is_ascii_alphanumeric()) {
+            lex.bump(keyword.len());
+            return token;
+        }
+    }
+    Token::TyMarker
+}
+
 impl Token {
     pub(crate) fn is_operator(&self) -> bool {
         use Token::*;
-        matches!(self, Plus | Minus | Slash | Star | Equals)
+        matches!(
+            self,
+            Plus | Minus | Slash | Star | Equals | NotEquals | LessThan | GreaterThan | LessThanOrEquals | GreaterThanOrEquals | And | Or
+        )
     }
 }
 
@@ -117,7 +183,17 @@ impl std::fmt::Display for Token {
             Minus => write!(f, "-"),
             Slash => write!(f, "/"),
             Star => write!(f, "*"),
+            NotEquals => write!(f, "!="),
+            LessThan => write!(f, "<"),
+            GreaterThan => write!(f, ">"),
+            LessThanOrEquals => write!(f, "<="),
+            GreaterThanOrEquals => write!(f, ">="),
+            And => write!(f, "and"),
+            Or => write!(f, "or"),
+            Not => write!(f, "not"),
+            With => write!(f, "with"),
             Integer => write!(f, "integer"),
+            Float => write!(f, "float"),
             Identifier => write!(f, "identifier"),
             FunctionKeyword => write!(f, "function"),
             InKeyword => write!(f, ":"),
@@ -129,16 +205,26 @@ impl std::fmt::Display for Token {
             Comment => write!(f, "{{- comment -}}"),
             Newline => write!(f, "newline"),
             TypeKeyword => write!(f, "type"),
+            AliasKeyword => write!(f, "alias"),
             Equals => write!(f, "="),
             Pipe => write!(f, "|"),
             ToKeyword => write!(f, "to"),
             ExportFunctionKeyword => write!(f, "Function"),
             ExportTypeKeyword => write!(f, "Type"),
+            InterfaceKeyword => write!(f, "interface"),
+            ExportInterfaceKeyword => write!(f, "export interface"),
+            ImplementKeyword => write!(f, "implement"),
+            ForKeyword => write!(f, "for"),
+            WhereKeyword => write!(f, "where"),
+            UsesKeyword => write!(f, "uses"),
+            PureKeyword => write!(f, "pure"),
+            Colon => write!(f, ":"),
             Tilde => write!(f, "~"),
             True => write!(f, "true"),
             False => write!(f, "false"),
             String => write!(f, "string"),
             Intrinsic => write!(f, "@intrinsic"),
+            Hole => write!(f, "?hole"),
             Let => write!(f, "let"),
             NewFile(source_id) => write!(f, "new file {source_id:?}"),
             Import => write!(f, "import"),
@@ -148,7 +234,12 @@ impl std::fmt::Display for Token {
             If => write!(f, "if"),
             Then => write!(f, "then"),
             Else => write!(f, "else"),
+            Match => write!(f, "match"),
+            FatArrow => write!(f, "=>"),
+            Underscore => write!(f, "_"),
             SumKeyword => write!(f, "'sum"),
+            FunctionTypeKeyword => write!(f, "'fn"),
+            Backslash => write!(f, "\\"),
             SumSymbol => write!(f, "Σ"),
             ReturnsSymbol => write!(f, "→"),
         }
@@ -199,6 +290,25 @@ impl Lexer {
         }
     }
 
+    /// Creates a lexer which only lexes `range` of a single source, e.g. to reparse part of a file.
+    /// Spans are still relative to the start of `source`, and no [`Token::NewFile`] is produced.
+    pub fn new_for_range(
+        source_id: SourceId,
+        source: &'static str,
+        range: std::ops::Range<usize>,
+    ) -> Self {
+        let mut lexer = Token::lexer(&source[..range.end]);
+        lexer.bump(range.start);
+        let mut map: IndexMap<_, _> = Default::default();
+        map.insert(lexer);
+        Self {
+            sources: map,
+            source: 0.into(),
+            offset: source_id.into(),
+            has_started_lexing: true,
+        }
+    }
+
     pub fn current_source(&self) -> SourceId {
         (Into::<usize>::into(self.source) + self.offset).into()
     }
@@ -72,3 +72,42 @@ fn test_symbols() {
             ]"#]],
     )
 }
+
+#[test]
+fn comparison_and_boolean_operators() {
+    check(
+        vec!["< <= > >= != = => and or not andy"],
+        expect![[r#"
+        [
+            SpannedItem NewFile(SourceId(0)) [Span { source: SourceId(0), span: SourceSpan { offset: SourceOffset(0), length: 0 } }],
+            SpannedItem LessThan [Span { source: SourceId(0), span: SourceSpan { offset: SourceOffset(0), length: 1 } }],
+            SpannedItem LessThanOrEquals [Span { source: SourceId(0), span: SourceSpan { offset: SourceOffset(2), length: 2 } }],
+            SpannedItem GreaterThan [Span { source: SourceId(0), span: SourceSpan { offset: SourceOffset(5), length: 1 } }],
+            SpannedItem GreaterThanOrEquals [Span { source: SourceId(0), span: SourceSpan { offset: SourceOffset(7), length: 2 } }],
+            SpannedItem NotEquals [Span { source: SourceId(0), span: SourceSpan { offset: SourceOffset(10), length: 2 } }],
+            SpannedItem Equals [Span { source: SourceId(0), span: SourceSpan { offset: SourceOffset(13), length: 1 } }],
+            SpannedItem FatArrow [Span { source: SourceId(0), span: SourceSpan { offset: SourceOffset(15), length: 2 } }],
+            SpannedItem And [Span { source: SourceId(0), span: SourceSpan { offset: SourceOffset(18), length: 3 } }],
+            SpannedItem Or [Span { source: SourceId(0), span: SourceSpan { offset: SourceOffset(22), length: 2 } }],
+            SpannedItem Not [Span { source: SourceId(0), span: SourceSpan { offset: SourceOffset(25), length: 3 } }],
+            SpannedItem Identifier [Span { source: SourceId(0), span: SourceSpan { offset: SourceOffset(29), length: 4 } }],
+        ]"#]],
+    )
+}
+
+#[test]
+fn type_keywords_are_whole_words() {
+    check(
+        vec!["'fn 'fnord 'sum 'summary"],
+        expect![[r#"
+            [
+                SpannedItem NewFile(SourceId(0)) [Span { source: SourceId(0), span: SourceSpan { offset: SourceOffset(0), length: 0 } }],
+                SpannedItem FunctionTypeKeyword [Span { source: SourceId(0), span: SourceSpan { offset: SourceOffset(0), length: 3 } }],
+                SpannedItem TyMarker [Span { source: SourceId(0), span: SourceSpan { offset: SourceOffset(4), length: 1 } }],
+                SpannedItem Identifier [Span { source: SourceId(0), span: SourceSpan { offset: SourceOffset(5), length: 5 } }],
+                SpannedItem SumKeyword [Span { source: SourceId(0), span: SourceSpan { offset: SourceOffset(11), length: 4 } }],
+                SpannedItem TyMarker [Span { source: SourceId(0), span: SourceSpan { offset: SourceOffset(16), length: 1 } }],
+                SpannedItem Identifier [Span { source: SourceId(0), span: SourceSpan { offset: SourceOffset(17), length: 7 } }],
+            ]"#]],
+    )
+}
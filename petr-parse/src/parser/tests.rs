@@ -289,6 +289,147 @@ fn nested_if_exp() {
     )
 }
 
+#[test]
+fn match_exp() {
+    check(
+        vec![
+            "fn to_int(s in 'Shape) returns 'int
+                match s
+                | Square side => side
+                | Rect 0 _ => 0
+                | std.Rect w h => ~std.ops.mult(w, h)
+                | 42 => 1
+                | _ => 0
+            ",
+        ],
+        expect![[r#"
+            AST
+            ____
+            module test =
+            Func to_int(
+              s ∈ 'Shape
+            ) -> 'int match var(s)
+              | variant(Square) bind(side) => var(side)
+              | variant(Rect) 0 _ => 0
+              | variant(std.Rect) bind(w) bind(h) => call std.ops.mult(var(w), var(h))
+              | 42 => 1
+              | _ => 0
+
+        "#]],
+    )
+}
+
+#[test]
+fn lambdas_and_function_types() {
+    check(
+        vec![
+            "fn compose(f in 'fn('int) returns 'int, g in 'fn('int) returns 'int) returns 'fn('int) returns 'int
+                \\(x in 'int) => ~f ~g x
+            ",
+        ],
+        expect![[r#"
+            AST
+            ____
+            module test =
+            Func compose(
+              f ∈ 'fn('int) returns 'int,
+              g ∈ 'fn('int) returns 'int
+            ) -> 'fn('int) returns 'int lambda(x ∈ 'int) => call f(call g(var(x)))
+
+        "#]],
+    )
+}
+
+#[test]
+fn records_named_args_field_access_and_update() {
+    check(
+        vec![
+            "type Point = Point x 'int y 'int
+            fn main() returns 'int
+                let p = ~Point y = 2, x = 1;
+                    q = p with x = 3, y = p.y
+                + q.x p.y
+            ",
+        ],
+        expect![[r#"
+            AST
+            ____
+            module test =
+            type Point =
+              Point(x: 'int y: 'int)Func main() -> 'int 
+              let p =     call Point(y = 2, x = 1),
+                  q = var(p) with x = 3, y = var(p).y
+            +(var(q).x var(p).y)
+
+
+
+        "#]],
+    )
+}
+
+#[test]
+fn generic_type_declarations() {
+    check(
+        vec![
+            "type Option 'A = None | Some value 'A
+            type Pair 'A 'B = Pair first 'A second 'B
+            fn nested(x in 'Option ('Option 'int), p in 'Pair 'int 'bool) returns 'Option 'int ~None
+            ",
+        ],
+        expect![[r#"
+            AST
+            ____
+            module test =
+            type Option 'A =
+              None() |
+              Some(value: 'A)type Pair 'A 'B =
+              Pair(first: 'A second: 'B)Func nested(
+              x ∈ 'Option ('Option 'int),
+              p ∈ 'Pair 'int 'bool
+            ) -> 'Option 'int call None()
+
+        "#]],
+    )
+}
+
+#[test]
+fn comparison_and_boolean_operators() {
+    check(
+        vec!["fn in_range(x in 'int) returns 'bool and >= x 10 not = x 20"],
+        expect![[r#"
+            AST
+            ____
+            module test =
+            Func in_range(
+              x ∈ 'int
+            ) -> 'bool and(>=(var(x) 10) not(=(var(x) 20)))
+
+        "#]],
+    )
+}
+
+#[test]
+fn match_exp_missing_arrow() {
+    check(
+        vec![
+            "fn to_int(s in 'Shape) returns 'int
+                match s
+                | Square side side
+            ",
+        ],
+        expect![[r#"
+            AST
+            ____
+            module test =
+
+
+
+            Errors
+            ____
+            SpannedItem ParseError { kind: ExpectedToken(FatArrow, Eof), help: Some("while parsing function declaration\n  ↪ while parsing expression\n    ↪ while parsing match expression\n      ↪ while parsing match arm\n        ↪ expected while parsing token =>") } [Span { source: SourceId(0), span: SourceSpan { offset: SourceOffset(107), length: 0 } }]"#]],
+    )
+}
+
 #[test]
 fn if_without_else() {
     check(
@@ -347,3 +488,257 @@ fn sum_types() {
         "#]],
     )
 }
+
+#[test]
+fn recovers_at_next_function_declaration() {
+    check(
+        vec![
+            r#"
+            fn broken(a in 'int returns 'int a
+            fn fine() returns 'int 5
+            "#,
+        ],
+        expect![[r#"
+            AST
+            ____
+            module test =
+            Func fine() -> 'int 5
+
+
+
+            Errors
+            ____
+            SpannedItem ParseError { kind: ExpectedToken(CloseParen, ReturnsKeyword), help: Some("while parsing function declaration\n  ↪ expected while parsing token )") } [Span { source: SourceId(0), span: SourceSpan { offset: SourceOffset(33), length: 7 } }]"#]],
+    )
+}
+
+#[test]
+fn reports_every_independent_error() {
+    check(
+        vec![
+            r#"
+            fn first() returns 'int 1
+            type = A | B
+            fn second() returns 2
+            import std.io
+            ) fn third() returns 'int 3
+            "#,
+        ],
+        expect![[r#"
+            AST
+            ____
+            module test =
+            Func first() -> 'int 1
+            import std.io
+            Func third() -> 'int 3
+
+
+
+            Errors
+            ____
+            SpannedItem ParseError { kind: ExpectedIdentifier("="), help: Some("expected type declaration") } [Span { source: SourceId(0), span: SourceSpan { offset: SourceOffset(56), length: 1 } }]
+            SpannedItem ParseError { kind: ExpectedOneOf([Identifier, OpenBracket, Tilde, True, False, String, Integer, Float, Intrinsic, Let, Backslash, OpenParen, Underscore, Hole], Import), help: Some("while parsing function declaration\n  ↪ expected expression") } [Span { source: SourceId(0), span: SourceSpan { offset: SourceOffset(110), length: 6 } }]
+            SpannedItem ParseError { kind: ExpectedOneOf([FunctionKeyword, TypeKeyword, InterfaceKeyword, ImplementKeyword, Import, Eof], CloseParen), help: None } [Span { source: SourceId(0), span: SourceSpan { offset: SourceOffset(136), length: 1 } }]"#]],
+    )
+}
+
+#[test]
+fn recovery_does_not_cross_files() {
+    check(
+        vec!["fn broken( returns 'int 1", "fn fine() returns 'int 2"],
+        expect![[r#"
+            AST
+            ____
+            module test =
+            module test =
+            Func fine() -> 'int 2
+
+
+
+            Errors
+            ____
+            SpannedItem ParseError { kind: ExpectedIdentifier("returns"), help: Some("while parsing function declaration\n  ↪ expected function parameter") } [Span { source: SourceId(0), span: SourceSpan { offset: SourceOffset(11), length: 7 } }]"#]],
+    )
+}
+
+#[test]
+fn lexer_errors_do_not_stop_parsing() {
+    check(
+        vec!["fn one() returns 'int $ 1\nfn two() returns 'int 2"],
+        expect![[r#"
+            AST
+            ____
+            module test =
+            Func one() -> 'int 1
+            Func two() -> 'int 2
+
+
+
+            Errors
+            ____
+            SpannedItem ParseError { kind: LexerError, help: Some("expected function declaration") } [Span { source: SourceId(0), span: SourceSpan { offset: SourceOffset(22), length: 1 } }]"#]],
+    )
+}
+
+#[test]
+fn concrete_syntax_trees_round_trip_every_source() {
+    let sources = ["fn a() returns 'int 1 {- trailing -}\n", "\n\ttype B = C\r\n"];
+    let parser = Parser::new(sources.iter().map(|s| ("test", *s)));
+    let csts = parser.concrete_syntax_trees();
+    let round_tripped = csts.iter().map(|cst| cst.to_string()).collect::<Vec<_>>();
+    assert_eq!(round_tripped, sources);
+}
+
+#[test]
+fn interfaces_implementations_and_where_clauses() {
+    check(
+        vec![
+            "export interface Size 'A with size(x in 'A) returns 'int, empty() returns 'A
+implement Size for 'bool with size = bool_size, empty = no
+fn measure(x in 'A) returns 'int where 'A: Size, 'A: std.ops.Add ~size x",
+        ],
+        expect![[r#"
+            AST
+            ____
+            module test =
+            exported interface Size 'A with
+              size(x ∈ 'A) -> 'int,
+              empty() -> 'A
+            implement Size for 'bool with size = bool_size, empty = no
+            Func measure(
+              x ∈ 'A
+            ) -> 'int where 'A: Size, 'A: std.ops.Add call size(var(x))
+
+        "#]],
+    )
+}
+
+#[test]
+fn effect_annotations() {
+    check(
+        vec![
+            "fn greet() returns 'unit uses io, alloc ~print \"hi\"
+fn add(a in 'int, b in 'int) returns 'int pure + a b",
+        ],
+        expect![[r#"
+            AST
+            ____
+            module test =
+            Func greet() -> 'unit uses io, alloc call print("hi")
+            Func add(
+              a ∈ 'int,
+              b ∈ 'int
+            ) -> 'int pure +(var(a) var(b))
+
+        "#]],
+    )
+}
+
+#[test]
+fn float_literals() {
+    check(
+        vec![
+            "fn half(x in 'float) returns 'float * x 0.5
+fn two() returns 'float 2.0",
+        ],
+        expect![[r#"
+            AST
+            ____
+            module test =
+            Func half(
+              x ∈ 'float
+            ) -> 'float *(var(x) 0.5)
+            Func two() -> 'float 2.0
+
+        "#]],
+    )
+}
+
+#[test]
+fn tuples() {
+    check(
+        vec![
+            "fn pair() returns '(int, 'Option 'string) (1, ~none)
+fn first() returns 'int
+  let (a, b) = ~pair();
+      c = (a)
+  c",
+        ],
+        expect![[r#"
+            AST
+            ____
+            module test =
+            Func pair() -> '(int, 'Option 'string) (1, call none())
+            Func first() -> 'int 
+              let (a, b) =     call pair(),
+                  c = var(a)
+            var(c)
+
+
+
+        "#]],
+    )
+}
+
+#[test]
+fn nested_tuple_patterns_are_not_supported() {
+    check(
+        vec![
+            "fn first() returns 'int
+  let (a, (b, c)) = (1, (2, 3))
+  a",
+        ],
+        expect![[r#"
+            AST
+            ____
+            module test =
+
+
+
+            Errors
+            ____
+            SpannedItem ParseError { kind: NestedTuplePattern, help: Some("while parsing function declaration\n  ↪ while parsing expression\n    ↪ expected let binding") } [Span { source: SourceId(0), span: SourceSpan { offset: SourceOffset(34), length: 1 } }]"#]],
+    )
+}
+
+#[test]
+fn type_aliases() {
+    check(
+        vec![
+            "type alias Id = 'int
+Type alias Small = 'sum 1 | 2 | 3
+type alias Pair = '(Id, Small)
+type Point = Point x 'Id y 'Id",
+        ],
+        expect![[r#"
+            AST
+            ____
+            module test =
+            type alias Id = 'int
+            exported type alias Small = ''lit ty 1 | 'lit ty 2 | 'lit ty 3
+            type alias Pair = '(Id, Small)
+            type Point =
+              Point(x: 'Id y: 'Id)
+        "#]],
+    )
+}
+
+#[test]
+fn holes() {
+    check(
+        vec![
+            "fn foo(a in 'int) returns 'int ~bar(a, ?rest, _)
+fn baz() returns 'int _",
+        ],
+        expect![[r#"
+            AST
+            ____
+            module test =
+            Func foo(
+              a ∈ 'int
+            ) -> 'int call bar(var(a), hole(rest), hole)
+            Func baz() -> 'int hole
+
+        "#]],
+    )
+}
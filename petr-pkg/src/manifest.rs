@@ -11,6 +11,28 @@ pub struct Manifest {
     pub formatter:    FormatterConfigManifestFormat,
     #[serde(default)]
     pub dependencies: BTreeMap<String, Dependency>,
+    /// How the warnings of each lint are reported, by the name of the lint, like
+    /// `unreachable_match_arm = "deny"`
+    #[serde(default)]
+    pub lints:        BTreeMap<String, LintLevelManifestFormat>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+pub enum LintLevelManifestFormat {
+    Allow,
+    Warn,
+    Deny,
+}
+
+impl From<LintLevelManifestFormat> for petr_utils::LintLevel {
+    fn from(value: LintLevelManifestFormat) -> Self {
+        match value {
+            LintLevelManifestFormat::Allow => petr_utils::LintLevel::Allow,
+            LintLevelManifestFormat::Warn => petr_utils::LintLevel::Warn,
+            LintLevelManifestFormat::Deny => petr_utils::LintLevel::Deny,
+        }
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, Default)]
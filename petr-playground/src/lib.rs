@@ -2,7 +2,7 @@
 //! Nothing fancy at all, could definitely be improved over time to support better error reporting,
 //! etc
 
-use petr_api::{render_error, resolve_symbols, type_check, Formattable, FormatterContext, Lowerer, Parser, Vm};
+use petr_api::{render_error, resolve_symbols, type_check, Diagnostics, Formattable, FormatterContext, Lowerer, Parser, Vm};
 use wasm_bindgen::prelude::*;
 
 #[cfg(test)]
@@ -37,7 +37,8 @@ fn run_snippet_inner<F>(
         },
     };
 
-    let (data, instructions) = lowerer.finalize();
+    // the playground only shows errors, so warnings are dropped
+    let (data, instructions) = lowerer.finalize(&mut Diagnostics::default());
 
     let vm = Vm::new(instructions, data);
     let (result, _stack, logs) = match vm.run() {
@@ -1,12 +1,15 @@
 //! given bindings, fully resolve an AST
 //! This crate's job is to tee up the type checker for the next stage of compilation.
 
-pub use petr_ast::{Intrinsic as IntrinsicName, Literal, Ty};
+pub use petr_ast::{Float, Intrinsic as IntrinsicName, Literal, Ty};
 pub use petr_bind::Dependency;
 use petr_utils::{SpannedItem, SymbolInterner};
 pub use resolved::QueryableResolvedItems;
 use resolver::Resolver;
-pub use resolver::{Expr, ExprKind, Function, FunctionCall, Intrinsic, ResolutionError, Type};
+pub use resolver::{
+    BindingPattern, Expr, ExprKind, FieldPattern, Function, FunctionCall, Implementation, Interface, Intrinsic, MatchArm, Pattern, ResolutionError,
+    Type,
+};
 
 mod resolved;
 mod resolver;
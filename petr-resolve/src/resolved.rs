@@ -1,17 +1,19 @@
 use std::collections::BTreeMap;
 
 use petr_bind::FunctionId;
-use petr_utils::{SymbolInterner, TypeId};
+use petr_utils::{InterfaceId, SymbolInterner, TypeId};
 
-use crate::resolver::{Function, TypeDeclaration};
+use crate::resolver::{Function, Implementation, Interface, TypeDeclaration};
 /// Contains things that have already been resolved.
 /// Resolved items cannot be queried during resolution. This is because the resolution
 /// stage should only query the binder, then the type checking stage can query
 /// the [QueryableResolved] to get the resolved items -- [QueryableResolvedItems] is the
 /// immutable result of resolution, and resolved items can no longer be mutated.
 pub(crate) struct ResolvedItems {
-    pub resolved_functions: BTreeMap<FunctionId, Function>,
-    pub resolved_types:     BTreeMap<TypeId, TypeDeclaration>,
+    pub resolved_functions:  BTreeMap<FunctionId, Function>,
+    pub resolved_types:      BTreeMap<TypeId, TypeDeclaration>,
+    pub resolved_interfaces: BTreeMap<InterfaceId, Interface>,
+    pub implementations:     Vec<Implementation>,
 }
 
 impl ResolvedItems {
@@ -31,29 +33,45 @@ impl ResolvedItems {
         self.resolved_types.insert(id, type_decl);
     }
 
+    pub fn insert_interface(
+        &mut self,
+        id: InterfaceId,
+        interface: Interface,
+    ) {
+        self.resolved_interfaces.insert(id, interface);
+    }
+
     pub(crate) fn new() -> Self {
         Self {
-            resolved_functions: Default::default(),
-            resolved_types:     Default::default(),
+            resolved_functions:  Default::default(),
+            resolved_types:      Default::default(),
+            resolved_interfaces: Default::default(),
+            implementations:     Default::default(),
         }
     }
 }
 
 pub struct QueryableResolvedItems {
-    resolved_functions: BTreeMap<FunctionId, Function>,
-    resolved_types:     BTreeMap<TypeId, TypeDeclaration>,
-    pub interner:       SymbolInterner,
+    resolved_functions:  BTreeMap<FunctionId, Function>,
+    resolved_types:      BTreeMap<TypeId, TypeDeclaration>,
+    resolved_interfaces: BTreeMap<InterfaceId, Interface>,
+    implementations:     Vec<Implementation>,
+    pub interner:        SymbolInterner,
 }
 
 impl QueryableResolvedItems {
     pub fn new(
         resolved_functions: BTreeMap<FunctionId, Function>,
         resolved_types: BTreeMap<TypeId, TypeDeclaration>,
+        resolved_interfaces: BTreeMap<InterfaceId, Interface>,
+        implementations: Vec<Implementation>,
         interner: SymbolInterner,
     ) -> Self {
         Self {
             resolved_functions,
             resolved_types,
+            resolved_interfaces,
+            implementations,
             interner,
         }
     }
@@ -74,6 +92,20 @@ impl QueryableResolvedItems {
         self.resolved_types.get(&id).expect("type IDs should always correspond to resolved types")
     }
 
+    pub fn get_interface(
+        &self,
+        id: InterfaceId,
+    ) -> &Interface {
+        self.resolved_interfaces
+            .get(&id)
+            .expect("interface IDs should always correspond to resolved interfaces")
+    }
+
+    /// All of the implementations of interfaces
+    pub fn implementations(&self) -> &[Implementation] {
+        &self.implementations
+    }
+
     // TODO  The cloning of the below iterators (`functions` and `types`) is not ideal.
     pub fn functions(&self) -> impl Iterator<Item = (FunctionId, Function)> {
         self.resolved_functions
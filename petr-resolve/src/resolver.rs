@@ -1,36 +1,89 @@
 // TODO:
 // use a push_error API which sets more diagnostic fields, similar to how the parser does it
 
-use std::rc::Rc;
+use std::{collections::BTreeMap, rc::Rc};
 
 use miette::Diagnostic;
-use petr_ast::{Ast, Commented, Expression, FunctionDeclaration, FunctionParameter, OperatorExpression};
-use petr_bind::{Binder, Dependency, FunctionId, Item, ScopeId};
-use petr_utils::{Identifier, Path, Span, SpannedItem, SymbolInterner, TypeId};
+use petr_ast::{Ast, Commented, Expression, FunctionDeclaration, FunctionParameter, ImplementationDeclaration, OperatorExpression};
+use petr_bind::{Binder, Dependency, FunctionId, Item, ScopeId, TypeAliasId};
+use petr_utils::{Identifier, IntegerType, InterfaceId, Path, Span, SpannedItem, SymbolInterner, TypeId};
 use thiserror::Error;
 
 use crate::resolved::{QueryableResolvedItems, ResolvedItems};
 #[derive(Debug, Error, Diagnostic)]
 pub enum ResolutionError {
     #[error("Function parameter not found: {0}")]
+    #[diagnostic(code(E0201))]
     FunctionParameterNotFound(String),
     #[error("Symbol not found: {0}")]
+    #[diagnostic(code(E0202))]
     NotFound(String),
     #[error("Could not find implementation for operator: {0} at {1}")]
+    #[diagnostic(code(E0203))]
     OperatorImplementationNotFound(String, String),
     #[error("This item is not a valid member of a path. Valid members are modules, functions, or types.")]
+    #[diagnostic(code(E0204))]
     ItemIsNotValidPath,
+    #[error("Expected a type variant in pattern, but {0} is not a variant of any type")]
+    #[diagnostic(code(E0205))]
+    NotAVariant(String),
+    #[error("Variant {variant} has {expected} fields, but this pattern has {got}")]
+    #[diagnostic(code(E0206))]
+    PatternFieldCountMismatch { variant: String, expected: usize, got: usize },
+    #[error("Expected a value, but {0} is a type")]
+    #[diagnostic(code(E0207))]
+    TypeUsedAsValue(String),
+    #[error("Function {function} has no parameter named {name}")]
+    #[diagnostic(code(E0208))]
+    UnknownArgumentName { function: String, name: String },
+    #[error("Argument {0} was passed more than once")]
+    #[diagnostic(code(E0209))]
+    DuplicateArgument(String),
+    #[error("Missing argument {name} in call to {function}")]
+    #[diagnostic(code(E0210))]
+    MissingArgument { function: String, name: String },
+    #[error("Arguments can't be passed by name when calling a function value")]
+    #[diagnostic(code(E0211))]
+    NamedArgumentsToFunctionValue,
+    #[error("Field {0} was updated more than once")]
+    #[diagnostic(code(E0212))]
+    DuplicateField(String),
+    #[error("Type {ty} takes {expected} type arguments, but {got} were provided")]
+    #[diagnostic(code(E0213))]
+    TypeArgumentCountMismatch { ty: String, expected: usize, got: usize },
+    #[error("Expected an interface, but {0} is not one")]
+    #[diagnostic(code(E0214))]
+    NotAnInterface(String),
+    #[error("Implementation of {interface} is missing method {method}")]
+    #[diagnostic(code(E0215))]
+    MissingMethod { interface: String, method: String },
+    #[error("Interface {interface} has no method named {method}")]
+    #[diagnostic(code(E0216))]
+    UnknownMethod { interface: String, method: String },
+    #[error("Type alias {0} refers to itself")]
+    #[diagnostic(
+        code(E0217),
+        help("an alias is only another name for a type; declare a new type with `type` for it to be able to contain itself")
+    )]
+    CyclicTypeAlias(String),
+    #[error("Type {ty} has no type parameter {param}")]
+    #[diagnostic(code(E0218), help("a type's parameters are declared after its name, like `type {ty} '{param} = ...`"))]
+    UndeclaredTypeParameter { ty: String, param: String },
 }
 
 pub(crate) struct Resolver {
     pub resolved: ResolvedItems,
     pub interner: SymbolInterner,
     pub errs:     Vec<SpannedItem<ResolutionError>>,
+    /// The types that type aliases stand for. An alias whose type is still being resolved doesn't
+    /// have one yet, which is how aliases that refer to themselves are found.
+    type_aliases: BTreeMap<TypeAliasId, Option<Type>>,
 }
 
 #[derive(Debug, Clone)]
 pub struct TypeDeclaration {
     pub name: Identifier,
+    pub type_params: Box<[Identifier]>,
     pub variants: Box<[TypeVariant]>,
     pub constant_literal_types: Vec<petr_ast::Literal>,
 }
@@ -47,18 +100,77 @@ pub struct TypeField {
     pub ty:   Type,
 }
 
+#[derive(Debug, Clone)]
+pub struct Interface {
+    pub name:       Identifier,
+    pub type_param: Identifier,
+    /// The functions which call each method of the interface, in the order they are declared
+    pub methods:    Box<[FunctionId]>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Implementation {
+    pub interface: InterfaceId,
+    pub ty:        Type,
+    /// The functions which implement each method of the interface, in the order the interface
+    /// declares them
+    pub methods:   Box<[FunctionId]>,
+    pub span:      Span,
+}
+
 #[derive(Clone, Debug)]
 pub enum Type {
     Integer,
+    /// A fixed-width integer type, like `'u8`
+    SizedInteger(IntegerType),
+    Float,
     Bool,
     Unit,
     String,
     // like `Unit`, but doesn't throw additional type errors to prevent cascading errors.
     ErrorRecovery(Span),
     Named(TypeId),
+    /// A generic type applied to type arguments. A generic type which is [`Type::Named`] without
+    /// any arguments has its type arguments inferred instead.
+    Applied(TypeId, Box<[Type]>),
     Generic(Identifier),
     Sum(Box<[Type]>),
     Literal(petr_ast::Literal),
+    /// The type of a function value: its parameter types and its return type
+    Arrow(Box<[Type]>, Box<Type>),
+    /// An anonymous tuple type, like `'(int, string)`
+    Tuple(Box<[Type]>),
+    /// A type alias, like `'Id`, and the type that it stands for. The alias is kept so that type
+    /// errors can refer to the type by the name it was written with.
+    Alias(Identifier, Box<Type>),
+}
+
+impl Type {
+    /// Collects the type parameters that this type refers to into `params`. The types that aliases
+    /// stand for are left out, since their type parameters belong to the alias.
+    fn type_params(
+        &self,
+        params: &mut Vec<Identifier>,
+    ) {
+        match self {
+            Type::Generic(name) => params.push(*name),
+            Type::Applied(_, tys) | Type::Sum(tys) | Type::Tuple(tys) => tys.iter().for_each(|ty| ty.type_params(params)),
+            Type::Arrow(param_tys, return_ty) => {
+                param_tys.iter().for_each(|ty| ty.type_params(params));
+                return_ty.type_params(params);
+            },
+            Type::Integer
+            | Type::SizedInteger(_)
+            | Type::Float
+            | Type::Bool
+            | Type::Unit
+            | Type::String
+            | Type::ErrorRecovery(_)
+            | Type::Named(_)
+            | Type::Literal(_)
+            | Type::Alias(..) => (),
+        }
+    }
 }
 
 impl Resolve for petr_ast::Ty {
@@ -66,27 +178,87 @@ impl Resolve for petr_ast::Ty {
 
     fn resolve(
         &self,
-        _resolver: &mut Resolver,
+        resolver: &mut Resolver,
         binder: &Binder,
         scope_id: ScopeId,
     ) -> Option<Type> {
         Some(match self {
             petr_ast::Ty::Int => Type::Integer,
+            petr_ast::Ty::SizedInt(ty) => Type::SizedInteger(*ty),
+            petr_ast::Ty::Float => Type::Float,
             petr_ast::Ty::Bool => Type::Bool,
             petr_ast::Ty::String => Type::String,
             petr_ast::Ty::Unit => Type::Unit,
             petr_ast::Ty::Named(name) => match binder.find_type_in_scope(name.id, scope_id) {
+                Some(id) if !binder.get_type(id).type_params.is_empty() => {
+                    let ty = resolver.interner.get(name.id).to_string();
+                    resolver.errs.push(name.span.with_item(ResolutionError::TypeArgumentCountMismatch {
+                        ty,
+                        expected: binder.get_type(id).type_params.len(),
+                        got: 0,
+                    }));
+                    return Some(Type::ErrorRecovery(name.span));
+                },
                 Some(id) => Type::Named(id),
-                None => Type::Generic(*name),
+                None => match binder.find_type_alias_in_scope(name.id, scope_id) {
+                    Some(id) => resolver.resolve_type_alias(binder, id, *name),
+                    None => Type::Generic(*name),
+                },
+            },
+            petr_ast::Ty::Applied(name, args) => {
+                if binder.find_type_in_scope(name.id, scope_id).is_none() && binder.find_type_alias_in_scope(name.id, scope_id).is_some() {
+                    let ty = resolver.interner.get(name.id).to_string();
+                    resolver.errs.push(name.span.with_item(ResolutionError::TypeArgumentCountMismatch {
+                        ty,
+                        expected: 0,
+                        got: args.len(),
+                    }));
+                    return Some(Type::ErrorRecovery(name.span));
+                }
+                let Some(id) = binder.find_type_in_scope(name.id, scope_id) else {
+                    let name_str = resolver.interner.get(name.id).to_string();
+                    resolver.errs.push(name.span.with_item(ResolutionError::NotFound(name_str)));
+                    return Some(Type::ErrorRecovery(name.span));
+                };
+                let expected = binder.get_type(id).type_params.len();
+                if expected != args.len() {
+                    let ty = resolver.interner.get(name.id).to_string();
+                    resolver.errs.push(name.span.with_item(ResolutionError::TypeArgumentCountMismatch {
+                        ty,
+                        expected,
+                        got: args.len(),
+                    }));
+                    return Some(Type::ErrorRecovery(name.span));
+                }
+                let args = args
+                    .iter()
+                    .map(|x| x.resolve(resolver, binder, scope_id).unwrap_or(Type::Unit))
+                    .collect::<Vec<_>>();
+                Type::Applied(id, args.into_boxed_slice())
             },
             petr_ast::Ty::Literal(l) => Type::Literal(l.clone()),
             petr_ast::Ty::Sum(tys) => {
                 let tys = tys
                     .iter()
-                    .map(|x| x.resolve(_resolver, binder, scope_id).unwrap_or(Type::Unit))
+                    .map(|x| x.resolve(resolver, binder, scope_id).unwrap_or(Type::Unit))
                     .collect::<Vec<_>>();
                 Type::Sum(tys.into_boxed_slice())
             },
+            petr_ast::Ty::Tuple(tys) => {
+                let tys = tys
+                    .iter()
+                    .map(|x| x.resolve(resolver, binder, scope_id).unwrap_or(Type::Unit))
+                    .collect::<Vec<_>>();
+                Type::Tuple(tys.into_boxed_slice())
+            },
+            petr_ast::Ty::Arrow(params, return_ty) => {
+                let params = params
+                    .iter()
+                    .map(|x| x.resolve(resolver, binder, scope_id).unwrap_or(Type::Unit))
+                    .collect::<Vec<_>>();
+                let return_ty = return_ty.resolve(resolver, binder, scope_id).unwrap_or(Type::Unit);
+                Type::Arrow(params.into_boxed_slice(), Box::new(return_ty))
+            },
         })
     }
 }
@@ -109,6 +281,10 @@ pub struct Function {
     pub name:        Identifier,
     pub params:      Vec<(Identifier, Type)>,
     pub return_type: Type,
+    /// The interfaces that the function's type parameters must implement
+    pub constraints: Vec<(Identifier, InterfaceId)>,
+    /// The effects the function is declared to have, if it has an effect annotation
+    pub effects:     Option<Vec<Identifier>>,
     pub body:        Expr,
 }
 
@@ -145,8 +321,14 @@ pub enum ExprKind {
     },
     Intrinsic(Intrinsic),
     Unit,
-    // the `id` is the id of the type declaration that defined this constructor
-    TypeConstructor(TypeId, Box<[Expr]>),
+    /// An anonymous tuple, like `(1, "one")`
+    Tuple(Box<[Expr]>),
+    // the `id` is the id of the type declaration that defined this constructor, and the `usize`
+    // is the index of the variant being constructed
+    TypeConstructor(TypeId, usize, Box<[Expr]>),
+    /// The body of an interface method. The `usize` is the index of the method within the
+    /// interface, and the implementation it calls depends on the type it is called with.
+    InterfaceMethod(InterfaceId, usize),
     ErrorRecovery,
     ExpressionWithBindings {
         bindings:   Vec<Binding>,
@@ -157,11 +339,65 @@ pub enum ExprKind {
         then_branch: Box<Expr>,
         else_branch: Box<Expr>,
     },
+    Match {
+        scrutinee: Box<Expr>,
+        arms:      Vec<MatchArm>,
+    },
+    /// A named function used as a value, rather than being called directly
+    FunctionValue(FunctionId),
+    Lambda {
+        params:   Vec<(Identifier, Type)>,
+        /// the locals from enclosing scopes that the body references
+        captures: Vec<Identifier>,
+        body:     Box<Expr>,
+    },
+    /// A call through a local binding or parameter that holds a function value
+    ClosureCall {
+        callee: Box<Expr>,
+        args:   Vec<Expr>,
+    },
+    FieldAccess {
+        target: Box<Expr>,
+        field:  Identifier,
+    },
+    /// A copy of `target` with the given fields replaced
+    RecordUpdate {
+        target: Box<Expr>,
+        fields: Vec<(Identifier, Expr)>,
+    },
+    /// An expression that hasn't been written yet, with its name if it has one
+    Hole {
+        name:   Option<Identifier>,
+        /// the local bindings and parameters that are in scope where the hole is
+        locals: Vec<Identifier>,
+    },
+}
+
+#[derive(Clone, Debug)]
+pub struct MatchArm {
+    pub pattern: SpannedItem<Pattern>,
+    pub body:    Expr,
 }
 
+#[derive(Clone, Debug)]
+pub enum Pattern {
+    Wildcard,
+    Literal(petr_ast::Literal),
+    Variant {
+        /// the type that this variant belongs to
+        ty:      TypeId,
+        /// the index of the variant within the type. The constant literal variants of a type
+        /// are all represented by the index after its last named variant.
+        variant: usize,
+        fields:  Box<[SpannedItem<FieldPattern>]>,
+    },
+}
+
+pub use petr_ast::{BindingPattern, FieldPattern};
+
 #[derive(Clone, Debug)]
 pub struct Binding {
-    pub name:       Identifier,
+    pub pattern:    BindingPattern,
     pub expression: Expr,
 }
 
@@ -182,6 +418,7 @@ impl Resolver {
             errs: Vec::new(),
             resolved: ResolvedItems::new(),
             interner,
+            type_aliases: BTreeMap::new(),
         };
         resolver.add_package(&binder);
         resolver
@@ -197,6 +434,7 @@ impl Resolver {
             errs: Vec::new(),
             resolved: ResolvedItems::new(),
             interner,
+            type_aliases: BTreeMap::new(),
         };
         resolver.add_package(&binder);
         resolver
@@ -213,6 +451,9 @@ impl Resolver {
                 self.resolve_item(item.item(), binder, scope_id)
             }
         }
+        for (implementation, scope_id) in binder.implementations() {
+            self.resolve_implementation(binder, implementation, *scope_id);
+        }
     }
 
     fn resolve_item(
@@ -225,6 +466,13 @@ impl Resolver {
         match item {
             Function(func, func_scope) => self.resolve_function(binder, *func, *func_scope),
             Type(ty) => self.resolve_type(binder, *ty, scope_id),
+            // aliases are expanded wherever they're used, but resolving them here reports errors
+            // in aliases that are never used
+            TypeAlias(id) => {
+                let name = binder.get_type_alias(*id).0.item().name;
+                let _ = self.resolve_type_alias(binder, *id, name);
+            },
+            Interface(id) => self.resolve_interface(binder, *id),
             FunctionParameter(_ty) => {
                 // I don't think we have to do anything here but not sure
             },
@@ -307,6 +555,104 @@ impl Resolver {
         self.resolved.insert_type(ty, resolved);
     }
 
+    /// The type that the alias stands for, referred to by `name`
+    fn resolve_type_alias(
+        &mut self,
+        binder: &Binder,
+        id: TypeAliasId,
+        name: Identifier,
+    ) -> Type {
+        let ty = match self.type_aliases.get(&id) {
+            Some(Some(ty)) => ty.clone(),
+            Some(None) => {
+                let alias_name = self.interner.get(name.id).to_string();
+                self.errs.push(name.span.with_item(ResolutionError::CyclicTypeAlias(alias_name)));
+                Type::ErrorRecovery(name.span)
+            },
+            None => {
+                self.type_aliases.insert(id, None);
+                let (alias, scope_id) = binder.get_type_alias(id);
+                let ty = alias.item().ty.resolve(self, binder, *scope_id).unwrap_or(Type::ErrorRecovery(name.span));
+                self.type_aliases.insert(id, Some(ty.clone()));
+                ty
+            },
+        };
+        Type::Alias(name, Box::new(ty))
+    }
+
+    fn resolve_interface(
+        &mut self,
+        binder: &Binder,
+        id: InterfaceId,
+    ) {
+        let interface = binder.get_interface(id);
+        self.resolved.insert_interface(
+            id,
+            Interface {
+                name:       interface.declaration.name,
+                type_param: interface.declaration.type_param,
+                methods:    interface.methods.clone(),
+            },
+        );
+    }
+
+    /// Resolves the interface and the type being implemented, and pairs each of the interface's
+    /// methods with the function that implements it.
+    fn resolve_implementation(
+        &mut self,
+        binder: &Binder,
+        implementation: &SpannedItem<ImplementationDeclaration>,
+        scope_id: ScopeId,
+    ) {
+        let decl = implementation.item();
+        let Some(interface_id) = resolve_interface_path(&decl.interface, self, binder, scope_id) else {
+            return;
+        };
+        let interface = &binder.get_interface(interface_id).declaration;
+        let interface_name = self.interner.get(interface.name.id).to_string();
+        let ty = decl
+            .ty
+            .resolve(self, binder, scope_id)
+            .unwrap_or(Type::ErrorRecovery(implementation.span()));
+
+        for (name, _) in decl.methods.iter() {
+            if !interface.methods.iter().any(|method| method.name.id == name.id) {
+                self.errs.push(name.span.with_item(ResolutionError::UnknownMethod {
+                    interface: interface_name.clone(),
+                    method:    self.interner.get(name.id).to_string(),
+                }));
+            }
+        }
+
+        let mut methods = Vec::with_capacity(interface.methods.len());
+        for method in interface.methods.iter() {
+            let Some((name, path)) = decl.methods.iter().find(|(name, _)| name.id == method.name.id) else {
+                self.errs.push(implementation.span().with_item(ResolutionError::MissingMethod {
+                    interface: interface_name.clone(),
+                    method:    self.interner.get(method.name.id).to_string(),
+                }));
+                continue;
+            };
+            match path.resolve(self, binder, scope_id) {
+                Some(either::Left(function)) => methods.push(function),
+                Some(either::Right(_ty)) => {
+                    let name = self.interner.get(name.id).to_string();
+                    self.errs.push(name_span(path).with_item(ResolutionError::TypeUsedAsValue(name)));
+                },
+                None => (),
+            }
+        }
+
+        if methods.len() == interface.methods.len() {
+            self.resolved.implementations.push(Implementation {
+                interface: interface_id,
+                ty,
+                methods: methods.into_boxed_slice(),
+                span: implementation.span(),
+            });
+        }
+    }
+
     fn resolve_function(
         &mut self,
         binder: &Binder,
@@ -328,7 +674,13 @@ impl Resolver {
     pub fn into_queryable(self) -> (Vec<SpannedItem<ResolutionError>>, QueryableResolvedItems) {
         (
             self.errs,
-            QueryableResolvedItems::new(self.resolved.resolved_functions, self.resolved.resolved_types, self.interner),
+            QueryableResolvedItems::new(
+                self.resolved.resolved_functions,
+                self.resolved.resolved_types,
+                self.resolved.resolved_interfaces,
+                self.resolved.implementations,
+                self.interner,
+            ),
         )
     }
 }
@@ -368,6 +720,16 @@ impl Resolve for SpannedItem<FunctionDeclaration> {
 
         let return_type = self.item().return_type.resolve(resolver, binder, scope_id).unwrap_or(Type::Unit);
 
+        let constraints = self
+            .item()
+            .constraints
+            .iter()
+            .filter_map(|constraint| {
+                let interface = resolve_interface_path(&constraint.interface, resolver, binder, scope_id)?;
+                Some((constraint.type_param, interface))
+            })
+            .collect();
+
         let body = match self.item().body.resolve(resolver, binder, scope_id) {
             Some(x) => x,
             // need to use an error recovery func here, so the FunctionId still exists in the
@@ -381,6 +743,8 @@ impl Resolve for SpannedItem<FunctionDeclaration> {
             name: self.item().name,
             params: params_buf,
             return_type,
+            constraints,
+            effects: self.item().effects.as_ref().map(|effects| effects.to_vec()),
             body,
         })
     }
@@ -409,46 +773,123 @@ impl Resolve for SpannedItem<Expression> {
                 // TODO: do list combination type if list of unit, which functions like a block
                 Expr::new(ExprKind::List(list.into_boxed_slice()), self.span())
             },
+            Expression::Tuple(elements) => {
+                let elements = elements
+                    .iter()
+                    .map(|x| x.resolve(resolver, binder, scope_id).unwrap_or_else(|| Expr::error_recovery(x.span())))
+                    .collect::<Vec<_>>();
+                Expr::new(ExprKind::Tuple(elements.into_boxed_slice()), self.span())
+            },
+            Expression::Hole(name) => {
+                // bindings which are declared later in the same scope aren't visible yet
+                let locals = binder
+                    .locals_in_scope(scope_id)
+                    .into_iter()
+                    .filter(|local| local.span < self.span())
+                    .collect();
+                Expr::new(ExprKind::Hole { name: *name, locals }, self.span())
+            },
             Expression::Operator(op) => {
                 let OperatorExpression { lhs, rhs, op } = *op.clone();
-                // resolves to a call to stdlib
                 use petr_ast::Operator::*;
+                // `and` and `or` short-circuit, so they can't be calls, which would evaluate both
+                // operands before the call is made.
+                let short_circuiting = match op.item() {
+                    And => Some(petr_ast::Intrinsic::And),
+                    Or => Some(petr_ast::Intrinsic::Or),
+                    _ => None,
+                };
+                if let Some(intrinsic) = short_circuiting {
+                    let args = Box::new([lhs.resolve(resolver, binder, scope_id)?, rhs.resolve(resolver, binder, scope_id)?]);
+                    return Some(Expr::new(ExprKind::Intrinsic(Intrinsic { intrinsic, args }), self.span()));
+                }
+
+                // arithmetic and comparisons resolve to a call to the method of the interface in
+                // `std.ops` which implements them, so they work for any type which implements that
+                // interface
+                let interface = match op.item() {
+                    Plus => Some("Add"),
+                    Minus => Some("Sub"),
+                    Star => Some("Mul"),
+                    Slash => Some("Div"),
+                    LessThan => Some("Lt"),
+                    GreaterThan => Some("Gt"),
+                    LessThanOrEq => Some("Lte"),
+                    GreaterThanOrEq => Some("Gte"),
+                    _ => None,
+                };
+                if let Some(interface) = interface {
+                    let function = resolve_operator_interface(interface, self.span(), resolver, binder, scope_id)?;
+                    let call = FunctionCall {
+                        function,
+                        args: vec![lhs.resolve(resolver, binder, scope_id)?, rhs.resolve(resolver, binder, scope_id)?],
+                        span: self.span(),
+                    };
+                    return Some(Expr::new(ExprKind::FunctionCall(call), self.span()));
+                }
+
+                // the rest resolve to a call to stdlib
                 let func = match op.item() {
-                    Plus => "add",
-                    Minus => "sub",
-                    Star => "mul",
-                    Slash => "div",
                     Eq => "eq",
+                    NotEq => "neq",
+                    And | Or => unreachable!("short-circuiting operators are resolved to intrinsics above"),
+                    Plus | Minus | Star | Slash | LessThan | GreaterThan | LessThanOrEq | GreaterThanOrEq => {
+                        unreachable!("arithmetic and comparison operators are resolved to interface methods above")
+                    },
                 };
-                let path = ["std", "ops", func];
-
-                let func_path = Path {
-                    identifiers: path
-                        .iter()
-                        .map(|x| Identifier {
-                            id:   resolver.interner.insert(Rc::from(*x)),
-                            span: self.span(),
-                        })
-                        .collect(),
+                let function = resolve_operator_function(func, self.span(), resolver, binder, scope_id)?;
+
+                let call = FunctionCall {
+                    function,
+                    args: vec![lhs.resolve(resolver, binder, scope_id)?, rhs.resolve(resolver, binder, scope_id)?],
+                    span: self.span(),
                 };
 
-                let Some(either::Left(function)) = func_path.resolve(resolver, binder, scope_id) else {
-                    resolver.errs.push(
-                        self.span()
-                            .with_item(ResolutionError::OperatorImplementationNotFound(func.to_string(), path.join("."))),
-                    );
-                    return None;
+                Expr::new(ExprKind::FunctionCall(call), self.span())
+            },
+            Expression::UnaryOperator(op) => {
+                let func = match op.op.item() {
+                    petr_ast::UnaryOperator::Not => "logical_not",
                 };
+                let function = resolve_operator_function(func, self.span(), resolver, binder, scope_id)?;
 
                 let call = FunctionCall {
                     function,
-                    args: vec![lhs.resolve(resolver, binder, scope_id)?, rhs.resolve(resolver, binder, scope_id)?],
+                    args: vec![op.operand.resolve(resolver, binder, scope_id)?],
                     span: self.span(),
                 };
 
                 Expr::new(ExprKind::FunctionCall(call), self.span())
             },
             Expression::FunctionCall(decl) => {
+                // a single identifier which names a local binding or parameter is a call through a
+                // function value, not to a declared function
+                if let [name] = &decl.func_name.identifiers[..] {
+                    if binder.find_binding_in_scope(name.id, scope_id).is_some()
+                        || binder.find_function_parameter_in_scope(name.id, scope_id).is_some()
+                    {
+                        if decl.arg_names.is_some() {
+                            resolver.errs.push(self.span().with_item(ResolutionError::NamedArgumentsToFunctionValue));
+                            return None;
+                        }
+                        let callee = name.span.with_item(Expression::Variable(*name)).resolve(resolver, binder, scope_id)?;
+                        let args = decl
+                            .args
+                            .iter()
+                            .map(|x| match x.resolve(resolver, binder, scope_id) {
+                                Some(x) => x,
+                                None => Expr::error_recovery(x.span()),
+                            })
+                            .collect();
+                        return Some(Expr::new(
+                            ExprKind::ClosureCall {
+                                callee: Box::new(callee),
+                                args,
+                            },
+                            self.span(),
+                        ));
+                    }
+                }
                 let resolved_call = self.span().with_item(decl).resolve(resolver, binder, scope_id)?;
 
                 Expr::new(ExprKind::FunctionCall(resolved_call), self.span())
@@ -461,13 +902,29 @@ impl Resolve for SpannedItem<Expression> {
                             name: *var,
                             // I think this works for inference -- instantiating a new generic
                             // type. Should revisit for soundness.
-                            ty:   Type::Generic(binding.name),
+                            ty:   Type::Generic(
+                                *binding
+                                    .pattern
+                                    .names()
+                                    .iter()
+                                    .find(|name| name.id == var.id)
+                                    .expect("the binding was found by this name"),
+                            ),
                         },
                         self.span(),
                     ),
                     None => {
                         let Some(ty) = binder.find_function_parameter_in_scope(var.id, scope_id) else {
-                            todo!("not found err");
+                            // not a local, so this must be a function that is being used as a value
+                            let path = Path::new(vec![*var]);
+                            return match path.resolve(resolver, binder, scope_id)? {
+                                either::Left(function) => Some(Expr::new(ExprKind::FunctionValue(function), var.span)),
+                                either::Right(_ty) => {
+                                    let name = resolver.interner.get(var.id).to_string();
+                                    resolver.errs.push(var.span.with_item(ResolutionError::TypeUsedAsValue(name)));
+                                    None
+                                },
+                            };
                         };
 
                         let ty = match ty.resolve(resolver, binder, scope_id) {
@@ -482,7 +939,7 @@ impl Resolve for SpannedItem<Expression> {
                     },
                 }
             },
-            Expression::TypeConstructor(parent_type_id, args) => {
+            Expression::TypeConstructor(parent_type_id, variant, args) => {
                 // Type constructor expressions themselves don't actually do anything.
                 // The function parameters and return types
                 // of the function are what get type checked -- there is no fn body, and this
@@ -494,8 +951,12 @@ impl Resolve for SpannedItem<Expression> {
                         None => todo!("error recov"),
                     })
                     .collect::<Vec<_>>();
-                Expr::new(ExprKind::TypeConstructor(*parent_type_id, resolved_args.into_boxed_slice()), self.span())
+                Expr::new(
+                    ExprKind::TypeConstructor(*parent_type_id, *variant, resolved_args.into_boxed_slice()),
+                    self.span(),
+                )
             },
+            Expression::InterfaceMethod(interface, method) => Expr::new(ExprKind::InterfaceMethod(*interface, *method), self.span()),
             Expression::IntrinsicCall(intrinsic) => {
                 let resolved = intrinsic.resolve(resolver, binder, scope_id)?;
                 Expr::new(ExprKind::Intrinsic(resolved), self.span())
@@ -506,7 +967,7 @@ impl Resolve for SpannedItem<Expression> {
                 for binding in &bound_expression.bindings {
                     let rhs = binding.val.resolve(resolver, binder, scope_id)?;
                     bindings.push(Binding {
-                        name:       binding.name,
+                        pattern:    binding.pattern.clone(),
                         expression: rhs,
                     });
                 }
@@ -542,6 +1003,281 @@ impl Resolve for SpannedItem<Expression> {
                     self.span(),
                 )
             },
+            Expression::Match(petr_ast::Match { scrutinee, arms }) => {
+                let scrutinee = (**scrutinee).resolve(resolver, binder, scope_id)?;
+                let mut resolved_arms = Vec::with_capacity(arms.len());
+                for arm in arms.iter() {
+                    let arm = arm.item();
+                    let scope_id = binder.get_expr_scope(arm.expr_id).expect("invariant: scope should exist");
+                    let pattern = arm.pattern.resolve(resolver, binder, scope_id)?;
+                    let body = arm.body.resolve(resolver, binder, scope_id)?;
+                    resolved_arms.push(MatchArm {
+                        pattern: arm.pattern.span().with_item(pattern),
+                        body,
+                    });
+                }
+                Expr::new(
+                    ExprKind::Match {
+                        scrutinee: Box::new(scrutinee),
+                        arms:      resolved_arms,
+                    },
+                    self.span(),
+                )
+            },
+            Expression::FieldAccess(access) => {
+                let target = access.target.resolve(resolver, binder, scope_id)?;
+                Expr::new(
+                    ExprKind::FieldAccess {
+                        target: Box::new(target),
+                        field:  access.field,
+                    },
+                    self.span(),
+                )
+            },
+            Expression::RecordUpdate(update) => {
+                let target = update.target.resolve(resolver, binder, scope_id)?;
+                let mut fields = Vec::with_capacity(update.fields.len());
+                for (name, value) in update.fields.iter() {
+                    if fields.iter().any(|(other, _): &(Identifier, Expr)| other.id == name.id) {
+                        resolver.errs.push(
+                            name.span
+                                .with_item(ResolutionError::DuplicateField(resolver.interner.get(name.id).to_string())),
+                        );
+                        return None;
+                    }
+                    fields.push((*name, value.resolve(resolver, binder, scope_id)?));
+                }
+                Expr::new(
+                    ExprKind::RecordUpdate {
+                        target: Box::new(target),
+                        fields,
+                    },
+                    self.span(),
+                )
+            },
+            Expression::Lambda(petr_ast::Lambda { parameters, body, expr_id }) => {
+                let scope_id = binder.get_expr_scope(*expr_id).expect("invariant: scope should exist");
+                let params = parameters
+                    .iter()
+                    .map(|param| (param.name, param.ty.resolve(resolver, binder, scope_id).unwrap_or(Type::Unit)))
+                    .collect();
+                let captures = binder.get_lambda_captures(*expr_id).to_vec();
+                let body = (**body).resolve(resolver, binder, scope_id)?;
+                Expr::new(
+                    ExprKind::Lambda {
+                        params,
+                        captures,
+                        body: Box::new(body),
+                    },
+                    self.span(),
+                )
+            },
+        })
+    }
+}
+
+/// Puts arguments which were passed by name into the order of the function's parameters
+fn order_named_arguments(
+    function: FunctionId,
+    names: &[Identifier],
+    args: Vec<Expr>,
+    span: Span,
+    resolver: &mut Resolver,
+    binder: &Binder,
+) -> Option<Vec<Expr>> {
+    let func_decl = binder.get_function(function).item();
+    let function_name = resolver.interner.get(func_decl.name.id).to_string();
+    let mut ordered: Vec<Option<Expr>> = vec![None; func_decl.parameters.len()];
+    let mut had_error = false;
+    for (name, arg) in names.iter().zip(args) {
+        let Some(ix) = func_decl.parameters.iter().position(|param| param.name.id == name.id) else {
+            resolver.errs.push(name.span.with_item(ResolutionError::UnknownArgumentName {
+                function: function_name.clone(),
+                name:     resolver.interner.get(name.id).to_string(),
+            }));
+            had_error = true;
+            continue;
+        };
+        if ordered[ix].is_some() {
+            resolver.errs.push(
+                name.span
+                    .with_item(ResolutionError::DuplicateArgument(resolver.interner.get(name.id).to_string())),
+            );
+            had_error = true;
+            continue;
+        }
+        ordered[ix] = Some(arg);
+    }
+
+    let mut args = Vec::with_capacity(ordered.len());
+    for (arg, param) in ordered.into_iter().zip(func_decl.parameters.iter()) {
+        match arg {
+            Some(arg) => args.push(arg),
+            None => {
+                resolver.errs.push(span.with_item(ResolutionError::MissingArgument {
+                    function: function_name.clone(),
+                    name:     resolver.interner.get(param.name.id).to_string(),
+                }));
+                had_error = true;
+            },
+        }
+    }
+
+    if had_error {
+        None
+    } else {
+        Some(args)
+    }
+}
+
+/// Operators are resolved to calls to the functions which implement them in `std.ops`
+fn resolve_operator_function(
+    func: &str,
+    span: Span,
+    resolver: &mut Resolver,
+    binder: &Binder,
+    scope_id: ScopeId,
+) -> Option<FunctionId> {
+    let path = ["std", "ops", func];
+
+    let func_path = Path {
+        identifiers: path
+            .iter()
+            .map(|x| Identifier {
+                id: resolver.interner.insert(Rc::from(*x)),
+                span,
+            })
+            .collect(),
+    };
+
+    let Some(either::Left(function)) = func_path.resolve(resolver, binder, scope_id) else {
+        resolver
+            .errs
+            .push(span.with_item(ResolutionError::OperatorImplementationNotFound(func.to_string(), path.join("."))));
+        return None;
+    };
+    Some(function)
+}
+
+/// Arithmetic and comparison operators are resolved to the first method of the interface in
+/// `std.ops` which implements them
+fn resolve_operator_interface(
+    interface: &str,
+    span: Span,
+    resolver: &mut Resolver,
+    binder: &Binder,
+    scope_id: ScopeId,
+) -> Option<FunctionId> {
+    let path = ["std", "ops", interface];
+
+    let interface_path = Path {
+        identifiers: path
+            .iter()
+            .map(|x| Identifier {
+                id: resolver.interner.insert(Rc::from(*x)),
+                span,
+            })
+            .collect(),
+    };
+
+    let Some(interface_id) = find_interface(&interface_path, binder, scope_id) else {
+        resolver
+            .errs
+            .push(span.with_item(ResolutionError::OperatorImplementationNotFound(interface.to_string(), path.join("."))));
+        return None;
+    };
+    Some(binder.get_interface(interface_id).methods[0])
+}
+
+/// Finds the interface that a path refers to, pushing an error if there isn't one
+fn resolve_interface_path(
+    path: &Path,
+    resolver: &mut Resolver,
+    binder: &Binder,
+    scope_id: ScopeId,
+) -> Option<InterfaceId> {
+    let interface = find_interface(path, binder, scope_id);
+    if interface.is_none() {
+        let name = path.identifiers.iter().map(|x| resolver.interner.get(x.id)).collect::<Vec<_>>().join(".");
+        let err = match binder.find_symbol_in_scope(path.identifiers[0].id, scope_id) {
+            Some(_) if path.identifiers.len() == 1 => ResolutionError::NotAnInterface(name),
+            _ => ResolutionError::NotFound(name),
+        };
+        resolver.errs.push(name_span(path).with_item(err));
+    }
+    interface
+}
+
+fn find_interface(
+    path: &Path,
+    binder: &Binder,
+    scope_id: ScopeId,
+) -> Option<InterfaceId> {
+    let (last, modules) = path.identifiers.split_last().expect("empty path shouldn't be possible");
+    let mut scope_id = scope_id;
+    for module in modules {
+        let Some(Item::Module(id)) = binder.find_symbol_in_scope(module.id, scope_id) else {
+            return None;
+        };
+        scope_id = binder.get_module(id).root_scope;
+    }
+    match binder.find_symbol_in_scope(last.id, scope_id)? {
+        Item::Interface(id) => Some(id),
+        Item::Import { path, .. } => find_interface(&path, binder, scope_id),
+        _ => None,
+    }
+}
+
+/// The span of the last identifier in a path, which is the name of the item it refers to
+fn name_span(path: &Path) -> Span {
+    path.identifiers.last().expect("empty path shouldn't be possible").span
+}
+
+impl Resolve for petr_ast::Pattern {
+    type Resolved = Pattern;
+
+    fn resolve(
+        &self,
+        resolver: &mut Resolver,
+        binder: &Binder,
+        scope_id: ScopeId,
+    ) -> Option<Self::Resolved> {
+        let petr_ast::VariantPattern { name, fields } = match self {
+            petr_ast::Pattern::Wildcard => return Some(Pattern::Wildcard),
+            petr_ast::Pattern::Literal(lit) => return Some(Pattern::Literal(lit.clone())),
+            petr_ast::Pattern::Variant(variant) => variant,
+        };
+
+        let stringified_name = name.identifiers.iter().map(|x| resolver.interner.get(x.id)).collect::<Vec<_>>().join(".");
+        let span = name.identifiers.last().expect("empty path shouldn't be possible").span;
+
+        // variants are matched by the name of their type constructor function
+        let constructor = match name.resolve(resolver, binder, scope_id)? {
+            either::Left(func) => binder.get_function(func),
+            either::Right(_ty) => {
+                resolver.errs.push(span.with_item(ResolutionError::NotAVariant(stringified_name)));
+                return None;
+            },
+        };
+        let Expression::TypeConstructor(ty, variant, _) = constructor.item().body.item() else {
+            resolver.errs.push(span.with_item(ResolutionError::NotAVariant(stringified_name)));
+            return None;
+        };
+
+        let expected = constructor.item().parameters.len();
+        if expected != fields.len() {
+            resolver.errs.push(span.with_item(ResolutionError::PatternFieldCountMismatch {
+                variant: stringified_name,
+                expected,
+                got: fields.len(),
+            }));
+            return None;
+        }
+
+        Some(Pattern::Variant {
+            ty:      *ty,
+            variant: *variant,
+            fields:  fields.clone(),
         })
     }
 }
@@ -624,7 +1360,7 @@ impl Resolve for SpannedItem<&petr_ast::FunctionCall> {
             },
         };
 
-        let args = self
+        let args: Vec<Expr> = self
             .item()
             .args
             .iter()
@@ -634,6 +1370,11 @@ impl Resolve for SpannedItem<&petr_ast::FunctionCall> {
             })
             .collect();
 
+        let args = match &self.item().arg_names {
+            Some(names) => order_named_arguments(resolved_id, names, args, self.span(), resolver, binder)?,
+            None => args,
+        };
+
         Some(FunctionCall {
             function: resolved_id,
             args,
@@ -740,6 +1481,18 @@ impl Resolve for petr_ast::TypeDeclaration {
             let mut field_types = Vec::with_capacity(variant.fields.len());
             for field in variant.fields.iter() {
                 if let Some(field_type) = field.item().ty.resolve(resolver, binder, scope_id) {
+                    let mut params = Vec::new();
+                    field_type.type_params(&mut params);
+                    for param in params
+                        .into_iter()
+                        .filter(|param| !self.type_params.iter().any(|declared| declared.id == param.id))
+                    {
+                        let ty = resolver.interner.get(self.name.id).to_string();
+                        let param_name = resolver.interner.get(param.id).to_string();
+                        resolver
+                            .errs
+                            .push(param.span.with_item(ResolutionError::UndeclaredTypeParameter { ty, param: param_name }));
+                    }
                     field_types.push(TypeField {
                         name: field.item().name,
                         ty:   field_type,
@@ -765,6 +1518,7 @@ impl Resolve for petr_ast::TypeDeclaration {
 
         Some(TypeDeclaration {
             name: self.name,
+            type_params: self.type_params.clone(),
             variants: variants.into_boxed_slice(),
             constant_literal_types,
         })
@@ -775,7 +1529,7 @@ impl Resolve for petr_ast::TypeDeclaration {
 mod tests {
     mod pretty_printing {
 
-        use super::{Expr, ExprKind};
+        use super::{Expr, ExprKind, FieldPattern, Pattern};
         use crate::{resolved::QueryableResolvedItems, resolver::Type};
         impl Expr {
             pub fn to_string(
@@ -793,6 +1547,8 @@ mod tests {
             ) -> String {
                 match self {
                     Type::Integer => "int".to_string(),
+                    Type::SizedInteger(ty) => ty.to_string(),
+                    Type::Float => "float".to_string(),
                     Type::Bool => "bool".to_string(),
                     Type::Unit => "()".to_string(),
                     Type::String => "string".to_string(),
@@ -800,11 +1556,50 @@ mod tests {
                     Type::Named(id) => {
                         format!("named type {}", resolver.interner.get(resolver.get_type(*id).name.id))
                     },
+                    Type::Applied(id, args) => format!(
+                        "named type {} applied to [{}]",
+                        resolver.interner.get(resolver.get_type(*id).name.id),
+                        args.iter().map(|x| x.to_string(resolver)).collect::<Vec<_>>().join(", ")
+                    ),
                     Type::Generic(a) => format!("generic type {}", resolver.interner.get(a.id)),
                     Type::Sum(tys) => {
                         format!("sum type [{}]", tys.iter().map(|x| x.to_string(resolver)).collect::<Vec<_>>().join(" | "))
                     },
                     Type::Literal(l) => format!("{:?}", l),
+                    Type::Arrow(params, return_ty) => format!(
+                        "fn({}) -> {}",
+                        params.iter().map(|x| x.to_string(resolver)).collect::<Vec<_>>().join(", "),
+                        return_ty.to_string(resolver)
+                    ),
+                    Type::Tuple(tys) => format!("({})", tys.iter().map(|x| x.to_string(resolver)).collect::<Vec<_>>().join(", ")),
+                    Type::Alias(name, ty) => format!("alias {} of {}", resolver.interner.get(name.id), ty.to_string(resolver)),
+                }
+            }
+        }
+
+        impl Pattern {
+            pub fn to_string(
+                &self,
+                resolver: &QueryableResolvedItems,
+            ) -> String {
+                match self {
+                    Pattern::Wildcard => "_".into(),
+                    Pattern::Literal(lit) => format!("Literal({:?})", lit),
+                    Pattern::Variant { ty, variant, fields } => {
+                        let fields = fields
+                            .iter()
+                            .map(|field| match field.item() {
+                                FieldPattern::Binding(name) => resolver.interner.get(name.id).to_string(),
+                                FieldPattern::Wildcard => "_".into(),
+                                FieldPattern::Literal(lit) => format!("Literal({:?})", lit),
+                            })
+                            .collect::<Vec<_>>();
+                        format!(
+                            "variant {variant} of {}({})",
+                            resolver.interner.get(resolver.get_type(*ty).name.id),
+                            fields.join(", ")
+                        )
+                    },
                 }
             }
         }
@@ -821,6 +1616,9 @@ mod tests {
                         format!("FunctionCall({})", call.function)
                     },
                     ExprKind::Unit => "Unit".to_string(),
+                    ExprKind::Tuple(exprs) => format!("({})", exprs.iter().map(|x| x.to_string(resolver)).collect::<Vec<_>>().join(", ")),
+                    ExprKind::Hole { name: None, .. } => "hole".to_string(),
+                    ExprKind::Hole { name: Some(name), .. } => format!("hole({})", resolver.interner.get(name.id)),
                     ExprKind::ErrorRecovery => "<error>".to_string(),
                     ExprKind::Variable { name, ty } => format!("{}: {}", resolver.interner.get(name.id), ty.to_string(resolver)),
                     ExprKind::Intrinsic(x) => format!(
@@ -829,6 +1627,49 @@ mod tests {
                         x.args.iter().map(|x| x.to_string(resolver)).collect::<Vec<_>>().join(", ")
                     ),
                     ExprKind::TypeConstructor(..) => "Type constructor".into(),
+                    ExprKind::InterfaceMethod(interface, method) => format!(
+                        "method {} of {}",
+                        method,
+                        resolver.interner.get(resolver.get_interface(*interface).name.id)
+                    ),
+                    ExprKind::Match { scrutinee, arms } => format!(
+                        "match {} {}",
+                        scrutinee.to_string(resolver),
+                        arms.iter()
+                            .map(|arm| format!("| {} => {}", arm.pattern.item().to_string(resolver), arm.body.to_string(resolver)))
+                            .collect::<Vec<_>>()
+                            .join(" ")
+                    ),
+                    ExprKind::FunctionValue(function) => format!("FunctionValue({function})"),
+                    ExprKind::Lambda { params, captures, body } => format!(
+                        "lambda({}) captures [{}] => {}",
+                        params
+                            .iter()
+                            .map(|(name, ty)| format!("{}: {}", resolver.interner.get(name.id), ty.to_string(resolver)))
+                            .collect::<Vec<_>>()
+                            .join(", "),
+                        captures
+                            .iter()
+                            .map(|x| resolver.interner.get(x.id).to_string())
+                            .collect::<Vec<_>>()
+                            .join(", "),
+                        body.to_string(resolver)
+                    ),
+                    ExprKind::ClosureCall { callee, args } => format!(
+                        "ClosureCall({})({})",
+                        callee.to_string(resolver),
+                        args.iter().map(|x| x.to_string(resolver)).collect::<Vec<_>>().join(", ")
+                    ),
+                    ExprKind::FieldAccess { target, field } => format!("{}.{}", target.to_string(resolver), resolver.interner.get(field.id)),
+                    ExprKind::RecordUpdate { target, fields } => format!(
+                        "{} with {}",
+                        target.to_string(resolver),
+                        fields
+                            .iter()
+                            .map(|(name, value)| format!("{} = {}", resolver.interner.get(name.id), value.to_string(resolver)))
+                            .collect::<Vec<_>>()
+                            .join(", ")
+                    ),
                     ExprKind::ExpressionWithBindings { .. } => todo!(),
                     ExprKind::If {
                         condition,
@@ -864,7 +1705,7 @@ mod tests {
         }
         let resolver = Resolver::new_from_single_ast(ast, interner);
         let (errs, queryable) = resolver.into_queryable();
-        assert!(errs.is_empty());
+        assert!(errs.is_empty(), "{errs:#?}");
         let result = pretty_print_resolution(&queryable);
         expect.assert_eq(&result);
     }
@@ -1097,4 +1938,240 @@ mod tests {
             "#]],
         )
     }
+
+    #[test]
+    fn match_patterns() {
+        check(
+            "
+            type Shape = Square side 'int | Rect width 'int height 'int
+
+            fn area(s in 'Shape) returns 'int
+                match s
+                | Square side => side
+                | Rect 0 _ => 0
+                | Rect w h => w
+                | _ => 1
+                ",
+            expect![[r#"
+                _____FUNCTIONS_____
+                #0 Square(  side: int, ) -> named type Shape   "Type constructor"
+                #1 Rect(  width: int,   height: int, ) -> named type Shape   "Type constructor"
+                #2 area(  s: named type Shape, ) -> int   "match s: named type Shape | variant 0 of Shape(side) => side: generic type side | variant 1 of Shape(Literal(Integer(0)), _) => Literal(Integer(0)) | variant 1 of Shape(w, h) => w: generic type w | _ => Literal(Integer(1))"
+                _____TYPES_____
+                #0 Shape
+
+            "#]],
+        )
+    }
+
+    #[test]
+    fn lambdas_and_function_values() {
+        check(
+            r#"
+            fn apply(f in 'fn('int) returns 'int, x in 'int) returns 'int ~f x
+
+            fn id(x in 'int) returns 'int x
+
+            fn main(a in 'int) returns 'int
+                ~apply(\(x in 'int) => a, ~apply(id, 2))
+                "#,
+            expect![[r#"
+                _____FUNCTIONS_____
+                #0 apply(  f: fn(int) -> int,   x: int, ) -> int   "ClosureCall(f: fn(int) -> int)(x: int)"
+                #1 id(  x: int, ) -> int   "x: int"
+                #2 main(  a: int, ) -> int   "FunctionCall(functionid0)"
+                _____TYPES_____
+            "#]],
+        )
+    }
+
+    #[test]
+    fn named_arguments_field_access_and_update() {
+        check(
+            r#"
+            type Point = Point x 'int y 'int
+
+            fn flip(p in 'Point) returns 'Point p with x = p.y, y = p.x
+
+            fn main(a in 'int) returns 'Point ~Point y = a, x = 1
+                "#,
+            expect![[r#"
+                _____FUNCTIONS_____
+                #0 Point(  x: int,   y: int, ) -> named type Point   "Type constructor"
+                #1 flip(  p: named type Point, ) -> named type Point   "p: named type Point with x = p: named type Point.y, y = p: named type Point.x"
+                #2 main(  a: int, ) -> named type Point   "FunctionCall(functionid0)"
+                _____TYPES_____
+                #0 Point
+
+            "#]],
+        )
+    }
+
+    #[test]
+    fn generic_type_declarations() {
+        check(
+            r#"
+            type Option 'A = None | Some value 'A
+
+            fn unwrap_or(opt in 'Option 'A, default in 'A) returns 'A default
+
+            fn main() returns 'int ~unwrap_or(~Some(1), 2)
+                "#,
+            expect![[r#"
+                _____FUNCTIONS_____
+                #0 None() -> named type Option applied to [generic type A]   "Type constructor"
+                #1 Some(  value: generic type A, ) -> named type Option applied to [generic type A]   "Type constructor"
+                #2 unwrap_or(  opt: named type Option applied to [generic type A],   default: generic type A, ) -> generic type A   "default: generic type A"
+                #3 main() -> int   "FunctionCall(functionid2)"
+                _____TYPES_____
+                #0 Option
+
+            "#]],
+        )
+    }
+
+    #[test]
+    fn type_argument_count_mismatch() {
+        let parser = petr_parse::Parser::new(vec![(
+            "test",
+            "type Option 'A = None | Some value 'A
+            fn main(x in 'Option 'int 'bool) returns 'int 1",
+        )]);
+        let (ast, errs, interner, _source_map) = parser.into_result();
+        assert!(errs.is_empty(), "{errs:#?}");
+        let (errs, _queryable) = Resolver::new_from_single_ast(ast, interner).into_queryable();
+        assert!(
+            matches!(
+                &errs[..],
+                [err] if matches!(err.item(), ResolutionError::TypeArgumentCountMismatch { expected: 1, got: 2, .. })
+            ),
+            "{errs:#?}"
+        );
+    }
+
+    #[test]
+    fn generic_type_without_type_arguments() {
+        let parser = petr_parse::Parser::new(vec![(
+            "test",
+            "type Option 'A = None | Some value 'A
+            fn main(x in 'Option) returns 'int 1",
+        )]);
+        let (ast, errs, interner, _source_map) = parser.into_result();
+        assert!(errs.is_empty(), "{errs:#?}");
+        let (errs, _queryable) = Resolver::new_from_single_ast(ast, interner).into_queryable();
+        assert!(
+            matches!(
+                &errs[..],
+                [err] if matches!(err.item(), ResolutionError::TypeArgumentCountMismatch { expected: 1, got: 0, .. })
+            ),
+            "{errs:#?}"
+        );
+    }
+
+    #[test]
+    fn undeclared_type_parameter() {
+        let parser = petr_parse::Parser::new(vec![("test", "type Option 'A = None | Some value 'B")]);
+        let (ast, errs, interner, _source_map) = parser.into_result();
+        assert!(errs.is_empty(), "{errs:#?}");
+        let (errs, _queryable) = Resolver::new_from_single_ast(ast, interner).into_queryable();
+        assert!(
+            matches!(
+                &errs[..],
+                [err] if matches!(err.item(), ResolutionError::UndeclaredTypeParameter { param, .. } if param == "B")
+            ),
+            "{errs:#?}"
+        );
+    }
+
+    #[test]
+    fn interface_declaration_and_implementation() {
+        check(
+            r#"
+            interface Size 'A with size(x in 'A) returns 'int
+
+            implement Size for 'bool with size = bool_size
+
+            fn bool_size(x in 'bool) returns 'int 1
+
+            fn measure(x in 'A) returns 'int where 'A: Size ~size x
+                "#,
+            expect![[r#"
+                _____FUNCTIONS_____
+                #0 size(  x: generic type A, ) -> int   "method 0 of Size"
+                #1 bool_size(  x: bool, ) -> int   "Literal(Integer(1))"
+                #2 measure(  x: generic type A, ) -> int   "FunctionCall(functionid0)"
+                _____TYPES_____
+            "#]],
+        )
+    }
+
+    #[test]
+    fn implementation_must_match_interface_methods() {
+        let parser = petr_parse::Parser::new(vec![(
+            "test",
+            "interface Size 'A with size(x in 'A) returns 'int
+            implement Size for 'bool with length = bool_size
+            fn bool_size(x in 'bool) returns 'int 1",
+        )]);
+        let (ast, errs, interner, _source_map) = parser.into_result();
+        assert!(errs.is_empty(), "{errs:#?}");
+        let (errs, _queryable) = Resolver::new_from_single_ast(ast, interner).into_queryable();
+        assert!(
+            matches!(
+                &errs[..],
+                [unknown, missing] if matches!(unknown.item(), ResolutionError::UnknownMethod { .. })
+                    && matches!(missing.item(), ResolutionError::MissingMethod { .. })
+            ),
+            "{errs:#?}"
+        );
+    }
+
+    #[test]
+    fn where_clause_requires_an_interface() {
+        let parser = petr_parse::Parser::new(vec![(
+            "test",
+            "type Size = Size
+            fn measure(x in 'A) returns 'int where 'A: Size 1",
+        )]);
+        let (ast, errs, interner, _source_map) = parser.into_result();
+        assert!(errs.is_empty(), "{errs:#?}");
+        let (errs, _queryable) = Resolver::new_from_single_ast(ast, interner).into_queryable();
+        assert!(
+            matches!(&errs[..], [err] if matches!(err.item(), ResolutionError::NotAnInterface(name) if name == "Size")),
+            "{errs:#?}"
+        );
+    }
+
+    #[test]
+    fn type_aliases_expand_to_their_types() {
+        check(
+            r#"
+            type alias Id = 'int
+            type alias Small = 'sum 1 | 2 | 3
+            type alias Ids = '(Id, Id)
+            fn foo(a in 'Small, b in 'Ids) returns 'Id a
+            "#,
+            expect![[r#"
+                _____FUNCTIONS_____
+                #0 foo(  a: alias Small of sum type [Integer(1) | Integer(2) | Integer(3)],   b: alias Ids of (alias Id of int, alias Id of int), ) -> alias Id of int   "a: alias Small of sum type [Integer(1) | Integer(2) | Integer(3)]"
+                _____TYPES_____
+            "#]],
+        );
+    }
+
+    #[test]
+    fn cyclic_type_alias() {
+        let parser = petr_parse::Parser::new(vec![(
+            "test",
+            "type alias A = 'sum 'int | 'B
+            type alias B = '(A, int)",
+        )]);
+        let (ast, errs, interner, _source_map) = parser.into_result();
+        assert!(errs.is_empty(), "{errs:#?}");
+        let (errs, _queryable) = Resolver::new_from_single_ast(ast, interner).into_queryable();
+        assert!(
+            matches!(&errs[..], [err] if matches!(err.item(), ResolutionError::CyclicTypeAlias(name) if name == "A")),
+            "{errs:#?}"
+        );
+    }
 }
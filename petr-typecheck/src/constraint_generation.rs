@@ -4,12 +4,14 @@ use std::{
 };
 
 use petr_bind::FunctionId;
-use petr_resolve::{Expr, FunctionCall, QueryableResolvedItems};
-use petr_utils::{Identifier, IndexMap, Span, SpannedItem, SymbolId};
+use petr_resolve::{Expr, ExprKind, FieldPattern, FunctionCall, Pattern, QueryableResolvedItems};
+use petr_utils::{Identifier, IndexMap, InterfaceId, Span, SpannedItem, SymbolId, TypeId};
 
 use crate::{
     error::TypeConstraintError,
-    solution::{TypeSolution, TypeSolutionEntry},
+    pretty_printing,
+    solution::{is_same_implementing_type, Implementation, TypeSolution, TypeSolutionEntry},
+    trace::SolverTrace,
     typed_ast::{TypedExpr, TypedExprKind},
     types::{GeneralType, SpecificType, Type, TypeVariant},
     Function, TypeError, TypeOrFunctionId, TypeVariable,
@@ -53,6 +55,23 @@ impl TypeConstraint {
             span,
         }
     }
+
+    fn narrow(
+        narrowed: TypeVariable,
+        original: TypeVariable,
+        literal: TypeVariable,
+        equal: bool,
+        span: Span,
+    ) -> Self {
+        Self {
+            kind: if equal {
+                TypeConstraintKind::NarrowTo(narrowed, original, literal)
+            } else {
+                TypeConstraintKind::NarrowExcluding(narrowed, original, literal)
+            },
+            span,
+        }
+    }
 }
 
 #[derive(Clone, Copy, Debug)]
@@ -63,10 +82,23 @@ pub enum TypeConstraintKind {
     // If a type variable is constrained to be an axiom, it means that the type variable
     // cannot be updated by the inference engine. It effectively fixes the type, or pins the type.
     Axiom(TypeVariable),
+    /// The type of a variable in the `then` branch of an `if` which checks that it equals a
+    /// literal, like `if = x 1 then ...`. The first type variable is the variable's narrowed type,
+    /// which is the literal's type, the third, if the variable's original type (the second)
+    /// contains it.
+    NarrowTo(TypeVariable, TypeVariable, TypeVariable),
+    /// The type of a variable in the `else` branch of such an `if`, which is its original type
+    /// without the literal's type, e.g. `'sum 2 | 3` for a variable of type `'sum 1 | 2 | 3`
+    /// which isn't `1`.
+    NarrowExcluding(TypeVariable, TypeVariable, TypeVariable),
 }
 
+/// The order of these is the order that constraints are applied in.
 #[derive(Clone, Copy, Debug, PartialOrd, Ord, PartialEq, Eq)]
 enum TypeConstraintKindValue {
+    // variables are narrowed before inference changes their types, so that everything else
+    // which uses a narrowed variable is checked against its narrowed type
+    Narrow,
     Unify,
     Satisfies,
     Axiom,
@@ -81,6 +113,8 @@ pub struct TypeContext {
     int_ty:         TypeVariable,
     bool_ty:        TypeVariable,
     error_recovery: TypeVariable,
+    /// The type variables of types that were written as type aliases, and the aliases' names
+    aliases:        BTreeMap<TypeVariable, Identifier>,
 }
 
 impl Default for TypeContext {
@@ -101,6 +135,7 @@ impl Default for TypeContext {
             string_ty,
             int_ty,
             error_recovery,
+            aliases: Default::default(),
         }
     }
 }
@@ -132,6 +167,17 @@ impl TypeContext {
         self.constraints.push(TypeConstraint::axiom(ty1, span));
     }
 
+    fn narrow(
+        &mut self,
+        narrowed: TypeVariable,
+        original: TypeVariable,
+        literal: TypeVariable,
+        equal: bool,
+        span: Span,
+    ) {
+        self.constraints.push(TypeConstraint::narrow(narrowed, original, literal, equal, span));
+    }
+
     fn new_variable(
         &mut self,
         span: Span,
@@ -158,6 +204,51 @@ impl TypeContext {
 
 pub type FunctionSignature = (FunctionId, Box<[GeneralType]>);
 
+/// A type which a function constrained by an interface was called with, and so must implement
+/// that interface. Whether it does is only known once the type is solved.
+pub(crate) struct InterfaceObligation {
+    pub ty: TypeVariable,
+    pub interface: InterfaceId,
+    pub interface_name: Identifier,
+    pub span: Span,
+    /// the constraints of the function that the call is in, whose type parameters are assumed
+    /// to implement their interfaces
+    pub assumptions: Vec<(TypeVariable, InterfaceId)>,
+}
+
+/// A hole in the program, whose expected type and the types of the locals around it are reported
+/// once the types are solved
+pub(crate) struct Hole {
+    pub span:   Span,
+    pub name:   Option<Identifier>,
+    pub ty:     TypeVariable,
+    pub locals: Vec<(Identifier, TypeVariable)>,
+}
+
+/// A name in scope while type checking, which is either a variable or a type parameter
+#[derive(Clone)]
+enum ScopedVariable {
+    Monomorphic(TypeVariable),
+    /// A lambda bound by `let`, whose type is generalized. Each use of the binding type checks
+    /// the lambda again, with fresh type variables, in the scope that the binding is in.
+    Generalized {
+        ty:     TypeVariable,
+        lambda: Rc<Expr>,
+        scope:  Rc<Vec<BTreeMap<Identifier, ScopedVariable>>>,
+    },
+    /// A variable in a branch of an `if` whose condition narrows its type, see
+    /// [`TypeConstraintKind::NarrowTo`]
+    Narrowed(TypeVariable),
+}
+
+impl ScopedVariable {
+    fn ty(&self) -> TypeVariable {
+        match self {
+            ScopedVariable::Monomorphic(ty) | ScopedVariable::Generalized { ty, .. } | ScopedVariable::Narrowed(ty) => *ty,
+        }
+    }
+}
+
 pub struct TypeChecker {
     ctx: TypeContext,
     type_map: BTreeMap<TypeOrFunctionId, TypeVariable>,
@@ -165,7 +256,14 @@ pub struct TypeChecker {
     typed_functions: BTreeMap<FunctionId, Function>,
     errors: Vec<TypeError>,
     resolved: QueryableResolvedItems,
-    variable_scope: Vec<BTreeMap<Identifier, TypeVariable>>,
+    variable_scope: Vec<BTreeMap<Identifier, ScopedVariable>>,
+    /// generic types whose fields are currently being instantiated, see [`Self::instantiate_type`]
+    types_being_instantiated: Vec<(TypeId, TypeVariable)>,
+    implementations: Vec<Implementation>,
+    obligations: Vec<InterfaceObligation>,
+    /// the constraints of the function being type checked
+    assumptions: Vec<(TypeVariable, InterfaceId)>,
+    holes: Vec<Hole>,
 }
 
 pub trait TypeCheck {
@@ -210,19 +308,17 @@ impl TypeChecker {
         res
     }
 
-    fn generic_type(
+    pub(crate) fn generic_type(
         &mut self,
         id: &Identifier,
     ) -> TypeVariable {
-        for scope in self.variable_scope.iter().rev() {
-            if let Some(ty) = scope.get(id) {
-                return *ty;
-            }
+        if let Some(ty) = self.find_variable(*id) {
+            return ty;
         }
         let fresh_ty = self.fresh_ty_var(id.span);
         match self.variable_scope.last_mut() {
             Some(entry) => {
-                entry.insert(*id, fresh_ty);
+                entry.insert(*id, ScopedVariable::Monomorphic(fresh_ty));
             },
             None => {
                 self.errors.push(id.span.with_item(TypeConstraintError::Internal(
@@ -238,17 +334,128 @@ impl TypeChecker {
         &self,
         id: Identifier,
     ) -> Option<TypeVariable> {
-        for scope in self.variable_scope.iter().rev() {
-            if let Some(ty) = scope.get(&id) {
-                return Some(*ty);
+        self.variable_scope.iter().rev().find_map(|scope| scope.get(&id)).map(ScopedVariable::ty)
+    }
+
+    /// If `id` refers to a generalized `let` binding, type checks a new instance of the binding's
+    /// lambda for this use of it
+    pub(crate) fn instantiate_variable(
+        &mut self,
+        id: Identifier,
+    ) -> Option<TypedExpr> {
+        let Some(ScopedVariable::Generalized { lambda, scope, .. }) = self.variable_scope.iter().rev().find_map(|scope| scope.get(&id)).cloned()
+        else {
+            return None;
+        };
+        let enclosing_scope = std::mem::replace(&mut self.variable_scope, (*scope).clone());
+        let instance = lambda.type_check(self);
+        self.variable_scope = enclosing_scope;
+        Some(instance)
+    }
+
+    /// The narrowed type of `id`, if it is narrowed in the current scope
+    pub(crate) fn narrowed_variable(
+        &self,
+        id: Identifier,
+    ) -> Option<TypeVariable> {
+        match self.variable_scope.iter().rev().find_map(|scope| scope.get(&id)) {
+            Some(ScopedVariable::Narrowed(ty)) => Some(*ty),
+            _ => None,
+        }
+    }
+
+    /// If `condition` checks whether a variable is equal to a literal, returns the variable, the
+    /// literal, and whether the condition is true when they are equal. The check is either the
+    /// `@equals` or `@not_equals` intrinsic, or a call to a function which just applies one of
+    /// them to its parameters, like the `std.ops` functions that `=` and `!=` resolve to.
+    pub(crate) fn narrowing_condition(
+        &self,
+        condition: &Expr,
+    ) -> Option<(Identifier, petr_resolve::Literal, bool)> {
+        fn equality_test(expr: &Expr) -> Option<(bool, &[Expr])> {
+            let ExprKind::Intrinsic(intrinsic) = &expr.kind else {
+                return None;
+            };
+            match intrinsic.intrinsic {
+                petr_resolve::IntrinsicName::Equals => Some((true, &intrinsic.args)),
+                petr_resolve::IntrinsicName::NotEquals => Some((false, &intrinsic.args)),
+                _ => None,
             }
         }
-        None
+
+        let (equal, args) = match &condition.kind {
+            ExprKind::FunctionCall(call) => {
+                let func = self.resolved.get_function(call.function);
+                let (equal, params) = equality_test(&func.body)?;
+                let applies_to_params = params.len() == 2
+                    && params
+                        .iter()
+                        .all(|param| matches!(&param.kind, ExprKind::Variable { name, .. } if func.params.iter().any(|(p, _)| p.id == name.id)));
+                if !applies_to_params {
+                    return None;
+                }
+                (equal, &call.args[..])
+            },
+            _ => equality_test(condition)?,
+        };
+        match args {
+            [Expr {
+                kind: ExprKind::Variable { name, .. },
+                ..
+            }, Expr {
+                kind: ExprKind::Literal(literal),
+                ..
+            }]
+            | [Expr {
+                kind: ExprKind::Literal(literal),
+                ..
+            }, Expr {
+                kind: ExprKind::Variable { name, .. },
+                ..
+            }] => Some((*name, literal.clone(), equal)),
+            _ => None,
+        }
+    }
+
+    /// Type checks a branch of an `if` whose condition checks whether `variable` is equal to
+    /// `literal`. `equal` is whether they are equal in this branch. The type of the variable is
+    /// narrowed accordingly within the branch.
+    pub(crate) fn with_narrowed_variable<T>(
+        &mut self,
+        variable: Identifier,
+        literal: &petr_resolve::Literal,
+        equal: bool,
+        span: Span,
+        f: impl FnOnce(&mut Self) -> T,
+    ) -> T {
+        let Some(original) = self.find_variable(variable) else {
+            return f(self);
+        };
+        let literal = self.convert_literal_to_type(literal);
+        // until the narrowing is solved, the narrowed type is the original one, so that whatever
+        // is looked up before solving, like the signatures of calls, stays as it was
+        let narrowed = self.ctx.types.insert(SpecificType::Ref(original));
+        self.ctx.narrow(narrowed, original, literal, equal, span);
+        self.with_type_scope(|ctx| {
+            ctx.variable_scope
+                .last_mut()
+                .expect("type scope was just pushed")
+                .insert(variable, ScopedVariable::Narrowed(narrowed));
+            f(ctx)
+        })
     }
 
     pub fn fully_type_check(&mut self) {
+        // every type is in the type map before any fields are converted, so that types can refer
+        // to themselves and to each other
         for (id, decl) in self.resolved.types() {
             let ty = self.fresh_ty_var(decl.name.span);
+            self.type_map.insert(id.into(), ty);
+        }
+        self.check_types_are_constructible();
+
+        for (id, decl) in self.resolved.types() {
+            let ty = *self.get_type(id);
             let variants = decl
                 .variants
                 .iter()
@@ -269,7 +476,6 @@ impl TypeChecker {
                     constant_literal_types: decl.constant_literal_types,
                 },
             );
-            self.type_map.insert(id.into(), ty);
         }
 
         for (id, func) in self.resolved.functions() {
@@ -279,6 +485,9 @@ impl TypeChecker {
             self.type_map.insert(id.into(), ty);
             self.typed_functions.insert(id, typed_function);
         }
+
+        self.type_check_implementations();
+
         // type check the main func with no params
         let main_func = self.get_main_function();
         // construct a function call for the main function, if one exists
@@ -306,22 +515,48 @@ impl TypeChecker {
     /// - satisfaction tries to make one type satisfy the constraints of another, although type
     ///   constraints don't exist in the language yet
     pub fn into_solution(self) -> Result<TypeSolution, Vec<TypeError>> {
+        self.solve(false).into_result()
+    }
+
+    /// Like [`Self::into_solution`], but also returns a trace of every constraint that was
+    /// applied, the rule the solver applied to it, and the type updates that resulted
+    pub fn into_solution_with_trace(self) -> (Result<TypeSolution, Vec<TypeError>>, SolverTrace) {
+        let mut solution = self.solve(true);
+        let trace = solution.take_trace().expect("tracing was enabled");
+        (solution.into_result(), trace)
+    }
+
+    fn solve(
+        self,
+        trace: bool,
+    ) -> TypeSolution {
         let constraints = self.ctx.constraints.clone();
+        let variant_names = self
+            .resolved
+            .types()
+            .map(|(_, decl)| (decl.name, decl.variants.iter().map(|variant| variant.name).collect()))
+            .collect::<Vec<_>>();
         let mut solution = TypeSolution::new(
             self.ctx.types.clone(),
             self.ctx.error_recovery,
             self.ctx.unit_ty,
             self.typed_functions,
             self.monomorphized_functions,
+            self.implementations,
             self.resolved.interner,
+            self.ctx.aliases.clone(),
             self.errors,
         );
+        if trace {
+            solution.enable_trace();
+        }
         for TypeConstraint { kind, span } in constraints.iter().filter(|c| matches!(c.kind, TypeConstraintKind::Axiom(_))) {
             let TypeConstraintKind::Axiom(axiomatic_variable) = kind else {
                 unreachable!("above filter ensures that all constraints are axioms here")
             };
             // first, pin all axiomatic type variables in the solution
             let ty = self.ctx.types.get(*axiomatic_variable).clone();
+            solution.trace_constraint("axiom", vec![*axiomatic_variable], *span);
             solution.insert_solution(*axiomatic_variable, TypeSolutionEntry::new_axiomatic(ty), *span);
         }
 
@@ -329,16 +564,30 @@ impl TypeChecker {
         for constraint in constraints.iter().filter(|c| !matches!(c.kind, TypeConstraintKind::Axiom(_))) {
             match &constraint.kind {
                 TypeConstraintKind::Unify(t1, t2) => {
+                    solution.trace_constraint("unify", vec![*t1, *t2], constraint.span);
                     solution.apply_unify_constraint(*t1, *t2, constraint.span);
                 },
                 TypeConstraintKind::Satisfies(t1, t2) => {
+                    solution.trace_constraint("satisfies", vec![*t1, *t2], constraint.span);
                     solution.apply_satisfies_constraint(*t1, *t2, constraint.span);
                 },
                 TypeConstraintKind::Axiom(_) => unreachable!(),
+                TypeConstraintKind::NarrowTo(narrowed, original, literal) => {
+                    solution.trace_constraint("narrow to", vec![*narrowed, *original, *literal], constraint.span);
+                    solution.apply_narrow_to_constraint(*narrowed, *original, *literal, constraint.span);
+                },
+                TypeConstraintKind::NarrowExcluding(narrowed, original, literal) => {
+                    solution.trace_constraint("narrow excluding", vec![*narrowed, *original, *literal], constraint.span);
+                    solution.apply_narrow_excluding_constraint(*narrowed, *original, *literal, constraint.span);
+                },
             }
         }
 
-        solution.into_result()
+        solution.check_matches(&variant_names);
+        solution.check_interface_obligations(&self.obligations);
+        solution.report_holes(&self.holes);
+
+        solution
     }
 
     pub fn new(resolved: QueryableResolvedItems) -> Self {
@@ -350,10 +599,22 @@ impl TypeChecker {
             typed_functions: Default::default(),
             resolved,
             variable_scope: Default::default(),
+            types_being_instantiated: Default::default(),
             monomorphized_functions: Default::default(),
+            implementations: Default::default(),
+            obligations: Default::default(),
+            assumptions: Default::default(),
+            holes: Default::default(),
         }
     }
 
+    pub(crate) fn insert_hole(
+        &mut self,
+        hole: Hole,
+    ) {
+        self.holes.push(hole);
+    }
+
     pub fn insert_variable(
         &mut self,
         id: Identifier,
@@ -362,7 +623,26 @@ impl TypeChecker {
         self.variable_scope
             .last_mut()
             .expect("inserted variable when no scope existed")
-            .insert(id, ty);
+            .insert(id, ScopedVariable::Monomorphic(ty));
+    }
+
+    /// Inserts a `let` binding of a lambda, whose type is generalized, so that each use of it
+    /// can instantiate it at different types
+    pub(crate) fn insert_generalized_variable(
+        &mut self,
+        id: Identifier,
+        ty: TypeVariable,
+        lambda: &Expr,
+    ) {
+        let scope = Rc::new(self.variable_scope.clone());
+        self.variable_scope.last_mut().expect("inserted variable when no scope existed").insert(
+            id,
+            ScopedVariable::Generalized {
+                ty,
+                lambda: Rc::new(lambda.clone()),
+                scope,
+            },
+        );
     }
 
     pub fn fresh_ty_var(
@@ -386,12 +666,514 @@ impl TypeChecker {
         self.ctx.types.insert(ty)
     }
 
+    /// The type of a function value, whose parameter types are followed by its return type.
+    /// Unlike [`Self::arrow_type`], a function value with no parameters does not collapse into
+    /// its return type, as it still has to be called to produce it.
+    pub(crate) fn function_value_type(
+        &mut self,
+        tys: Vec<TypeVariable>,
+    ) -> TypeVariable {
+        self.ctx.types.insert(SpecificType::Arrow(tys))
+    }
+
+    /// If `ty` is already known to be the type of a function value, returns its parameter types
+    /// followed by its return type
+    pub(crate) fn known_function_value_type(
+        &self,
+        ty: TypeVariable,
+    ) -> Option<Vec<TypeVariable>> {
+        let mut ty = self.ctx.types.get(ty);
+        let mut seen_vars = BTreeSet::new();
+        while let SpecificType::Ref(t) = ty {
+            if !seen_vars.insert(*t) {
+                return None;
+            }
+            ty = self.ctx.types.get(*t);
+        }
+        match ty {
+            SpecificType::Arrow(tys) => Some(tys.clone()),
+            _ => None,
+        }
+    }
+
+    /// If `ty` is already known to be a user-defined type, returns the id of its declaration
+    fn known_user_defined_type(
+        &self,
+        mut ty: TypeVariable,
+    ) -> Option<TypeId> {
+        let mut seen_vars = BTreeSet::new();
+        loop {
+            match self.ctx.types.get(ty) {
+                SpecificType::Ref(t) if seen_vars.insert(*t) => ty = *t,
+                SpecificType::UserDefined { name, .. } => {
+                    return self.resolved.types().find(|(_, decl)| decl.name == *name).map(|(id, _)| id);
+                },
+                _ => return None,
+            }
+        }
+    }
+
+    /// The numeric type that `ty` is known to be before solving, if it is one
+    fn known_numeric_type(
+        &self,
+        mut ty: TypeVariable,
+    ) -> Option<SpecificType> {
+        let mut seen_vars = BTreeSet::new();
+        loop {
+            match self.ctx.types.get(ty) {
+                SpecificType::Ref(t) if seen_vars.insert(*t) => ty = *t,
+                ty => return ty.numeric_type(),
+            }
+        }
+    }
+
+    /// The type of the numeric operands of an intrinsic: the type of the first operand known to be
+    /// a fixed-width integer or a float, or `'int`
+    fn known_numeric_operand_ty(
+        &mut self,
+        operands: &[&TypedExpr],
+    ) -> TypeVariable {
+        let known = operands.iter().find_map(|operand| {
+            let ty = self.known_expr_ty(operand);
+            self.known_numeric_type(ty)
+        });
+        match known {
+            Some(ty) => self.insert_type(&ty),
+            None => self.int(),
+        }
+    }
+
+    /// Returns the user-defined type `id`, with its type parameters substituted by `args`, or by
+    /// fresh type variables if `args` is `None` and they should be inferred.
+    /// Every use of a generic type is a new instance of it, whose fields are references to its own
+    /// type variables. Types without type parameters are the same everywhere they are used, so
+    /// they aren't instantiated.
+    ///
+    /// A recursive generic type refers to the instance of itself that is being instantiated, so
+    /// its type arguments are the same all the way down.
+    pub(crate) fn instantiate_type(
+        &mut self,
+        id: TypeId,
+        args: Option<Vec<TypeVariable>>,
+    ) -> TypeVariable {
+        let decl = self.resolved.get_type(id).clone();
+        if decl.type_params.is_empty() {
+            return *self.get_type(id);
+        }
+        if let Some((_, instance)) = self.types_being_instantiated.iter().rev().find(|(ty, _)| *ty == id) {
+            return *instance;
+        }
+
+        let instance = self.fresh_ty_var(decl.name.span);
+        self.types_being_instantiated.push((id, instance));
+        let variants = self.with_type_scope(|ctx| {
+            for (ix, param) in decl.type_params.iter().enumerate() {
+                let arg = match args {
+                    Some(ref args) => args[ix],
+                    None => ctx.fresh_ty_var(param.span),
+                };
+                ctx.insert_variable(*param, arg);
+            }
+            decl.variants
+                .iter()
+                .map(|variant| {
+                    let fields = variant
+                        .fields
+                        .iter()
+                        .map(|field| SpecificType::Ref(ctx.to_type_var(&field.ty)))
+                        .collect::<Vec<_>>();
+                    TypeVariant {
+                        fields: fields.into_boxed_slice(),
+                    }
+                })
+                .collect()
+        });
+        self.types_being_instantiated.pop();
+
+        self.ctx.update_type(
+            instance,
+            SpecificType::UserDefined {
+                name: decl.name,
+                variants,
+                constant_literal_types: decl.constant_literal_types,
+            },
+        );
+        instance
+    }
+
+    /// Reports the types which have no values, because every one of their variants has a field
+    /// that refers back to the type itself, like `type Loop = Loop next 'Loop`. A recursive type
+    /// needs a variant without such a field, which its values can end with.
+    fn check_types_are_constructible(&mut self) {
+        fn is_constructible(
+            ty: &petr_resolve::Type,
+            constructible: &BTreeSet<TypeId>,
+        ) -> bool {
+            match ty {
+                petr_resolve::Type::Named(id) | petr_resolve::Type::Applied(id, _) => constructible.contains(id),
+                petr_resolve::Type::Sum(tys) => tys.iter().any(|ty| is_constructible(ty, constructible)),
+                _ => true,
+            }
+        }
+
+        let mut constructible = BTreeSet::new();
+        loop {
+            let mut changed = false;
+            for (id, decl) in self.resolved.types() {
+                if constructible.contains(&id) {
+                    continue;
+                }
+                if !decl.constant_literal_types.is_empty()
+                    || decl
+                        .variants
+                        .iter()
+                        .any(|variant| variant.fields.iter().all(|field| is_constructible(&field.ty, &constructible)))
+                {
+                    constructible.insert(id);
+                    changed = true;
+                }
+            }
+            if !changed {
+                break;
+            }
+        }
+
+        for (id, decl) in self.resolved.types() {
+            if !constructible.contains(&id) {
+                let name = self.get_symbol(decl.name.id).to_string();
+                self.errors.push(decl.name.span.with_item(TypeConstraintError::UnconstructibleType(name)));
+            }
+        }
+    }
+
+    /// The types of the fields of `variant`, in the user-defined type which `ty` refers to
+    fn variant_field_types(
+        &mut self,
+        ty: TypeVariable,
+        variant: usize,
+    ) -> Vec<TypeVariable> {
+        let mut ty = self.ctx.types.get(ty).clone();
+        let mut seen_vars = BTreeSet::new();
+        while let SpecificType::Ref(t) = ty {
+            if !seen_vars.insert(t) {
+                break;
+            }
+            ty = self.ctx.types.get(t).clone();
+        }
+        let SpecificType::UserDefined { variants, .. } = ty else {
+            return vec![];
+        };
+        variants[variant]
+            .fields
+            .iter()
+            .map(|field| self.ctx.types.insert(field.clone()))
+            .collect()
+    }
+
+    /// Generic functions are instantiated with fresh type variables at every call, so the type
+    /// that a call returns only depends on the arguments of that call. Returns the instantiated
+    /// parameter types and return type, and the type of each type parameter.
+    fn instantiate_signature(
+        &mut self,
+        function: FunctionId,
+        arg_types: &[TypeVariable],
+    ) -> InstantiatedSignature {
+        let func = self.get_untyped_function(function).clone();
+        let mut generics = vec![];
+        for ty in func.params.iter().map(|(_, ty)| ty).chain(std::iter::once(&func.return_type)) {
+            collect_generics(ty, &mut generics);
+        }
+        self.with_type_scope(|ctx| {
+            let mut type_params = Vec::with_capacity(generics.len());
+            // a parameter whose type is just a type parameter determines that type parameter
+            // directly, so that the fields of the returned value can be accessed without waiting
+            // for unification
+            for param in generics {
+                let bound_arg = func.params.iter().zip(arg_types).find_map(|((_, ty), arg_ty)| match ty {
+                    petr_resolve::Type::Generic(name) if name.id == param.id => Some(*arg_ty),
+                    _ => None,
+                });
+                let ty = match bound_arg {
+                    // a type parameter which must implement an interface is bound to the type
+                    // that implements it, rather than to a literal of that type
+                    Some(arg_ty) if func.constraints.iter().any(|(constrained, _)| constrained.id == param.id) => ctx.widen_literal(arg_ty),
+                    Some(arg_ty) => arg_ty,
+                    None => ctx.fresh_ty_var(param.span),
+                };
+                ctx.insert_variable(param, ty);
+                type_params.push((param, ty));
+            }
+            let params = func.params.iter().map(|(_, ty)| ctx.to_type_var(ty)).collect();
+            let return_ty = ctx.to_type_var(&func.return_type);
+            InstantiatedSignature {
+                params,
+                return_ty,
+                type_params,
+            }
+        })
+    }
+
+    /// If `ty` is a literal, returns a new type variable for the type of that literal
+    fn widen_literal(
+        &mut self,
+        ty: TypeVariable,
+    ) -> TypeVariable {
+        let mut resolved = self.ctx.types.get(ty);
+        let mut seen_vars = BTreeSet::new();
+        while let SpecificType::Ref(t) = resolved {
+            if !seen_vars.insert(*t) {
+                return ty;
+            }
+            resolved = self.ctx.types.get(*t);
+        }
+        let widened = match resolved {
+            SpecificType::Literal(petr_resolve::Literal::Integer(_)) => SpecificType::Integer,
+            SpecificType::Literal(petr_resolve::Literal::Float(_)) => SpecificType::Float,
+            SpecificType::Literal(petr_resolve::Literal::Boolean(_)) => SpecificType::Boolean,
+            SpecificType::Literal(petr_resolve::Literal::String(_)) => SpecificType::String,
+            _ => return ty,
+        };
+        self.ctx.types.insert(widened)
+    }
+
+    /// Checks that the functions implementing each method of an interface have the method's
+    /// signature, with the interface's type parameter replaced by the implementing type.
+    fn type_check_implementations(&mut self) {
+        let implementations = self.resolved.implementations().to_vec();
+        // the interface, implementing type and span of each implementation checked so far
+        let mut implemented: Vec<(InterfaceId, GeneralType, Span)> = Vec::new();
+        for implementation in implementations {
+            let interface = self.resolved.get_interface(implementation.interface).clone();
+            let ty = self.with_type_scope(|ctx| ctx.to_type_var(&implementation.ty));
+
+            let general_ty = self.look_up_variable(ty).generalize(self.ctx().types());
+            if let Some((_, _, previous)) = implemented
+                .iter()
+                .find(|(other, other_ty, _)| *other == implementation.interface && is_same_implementing_type(other_ty, &general_ty))
+            {
+                self.push_conflicting_implementation(&interface, &general_ty, *previous, implementation.span);
+                continue;
+            }
+            implemented.push((implementation.interface, general_ty, implementation.span));
+
+            for (method, function) in interface.methods.iter().zip(implementation.methods.iter()) {
+                let method_decl = self.get_untyped_function(*method).clone();
+                let (expected_params, expected_return_ty) = self.with_type_scope(|ctx| {
+                    ctx.insert_variable(interface.type_param, ty);
+                    let params = method_decl.params.iter().map(|(_, ty)| ctx.to_type_var(ty)).collect::<Vec<_>>();
+                    (params, ctx.to_type_var(&method_decl.return_type))
+                });
+                let signature = self.instantiate_signature(*function, &[]);
+                if signature.params.len() != expected_params.len() {
+                    let function_name = self.get_untyped_function(*function).name;
+                    self.push_error(implementation.span.with_item(TypeConstraintError::MethodParameterCountMismatch {
+                        function: self.get_symbol(function_name.id).to_string(),
+                        method:   self.get_symbol(method_decl.name.id).to_string(),
+                        expected: expected_params.len(),
+                        got:      signature.params.len(),
+                    }));
+                    continue;
+                }
+                for (expected, param) in expected_params.iter().zip(signature.params.iter()) {
+                    self.unify(*expected, *param, implementation.span);
+                }
+                self.unify(expected_return_ty, signature.return_ty, implementation.span);
+
+                // the body of an implementing function is checked against its return type like
+                // that of any other called function
+                let func_decl = self.get_function(function);
+                let param_types = func_decl
+                    .params
+                    .iter()
+                    .map(|(_, ty)| self.look_up_variable(*ty).generalize(self.ctx().types()))
+                    .collect();
+                self.monomorphize(&func_decl, (*function, param_types));
+            }
+            self.implementations.push(Implementation {
+                interface: implementation.interface,
+                ty,
+                methods: implementation.methods.clone(),
+            });
+        }
+    }
+
+    /// Reports that the implementation at `span` implements `interface` for `ty` again, after
+    /// the one at `previous`
+    fn push_conflicting_implementation(
+        &mut self,
+        interface: &petr_resolve::Interface,
+        ty: &GeneralType,
+        previous: Span,
+        span: Span,
+    ) {
+        let interface = self.get_symbol(interface.name.id).to_string();
+        let ty = pretty_printing::pretty_print_petr_type(&ty.safely_upcast(), self.ctx().types(), &self.resolved().interner);
+        let same_file = previous.source() == span.source();
+        self.push_error(span.with_item(TypeConstraintError::ConflictingImplementation {
+            interface: interface.clone(),
+            ty:        ty.clone(),
+            previous:  same_file.then(|| previous.span()),
+        }));
+        if !same_file {
+            let note = format!("`{interface}` is first implemented for `{ty}` here");
+            self.push_error(previous.with_item(TypeConstraintError::RelatedLocation(note)));
+        }
+    }
+
+    /// Whether `ty` has any type parameters, which differ between uses of it
+    fn is_generic(
+        &self,
+        ty: &petr_resolve::Type,
+    ) -> bool {
+        use petr_resolve::Type::*;
+        match ty {
+            Generic(_) => true,
+            Named(id) => !self.resolved.get_type(*id).type_params.is_empty(),
+            Applied(_, args) | Sum(args) | Tuple(args) => args.iter().any(|arg| self.is_generic(arg)),
+            Arrow(params, return_ty) => params.iter().any(|param| self.is_generic(param)) || self.is_generic(return_ty),
+            Alias(_, ty) => self.is_generic(ty),
+            Integer | SizedInteger(_) | Float | Bool | Unit | String | ErrorRecovery(_) | Literal(_) => false,
+        }
+    }
+
+    /// The type of `expr`, as far as it is known before solving. A variable's own type is only
+    /// unified with its binding's during solving, so this is the binding's type for variables.
+    pub(crate) fn known_expr_ty(
+        &mut self,
+        expr: &TypedExpr,
+    ) -> TypeVariable {
+        match expr.kind {
+            TypedExprKind::Variable { name, .. } => self.find_variable(name).unwrap_or_else(|| self.expr_ty(expr)),
+            _ => self.expr_ty(expr),
+        }
+    }
+
+    /// Looks up a field of the record that `target` evaluates to, returning the field's index and
+    /// type. Fields are accessed by name, so the record's type must already be known here rather
+    /// than inferred later.
+    pub(crate) fn record_field(
+        &mut self,
+        target: &TypedExpr,
+        field: Identifier,
+    ) -> Option<(usize, TypeVariable)> {
+        let target_ty = self.known_expr_ty(target);
+        let Some(type_id) = self.known_user_defined_type(target_ty) else {
+            self.push_error(target.span().with_item(TypeConstraintError::UnknownRecordType));
+            return None;
+        };
+        let decl = self.resolved.get_type(type_id).clone();
+        let type_name = self.get_symbol(decl.name.id).to_string();
+        let variant = match &decl.variants[..] {
+            [variant] if decl.constant_literal_types.is_empty() => variant,
+            _ => {
+                self.push_error(target.span().with_item(TypeConstraintError::NotARecord(type_name)));
+                return None;
+            },
+        };
+        let Some(field_index) = variant.fields.iter().position(|x| x.name.id == field.id) else {
+            let field_name = self.get_symbol(field.id).to_string();
+            self.push_error(field.span.with_item(TypeConstraintError::NoSuchField {
+                ty:    type_name,
+                field: field_name,
+            }));
+            return None;
+        };
+        Some((field_index, self.variant_field_types(target_ty, 0)[field_index]))
+    }
+
+    pub(crate) fn type_check_function_value(
+        &mut self,
+        function: FunctionId,
+        span: Span,
+    ) -> TypedExprKind {
+        let func_decl = self.get_function(&function);
+        let concrete_param_types: Vec<_> = func_decl
+            .params
+            .iter()
+            .map(|(_, ty)| self.look_up_variable(*ty).generalize(self.ctx().types()))
+            .collect();
+
+        // there is no call to infer the type parameters from, so only functions with concrete
+        // parameter types can be used as values
+        if concrete_param_types.iter().any(|ty| matches!(ty, GeneralType::Infer(..))) {
+            let name = self.get_symbol(func_decl.name.id).to_string();
+            self.push_error(span.with_item(TypeConstraintError::GenericFunctionValue(name)));
+            return TypedExprKind::ErrorRecovery(span);
+        }
+
+        let signature: FunctionSignature = (function, concrete_param_types.into_boxed_slice());
+        self.monomorphize(&func_decl, signature.clone());
+
+        let tys = func_decl
+            .params
+            .iter()
+            .map(|(_, ty)| *ty)
+            .chain(std::iter::once(func_decl.return_ty))
+            .collect();
+        TypedExprKind::FunctionValue {
+            signature,
+            ty: self.function_value_type(tys),
+        }
+    }
+
+    /// Creates the monomorphization of `func_decl` for the given concrete parameter types, if it
+    /// doesn't exist already.
+    fn monomorphize(
+        &mut self,
+        func_decl: &Function,
+        signature: FunctionSignature,
+    ) {
+        if self.monomorphized_functions().contains_key(&signature) {
+            return;
+        }
+
+        // unify declared return type with body return type
+        let declared_return_type = func_decl.return_ty;
+
+        self.satisfy_expr_return(declared_return_type, &func_decl.body);
+
+        // a call in a generic function may pass that function's type parameters along, in which
+        // case the callee is monomorphized when the generic function is lowered, once the type
+        // parameters are known
+        if signature.1.iter().any(GeneralType::contains_infer) {
+            return;
+        }
+
+        // to create a monomorphized func decl, we don't actually have to update all of the types
+        // throughout the entire definition. We only need to update the parameter types.
+        let mut monomorphized_func_decl = Function {
+            name:      func_decl.name,
+            params:    func_decl.params.clone(),
+            return_ty: declared_return_type,
+            body:      func_decl.body.clone(),
+            effects:   func_decl.effects.clone(),
+        };
+
+        // update the parameter types to be the concrete types
+        for (param, concrete_ty) in monomorphized_func_decl.params.iter_mut().zip(signature.1.iter()) {
+            let param_ty = self.insert_type(concrete_ty);
+            param.1 = param_ty;
+        }
+
+        // if there are any variable exprs in the body, update those ref types
+        let mut num_replacements = 0;
+        replace_var_reference_types(
+            &mut monomorphized_func_decl.body.kind,
+            &monomorphized_func_decl.params,
+            &mut num_replacements,
+        );
+
+        self.insert_monomorphized_function(signature, monomorphized_func_decl);
+    }
+
     pub fn to_petr_type(
         &mut self,
         ty: &petr_resolve::Type,
     ) -> SpecificType {
         match ty {
             petr_resolve::Type::Integer => SpecificType::Integer,
+            petr_resolve::Type::SizedInteger(ty) => SpecificType::integer(*ty),
+            petr_resolve::Type::Float => SpecificType::Float,
             petr_resolve::Type::Bool => SpecificType::Boolean,
             petr_resolve::Type::Unit => SpecificType::Unit,
             petr_resolve::Type::String => SpecificType::String,
@@ -399,7 +1181,11 @@ impl TypeChecker {
                 // unifies to anything, fresh var
                 SpecificType::ErrorRecovery
             },
-            petr_resolve::Type::Named(ty_id) => SpecificType::Ref(*self.type_map.get(&ty_id.into()).expect("type did not exist in type map")),
+            petr_resolve::Type::Named(ty_id) => SpecificType::Ref(self.instantiate_type(*ty_id, None)),
+            petr_resolve::Type::Applied(ty_id, args) => {
+                let args = args.iter().map(|arg| self.to_type_var(arg)).collect();
+                SpecificType::Ref(self.instantiate_type(*ty_id, Some(args)))
+            },
             petr_resolve::Type::Generic(generic_name) => {
                 // TODO don't create an ID and then reference it -- this is messy
                 let id = self.generic_type(generic_name);
@@ -407,6 +1193,16 @@ impl TypeChecker {
             },
             petr_resolve::Type::Sum(tys) => SpecificType::Sum(tys.iter().map(|ty| self.to_petr_type(ty)).collect()),
             petr_resolve::Type::Literal(l) => SpecificType::Literal(l.clone()),
+            petr_resolve::Type::Arrow(params, return_ty) => {
+                let tys = params
+                    .iter()
+                    .chain(std::iter::once(&**return_ty))
+                    .map(|ty| self.to_type_var(ty))
+                    .collect();
+                SpecificType::Arrow(tys)
+            },
+            petr_resolve::Type::Tuple(tys) => SpecificType::Tuple(tys.iter().map(|ty| SpecificType::Ref(self.to_type_var(ty))).collect()),
+            petr_resolve::Type::Alias(_, ty) => self.to_petr_type(ty),
         }
     }
 
@@ -415,7 +1211,68 @@ impl TypeChecker {
         ty: &petr_resolve::Type,
     ) -> TypeVariable {
         let petr_ty = self.to_petr_type(ty);
-        self.ctx.types.insert(petr_ty)
+        let petr_ty = match (ty, petr_ty) {
+            // errors refer to the types of type aliases by their alias, so an alias of a
+            // user-defined type gets a copy of the type rather than a reference to it, which
+            // unification would see through
+            (petr_resolve::Type::Alias(..), SpecificType::Ref(var)) if matches!(self.ctx.types.get(var), SpecificType::UserDefined { .. }) => {
+                self.ctx.types.get(var).clone()
+            },
+            (_, petr_ty) => petr_ty,
+        };
+        let var = self.ctx.types.insert(petr_ty);
+        if let petr_resolve::Type::Alias(name, _) = ty {
+            self.ctx.aliases.insert(var, *name);
+        }
+        var
+    }
+
+    /// Constrains the type of a match expression's scrutinee with a pattern, and inserts the
+    /// pattern's bindings into the current variable scope.
+    pub(crate) fn type_check_pattern(
+        &mut self,
+        pattern: &SpannedItem<Pattern>,
+        scrutinee_ty: TypeVariable,
+    ) {
+        match pattern.item() {
+            Pattern::Wildcard => (),
+            Pattern::Literal(lit) => {
+                let lit_ty = self.convert_literal_to_type(lit);
+                self.satisfies(scrutinee_ty, lit_ty, pattern.span());
+            },
+            Pattern::Variant { ty, variant, fields } => {
+                // if the scrutinee is already known to be an instance of this type, its fields
+                // have the types of that instance's type arguments
+                let ty_var = if self.known_user_defined_type(scrutinee_ty) == Some(*ty) {
+                    scrutinee_ty
+                } else {
+                    let ty_var = self.instantiate_type(*ty, None);
+                    self.unify(ty_var, scrutinee_ty, pattern.span());
+                    ty_var
+                };
+
+                let decl = self.resolved.get_type(*ty).clone();
+                let field_types = if *variant < decl.variants.len() {
+                    self.variant_field_types(ty_var, *variant)
+                } else {
+                    // the constant literal constructor takes one of the literals as its only field
+                    vec![self.to_type_var(&petr_resolve::Type::Sum(
+                        decl.constant_literal_types.into_iter().map(petr_resolve::Type::Literal).collect(),
+                    ))]
+                };
+
+                for (field, field_ty) in fields.iter().zip(field_types) {
+                    match field.item() {
+                        FieldPattern::Binding(name) => self.insert_variable(*name, field_ty),
+                        FieldPattern::Wildcard => (),
+                        FieldPattern::Literal(lit) => {
+                            let lit_ty = self.convert_literal_to_type(lit);
+                            self.satisfies(field_ty, lit_ty, field.span());
+                        },
+                    }
+                }
+            },
+        }
     }
 
     pub fn get_type(
@@ -508,7 +1365,18 @@ impl TypeChecker {
             ErrorRecovery(..) => self.ctx.error_recovery,
             ExprWithBindings { expression, .. } => self.expr_ty(expression),
             TypeConstructor { ty, .. } => *ty,
+            InterfaceMethod { ty, .. } => *ty,
             If { then_branch, .. } => self.expr_ty(then_branch),
+            Match { ty, .. } => *ty,
+            FunctionValue { ty, .. } => *ty,
+            Lambda { ty, .. } => *ty,
+            ClosureCall { ty, .. } => *ty,
+            GeneralizedLambda { lambda } => self.expr_ty(lambda),
+            Instantiation { instance, .. } => self.expr_ty(instance),
+            FieldAccess { ty, .. } => *ty,
+            RecordUpdate { ty, .. } => *ty,
+            Tuple { ty, .. } => *ty,
+            Hole { ty, .. } => *ty,
         }
     }
 
@@ -538,6 +1406,10 @@ impl TypeChecker {
         self.ctx.bool_ty
     }
 
+    pub fn float(&mut self) -> TypeVariable {
+        self.insert_type(&SpecificType::Float)
+    }
+
     pub fn errors(&self) -> &[TypeError] {
         &self.errors
     }
@@ -589,6 +1461,7 @@ impl TypeChecker {
                 TypeConstraintKind::Unify(t1, t2) => (vec![*t1, *t2], Kind::Unify),
                 TypeConstraintKind::Satisfies(t1, t2) => (vec![*t1, *t2], Kind::Satisfies),
                 TypeConstraintKind::Axiom(t1) => (vec![*t1], Kind::Axiom),
+                TypeConstraintKind::NarrowTo(t1, t2, t3) | TypeConstraintKind::NarrowExcluding(t1, t2, t3) => (vec![*t1, *t2, *t3], Kind::Narrow),
             };
 
             // resolve all `Ref` types to get a resolved type variable
@@ -666,18 +1539,36 @@ impl ConstraintDeduplicator {
     }
 }
 
+/// Type checks the operands of an arithmetic or comparison intrinsic, returning them and the type
+/// they both have. They are `'int`s, unless either is already known to be a fixed-width integer or
+/// a float.
 pub fn unify_basic_math_op(
     lhs: &Expr,
     rhs: &Expr,
     ctx: &mut TypeChecker,
+) -> (TypedExpr, TypedExpr, TypeVariable) {
+    let lhs = lhs.type_check(ctx);
+    let rhs = rhs.type_check(ctx);
+    let lhs_ty = ctx.expr_ty(&lhs);
+    let rhs_ty = ctx.expr_ty(&rhs);
+    let operand_ty = ctx.known_numeric_operand_ty(&[&lhs, &rhs]);
+    ctx.unify(operand_ty, lhs_ty, lhs.span());
+    ctx.unify(operand_ty, rhs_ty, rhs.span());
+    (lhs, rhs, operand_ty)
+}
+
+pub fn unify_boolean_op(
+    lhs: &Expr,
+    rhs: &Expr,
+    ctx: &mut TypeChecker,
 ) -> (TypedExpr, TypedExpr) {
     let lhs = lhs.type_check(ctx);
     let rhs = rhs.type_check(ctx);
     let lhs_ty = ctx.expr_ty(&lhs);
     let rhs_ty = ctx.expr_ty(&rhs);
-    let int_ty = ctx.int();
-    ctx.unify(int_ty, lhs_ty, lhs.span());
-    ctx.unify(int_ty, rhs_ty, rhs.span());
+    let bool_ty = ctx.bool();
+    ctx.unify(bool_ty, lhs_ty, lhs.span());
+    ctx.unify(bool_ty, rhs_ty, rhs.span());
     (lhs, rhs)
 }
 
@@ -698,21 +1589,58 @@ impl TypeCheck for petr_resolve::Function {
                 ctx.axiom(*ty, name.span);
             }
 
+            // within the body, the constrained type parameters are assumed to implement their
+            // interfaces
+            let assumptions = self
+                .constraints
+                .iter()
+                .map(|(param, interface)| (ctx.generic_type(param), *interface))
+                .collect();
+            let enclosing_assumptions = std::mem::replace(&mut ctx.assumptions, assumptions);
+
             // unify types within the body with the parameter
             let body = self.body.type_check(ctx);
+            ctx.assumptions = enclosing_assumptions;
 
             let declared_return_type = ctx.to_type_var(&self.return_type);
+            // the body isn't checked against the declared return type, but a hole that the body
+            // returns is expected to have it
+            let mut returned_holes = vec![];
+            returned_holes_of(&body, &mut returned_holes);
+            for hole in returned_holes {
+                ctx.satisfies(declared_return_type, hole, body.span());
+            }
 
             Function {
                 name: self.name,
                 params,
                 return_ty: declared_return_type,
                 body,
+                effects: self.effects.clone(),
             }
         })
     }
 }
 
+/// The types of the holes which are the value of `expr`, rather than part of it
+fn returned_holes_of(
+    expr: &TypedExpr,
+    holes: &mut Vec<TypeVariable>,
+) {
+    match &expr.kind {
+        TypedExprKind::Hole { ty, .. } => holes.push(*ty),
+        TypedExprKind::ExprWithBindings { expression, .. } => returned_holes_of(expression, holes),
+        TypedExprKind::If {
+            then_branch, else_branch, ..
+        } => {
+            returned_holes_of(then_branch, holes);
+            returned_holes_of(else_branch, holes);
+        },
+        TypedExprKind::Match { arms, .. } => arms.iter().for_each(|arm| returned_holes_of(&arm.body, holes)),
+        _ => (),
+    }
+}
+
 impl TypeCheck for FunctionCall {
     type Output = TypedExprKind;
 
@@ -742,61 +1670,90 @@ impl TypeCheck for FunctionCall {
             args.push((*name, arg, arg_ty));
         }
 
-        let concrete_arg_types: Vec<_> = args
-            .iter()
-            .map(|(_, _, ty)| ctx.look_up_variable(*ty).generalize(ctx.ctx().types()).clone())
-            .collect();
-
-        let signature: FunctionSignature = (self.function, concrete_arg_types.clone().into_boxed_slice());
-        // now that we know the argument types, check if this signature has been monomorphized
-        // already
-        if ctx.monomorphized_functions().contains_key(&signature) {
-            return TypedExprKind::FunctionCall {
-                func: self.function,
-                args: args.into_iter().map(|(name, expr, _)| (name, expr)).collect(),
-                ty:   func_decl.return_ty,
-            };
-        }
-
-        // unify declared return type with body return type
-        let declared_return_type = func_decl.return_ty;
-
-        ctx.satisfy_expr_return(declared_return_type, &func_decl.body);
-
-        // to create a monomorphized func decl, we don't actually have to update all of the types
-        // throughout the entire definition. We only need to update the parameter types.
-        let mut monomorphized_func_decl = Function {
-            name:      func_decl.name,
-            params:    func_decl.params.clone(),
-            return_ty: declared_return_type,
-            body:      func_decl.body.clone(),
-        };
+        // the arguments determine the type parameters of this call's return type
+        let untyped_func = ctx.get_untyped_function(self.function).clone();
+        let instantiated_signature = (ctx.is_generic(&untyped_func.return_type) || !untyped_func.constraints.is_empty()).then(|| {
+            let arg_types: Vec<_> = args.iter().map(|(_, arg, _)| ctx.known_expr_ty(arg)).collect();
+            ctx.instantiate_signature(self.function, &arg_types)
+        });
+        if let Some(ref signature) = instantiated_signature {
+            for (ix, (_, arg, arg_ty)) in args.iter().enumerate() {
+                if ctx.is_generic(&untyped_func.params[ix].1) {
+                    ctx.unify(signature.params[ix], *arg_ty, arg.span());
+                }
+            }
 
-        // update the parameter types to be the concrete types
-        for (param, concrete_ty) in monomorphized_func_decl.params.iter_mut().zip(concrete_arg_types.iter()) {
-            let param_ty = ctx.insert_type(concrete_ty);
-            param.1 = param_ty;
+            // the types that constrained type parameters are instantiated with must implement
+            // their interfaces
+            for (param, interface) in &untyped_func.constraints {
+                let Some((_, ty)) = signature.type_params.iter().find(|(type_param, _)| type_param.id == param.id) else {
+                    continue;
+                };
+                ctx.obligations.push(InterfaceObligation {
+                    ty: *ty,
+                    interface: *interface,
+                    interface_name: ctx.resolved.get_interface(*interface).name,
+                    span: self.span(),
+                    assumptions: ctx.assumptions.clone(),
+                });
+            }
         }
 
-        // if there are any variable exprs in the body, update those ref types
-        let mut num_replacements = 0;
-        replace_var_reference_types(
-            &mut monomorphized_func_decl.body.kind,
-            &monomorphized_func_decl.params,
-            &mut num_replacements,
-        );
+        let concrete_arg_types: Vec<_> = args
+            .iter()
+            .zip(func_decl.params.iter())
+            .map(|((_, _, arg_ty), (_, param_ty))| {
+                // an integer literal is only known to be an `'int`, so an argument to a
+                // fixed-width integer parameter has the parameter's type
+                match ctx.look_up_variable(*param_ty).generalize(ctx.ctx().types()) {
+                    ty @ GeneralType::SizedInteger(_) => ty,
+                    _ => ctx.look_up_variable(*arg_ty).generalize(ctx.ctx().types()).clone(),
+                }
+            })
+            .collect();
 
-        ctx.insert_monomorphized_function(signature, monomorphized_func_decl);
-        // if there are any variable exprs in the body, update those ref types
+        let signature: FunctionSignature = (self.function, concrete_arg_types.into_boxed_slice());
+        // now that we know the argument types, monomorphize the function for them
+        ctx.monomorphize(&func_decl, signature);
 
         TypedExprKind::FunctionCall {
             func: self.function,
             args: args.into_iter().map(|(name, expr, _)| (name, expr)).collect(),
-            ty:   declared_return_type,
+            ty:   instantiated_signature.map(|signature| signature.return_ty).unwrap_or(func_decl.return_ty),
         }
     }
 }
 
+/// A generic function's signature, instantiated for one call, see
+/// [`TypeChecker::instantiate_signature`]
+struct InstantiatedSignature {
+    params:      Vec<TypeVariable>,
+    return_ty:   TypeVariable,
+    type_params: Vec<(Identifier, TypeVariable)>,
+}
+
+/// Collects the names of the type parameters that appear in `ty`
+fn collect_generics(
+    ty: &petr_resolve::Type,
+    generics: &mut Vec<Identifier>,
+) {
+    use petr_resolve::Type::*;
+    match ty {
+        Generic(name) => {
+            if !generics.iter().any(|x| x.id == name.id) {
+                generics.push(*name);
+            }
+        },
+        Applied(_, args) | Sum(args) | Tuple(args) => args.iter().for_each(|arg| collect_generics(arg, generics)),
+        Arrow(params, return_ty) => {
+            params.iter().for_each(|param| collect_generics(param, generics));
+            collect_generics(return_ty, generics);
+        },
+        Alias(_, ty) => collect_generics(ty, generics),
+        Named(_) | Integer | SizedInteger(_) | Float | Bool | Unit | String | ErrorRecovery(_) | Literal(_) => (),
+    }
+}
+
 impl TypeCheck for SpannedItem<petr_resolve::Intrinsic> {
     type Output = TypedExpr;
 
@@ -822,21 +1779,21 @@ impl TypeCheck for SpannedItem<petr_resolve::Intrinsic> {
                 if self.item().args.len() != 2 {
                     todo!("add arg len check");
                 }
-                let (lhs, rhs) = unify_basic_math_op(&self.item().args[0], &self.item().args[1], ctx);
+                let (lhs, rhs, ty) = unify_basic_math_op(&self.item().args[0], &self.item().args[1], ctx);
                 TypedExprKind::Intrinsic {
                     intrinsic: crate::Intrinsic::Add(Box::new(lhs), Box::new(rhs)),
-                    ty:        ctx.int(),
+                    ty,
                 }
             },
             Subtract => {
                 if self.item().args.len() != 2 {
                     todo!("sub arg len check");
                 }
-                let (lhs, rhs) = unify_basic_math_op(&self.item().args[0], &self.item().args[1], ctx);
+                let (lhs, rhs, ty) = unify_basic_math_op(&self.item().args[0], &self.item().args[1], ctx);
 
                 TypedExprKind::Intrinsic {
                     intrinsic: crate::Intrinsic::Subtract(Box::new(lhs), Box::new(rhs)),
-                    ty:        ctx.int(),
+                    ty,
                 }
             },
             Multiply => {
@@ -844,10 +1801,10 @@ impl TypeCheck for SpannedItem<petr_resolve::Intrinsic> {
                     todo!("mult arg len check");
                 }
 
-                let (lhs, rhs) = unify_basic_math_op(&self.item().args[0], &self.item().args[1], ctx);
+                let (lhs, rhs, ty) = unify_basic_math_op(&self.item().args[0], &self.item().args[1], ctx);
                 TypedExprKind::Intrinsic {
                     intrinsic: crate::Intrinsic::Multiply(Box::new(lhs), Box::new(rhs)),
-                    ty:        ctx.int(),
+                    ty,
                 }
             },
 
@@ -856,10 +1813,10 @@ impl TypeCheck for SpannedItem<petr_resolve::Intrinsic> {
                     todo!("Divide arg len check");
                 }
 
-                let (lhs, rhs) = unify_basic_math_op(&self.item().args[0], &self.item().args[1], ctx);
+                let (lhs, rhs, ty) = unify_basic_math_op(&self.item().args[0], &self.item().args[1], ctx);
                 TypedExprKind::Intrinsic {
                     intrinsic: crate::Intrinsic::Divide(Box::new(lhs), Box::new(rhs)),
-                    ty:        ctx.int(),
+                    ty,
                 }
             },
             Malloc => {
@@ -905,12 +1862,129 @@ impl TypeCheck for SpannedItem<petr_resolve::Intrinsic> {
                     ty:        ctx.bool(),
                 }
             },
+            NotEquals => {
+                if self.item().args.len() != 2 {
+                    return intrinsic_arity_error(self, 2, ctx);
+                }
+
+                let lhs = self.item().args[0].type_check(ctx);
+                let rhs = self.item().args[1].type_check(ctx);
+                ctx.unify(ctx.expr_ty(&lhs), ctx.expr_ty(&rhs), self.span());
+                TypedExprKind::Intrinsic {
+                    intrinsic: crate::Intrinsic::NotEquals(Box::new(lhs), Box::new(rhs)),
+                    ty:        ctx.bool(),
+                }
+            },
+            LessThan | GreaterThan | LessThanOrEquals | GreaterThanOrEquals => {
+                if self.item().args.len() != 2 {
+                    return intrinsic_arity_error(self, 2, ctx);
+                }
+
+                let (lhs, rhs, _) = unify_basic_math_op(&self.item().args[0], &self.item().args[1], ctx);
+                let (lhs, rhs) = (Box::new(lhs), Box::new(rhs));
+                let intrinsic = match self.item().intrinsic {
+                    LessThan => crate::Intrinsic::LessThan(lhs, rhs),
+                    GreaterThan => crate::Intrinsic::GreaterThan(lhs, rhs),
+                    LessThanOrEquals => crate::Intrinsic::LessThanOrEquals(lhs, rhs),
+                    GreaterThanOrEquals => crate::Intrinsic::GreaterThanOrEquals(lhs, rhs),
+                    _ => unreachable!("only comparison intrinsics are handled here"),
+                };
+                TypedExprKind::Intrinsic { intrinsic, ty: ctx.bool() }
+            },
+            And | Or => {
+                if self.item().args.len() != 2 {
+                    return intrinsic_arity_error(self, 2, ctx);
+                }
+
+                let (lhs, rhs) = unify_boolean_op(&self.item().args[0], &self.item().args[1], ctx);
+                let (lhs, rhs) = (Box::new(lhs), Box::new(rhs));
+                let intrinsic = match self.item().intrinsic {
+                    And => crate::Intrinsic::And(lhs, rhs),
+                    Or => crate::Intrinsic::Or(lhs, rhs),
+                    _ => unreachable!("only boolean intrinsics are handled here"),
+                };
+                TypedExprKind::Intrinsic { intrinsic, ty: ctx.bool() }
+            },
+            Not => {
+                if self.item().args.len() != 1 {
+                    return intrinsic_arity_error(self, 1, ctx);
+                }
+                let arg = self.item().args[0].type_check(ctx);
+                let arg_ty = ctx.expr_ty(&arg);
+                let bool_ty = ctx.bool();
+                ctx.unify(bool_ty, arg_ty, arg.span());
+                TypedExprKind::Intrinsic {
+                    intrinsic: crate::Intrinsic::Not(Box::new(arg)),
+                    ty:        bool_ty,
+                }
+            },
+            ConvertTo(to) => {
+                if self.item().args.len() != 1 {
+                    return intrinsic_arity_error(self, 1, ctx);
+                }
+                let arg = self.item().args[0].type_check(ctx);
+                let arg_ty = ctx.expr_ty(&arg);
+                let from_ty = ctx.known_numeric_operand_ty(&[&arg]);
+                ctx.unify(from_ty, arg_ty, arg.span());
+                let ty = ctx.insert_type(&SpecificType::integer(to));
+                TypedExprKind::Intrinsic {
+                    intrinsic: crate::Intrinsic::ConvertTo(to, Box::new(arg)),
+                    ty,
+                }
+            },
+            ToFloat => {
+                if self.item().args.len() != 1 {
+                    return intrinsic_arity_error(self, 1, ctx);
+                }
+                let arg = self.item().args[0].type_check(ctx);
+                let arg_ty = ctx.expr_ty(&arg);
+                let from_ty = ctx.known_numeric_operand_ty(&[&arg]);
+                ctx.unify(from_ty, arg_ty, arg.span());
+                TypedExprKind::Intrinsic {
+                    intrinsic: crate::Intrinsic::ToFloat(Box::new(arg)),
+                    ty:        ctx.float(),
+                }
+            },
+            Sqrt | Floor => {
+                if self.item().args.len() != 1 {
+                    return intrinsic_arity_error(self, 1, ctx);
+                }
+                let arg = self.item().args[0].type_check(ctx);
+                let arg_ty = ctx.expr_ty(&arg);
+                let float_ty = ctx.float();
+                ctx.unify(float_ty, arg_ty, arg.span());
+                let arg = Box::new(arg);
+                let intrinsic = match self.item().intrinsic {
+                    Sqrt => crate::Intrinsic::Sqrt(arg),
+                    Floor => crate::Intrinsic::Floor(arg),
+                    _ => unreachable!("only float intrinsics are handled here"),
+                };
+                TypedExprKind::Intrinsic { intrinsic, ty: float_ty }
+            },
         };
 
         TypedExpr { kind, span: self.span() }
     }
 }
 
+/// Reports that `intrinsic` wasn't called with the `expected` number of arguments, and recovers
+/// from it
+fn intrinsic_arity_error(
+    intrinsic: &SpannedItem<petr_resolve::Intrinsic>,
+    expected: usize,
+    ctx: &mut TypeChecker,
+) -> TypedExpr {
+    ctx.push_error(intrinsic.span().with_item(TypeConstraintError::ArgumentCountMismatch {
+        function: format!("@{}", intrinsic.item().intrinsic),
+        expected,
+        got: intrinsic.item().args.len(),
+    }));
+    TypedExpr {
+        kind: TypedExprKind::ErrorRecovery(intrinsic.span()),
+        span: intrinsic.span(),
+    }
+}
+
 fn replace_var_reference_types(
     expr: &mut TypedExprKind,
     params: &Vec<(Identifier, TypeVariable)>,
@@ -932,16 +2006,54 @@ fn replace_var_reference_types(
             use crate::Intrinsic::*;
             match intrinsic {
                 // intrinsics which take one arg, grouped for convenience
-                Puts(a) | Malloc(a) | SizeOf(a) => {
+                Puts(a) | Malloc(a) | SizeOf(a) | Not(a) | ConvertTo(_, a) | ToFloat(a) | Sqrt(a) | Floor(a) => {
                     replace_var_reference_types(&mut a.kind, params, num_replacements);
                 },
                 // intrinsics which take two args, grouped for convenience
-                Add(a, b) | Subtract(a, b) | Multiply(a, b) | Divide(a, b) | Equals(a, b) => {
+                Add(a, b)
+                | Subtract(a, b)
+                | Multiply(a, b)
+                | Divide(a, b)
+                | Equals(a, b)
+                | NotEquals(a, b)
+                | LessThan(a, b)
+                | GreaterThan(a, b)
+                | LessThanOrEquals(a, b)
+                | GreaterThanOrEquals(a, b)
+                | And(a, b)
+                | Or(a, b) => {
                     replace_var_reference_types(&mut a.kind, params, num_replacements);
                     replace_var_reference_types(&mut b.kind, params, num_replacements);
                 },
             }
         },
+        TypedExprKind::FieldAccess { target, .. } => replace_var_reference_types(&mut target.kind, params, num_replacements),
+        TypedExprKind::RecordUpdate { target, fields, .. } => {
+            replace_var_reference_types(&mut target.kind, params, num_replacements);
+            for (_, value) in fields {
+                replace_var_reference_types(&mut value.kind, params, num_replacements);
+            }
+        },
+        TypedExprKind::ClosureCall { callee, args, .. } => {
+            replace_var_reference_types(&mut callee.kind, params, num_replacements);
+            for arg in args {
+                replace_var_reference_types(&mut arg.kind, params, num_replacements);
+            }
+        },
+        TypedExprKind::Lambda {
+            params: lambda_params, body, ..
+        } => {
+            // the lambda's own parameters shadow the outer ones
+            let params = params
+                .iter()
+                .filter(|(name, _)| !lambda_params.iter().any(|(lambda_param, _)| lambda_param.id == name.id))
+                .cloned()
+                .collect();
+            replace_var_reference_types(&mut body.kind, &params, num_replacements);
+        },
+        TypedExprKind::GeneralizedLambda { lambda } | TypedExprKind::Instantiation { instance: lambda, .. } => {
+            replace_var_reference_types(&mut lambda.kind, params, num_replacements)
+        },
         // TODO other expr kinds like bindings
         _ => (),
     }
@@ -1,23 +1,125 @@
-use miette::Diagnostic;
+use miette::{Diagnostic, SourceSpan};
 use thiserror::Error;
 
-#[derive(Error, Debug, Diagnostic, PartialEq, Clone, Ord, PartialOrd, Eq)]
+#[derive(Error, Debug, Diagnostic, PartialEq, Clone, Eq)]
 pub enum TypeConstraintError {
-    #[error("failed to unify types: `{0}` and `{1}`")]
-    UnificationFailure(String, String),
-    #[error("type `{0}` does not satisfy the constraints of type {1}")]
-    FailedToSatisfy(String, String),
-    #[error("type `{1}` is not a subtype of sum type `{}`", .0.join(" | "))]
-    NotSubtype(Vec<String>, String),
+    #[error("failed to unify types: `{expected}` and `{found}`")]
+    #[diagnostic(code(E0301))]
+    UnificationFailure {
+        expected:    String,
+        found:       String,
+        #[label("expected `{expected}` because of this type annotation")]
+        annotation:  Option<SourceSpan>,
+        #[label("expected `{expected}` because of this")]
+        inferred_at: Option<SourceSpan>,
+        #[label("found `{found}` here")]
+        found_at:    SourceSpan,
+        #[help]
+        help:        Option<String>,
+    },
+    #[error("type `{expected}` does not satisfy the constraints of type {found}")]
+    #[diagnostic(code(E0302))]
+    FailedToSatisfy {
+        expected:    String,
+        found:       String,
+        #[label("expected `{expected}` because of this type annotation")]
+        annotation:  Option<SourceSpan>,
+        #[label("expected `{expected}` because of this")]
+        inferred_at: Option<SourceSpan>,
+        #[label("found `{found}` here")]
+        found_at:    SourceSpan,
+        #[help]
+        help:        Option<String>,
+    },
+    #[error("type `{found}` is not a subtype of sum type `{sum}`")]
+    #[diagnostic(
+        code(E0303),
+        help("the values of a sum type are the values of the types in it, so `{found}` can only be used if it is one of them")
+    )]
+    NotSubtype {
+        sum:         String,
+        found:       String,
+        #[label("expected `{sum}` because of this type annotation")]
+        annotation:  Option<SourceSpan>,
+        #[label("expected `{sum}` because of this")]
+        inferred_at: Option<SourceSpan>,
+        #[label("found `{found}` here")]
+        found_at:    SourceSpan,
+    },
     #[error("Function {function} takes {expected:?} arguments, but got {got:?} arguments.")]
+    #[diagnostic(code(E0304))]
     ArgumentCountMismatch { function: String, expected: usize, got: usize },
     #[error("type could not be inferred")]
+    #[diagnostic(code(E0305))]
     UnknownInference,
     #[error("internal compiler error: {0}")]
+    #[diagnostic(code(E0306))]
     Internal(String),
     // TODO better errors here
     #[error("This type references itself in a circular way")]
+    #[diagnostic(code(E0307))]
     CircularType,
+    #[error("type `{0}` has no values, as every one of its variants refers back to it")]
+    #[diagnostic(
+        code(E0308),
+        help("add a variant which doesn't refer to `{0}`, like `Nil` or `Leaf`, for its values to end with")
+    )]
+    UnconstructibleType(String),
     #[error("Type {1} is not castable to type {0}")]
+    #[diagnostic(code(E0309))]
     InvalidTypeUpdate(String, String),
+    #[error("match is not exhaustive, these cases are not covered: {}", .0.iter().map(|case| format!("`{case}`")).collect::<Vec<_>>().join(", "))]
+    #[diagnostic(code(E0310), help("add an arm for each missing case, or a wildcard `_` arm to cover the rest"))]
+    NonExhaustiveMatch(Vec<String>),
+    #[error("generic function `{0}` cannot be used as a value")]
+    #[diagnostic(code(E0311), help("wrap it in a lambda with concrete parameter types, like `\\(x in 'int) => ~{0} x`"))]
+    GenericFunctionValue(String),
+    #[error("the type of this value must be known before its fields can be accessed")]
+    #[diagnostic(
+        code(E0312),
+        help("fields can be accessed on function parameters and bindings whose record type is known")
+    )]
+    UnknownRecordType,
+    #[error("type `{0}` is not a record, so its fields can't be accessed by name")]
+    #[diagnostic(
+        code(E0313),
+        help("only types with exactly one variant have named fields; use `match` to read the fields of other types")
+    )]
+    NotARecord(String),
+    #[error("type `{ty}` has no field named `{field}`")]
+    #[diagnostic(code(E0314))]
+    NoSuchField { ty: String, field: String },
+    #[error("type `{ty}` does not implement interface `{interface}`")]
+    #[diagnostic(code(E0315), help("implement it with `implement {interface} for '{ty} with ...`"))]
+    MissingImplementation { interface: String, ty: String },
+    #[error("this type parameter is not known to implement interface `{0}`")]
+    #[diagnostic(code(E0316), help("constrain the type parameter in the function's signature, like `where 'A: {0}`"))]
+    UnprovenConstraint(String),
+    #[error("`{function}` implements method `{method}`, which takes {expected} parameters, but `{function}` takes {got}")]
+    #[diagnostic(code(E0317))]
+    MethodParameterCountMismatch {
+        function: String,
+        method:   String,
+        expected: usize,
+        got:      usize,
+    },
+    #[error("unreachable match arm: the arms above it already cover every value it matches")]
+    #[diagnostic(code(E0318), severity(Warning))]
+    UnreachableMatchArm,
+    #[error("interface `{interface}` is implemented for type `{ty}` more than once")]
+    #[diagnostic(code(E0320), help("remove all but one of the implementations"))]
+    ConflictingImplementation {
+        interface: String,
+        ty:        String,
+        #[label("`{interface}` is first implemented for `{ty}` here")]
+        previous:  Option<SourceSpan>,
+    },
+    /// Points out a place in another file than the error reported before it, which labels can't
+    /// point into
+    #[error("{0}")]
+    #[diagnostic(severity(Advice))]
+    RelatedLocation(String),
+    #[error("hole `{hole}` expects {expected}")]
+    #[diagnostic(code(E0319), severity(Warning), help("{in_scope}"))]
+    TypedHole { hole: String, expected: String, in_scope: String },
 }
@@ -0,0 +1,394 @@
+//! Checks that `match` expressions cover every value of their scrutinee, and that every arm of a
+//! match can be reached. This runs after the constraints have been solved, as it needs to know the
+//! type of each scrutinee.
+//!
+//! The check is based on the notion of whether or not a pattern is _useful_ with respect to the
+//! patterns before it, i.e. whether there exists a value which it matches and none of the previous
+//! patterns do. An arm which is not useful is unreachable, and a match is exhaustive if a wildcard
+//! arm added to the end of it would not be useful.
+
+use petr_resolve::{FieldPattern, Literal, Pattern};
+use petr_utils::Identifier;
+
+use crate::{
+    solution::TypeSolution,
+    typed_ast::{Intrinsic, TypedExpr, TypedExprKind as Kind},
+    SpecificType, TypeConstraintError, TypeError,
+};
+
+/// A simplified pattern, where bindings are just wildcards.
+#[derive(Clone, Debug)]
+enum Pat {
+    Wildcard,
+    Constructor(Constructor, Vec<Pat>),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Constructor {
+    /// A variant of a user-defined type, by index. Like in [`Pattern::Variant`], the index after
+    /// the last named variant is the constant literal variant, which has a single field.
+    Variant(usize),
+    Literal(Literal),
+}
+
+impl From<&Pattern> for Pat {
+    fn from(pattern: &Pattern) -> Self {
+        match pattern {
+            Pattern::Wildcard => Pat::Wildcard,
+            Pattern::Literal(lit) => Pat::Constructor(Constructor::Literal(lit.clone()), vec![]),
+            Pattern::Variant { variant, fields, .. } => Pat::Constructor(
+                Constructor::Variant(*variant),
+                fields
+                    .iter()
+                    .map(|field| match field.item() {
+                        FieldPattern::Binding(_) | FieldPattern::Wildcard => Pat::Wildcard,
+                        FieldPattern::Literal(lit) => Pat::Constructor(Constructor::Literal(lit.clone()), vec![]),
+                    })
+                    .collect(),
+            ),
+        }
+    }
+}
+
+pub(crate) struct ExhaustivenessChecker<'a> {
+    solution:      &'a TypeSolution,
+    /// The names of the variants of each user-defined type, used to render missing cases
+    variant_names: &'a [(Identifier, Box<[Identifier]>)],
+    errors:        Vec<TypeError>,
+    warnings:      Vec<TypeError>,
+}
+
+impl<'a> ExhaustivenessChecker<'a> {
+    pub(crate) fn new(
+        solution: &'a TypeSolution,
+        variant_names: &'a [(Identifier, Box<[Identifier]>)],
+    ) -> Self {
+        Self {
+            solution,
+            variant_names,
+            errors: vec![],
+            warnings: vec![],
+        }
+    }
+
+    /// Returns the errors and warnings which were found
+    pub(crate) fn into_diagnostics(self) -> (Vec<TypeError>, Vec<TypeError>) {
+        (self.errors, self.warnings)
+    }
+
+    pub(crate) fn check_expr(
+        &mut self,
+        expr: &TypedExpr,
+    ) {
+        match &expr.kind {
+            Kind::FunctionCall { args, .. } => args.iter().for_each(|(_, arg)| self.check_expr(arg)),
+            Kind::List { elements, .. } | Kind::Tuple { elements, .. } => elements.iter().for_each(|elem| self.check_expr(elem)),
+            Kind::Literal { .. }
+            | Kind::Unit
+            | Kind::Variable { .. }
+            | Kind::FunctionValue { .. }
+            | Kind::InterfaceMethod { .. }
+            // the instance is the same lambda as the binding, which is checked already
+            | Kind::Instantiation { .. }
+            | Kind::Hole { .. }
+            | Kind::ErrorRecovery(_) => (),
+            Kind::GeneralizedLambda { lambda } => self.check_expr(lambda),
+            Kind::Lambda { body, .. } | Kind::FieldAccess { target: body, .. } => self.check_expr(body),
+            Kind::RecordUpdate { target, fields, .. } => {
+                self.check_expr(target);
+                fields.iter().for_each(|(_, value)| self.check_expr(value));
+            },
+            Kind::ClosureCall { callee, args, .. } => {
+                self.check_expr(callee);
+                args.iter().for_each(|arg| self.check_expr(arg));
+            },
+            Kind::Intrinsic { intrinsic, .. } => match intrinsic {
+                Intrinsic::Puts(expr)
+                | Intrinsic::Malloc(expr)
+                | Intrinsic::SizeOf(expr)
+                | Intrinsic::Not(expr)
+                | Intrinsic::ConvertTo(_, expr)
+                | Intrinsic::ToFloat(expr)
+                | Intrinsic::Sqrt(expr)
+                | Intrinsic::Floor(expr) => self.check_expr(expr),
+                Intrinsic::Add(lhs, rhs)
+                | Intrinsic::Multiply(lhs, rhs)
+                | Intrinsic::Divide(lhs, rhs)
+                | Intrinsic::Subtract(lhs, rhs)
+                | Intrinsic::Equals(lhs, rhs)
+                | Intrinsic::NotEquals(lhs, rhs)
+                | Intrinsic::LessThan(lhs, rhs)
+                | Intrinsic::GreaterThan(lhs, rhs)
+                | Intrinsic::LessThanOrEquals(lhs, rhs)
+                | Intrinsic::GreaterThanOrEquals(lhs, rhs)
+                | Intrinsic::And(lhs, rhs)
+                | Intrinsic::Or(lhs, rhs) => {
+                    self.check_expr(lhs);
+                    self.check_expr(rhs);
+                },
+            },
+            Kind::ExprWithBindings { bindings, expression } => {
+                bindings.iter().for_each(|(_, binding)| self.check_expr(binding));
+                self.check_expr(expression);
+            },
+            Kind::TypeConstructor { args, .. } => args.iter().for_each(|arg| self.check_expr(arg)),
+            Kind::If {
+                condition,
+                then_branch,
+                else_branch,
+            } => {
+                self.check_expr(condition);
+                self.check_expr(then_branch);
+                self.check_expr(else_branch);
+            },
+            Kind::Match { scrutinee, arms, .. } => {
+                self.check_expr(scrutinee);
+                arms.iter().for_each(|arm| self.check_expr(&arm.body));
+
+                let scrutinee_ty = self.resolve(self.solution.get_latest_type(self.solution.expr_ty(scrutinee)));
+                let mut rows: Vec<Vec<Pat>> = Vec::with_capacity(arms.len());
+                for arm in arms {
+                    let row = vec![Pat::from(arm.pattern.item())];
+                    if !self.is_useful(&rows, &row, &[scrutinee_ty.clone()]) {
+                        self.warnings.push(arm.pattern.span().with_item(TypeConstraintError::UnreachableMatchArm));
+                    }
+                    rows.push(row);
+                }
+
+                let missing = self.missing(&rows, &[scrutinee_ty.clone()]);
+                if !missing.is_empty() {
+                    let missing = missing
+                        .iter()
+                        .map(|witness| self.render_pattern(&witness[0], &scrutinee_ty))
+                        .collect::<Vec<_>>();
+                    self.errors.push(expr.span().with_item(TypeConstraintError::NonExhaustiveMatch(missing)));
+                }
+            },
+        }
+    }
+
+    /// Follows [`SpecificType::Ref`]s until reaching a concrete type
+    fn resolve(
+        &self,
+        mut ty: SpecificType,
+    ) -> SpecificType {
+        while let SpecificType::Ref(var) = ty {
+            ty = self.solution.get_latest_type(var);
+        }
+        ty
+    }
+
+    /// All of the constructors of a type, or `None` if there are too many to list, e.g. for integers.
+    fn constructors(
+        &self,
+        ty: &SpecificType,
+    ) -> Option<Vec<Constructor>> {
+        match ty {
+            SpecificType::UserDefined {
+                variants,
+                constant_literal_types,
+                ..
+            } => {
+                let num_constructors = variants.len() + usize::from(!constant_literal_types.is_empty());
+                Some((0..num_constructors).map(Constructor::Variant).collect())
+            },
+            SpecificType::Boolean => Some(vec![
+                Constructor::Literal(Literal::Boolean(true)),
+                Constructor::Literal(Literal::Boolean(false)),
+            ]),
+            SpecificType::Literal(lit) => Some(vec![Constructor::Literal(lit.clone())]),
+            SpecificType::Sum(tys) => tys
+                .iter()
+                .map(|ty| match ty {
+                    SpecificType::Literal(lit) => Some(Constructor::Literal(lit.clone())),
+                    _ => None,
+                })
+                .collect(),
+            _ => None,
+        }
+    }
+
+    /// The types of the fields of a constructor of `ty`
+    fn field_types(
+        &self,
+        ty: &SpecificType,
+        constructor: &Constructor,
+    ) -> Vec<SpecificType> {
+        match (ty, constructor) {
+            (
+                SpecificType::UserDefined {
+                    variants,
+                    constant_literal_types,
+                    ..
+                },
+                Constructor::Variant(variant),
+            ) => match variants.get(*variant) {
+                Some(variant) => variant.fields.iter().map(|field| self.resolve(field.clone())).collect(),
+                None => vec![SpecificType::Sum(
+                    constant_literal_types.iter().cloned().map(SpecificType::Literal).collect(),
+                )],
+            },
+            _ => vec![],
+        }
+    }
+
+    /// Whether `row` matches any value which none of `rows` match. `tys` are the types of each column.
+    fn is_useful(
+        &self,
+        rows: &[Vec<Pat>],
+        row: &[Pat],
+        tys: &[SpecificType],
+    ) -> bool {
+        let Some((head, rest)) = row.split_first() else {
+            return rows.is_empty();
+        };
+        match head {
+            Pat::Constructor(constructor, fields) => self.is_useful_for_constructor(rows, constructor, fields.clone(), rest, tys),
+            Pat::Wildcard => match self.complete_constructors(rows, &tys[0]) {
+                Some(constructors) => constructors.iter().any(|constructor| {
+                    let fields = vec![Pat::Wildcard; self.field_types(&tys[0], constructor).len()];
+                    self.is_useful_for_constructor(rows, constructor, fields, rest, tys)
+                }),
+                None => self.is_useful(&default_rows(rows), rest, &tys[1..]),
+            },
+        }
+    }
+
+    fn is_useful_for_constructor(
+        &self,
+        rows: &[Vec<Pat>],
+        constructor: &Constructor,
+        fields: Vec<Pat>,
+        rest: &[Pat],
+        tys: &[SpecificType],
+    ) -> bool {
+        let field_tys = self.field_types(&tys[0], constructor);
+        let row = [fields, rest.to_vec()].concat();
+        let tys = [field_tys.clone(), tys[1..].to_vec()].concat();
+        self.is_useful(&specialize(rows, constructor, field_tys.len()), &row, &tys)
+    }
+
+    /// The values which none of `rows` match, with wildcards wherever the exact value doesn't matter.
+    /// Every constructor which isn't fully covered gets its own entry.
+    fn missing(
+        &self,
+        rows: &[Vec<Pat>],
+        tys: &[SpecificType],
+    ) -> Vec<Vec<Pat>> {
+        let Some((ty, rest_tys)) = tys.split_first() else {
+            return if rows.is_empty() { vec![vec![]] } else { vec![] };
+        };
+
+        let missing_in_default = || self.missing(&default_rows(rows), rest_tys);
+        let Some(constructors) = self.constructors(ty) else {
+            // only wildcards can cover the values of types whose constructors can't be listed
+            return missing_in_default()
+                .into_iter()
+                .map(|rest| [vec![Pat::Wildcard], rest].concat())
+                .collect();
+        };
+
+        let present = head_constructors(rows);
+        let mut missing = vec![];
+        for constructor in constructors {
+            let field_tys = self.field_types(ty, &constructor);
+            let arity = field_tys.len();
+            if present.contains(&constructor) {
+                let specialized = specialize(rows, &constructor, arity);
+                for witness in self.missing(&specialized, &[field_tys, rest_tys.to_vec()].concat()) {
+                    let (fields, rest) = witness.split_at(arity);
+                    missing.push([vec![Pat::Constructor(constructor.clone(), fields.to_vec())], rest.to_vec()].concat());
+                }
+            } else {
+                // no pattern mentions this constructor, so it is missing entirely, unless the wildcards cover it
+                for rest in missing_in_default() {
+                    missing.push([vec![Pat::Constructor(constructor.clone(), vec![Pat::Wildcard; arity])], rest].concat());
+                }
+            }
+        }
+        missing
+    }
+
+    /// If every constructor of `ty` appears at the head of one of `rows`, returns the constructors.
+    fn complete_constructors(
+        &self,
+        rows: &[Vec<Pat>],
+        ty: &SpecificType,
+    ) -> Option<Vec<Constructor>> {
+        let constructors = self.constructors(ty)?;
+        let present = head_constructors(rows);
+        if constructors.iter().all(|constructor| present.contains(constructor)) {
+            Some(constructors)
+        } else {
+            None
+        }
+    }
+
+    fn render_pattern(
+        &self,
+        pattern: &Pat,
+        ty: &SpecificType,
+    ) -> String {
+        match pattern {
+            Pat::Wildcard => "_".into(),
+            Pat::Constructor(Constructor::Literal(lit), _) => lit.to_string(),
+            Pat::Constructor(constructor @ Constructor::Variant(variant), fields) => {
+                let SpecificType::UserDefined { name, .. } = ty else {
+                    unreachable!("only user-defined types have variants")
+                };
+                let interner = self.solution.interner();
+                // the constant literal variant is written with the name of the type
+                let variant_name = self
+                    .variant_names
+                    .iter()
+                    .find(|(ty_name, _)| ty_name == name)
+                    .and_then(|(_, variant_names)| variant_names.get(*variant))
+                    .unwrap_or(name);
+                let mut buf = interner.get(variant_name.id).to_string();
+                for (field, field_ty) in fields.iter().zip(self.field_types(ty, constructor)) {
+                    buf.push(' ');
+                    buf.push_str(&self.render_pattern(field, &field_ty));
+                }
+                buf
+            },
+        }
+    }
+}
+
+fn head_constructors(rows: &[Vec<Pat>]) -> Vec<Constructor> {
+    rows.iter()
+        .filter_map(|row| match row.first() {
+            Some(Pat::Constructor(constructor, _)) => Some(constructor.clone()),
+            _ => None,
+        })
+        .collect()
+}
+
+/// The rows which match `constructor`, with their head replaced by the constructor's fields
+fn specialize(
+    rows: &[Vec<Pat>],
+    constructor: &Constructor,
+    arity: usize,
+) -> Vec<Vec<Pat>> {
+    rows.iter()
+        .filter_map(|row| {
+            let (head, rest) = row.split_first()?;
+            let fields = match head {
+                Pat::Constructor(head_constructor, fields) if head_constructor == constructor => fields.clone(),
+                Pat::Constructor(..) => return None,
+                Pat::Wildcard => vec![Pat::Wildcard; arity],
+            };
+            Some([fields, rest.to_vec()].concat())
+        })
+        .collect()
+}
+
+/// The rows which match any value with a wildcard at their head, with the head removed
+fn default_rows(rows: &[Vec<Pat>]) -> Vec<Vec<Pat>> {
+    rows.iter()
+        .filter_map(|row| match row.split_first() {
+            Some((Pat::Wildcard, rest)) => Some(rest.to_vec()),
+            _ => None,
+        })
+        .collect()
+}
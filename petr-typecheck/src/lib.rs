@@ -10,17 +10,20 @@ pub use constraint_generation::{unify_basic_math_op, FunctionSignature, TypeChec
 pub use error::TypeConstraintError;
 pub use petr_bind::FunctionId;
 use petr_resolve::QueryableResolvedItems;
-pub use petr_resolve::{Intrinsic as ResolvedIntrinsic, IntrinsicName, Literal};
+pub use petr_resolve::{BindingPattern, FieldPattern, Intrinsic as ResolvedIntrinsic, IntrinsicName, Literal, Pattern};
 use petr_utils::{idx_map_key, IndexMap, SpannedItem, TypeId};
-pub use solution::TypeSolution;
+pub use solution::{Implementation, TypeSolution};
+pub use trace::{SolverTrace, TraceEvent, TraceStep};
 pub use typed_ast::*;
 pub use types::*;
 
 mod constraint_generation;
+mod exhaustiveness;
 mod pretty_printing;
 mod solution;
 #[cfg(test)]
 mod tests;
+mod trace;
 mod typed_ast;
 mod types;
 
@@ -34,6 +37,15 @@ pub fn type_check(resolved: QueryableResolvedItems) -> Result<TypeSolution, Vec<
     type_checker.into_solution()
 }
 
+/// Type checks like [`type_check`], and traces what the constraint solver did along the way.
+/// The trace is returned whether or not type checking succeeded.
+pub fn type_check_with_trace(resolved: QueryableResolvedItems) -> (Result<TypeSolution, Vec<SpannedItem<TypeConstraintError>>>, SolverTrace) {
+    let mut type_checker = TypeChecker::new(resolved);
+    type_checker.fully_type_check();
+
+    type_checker.into_solution_with_trace()
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
 pub enum TypeOrFunctionId {
     TypeId(TypeId),
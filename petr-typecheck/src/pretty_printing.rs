@@ -1,4 +1,4 @@
-use petr_utils::SymbolInterner;
+use petr_utils::{SymbolId, SymbolInterner};
 use types::SpecificType;
 
 use crate::*;
@@ -24,7 +24,16 @@ pub fn pretty_print_type_checker(type_checker: &TypeChecker) -> String {
         };
         s.push_str(&text);
         s.push_str(": ");
-        s.push_str(&pretty_print_ty(ty, type_checker.ctx().types(), &type_checker.resolved().interner));
+        let type_parameters = match id {
+            TypeOrFunctionId::FunctionId(func) => interface_type_parameter(type_checker, func).into_iter().collect(),
+            TypeOrFunctionId::TypeId(_) => vec![],
+        };
+        s.push_str(&print_ty(
+            ty,
+            type_checker.ctx().types(),
+            &type_checker.resolved().interner,
+            &type_parameters,
+        ));
 
         s.push('\n');
         match id {
@@ -65,29 +74,72 @@ pub fn pretty_print_type_checker(type_checker: &TypeChecker) -> String {
     s
 }
 
+/// The id of the type variable that an interface method's type parameter was given, and the name
+/// it was declared with, if `func` is an interface method
+#[cfg(test)]
+fn interface_type_parameter(
+    type_checker: &TypeChecker,
+    func: &FunctionId,
+) -> Option<(usize, SymbolId)> {
+    let TypedExprKind::InterfaceMethod { interface, self_ty, .. } = type_checker.typed_functions().get(func)?.body.kind else {
+        return None;
+    };
+    let mut ty = type_checker.ctx().types().get(self_ty);
+    while let SpecificType::Ref(t) = ty {
+        ty = type_checker.ctx().types().get(*t);
+    }
+    match ty {
+        SpecificType::Infer(id, _) => Some((*id, type_checker.resolved().get_interface(interface).type_param.id)),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
 pub fn pretty_print_ty(
     ty: &TypeVariable,
     types: &IndexMap<TypeVariable, SpecificType>,
     interner: &SymbolInterner,
+) -> String {
+    print_ty(ty, types, interner, &[])
+}
+
+pub fn pretty_print_petr_type(
+    ty: &SpecificType,
+    types: &IndexMap<TypeVariable, SpecificType>,
+    interner: &SymbolInterner,
+) -> String {
+    print_petr_type(ty, types, interner, &[])
+}
+
+/// Prints `ty`, printing the type variables in `type_parameters` as the names of the type
+/// parameters they stand for
+fn print_ty(
+    ty: &TypeVariable,
+    types: &IndexMap<TypeVariable, SpecificType>,
+    interner: &SymbolInterner,
+    type_parameters: &[(usize, SymbolId)],
 ) -> String {
     let mut ty = types.get(*ty);
     while let SpecificType::Ref(t) = ty {
         ty = types.get(*t);
     }
-    pretty_print_petr_type(ty, types, interner)
+    print_petr_type(ty, types, interner, type_parameters)
 }
 
-pub fn pretty_print_petr_type(
+fn print_petr_type(
     ty: &SpecificType,
     types: &IndexMap<TypeVariable, SpecificType>,
     interner: &SymbolInterner,
+    type_parameters: &[(usize, SymbolId)],
 ) -> String {
     match ty {
         SpecificType::Unit => "unit".to_string(),
         SpecificType::Integer => "int".to_string(),
+        SpecificType::SizedInteger(ty) => ty.to_string(),
+        SpecificType::Float => "float".to_string(),
         SpecificType::Boolean => "bool".to_string(),
         SpecificType::String => "string".to_string(),
-        SpecificType::Ref(ty) => pretty_print_ty(ty, types, interner),
+        SpecificType::Ref(ty) => print_ty(ty, types, interner, type_parameters),
         SpecificType::UserDefined { name, .. } => {
             let name = interner.get(name.id);
             name.to_string()
@@ -98,7 +150,7 @@ pub fn pretty_print_petr_type(
             for (ix, ty) in tys.iter().enumerate() {
                 let is_last = ix == tys.len() - 1;
 
-                s.push_str(&pretty_print_ty(ty, types, interner));
+                s.push_str(&print_ty(ty, types, interner, type_parameters));
                 if !is_last {
                     s.push_str(" → ");
                 }
@@ -107,15 +159,18 @@ pub fn pretty_print_petr_type(
             s
         },
         SpecificType::ErrorRecovery => "error recovery".to_string(),
-        SpecificType::List(ty) => format!("[{}]", pretty_print_petr_type(ty, types, interner)),
-        SpecificType::Infer(id, _) => format!("infer t{id}"),
+        SpecificType::List(ty) => format!("[{}]", print_petr_type(ty, types, interner, type_parameters)),
+        SpecificType::Infer(id, _) => match type_parameters.iter().find(|(infer_id, _)| infer_id == id) {
+            Some((_, name)) => format!("'{}", interner.get(*name)),
+            None => format!("infer t{id}"),
+        },
         SpecificType::Sum(tys) => {
             let mut s = String::new();
             s.push('(');
             for (ix, ty) in tys.iter().enumerate() {
                 let is_last = ix == tys.len() - 1;
                 // print the petr ty
-                s.push_str(&pretty_print_petr_type(ty, types, interner));
+                s.push_str(&print_petr_type(ty, types, interner, type_parameters));
                 if !is_last {
                     s.push_str(" | ");
                 }
@@ -124,6 +179,13 @@ pub fn pretty_print_petr_type(
             s
         },
         SpecificType::Literal(l) => format!("{}", l),
+        SpecificType::Tuple(tys) => {
+            let tys = tys
+                .iter()
+                .map(|ty| print_petr_type(ty, types, interner, type_parameters))
+                .collect::<Vec<_>>();
+            format!("({})", tys.join(", "))
+        },
     }
 }
 
@@ -137,8 +199,14 @@ pub fn pretty_print_typed_expr(
     match &typed_expr.kind {
         TypedExprKind::ExprWithBindings { bindings, expression } => {
             let mut s = String::new();
-            for (name, expr) in bindings {
-                let ident = interner.get(name.id);
+            for (pattern, expr) in bindings {
+                let ident = match pattern {
+                    BindingPattern::Name(name) => interner.get(name.id).to_string(),
+                    BindingPattern::Tuple(names) => {
+                        let names = names.iter().map(|name| interner.get(name.id)).collect::<Vec<_>>();
+                        format!("({})", names.join(", "))
+                    },
+                };
                 let ty = type_checker.expr_ty(expr);
                 let ty = pretty_print_ty(&ty, types, interner);
                 s.push_str(&format!("{ident}: {:?} ({}),\n", expr, ty));
@@ -1,15 +1,16 @@
 use std::collections::{BTreeMap, BTreeSet};
 
 use petr_bind::FunctionId;
-use petr_resolve::Literal;
-use petr_utils::{IndexMap, Span, SpannedItem, SymbolInterner};
+use petr_utils::{Diagnostics, Identifier, IndexMap, InterfaceId, Span, SpannedItem, SymbolInterner};
 
 use crate::{
-    constraint_generation::FunctionSignature,
+    constraint_generation::{FunctionSignature, Hole, InterfaceObligation},
     error::TypeConstraintError,
+    exhaustiveness::ExhaustivenessChecker,
     pretty_printing,
+    trace::{SolverTrace, TraceEvent},
     typed_ast::{TypedExpr, TypedExprKind},
-    types::{GeneralType, GeneralizedTypeVariant, SpecificType},
+    types::{GeneralType, SpecificType},
     Function, TypeError, TypeVariable,
 };
 
@@ -17,6 +18,9 @@ use crate::{
 pub struct TypeSolutionEntry {
     source: TypeSolutionSource,
     ty:     SpecificType,
+    /// the span of the constraint which determined this type, which errors point to as where it
+    /// came from
+    span:   Option<Span>,
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
@@ -31,6 +35,7 @@ impl TypeSolutionEntry {
         Self {
             source: TypeSolutionSource::Axiomatic,
             ty,
+            span: None,
         }
     }
 
@@ -38,6 +43,7 @@ impl TypeSolutionEntry {
         Self {
             source: TypeSolutionSource::Inherent,
             ty,
+            span: None,
         }
     }
 
@@ -45,6 +51,7 @@ impl TypeSolutionEntry {
         Self {
             source: TypeSolutionSource::Inferred,
             ty,
+            span: None,
         }
     }
 
@@ -53,25 +60,56 @@ impl TypeSolutionEntry {
     }
 }
 
+/// Whether a type mismatch was found while unifying two types, or while checking that one
+/// satisfies the other
+#[derive(Clone, Copy)]
+enum MismatchKind {
+    Unify,
+    Satisfy,
+}
+
+/// An implementation of an interface for a type
+#[derive(Clone, Debug)]
+pub struct Implementation {
+    pub interface: InterfaceId,
+    pub ty:        TypeVariable,
+    /// The functions which implement each method of the interface, in the order the interface
+    /// declares them
+    pub methods:   Box<[FunctionId]>,
+}
+
 pub struct TypeSolution {
     solution: BTreeMap<TypeVariable, TypeSolutionEntry>,
     unsolved_types: IndexMap<TypeVariable, SpecificType>,
     errors: Vec<TypeError>,
+    /// Problems which don't prevent the program from compiling, like unreachable match arms
+    warnings: Vec<TypeError>,
     interner: SymbolInterner,
+    /// The type variables of types that were written as type aliases, and the aliases' names
+    aliases: BTreeMap<TypeVariable, Identifier>,
     error_recovery: TypeVariable,
     unit: TypeVariable,
     functions: BTreeMap<FunctionId, Function>,
     monomorphized_functions: BTreeMap<FunctionSignature, Function>,
+    implementations: Vec<Implementation>,
+    /// Pairs of user-defined types whose fields are being unified or satisfied. The fields of
+    /// recursive types refer back to them, and are already being compared.
+    user_defined_types_in_progress: BTreeSet<(TypeVariable, TypeVariable)>,
+    /// What the solver did, if tracing is enabled
+    trace: Option<SolverTrace>,
 }
 
 impl TypeSolution {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         unsolved_types: IndexMap<TypeVariable, SpecificType>,
         error_recovery: TypeVariable,
         unit: TypeVariable,
         functions: BTreeMap<FunctionId, Function>,
         monomorphized_functions: BTreeMap<FunctionSignature, Function>,
+        implementations: Vec<Implementation>,
         interner: SymbolInterner,
+        aliases: BTreeMap<TypeVariable, Identifier>,
         preexisting_errors: Vec<TypeError>,
     ) -> Self {
         let solution = vec![
@@ -84,11 +122,80 @@ impl TypeSolution {
             solution,
             unsolved_types,
             errors: preexisting_errors,
+            warnings: Default::default(),
             interner,
+            aliases,
             functions,
             monomorphized_functions,
+            implementations,
             unit,
             error_recovery,
+            user_defined_types_in_progress: Default::default(),
+            trace: None,
+        }
+    }
+
+    pub(crate) fn enable_trace(&mut self) {
+        self.trace = Some(SolverTrace::default());
+    }
+
+    pub(crate) fn take_trace(&mut self) -> Option<SolverTrace> {
+        self.trace.take()
+    }
+
+    /// Starts a new step in the trace, for a constraint which is about to be applied
+    pub(crate) fn trace_constraint(
+        &mut self,
+        constraint: &'static str,
+        variables: Vec<TypeVariable>,
+        span: Span,
+    ) {
+        if let Some(trace) = &mut self.trace {
+            trace.start_step(constraint, variables, span);
+        }
+    }
+
+    /// Records that `rule` was applied to the current types of `t1` and `t2`
+    fn trace_rule(
+        &mut self,
+        rule: &'static str,
+        t1: TypeVariable,
+        t2: TypeVariable,
+    ) {
+        if self.trace.is_none() {
+            return;
+        }
+        let lhs = self.trace_type(&self.get_latest_type(t1));
+        let rhs = self.trace_type(&self.get_latest_type(t2));
+        if let Some(trace) = &mut self.trace {
+            trace.record(TraceEvent::Rule { rule, lhs, rhs });
+        }
+    }
+
+    fn trace_update(
+        &mut self,
+        variable: TypeVariable,
+        to: &SpecificType,
+    ) {
+        if self.trace.is_none() {
+            return;
+        }
+        let from = self.trace_type(&self.get_latest_type(variable));
+        let to = self.trace_type(to);
+        if let Some(trace) = &mut self.trace {
+            trace.record(TraceEvent::Update { variable, from, to });
+        }
+    }
+
+    /// Describes `ty` for the trace. Unlike in errors, references to other type variables are
+    /// written as the variable, so that the trace shows how type variables are linked.
+    fn trace_type(
+        &self,
+        ty: &SpecificType,
+    ) -> String {
+        match ty {
+            SpecificType::Ref(var) => format!("t{}", usize::from(*var)),
+            ty => self.pretty_print_type(ty),
         }
     }
 
@@ -102,13 +209,15 @@ impl TypeSolution {
     pub fn insert_solution(
         &mut self,
         ty: TypeVariable,
-        entry: TypeSolutionEntry,
+        mut entry: TypeSolutionEntry,
         span: Span,
     ) {
         if self.solution.contains_key(&ty) {
             self.update_type(ty, entry, span);
             return;
         }
+        entry.span = Some(span);
+        self.trace_update(ty, &entry.ty);
         self.solution.insert(ty, entry);
     }
 
@@ -119,38 +228,144 @@ impl TypeSolution {
         pretty_printing::pretty_print_petr_type(ty, &self.unsolved_types, &self.interner)
     }
 
-    fn unify_err(
+    /// The name of the type alias that `ty` was written as, as long as solving hasn't changed
+    /// the type since
+    fn alias_name(
+        &self,
+        ty: TypeVariable,
+    ) -> Option<String> {
+        let name = self.aliases.get(&ty)?;
+        if self.get_latest_type(ty) != *self.unsolved_types.get(ty) {
+            return None;
+        }
+        Some(self.interner.get(name.id).to_string())
+    }
+
+    /// Describes `ty` for error messages, by the alias it was written as if there is one
+    fn describe_type(
         &self,
+        var: Option<TypeVariable>,
+        ty: &SpecificType,
+    ) -> String {
+        var.and_then(|var| self.alias_name(var)).unwrap_or_else(|| self.pretty_print_type(ty))
+    }
+
+    fn push_unify_err(
+        &mut self,
+        t1: Option<TypeVariable>,
         clone_1: SpecificType,
+        t2: Option<TypeVariable>,
         clone_2: SpecificType,
-    ) -> TypeConstraintError {
-        let pretty_printed_b = self.pretty_print_type(&clone_2);
-        match clone_1 {
-            SpecificType::Sum(tys) => {
-                let tys = tys.iter().map(|ty| self.pretty_print_type(ty)).collect::<Vec<_>>();
-                TypeConstraintError::NotSubtype(tys, pretty_printed_b)
-            },
-            _ => {
-                let pretty_printed_a = self.pretty_print_type(&clone_1);
-                TypeConstraintError::UnificationFailure(pretty_printed_a, pretty_printed_b)
-            },
-        }
+        span: Span,
+    ) {
+        self.push_mismatch_err(MismatchKind::Unify, t1, clone_1, t2, clone_2, span)
     }
 
-    fn satisfy_err(
+    fn push_satisfy_err(
+        &mut self,
+        t1: Option<TypeVariable>,
+        clone_1: SpecificType,
+        t2: Option<TypeVariable>,
+        clone_2: SpecificType,
+        span: Span,
+    ) {
+        self.push_mismatch_err(MismatchKind::Satisfy, t1, clone_1, t2, clone_2, span)
+    }
+
+    /// Reports a mismatch at `span`. Labels can't point into another file, so if the expected type
+    /// came from one, that place gets a note of its own.
+    fn push_mismatch_err(
+        &mut self,
+        kind: MismatchKind,
+        t1: Option<TypeVariable>,
+        clone_1: SpecificType,
+        t2: Option<TypeVariable>,
+        clone_2: SpecificType,
+        span: Span,
+    ) {
+        let error = self.mismatch_err(kind, t1, clone_1, t2, clone_2, span);
+        let expected = match &error {
+            TypeConstraintError::NotSubtype { sum, .. } => sum.clone(),
+            TypeConstraintError::UnificationFailure { expected, .. } | TypeConstraintError::FailedToSatisfy { expected, .. } => expected.clone(),
+            _ => unreachable!("mismatch_err only describes mismatches"),
+        };
+        self.push_error(span.with_item(error));
+        let Some((source, origin)) = self.origin(t1, span).filter(|(_, origin)| origin.source() != span.source()) else {
+            return;
+        };
+        let note = match source {
+            TypeSolutionSource::Axiomatic => format!("expected `{expected}` because of this type annotation"),
+            TypeSolutionSource::Inferred => format!("expected `{expected}` because of this"),
+            TypeSolutionSource::Inherent => return,
+        };
+        self.push_error(origin.with_item(TypeConstraintError::RelatedLocation(note)));
+    }
+
+    /// Where the type of `t1` came from, if that isn't the place `span` of the mismatch itself
+    fn origin(
         &self,
+        t1: Option<TypeVariable>,
+        span: Span,
+    ) -> Option<(TypeSolutionSource, Span)> {
+        t1.and_then(|t1| self.solution.get(&t1))
+            .and_then(|entry| Some((entry.source, entry.span?)))
+            .filter(|(_, origin)| *origin != span)
+    }
+
+    /// Describes a mismatch between the type that was expected, `clone_1`, and the type that was
+    /// found at `span`, `clone_2`. Where the expected type came from is labelled as well, if it's
+    /// known and in the same file.
+    fn mismatch_err(
+        &self,
+        kind: MismatchKind,
+        t1: Option<TypeVariable>,
         clone_1: SpecificType,
+        t2: Option<TypeVariable>,
         clone_2: SpecificType,
+        span: Span,
     ) -> TypeConstraintError {
-        let pretty_printed_b = self.pretty_print_type(&clone_2);
-        match clone_1 {
-            SpecificType::Sum(tys) => {
-                let tys = tys.iter().map(|ty| self.pretty_print_type(ty)).collect::<Vec<_>>();
-                TypeConstraintError::NotSubtype(tys, pretty_printed_b)
+        let found = self.describe_type(t2, &clone_2);
+        let found_at = span.span();
+        let origin = self.origin(t1, span).filter(|(_, origin)| origin.source() == span.source());
+        let (annotation, inferred_at) = match origin {
+            Some((TypeSolutionSource::Axiomatic, origin)) => (Some(origin.span()), None),
+            Some((TypeSolutionSource::Inferred, origin)) => (None, Some(origin.span())),
+            Some((TypeSolutionSource::Inherent, _)) | None => (None, None),
+        };
+        let sum = match (t1.and_then(|t1| self.alias_name(t1)), &clone_1) {
+            (Some(alias), SpecificType::Sum(_)) => Some(alias),
+            (None, SpecificType::Sum(tys)) => Some(tys.iter().map(|ty| self.pretty_print_type(ty)).collect::<Vec<_>>().join(" | ")),
+            _ => None,
+        };
+        if let Some(sum) = sum {
+            return TypeConstraintError::NotSubtype {
+                sum,
+                found,
+                annotation,
+                inferred_at,
+                found_at,
+            };
+        }
+        let expected = self.describe_type(t1, &clone_1);
+        // a sum type can only be used where each of the types in it can be
+        let help =
+            matches!(clone_2, SpecificType::Sum(_)).then(|| format!("`{found}` is a sum type, so each of the types in it must be a `{expected}`"));
+        match kind {
+            MismatchKind::Unify => TypeConstraintError::UnificationFailure {
+                expected,
+                found,
+                annotation,
+                inferred_at,
+                found_at,
+                help,
             },
-            _ => {
-                let pretty_printed_a = self.pretty_print_type(&clone_1);
-                TypeConstraintError::FailedToSatisfy(pretty_printed_a, pretty_printed_b)
+            MismatchKind::Satisfy => TypeConstraintError::FailedToSatisfy {
+                expected,
+                found,
+                annotation,
+                inferred_at,
+                found_at,
+                help,
             },
         }
     }
@@ -158,26 +373,179 @@ impl TypeSolution {
     pub fn update_type(
         &mut self,
         ty: TypeVariable,
-        entry: TypeSolutionEntry,
+        mut entry: TypeSolutionEntry,
         span: Span,
     ) {
-        match self.solution.get_mut(&ty) {
-            Some(e) => {
-                if e.is_axiomatic() {
-                    let pretty_printed_preexisting = pretty_printing::pretty_print_petr_type(&entry.ty, &self.unsolved_types, &self.interner);
-                    let pretty_printed_ty = pretty_printing::pretty_print_petr_type(&entry.ty, &self.unsolved_types, &self.interner);
-                    self.errors
-                        .push(span.with_item(TypeConstraintError::InvalidTypeUpdate(pretty_printed_preexisting, pretty_printed_ty)));
-                    return;
-                }
-                *e = entry;
-            },
-            None => {
-                self.solution.insert(ty, entry);
-            },
+        entry.span = Some(span);
+        if self.solution.get(&ty).is_some_and(TypeSolutionEntry::is_axiomatic) {
+            let pretty_printed_preexisting = pretty_printing::pretty_print_petr_type(&entry.ty, &self.unsolved_types, &self.interner);
+            let pretty_printed_ty = pretty_printing::pretty_print_petr_type(&entry.ty, &self.unsolved_types, &self.interner);
+            self.errors
+                .push(span.with_item(TypeConstraintError::InvalidTypeUpdate(pretty_printed_preexisting, pretty_printed_ty)));
+            return;
+        }
+        self.trace_update(ty, &entry.ty);
+        self.solution.insert(ty, entry);
+    }
+
+    /// Checks that every match expression is exhaustive and has no unreachable arms.
+    /// `variant_names` are the names of the variants of each user-defined type, by type name.
+    pub(crate) fn check_matches(
+        &mut self,
+        variant_names: &[(Identifier, Box<[Identifier]>)],
+    ) {
+        let mut checker = ExhaustivenessChecker::new(self, variant_names);
+        for func in self.functions.values() {
+            checker.check_expr(&func.body);
+        }
+        let (mut errors, mut warnings) = checker.into_diagnostics();
+        self.errors.append(&mut errors);
+        self.warnings.append(&mut warnings);
+    }
+
+    /// Checks that every type which an interface's method was called with implements the
+    /// interface. A type parameter implements the interface if the function it belongs to is
+    /// constrained to only be called with types that implement it.
+    pub(crate) fn check_interface_obligations(
+        &mut self,
+        obligations: &[InterfaceObligation],
+    ) {
+        for obligation in obligations {
+            let interface = self.interner.get(obligation.interface_name.id).to_string();
+            match self.generalize(&self.get_latest_type(obligation.ty)) {
+                GeneralType::ErrorRecovery => (),
+                GeneralType::Infer(id, _) => {
+                    let assumed = obligation.assumptions.iter().any(|(ty, assumed_interface)| {
+                        *assumed_interface == obligation.interface
+                            && matches!(self.generalize(&self.get_latest_type(*ty)), GeneralType::Infer(assumed_id, _) if assumed_id == id)
+                    });
+                    if !assumed {
+                        self.push_error(obligation.span.with_item(TypeConstraintError::UnprovenConstraint(interface)));
+                    }
+                },
+                ty => {
+                    if self.implementation_for(obligation.interface, &ty).is_none() {
+                        let ty = self.pretty_print_type(&ty.safely_upcast());
+                        self.push_error(obligation.span.with_item(TypeConstraintError::MissingImplementation { interface, ty }));
+                    }
+                },
+            }
+        }
+    }
+
+    /// Reports the type that each hole is expected to have, and the types of the locals in scope
+    /// around it. A function may be type checked more than once, so each hole is only reported
+    /// the first time.
+    pub(crate) fn report_holes(
+        &mut self,
+        holes: &[Hole],
+    ) {
+        let mut reported = BTreeSet::new();
+        for hole in holes {
+            if !reported.insert(hole.span) {
+                continue;
+            }
+            let name = match hole.name {
+                Some(name) => format!("?{}", self.interner.get(name.id)),
+                None => "_".to_string(),
+            };
+            let expected = match self.solved_type_name(hole.ty) {
+                Some(ty) => format!("a value of type `{ty}`"),
+                None => "a value of any type".to_string(),
+            };
+            let locals = hole
+                .locals
+                .iter()
+                .map(|(local, ty)| {
+                    let ty = self.solved_type_name(*ty).unwrap_or_else(|| "unknown".to_string());
+                    format!("`{}: {ty}`", self.interner.get(local.id))
+                })
+                .collect::<Vec<_>>();
+            let in_scope = if locals.is_empty() {
+                "there are no local bindings in scope".to_string()
+            } else {
+                format!("in scope: {}", locals.join(", "))
+            };
+            self.warnings.push(hole.span.with_item(TypeConstraintError::TypedHole {
+                hole: name,
+                expected,
+                in_scope,
+            }));
+        }
+    }
+
+    /// The name of the type that `ty` was solved to, by its alias if it was written as one, or
+    /// `None` if nothing determined it
+    fn solved_type_name(
+        &self,
+        ty: TypeVariable,
+    ) -> Option<String> {
+        match self.generalize(&self.get_latest_type(ty)) {
+            GeneralType::Infer(..) => None,
+            solved => Some(self.describe_type(Some(ty), &solved.safely_upcast())),
+        }
+    }
+
+    /// The function which implements method `method` of `interface` for `ty`, if `ty`
+    /// implements the interface. Implementations for a generic type apply to all of its
+    /// instances.
+    pub fn find_implementation(
+        &self,
+        interface: InterfaceId,
+        method: usize,
+        ty: &GeneralType,
+    ) -> Option<FunctionId> {
+        self.implementation_for(interface, ty)
+            .and_then(|implementation| implementation.methods.get(method).copied())
+    }
+
+    /// The functions which implement method `method` of `interface`, for every type that
+    /// implements it
+    pub fn implementations_of(
+        &self,
+        interface: InterfaceId,
+        method: usize,
+    ) -> impl Iterator<Item = FunctionId> + '_ {
+        self.implementations
+            .iter()
+            .filter(move |implementation| implementation.interface == interface)
+            .filter_map(move |implementation| implementation.methods.get(method).copied())
+    }
+
+    fn implementation_for(
+        &self,
+        interface: InterfaceId,
+        ty: &GeneralType,
+    ) -> Option<&Implementation> {
+        self.implementations
+            .iter()
+            .filter(|implementation| implementation.interface == interface)
+            .find(|implementation| is_same_implementing_type(&self.generalize(&self.get_latest_type(implementation.ty)), ty))
+    }
+
+    pub fn warnings(&self) -> &[TypeError] {
+        &self.warnings
+    }
+
+    /// Reports the warnings to `diagnostics`. Unreachable match arms are warnings of the
+    /// `unreachable_match_arm` lint. Typed holes are notes, since a hole is an error once the
+    /// program is lowered anyway.
+    pub fn report_warnings(
+        &self,
+        diagnostics: &mut Diagnostics,
+    ) {
+        for warning in &self.warnings {
+            match warning.item() {
+                TypeConstraintError::TypedHole { .. } => diagnostics.note(warning.clone()),
+                _ => diagnostics.warning("unreachable_match_arm", warning.clone()),
+            }
         }
     }
 
+    pub fn interner(&self) -> &SymbolInterner {
+        &self.interner
+    }
+
     pub(crate) fn into_result(self) -> Result<TypeSolution, Vec<SpannedItem<TypeConstraintError>>> {
         if self.errors.is_empty() {
             Ok(self)
@@ -197,17 +565,85 @@ impl TypeSolution {
         let ty2 = self.get_latest_type(t2).clone();
         use SpecificType::*;
         match (ty1, ty2) {
-            (a, b) if a == b => (),
-            (ErrorRecovery, _) | (_, ErrorRecovery) => (),
-            (Ref(a), _) => self.apply_unify_constraint(a, t2, span),
-            (_, Ref(b)) => self.apply_unify_constraint(t1, b, span),
+            (a, b) if a == b => self.trace_rule("equal types", t1, t2),
+            (ErrorRecovery, _) | (_, ErrorRecovery) => self.trace_rule("error recovery", t1, t2),
+            (Ref(a), _) => {
+                self.trace_rule("follow reference", t1, t2);
+                self.apply_unify_constraint(a, t2, span)
+            },
+            (_, Ref(b)) => {
+                self.trace_rule("follow reference", t1, t2);
+                self.apply_unify_constraint(t1, b, span)
+            },
+            // function types unify if their parameter and return types do
+            (Arrow(a_tys), Arrow(b_tys)) if a_tys.len() == b_tys.len() => {
+                self.trace_rule("function types", t1, t2);
+                for (a, b) in a_tys.into_iter().zip(b_tys) {
+                    self.apply_unify_constraint(a, b, span);
+                }
+            },
+            // instances of the same generic type unify if their fields do
+            (
+                UserDefined {
+                    name: a_name,
+                    variants: a_variants,
+                    ..
+                },
+                UserDefined {
+                    name: b_name,
+                    variants: b_variants,
+                    ..
+                },
+            ) if a_name == b_name => {
+                self.trace_rule("same user-defined type", t1, t2);
+                if !self.user_defined_types_in_progress.insert((t1, t2)) {
+                    return;
+                }
+                for (a, b) in a_variants
+                    .iter()
+                    .zip(b_variants.iter())
+                    .flat_map(|(a, b)| a.fields.iter().zip(b.fields.iter()))
+                {
+                    match (a, b) {
+                        (Ref(a), Ref(b)) => self.apply_unify_constraint(*a, *b, span),
+                        (a, b) if a == b => (),
+                        (a, b) => self.push_unify_err(None, a.clone(), None, b.clone(), span),
+                    }
+                }
+                self.user_defined_types_in_progress.remove(&(t1, t2));
+            },
+            // tuples of the same length unify if their elements do
+            (Tuple(a_tys), Tuple(b_tys)) if a_tys.len() == b_tys.len() => {
+                self.trace_rule("tuples", t1, t2);
+                for (a, b) in a_tys.iter().zip(b_tys.iter()) {
+                    match (a, b) {
+                        (Ref(a), Ref(b)) => self.apply_unify_constraint(*a, *b, span),
+                        (a, b) if a == b => (),
+                        (a, b) => self.push_unify_err(None, a.clone(), None, b.clone(), span),
+                    }
+                }
+            },
             (Infer(id, _), Infer(id2, _)) if id != id2 => {
+                self.trace_rule("two inferred types", t1, t2);
                 // if two different inferred types are unified, replace the second with a reference
                 // to the first
                 let entry = TypeSolutionEntry::new_inferred(Ref(t1));
                 self.update_type(t2, entry, span);
             },
+            // instantiate the infer type with the known type. This comes before the rules for sum
+            // types, which would otherwise treat an infer type as a member of the sum
+            (Infer(_, _), _known) => {
+                self.trace_rule("instantiate inferred type", t1, t2);
+                let entry = TypeSolutionEntry::new_inferred(Ref(t2));
+                self.update_type(t1, entry, span);
+            },
+            (_known, Infer(_, _)) => {
+                self.trace_rule("instantiate inferred type", t1, t2);
+                let entry = TypeSolutionEntry::new_inferred(Ref(t1));
+                self.update_type(t2, entry, span);
+            },
             (a @ Sum(_), b @ Sum(_)) => {
+                self.trace_rule("sum with sum", t1, t2);
                 // the unification of two sum types is the union of the two types if and only if
                 // `t2` is a total subset of `t1`
                 // `t1` remains unchanged, as we are trying to coerce `t2` into something that
@@ -237,6 +673,7 @@ impl TypeSolution {
             // exactly the same specific type OR the generalization of that type
             // If the latter, then the specific type must be updated to its generalization
             (ref t1_ty @ Sum(_), other) => {
+                self.trace_rule("sum with non-sum", t1, t2);
                 if self.a_superset_of_b(t1_ty, &other) {
                     // t2 unifies to the more general form provided by t1
                     let entry = TypeSolutionEntry::new_inferred(Ref(t1));
@@ -253,9 +690,10 @@ impl TypeSolution {
                 }
             },
             // literals can unify to each other if they're equal
-            (Literal(l1), Literal(l2)) if l1 == l2 => (),
+            (Literal(l1), Literal(l2)) if l1 == l2 => self.trace_rule("equal literals", t1, t2),
             // if they're not equal, their unification is the sum of both
             (Literal(l1), Literal(l2)) if l1 != l2 => {
+                self.trace_rule("different literals", t1, t2);
                 // update t1 to a sum type of both,
                 // and update t2 to reference t1
                 let sum = Sum([Literal(l1), Literal(l2)].into());
@@ -265,6 +703,7 @@ impl TypeSolution {
                 self.update_type(t2, t2_entry, span);
             },
             (Literal(l1), Sum(tys)) => {
+                self.trace_rule("literal with sum", t1, t2);
                 // update t1 to a sum type of both,
                 // and update t2 to reference t1
                 let sum = Sum([Literal(l1)].iter().chain(tys.iter()).cloned().collect());
@@ -274,6 +713,7 @@ impl TypeSolution {
                 self.update_type(t2, t2_entry, span);
             },
             (a, b) if self.a_superset_of_b(&a, &b) => {
+                self.trace_rule("supertype", t1, t2);
                 // if `a` is a superset of `b`, then `b` unifies to `a` as it is more general
                 let entry = TypeSolutionEntry::new_inferred(Ref(t1));
                 self.update_type(t2, entry, span);
@@ -304,6 +744,7 @@ impl TypeSolution {
             },
             */
             (other, ref t2_ty @ Sum(_)) => {
+                self.trace_rule("non-sum with sum", t1, t2);
                 // if `other` is a superset of `t2`, then `t2` unifies to `other` as it is more
                 // general
                 if self.a_superset_of_b(&other, t2_ty) {
@@ -311,22 +752,73 @@ impl TypeSolution {
                     self.update_type(t2, entry, span);
                 }
             },
-            // instantiate the infer type with the known type
-            (Infer(_, _), _known) => {
-                let entry = TypeSolutionEntry::new_inferred(Ref(t2));
-                self.update_type(t1, entry, span);
-            },
-            (_known, Infer(_, _)) => {
-                let entry = TypeSolutionEntry::new_inferred(Ref(t1));
-                self.update_type(t2, entry, span);
-            },
             // lastly, if no unification rule exists for these two types, it is a mismatch
             (a, b) => {
-                self.push_error(span.with_item(self.unify_err(a, b)));
+                self.trace_rule("mismatch", t1, t2);
+                self.push_unify_err(Some(t1), a, Some(t2), b, span);
             },
         }
     }
 
+    /// Narrows `original` to the type of `literal`, if it is a value of `original`, see
+    /// [`crate::constraint_generation::TypeConstraintKind::NarrowTo`]. Otherwise the narrowed type
+    /// is the original one.
+    pub(crate) fn apply_narrow_to_constraint(
+        &mut self,
+        narrowed: TypeVariable,
+        original: TypeVariable,
+        literal: TypeVariable,
+        span: Span,
+    ) {
+        let original_ty = self.resolve(original);
+        let literal_ty = self.get_latest_type(literal);
+        let ty = if self.a_superset_of_b(&original_ty, &literal_ty) {
+            self.trace_rule("narrow to literal", original, literal);
+            literal_ty
+        } else {
+            self.trace_rule("keep original type", original, literal);
+            SpecificType::Ref(original)
+        };
+        self.update_type(narrowed, TypeSolutionEntry::new_inferred(ty), span);
+    }
+
+    /// Narrows `original` to the values it can have which aren't `literal`, see
+    /// [`crate::constraint_generation::TypeConstraintKind::NarrowExcluding`]. Only sums of
+    /// literals and booleans can be narrowed like this, the narrowed type of anything else is
+    /// the original one.
+    pub(crate) fn apply_narrow_excluding_constraint(
+        &mut self,
+        narrowed: TypeVariable,
+        original: TypeVariable,
+        literal: TypeVariable,
+        span: Span,
+    ) {
+        use SpecificType::*;
+        let literal_ty = self.get_latest_type(literal);
+        let (rule, ty) = match (self.resolve(original), &literal_ty) {
+            (Sum(tys), literal_ty) if tys.contains(literal_ty) => (
+                "remove literal from sum",
+                SpecificType::sum(tys.into_iter().filter(|ty| ty != literal_ty).collect()),
+            ),
+            (Boolean, Literal(petr_resolve::Literal::Boolean(b))) => ("negate boolean", Literal(petr_resolve::Literal::Boolean(!b))),
+            _ => ("keep original type", Ref(original)),
+        };
+        self.trace_rule(rule, original, literal);
+        self.update_type(narrowed, TypeSolutionEntry::new_inferred(ty), span);
+    }
+
+    /// Follows [`SpecificType::Ref`]s from `ty` until reaching a type which isn't one
+    fn resolve(
+        &self,
+        ty: TypeVariable,
+    ) -> SpecificType {
+        let mut resolved = self.get_latest_type(ty);
+        while let SpecificType::Ref(ty) = resolved {
+            resolved = self.get_latest_type(ty);
+        }
+        resolved
+    }
+
     // This function will need to be rewritten when type constraints and bounded polymorphism are
     // implemented.
     pub(crate) fn apply_satisfies_constraint(
@@ -339,18 +831,101 @@ impl TypeSolution {
         let ty2 = self.get_latest_type(t2);
         use SpecificType::*;
         match (ty1, ty2) {
-            (a, b) if a == b => (),
-            (ErrorRecovery, _) | (_, ErrorRecovery) => (),
-            (Ref(a), _) => self.apply_satisfies_constraint(a, t2, span),
-            (_, Ref(b)) => self.apply_satisfies_constraint(t1, b, span),
+            (a, b) if a == b => self.trace_rule("equal types", t1, t2),
+            (ErrorRecovery, _) | (_, ErrorRecovery) => self.trace_rule("error recovery", t1, t2),
+            (Ref(a), _) => {
+                self.trace_rule("follow reference", t1, t2);
+                self.apply_satisfies_constraint(a, t2, span)
+            },
+            (_, Ref(b)) => {
+                self.trace_rule("follow reference", t1, t2);
+                self.apply_satisfies_constraint(t1, b, span)
+            },
+            (Arrow(a_tys), Arrow(b_tys)) if a_tys.len() == b_tys.len() => {
+                self.trace_rule("function types", t1, t2);
+                for (a, b) in a_tys.into_iter().zip(b_tys) {
+                    self.apply_satisfies_constraint(a, b, span);
+                }
+            },
+            (
+                UserDefined {
+                    name: a_name,
+                    variants: a_variants,
+                    ..
+                },
+                UserDefined {
+                    name: b_name,
+                    variants: b_variants,
+                    ..
+                },
+            ) if a_name == b_name => {
+                self.trace_rule("same user-defined type", t1, t2);
+                if !self.user_defined_types_in_progress.insert((t1, t2)) {
+                    return;
+                }
+                for (a, b) in a_variants
+                    .iter()
+                    .zip(b_variants.iter())
+                    .flat_map(|(a, b)| a.fields.iter().zip(b.fields.iter()))
+                {
+                    match (a, b) {
+                        (Ref(a), Ref(b)) => self.apply_satisfies_constraint(*a, *b, span),
+                        (a, b) if a == b => (),
+                        (a, b) => self.push_satisfy_err(None, a.clone(), None, b.clone(), span),
+                    }
+                }
+                self.user_defined_types_in_progress.remove(&(t1, t2));
+            },
+            (Tuple(a_tys), Tuple(b_tys)) if a_tys.len() == b_tys.len() => {
+                self.trace_rule("tuples", t1, t2);
+                for (a, b) in a_tys.iter().zip(b_tys.iter()) {
+                    match (a, b) {
+                        (Ref(a), Ref(b)) => self.apply_satisfies_constraint(*a, *b, span),
+                        (a, b) if a == b => (),
+                        (a, b) => self.push_satisfy_err(None, a.clone(), None, b.clone(), span),
+                    }
+                }
+            },
             // if t1 is a fully instantiated type, then t2 can be updated to be a reference to t1
-            (Unit | Integer | Boolean | UserDefined { .. } | String | Arrow(..) | List(..) | Literal(_) | Sum(_), Infer(_, _)) => {
+            (
+                Unit
+                | Integer
+                | SizedInteger(_)
+                | Float
+                | Boolean
+                | UserDefined { .. }
+                | String
+                | Arrow(..)
+                | List(..)
+                | Literal(_)
+                | Sum(_)
+                | Tuple(_),
+                Infer(_, _),
+            ) => {
+                self.trace_rule("instantiate inferred type", t1, t2);
                 let entry = TypeSolutionEntry::new_inferred(Ref(t1));
                 self.update_type(t2, entry, span);
             },
-            // the "parent" infer type will not instantiate to the "child" type
-            (Infer(_, _), Unit | Integer | Boolean | UserDefined { .. } | String | Arrow(..) | List(..) | Literal(_) | Sum(_)) => (),
+            (
+                Infer(_, _),
+                Unit
+                | Integer
+                | SizedInteger(_)
+                | Float
+                | Boolean
+                | UserDefined { .. }
+                | String
+                | Arrow(..)
+                | List(..)
+                | Literal(_)
+                | Sum(_)
+                | Tuple(_),
+            ) => {
+                // the "parent" infer type will not instantiate to the "child" type
+                self.trace_rule("inferred type with known type", t1, t2);
+            },
             (Sum(a_tys), Sum(b_tys)) => {
+                self.trace_rule("sum with sum", t1, t2);
                 // calculate the intersection of these types, update t2 to the intersection
                 let intersection = a_tys.iter().filter(|a_ty| b_tys.contains(a_ty)).cloned().collect();
                 let entry = TypeSolutionEntry::new_inferred(SpecificType::sum(intersection));
@@ -358,21 +933,26 @@ impl TypeSolution {
             },
             // if `ty1` is a generalized version of the sum type,
             // then it satisfies the sum type
-            (ty1, other) if self.a_superset_of_b(&ty1, &other) => (),
-            (Literal(l1), Literal(l2)) if l1 == l2 => (),
+            (ty1, other) if self.a_superset_of_b(&ty1, &other) => self.trace_rule("supertype", t1, t2),
+            (Literal(l1), Literal(l2)) if l1 == l2 => self.trace_rule("equal literals", t1, t2),
             // Literals can satisfy broader parent types
-            (ty, Literal(lit)) => match (lit, ty) {
-                (petr_resolve::Literal::Integer(_), Integer) => (),
-                (petr_resolve::Literal::Boolean(_), Boolean) => (),
-                (petr_resolve::Literal::String(_), String) => (),
-                (lit, ty) => {
-                    self.push_error(span.with_item(self.satisfy_err(ty.clone(), SpecificType::Literal(lit.clone()))));
-                },
+            (ty, Literal(lit)) => {
+                self.trace_rule("literal", t1, t2);
+                match (lit, ty) {
+                    (petr_resolve::Literal::Integer(_), Integer) => (),
+                    (petr_resolve::Literal::Float(_), Float) => (),
+                    (petr_resolve::Literal::Boolean(_), Boolean) => (),
+                    (petr_resolve::Literal::String(_), String) => (),
+                    (lit, ty) => {
+                        self.push_satisfy_err(Some(t1), ty.clone(), Some(t2), SpecificType::Literal(lit.clone()), span);
+                    },
+                }
             },
             // if we are trying to satisfy an inferred type with no bounds, this is ok
-            (Infer(..), _) => (),
+            (Infer(..), _) => self.trace_rule("unbounded inferred type", t1, t2),
             (a, b) => {
-                self.push_error(span.with_item(self.satisfy_err(a.clone(), b.clone())));
+                self.trace_rule("mismatch", t1, t2);
+                self.push_satisfy_err(Some(t1), a.clone(), Some(t2), b.clone(), span);
             },
         }
     }
@@ -413,6 +993,9 @@ impl TypeSolution {
         match (a, b) {
             // If `a` is the generalized form of `b`, then `b` satisfies the constraint.
             (a, b) if a == b || *a == generalized_b => true,
+            // integer literals are values of the fixed-width integer types they fit in
+            (SizedInteger(ty), Literal(petr_resolve::Literal::Integer(value))) => ty.contains(*value as i128),
+            (SizedInteger(_), Sum(b_tys)) => b_tys.iter().all(|b_ty| self.a_superset_of_b(a, b_ty)),
             // If `a` is a sum type which contains `b` OR the generalized form of `b`, then `b`
             // satisfies the constraint.
             (Sum(a_tys), b) if a_tys.contains(b) || a_tys.contains(&generalized_b) => true,
@@ -440,53 +1023,7 @@ impl TypeSolution {
         &self,
         b: &SpecificType,
     ) -> GeneralType {
-        match b {
-            SpecificType::Unit => GeneralType::Unit,
-            SpecificType::Integer => GeneralType::Integer,
-            SpecificType::Boolean => GeneralType::Boolean,
-            SpecificType::String => GeneralType::String,
-            SpecificType::Ref(ty) => self.generalize(&self.get_latest_type(*ty)),
-            SpecificType::UserDefined {
-                name,
-                variants,
-                constant_literal_types,
-            } => GeneralType::UserDefined {
-                name: *name,
-                variants: variants
-                    .iter()
-                    .map(|variant| {
-                        let generalized_fields = variant.fields.iter().map(|field| self.generalize(field)).collect::<Vec<_>>();
-
-                        GeneralizedTypeVariant {
-                            fields: generalized_fields.into_boxed_slice(),
-                        }
-                    })
-                    .collect(),
-                constant_literal_types: constant_literal_types.clone(),
-            },
-            SpecificType::Arrow(tys) => GeneralType::Arrow(tys.clone()),
-            SpecificType::ErrorRecovery => GeneralType::ErrorRecovery,
-            SpecificType::List(ty) => {
-                let ty = self.generalize(ty);
-                GeneralType::List(Box::new(ty))
-            },
-            SpecificType::Infer(u, s) => GeneralType::Infer(*u, *s),
-            SpecificType::Literal(l) => match l {
-                Literal::Integer(_) => GeneralType::Integer,
-                Literal::Boolean(_) => GeneralType::Boolean,
-                Literal::String(_) => GeneralType::String,
-            },
-            SpecificType::Sum(tys) => {
-                // generalize all types, fold if possible
-                let all_generalized: BTreeSet<_> = tys.iter().map(|ty| self.generalize(ty)).collect();
-                if all_generalized.len() == 1 {
-                    // in this case, all specific types generalized to the same type
-                    all_generalized.into_iter().next().expect("invariant")
-                } else {
-                    GeneralType::Sum(all_generalized.into_iter().collect())
-                }
-            },
-        }
+        b.generalize_with(&|ty| self.get_latest_type(ty))
     }
 
     #[cfg(test)]
@@ -516,6 +1053,29 @@ impl TypeSolution {
         self.monomorphized_functions.get(id).expect("invariant: should exist")
     }
 
+    /// The monomorphization of a function for the given signature, if one was created while
+    /// generating constraints. Calls within generic functions aren't monomorphized until their
+    /// caller is.
+    pub fn find_monomorphized_function(
+        &self,
+        id: &FunctionSignature,
+    ) -> Option<&Function> {
+        self.monomorphized_functions.get(id)
+    }
+
+    /// The declarations of every function in the program, with the types they were declared with
+    pub fn functions(&self) -> impl Iterator<Item = (&FunctionId, &Function)> {
+        self.functions.iter()
+    }
+
+    /// The declaration of a function, with the types it was declared with
+    pub fn get_function(
+        &self,
+        id: &FunctionId,
+    ) -> &Function {
+        self.functions.get(id).expect("function IDs should always correspond to typed functions")
+    }
+
     pub fn expr_ty(
         &self,
         expr: &TypedExpr,
@@ -531,7 +1091,30 @@ impl TypeSolution {
             ErrorRecovery(..) => self.error_recovery,
             ExprWithBindings { expression, .. } => self.expr_ty(expression),
             TypeConstructor { ty, .. } => *ty,
+            InterfaceMethod { ty, .. } => *ty,
             If { then_branch, .. } => self.expr_ty(then_branch),
+            Match { ty, .. } => *ty,
+            FunctionValue { ty, .. } => *ty,
+            Lambda { ty, .. } => *ty,
+            ClosureCall { ty, .. } => *ty,
+            GeneralizedLambda { lambda } => self.expr_ty(lambda),
+            Instantiation { instance, .. } => self.expr_ty(instance),
+            FieldAccess { ty, .. } => *ty,
+            RecordUpdate { ty, .. } => *ty,
+            Tuple { ty, .. } => *ty,
+            Hole { ty, .. } => *ty,
         }
     }
 }
+
+/// Whether implementations for `a` and for `b` implement an interface for the same type. A
+/// generic type is implemented for all of its type arguments at once.
+pub(crate) fn is_same_implementing_type(
+    a: &GeneralType,
+    b: &GeneralType,
+) -> bool {
+    match (a, b) {
+        (GeneralType::UserDefined { name: a, .. }, GeneralType::UserDefined { name: b, .. }) => a.id == b.id,
+        (a, b) => a == b,
+    }
+}
@@ -25,7 +25,21 @@ fn check(
     let mut res = pretty_print_type_checker(&type_checker);
 
     let solved_constraints = match type_checker.into_solution() {
-        Ok(solution) => solution.pretty_print(),
+        Ok(solution) => {
+            let mut pretty = solution.pretty_print();
+            if !solution.warnings().is_empty() {
+                pretty.push_str("\n__WARNINGS__\n");
+                pretty.push_str(
+                    &solution
+                        .warnings()
+                        .iter()
+                        .map(|warning| format!("{:?}", warning))
+                        .collect::<Vec<_>>()
+                        .join("\n"),
+                );
+            }
+            pretty
+        },
         Err(errs) => {
             res.push_str("__ERRORS__\n");
             errs.into_iter().map(|err| format!("{:?}", err)).collect::<Vec<_>>().join("\n")
@@ -38,6 +52,22 @@ fn check(
     expect.assert_eq(res.trim());
 }
 
+/// Type checks `sources` together, and prints the errors
+fn check_errors(
+    sources: Vec<(&'static str, &'static str)>,
+    expect: Expect,
+) {
+    let (ast, errs, interner, _) = petr_parse::Parser::new(sources).into_result();
+    assert!(errs.is_empty(), "test failed: code didn't parse");
+    let (errs, resolved) = resolve_symbols(ast, interner, Default::default());
+    assert!(errs.is_empty(), "unresolved symbols in test");
+    let errs = match crate::type_check(resolved) {
+        Ok(_) => vec![],
+        Err(errs) => errs,
+    };
+    expect.assert_eq(&errs.iter().map(|err| format!("{err:?}")).collect::<Vec<_>>().join("\n"));
+}
+
 #[test]
 fn identity_resolution_concrete_type() {
     check(
@@ -100,8 +130,8 @@ fn identity_resolution_two_custom_types() {
     check(
         r#"
             type MyType = A | B
-            type MyComposedType = firstVariant someField 'MyType | secondVariant someField 'int someField2 'MyType someField3 'GenericType
-            fn foo(x in 'MyType) returns 'MyComposedType ~firstVariant(x)
+            type MyComposedType 'GenericType = firstVariant someField 'MyType | secondVariant someField 'int someField2 'MyType someField3 'GenericType
+            fn foo(x in 'MyType) returns 'MyComposedType 'int ~firstVariant(x)
             "#,
         expect![[r#"
                 type MyType: MyType
@@ -117,7 +147,7 @@ fn identity_resolution_two_custom_types() {
                 fn firstVariant: (MyType → MyComposedType)
                 type constructor: MyComposedType
 
-                fn secondVariant: (int → MyType → infer t16 → MyComposedType)
+                fn secondVariant: (int → MyType → infer t28 → MyComposedType)
                 type constructor: MyComposedType
 
                 fn foo: (MyType → MyComposedType)
@@ -127,9 +157,9 @@ fn identity_resolution_two_custom_types() {
                 fn firstVariant(["MyType"]) -> MyComposedType
 
                 __SOLVED TYPES__
-                14: int
-                17: infer t16
-                23: MyType"#]],
+                26: int
+                29: infer t28
+                46: MyType"#]],
     );
 }
 
@@ -214,7 +244,7 @@ fn pass_wrong_type_literal_to_intrinsic() {
 
                 __ERRORS__
 
-                SpannedItem UnificationFailure("string", "true") [Span { source: SourceId(0), span: SourceSpan { offset: SourceOffset(52), length: 4 } }]"#]],
+                SpannedItem UnificationFailure { expected: "string", found: "true", annotation: None, inferred_at: None, found_at: SourceSpan { offset: SourceOffset(52), length: 4 }, help: None } [Span { source: SourceId(0), span: SourceSpan { offset: SourceOffset(52), length: 4 } }]"#]],
     );
 }
 
@@ -254,7 +284,44 @@ fn pass_wrong_type_fn_call_to_intrinsic() {
                 fn bool_literal([]) -> bool
                 __ERRORS__
 
-                SpannedItem UnificationFailure("string", "bool") [Span { source: SourceId(0), span: SourceSpan { offset: SourceOffset(110), length: 14 } }]"#]],
+                SpannedItem UnificationFailure { expected: "string", found: "bool", annotation: None, inferred_at: None, found_at: SourceSpan { offset: SourceOffset(110), length: 13 }, help: None } [Span { source: SourceId(0), span: SourceSpan { offset: SourceOffset(110), length: 13 } }]"#]],
+    );
+}
+
+#[test]
+fn intrinsic_argument_count_mismatch() {
+    check(
+        r#"
+        fn less(a in 'int) returns 'bool
+          @less_than a
+
+        fn neither(a in 'bool, b in 'bool) returns 'bool
+          @not a, b
+
+        fn to_byte(a in 'int, b in 'int) returns 'u8
+          @to_u8 a, b
+
+        fn root(a in 'float) returns 'float
+          @sqrt"#,
+        expect![[r#"
+            fn less: (int → bool)
+            error recovery Span { source: SourceId(0), span: SourceSpan { offset: SourceOffset(52), length: 12 } }
+
+            fn neither: (bool → bool → bool)
+            error recovery Span { source: SourceId(0), span: SourceSpan { offset: SourceOffset(133), length: 9 } }
+
+            fn to_byte: (int → int → u8)
+            error recovery Span { source: SourceId(0), span: SourceSpan { offset: SourceOffset(207), length: 11 } }
+
+            fn root: (float → float)
+            error recovery Span { source: SourceId(0), span: SourceSpan { offset: SourceOffset(274), length: 5 } }
+
+            __ERRORS__
+
+            SpannedItem ArgumentCountMismatch { function: "@less_than", expected: 2, got: 1 } [Span { source: SourceId(0), span: SourceSpan { offset: SourceOffset(52), length: 12 } }]
+            SpannedItem ArgumentCountMismatch { function: "@not", expected: 1, got: 2 } [Span { source: SourceId(0), span: SourceSpan { offset: SourceOffset(133), length: 9 } }]
+            SpannedItem ArgumentCountMismatch { function: "@to_u8", expected: 1, got: 2 } [Span { source: SourceId(0), span: SourceSpan { offset: SourceOffset(207), length: 11 } }]
+            SpannedItem ArgumentCountMismatch { function: "@sqrt", expected: 1, got: 0 } [Span { source: SourceId(0), span: SourceSpan { offset: SourceOffset(274), length: 5 } }]"#]],
     );
 }
 
@@ -321,11 +388,11 @@ fn incorrect_number_of_args() {
                 variable a: int
 
                 fn add_five: (int → int)
-                error recovery Span { source: SourceId(0), span: SourceSpan { offset: SourceOffset(113), length: 8 } }
+                error recovery Span { source: SourceId(0), span: SourceSpan { offset: SourceOffset(114), length: 7 } }
 
                 __ERRORS__
 
-                SpannedItem ArgumentCountMismatch { function: "add", expected: 2, got: 1 } [Span { source: SourceId(0), span: SourceSpan { offset: SourceOffset(113), length: 8 } }]"#]],
+                SpannedItem ArgumentCountMismatch { function: "add", expected: 2, got: 1 } [Span { source: SourceId(0), span: SourceSpan { offset: SourceOffset(114), length: 7 } }]"#]],
     );
 }
 
@@ -382,7 +449,7 @@ fn if_rejects_non_bool_condition() {
                 fn main([]) -> int
                 __ERRORS__
 
-                SpannedItem UnificationFailure("bool", "int") [Span { source: SourceId(0), span: SourceSpan { offset: SourceOffset(61), length: 2 } }]"#]],
+                SpannedItem UnificationFailure { expected: "bool", found: "int", annotation: None, inferred_at: None, found_at: SourceSpan { offset: SourceOffset(61), length: 2 }, help: None } [Span { source: SourceId(0), span: SourceSpan { offset: SourceOffset(61), length: 2 } }]"#]],
     )
 }
 
@@ -405,7 +472,7 @@ fn if_rejects_non_unit_missing_else() {
                 fn main([]) -> int
                 __ERRORS__
 
-                SpannedItem UnificationFailure("1", "unit") [Span { source: SourceId(0), span: SourceSpan { offset: SourceOffset(33), length: 46 } }]"#]],
+                SpannedItem UnificationFailure { expected: "1", found: "unit", annotation: None, inferred_at: None, found_at: SourceSpan { offset: SourceOffset(50), length: 14 }, help: None } [Span { source: SourceId(0), span: SourceSpan { offset: SourceOffset(50), length: 14 } }]"#]],
     )
 }
 
@@ -457,7 +524,7 @@ fn disallow_incorrect_constant_int() {
                 fn main([]) -> OneOrTwo
                 __ERRORS__
 
-                SpannedItem NotSubtype(["1", "2"], "10") [Span { source: SourceId(0), span: SourceSpan { offset: SourceOffset(104), length: 0 } }]"#]],
+                SpannedItem NotSubtype { sum: "1 | 2", found: "10", annotation: Some(SourceSpan { offset: SourceOffset(13), length: 21 }), inferred_at: None, found_at: SourceSpan { offset: SourceOffset(102), length: 2 } } [Span { source: SourceId(0), span: SourceSpan { offset: SourceOffset(102), length: 2 } }]"#]],
     )
 }
 
@@ -484,7 +551,7 @@ fn disallow_incorrect_constant_string() {
                 fn main([]) -> AOrB
                 __ERRORS__
 
-                SpannedItem NotSubtype(["\"A\"", "\"B\""], "\"c\"") [Span { source: SourceId(0), span: SourceSpan { offset: SourceOffset(97), length: 0 } }]"#]],
+                SpannedItem NotSubtype { sum: "\"A\" | \"B\"", found: "\"c\"", annotation: Some(SourceSpan { offset: SourceOffset(13), length: 21 }), inferred_at: None, found_at: SourceSpan { offset: SourceOffset(94), length: 3 } } [Span { source: SourceId(0), span: SourceSpan { offset: SourceOffset(94), length: 3 } }]"#]],
     )
 }
 
@@ -511,7 +578,7 @@ fn disallow_incorrect_constant_bool() {
                 fn main([]) -> AlwaysTrue
                 __ERRORS__
 
-                SpannedItem NotSubtype(["true"], "false") [Span { source: SourceId(0), span: SourceSpan { offset: SourceOffset(100), length: 0 } }]"#]],
+                SpannedItem NotSubtype { sum: "true", found: "false", annotation: Some(SourceSpan { offset: SourceOffset(9), length: 22 }), inferred_at: None, found_at: SourceSpan { offset: SourceOffset(95), length: 5 } } [Span { source: SourceId(0), span: SourceSpan { offset: SourceOffset(95), length: 5 } }]"#]],
     )
 }
 
@@ -534,7 +601,7 @@ fn disallow_wrong_sum_type_in_add() {
 
                 __ERRORS__
 
-                SpannedItem UnificationFailure("int", "IntBelowFive") [Span { source: SourceId(0), span: SourceSpan { offset: SourceOffset(208), length: 2 } }]"#]],
+                SpannedItem UnificationFailure { expected: "int", found: "IntBelowFive", annotation: None, inferred_at: None, found_at: SourceSpan { offset: SourceOffset(208), length: 2 }, help: None } [Span { source: SourceId(0), span: SourceSpan { offset: SourceOffset(208), length: 2 } }]"#]],
     )
 }
 
@@ -657,7 +724,7 @@ fn disallow_bad_generalization() {
                 fn main([]) -> int
                 __ERRORS__
 
-                SpannedItem NotSubtype(["int", "string"], "bool") [Span { source: SourceId(0), span: SourceSpan { offset: SourceOffset(129), length: 0 } }]"#]],
+                SpannedItem NotSubtype { sum: "int | string", found: "bool", annotation: None, inferred_at: None, found_at: SourceSpan { offset: SourceOffset(128), length: 1 } } [Span { source: SourceId(0), span: SourceSpan { offset: SourceOffset(128), length: 1 } }]"#]],
     )
 }
 
@@ -708,3 +775,1123 @@ fn if_exp_basic() {
             7: 1"#]],
     );
 }
+
+#[test]
+fn match_exp() {
+    check(
+        r#"
+        type Shape = Square side 'int | Rect width 'int height 'int
+
+        fn area(s in 'Shape) returns 'int
+            match s
+            | Square side => side
+            | Rect w 0 => w
+            | _ => 1
+        "#,
+        expect![[r#"
+            type Shape: Shape
+
+            fn Square: (int → Shape)
+            type constructor: Shape
+
+            fn Rect: (int → int → Shape)
+            type constructor: Shape
+
+            fn area: (Shape → int)
+            match variable: symbolid9 | variant 0 of typeid0 symbolid3 => variable: symbolid3 | variant 1 of typeid0 symbolid10 0 => variable: symbolid10 | _ => literal: 1
+
+
+            __SOLVED TYPES__
+            6: int
+            10: int
+            11: int
+            16: Shape
+            24: int"#]],
+    );
+}
+
+#[test]
+fn match_pattern_field_type_mismatch() {
+    check(
+        r#"
+        type Shape = Square side 'int | Rect width 'int height 'int
+
+        fn area(s in 'Shape) returns 'int
+            match s
+            | Rect w "tall" => w
+            | _ => 1
+        "#,
+        expect![[r#"
+            type Shape: Shape
+
+            fn Square: (int → Shape)
+            type constructor: Shape
+
+            fn Rect: (int → int → Shape)
+            type constructor: Shape
+
+            fn area: (Shape → int)
+            match variable: symbolid9 | variant 1 of typeid0 symbolid10 "tall" => variable: symbolid10 | _ => literal: 1
+
+            __ERRORS__
+
+            SpannedItem FailedToSatisfy { expected: "int", found: "\"tall\"", annotation: None, inferred_at: None, found_at: SourceSpan { offset: SourceOffset(153), length: 6 }, help: None } [Span { source: SourceId(0), span: SourceSpan { offset: SourceOffset(153), length: 6 } }]"#]],
+    );
+}
+
+#[test]
+fn non_exhaustive_match() {
+    check(
+        r#"
+        type Shape = Square side 'int | Rect width 'int height 'int | Circle radius 'int
+
+        fn area(s in 'Shape) returns 'int
+            match s
+            | Square side => side
+            | Rect w 0 => w
+        "#,
+        expect![[r#"
+            type Shape: Shape
+
+            fn Square: (int → Shape)
+            type constructor: Shape
+
+            fn Rect: (int → int → Shape)
+            type constructor: Shape
+
+            fn Circle: (int → Shape)
+            type constructor: Shape
+
+            fn area: (Shape → int)
+            match variable: symbolid11 | variant 0 of typeid0 symbolid3 => variable: symbolid3 | variant 1 of typeid0 symbolid12 0 => variable: symbolid12
+
+            __ERRORS__
+
+            SpannedItem NonExhaustiveMatch(["Rect _ _", "Circle _"]) [Span { source: SourceId(0), span: SourceSpan { offset: SourceOffset(145), length: 69 } }]"#]],
+    );
+}
+
+#[test]
+fn exhaustive_match_on_constant_literals() {
+    check(
+        r#"
+        type Small = Big value 'int | 1 | 2
+
+        fn to_int(s in 'Small) returns 'int
+            match s
+            | Big value => value
+            | Small 1 => 1
+            | Small 2 => 2
+        "#,
+        expect![[r#"
+            type Small: Small
+
+            fn Big: (int → Small)
+            type constructor: Small
+
+            fn Small: ((1 | 2) → Small)
+            type constructor: Small
+
+            fn to_int: (Small → int)
+            match variable: symbolid6 | variant 0 of typeid0 symbolid3 => variable: symbolid3 | variant 1 of typeid0 1 => literal: 1 | variant 1 of typeid0 2 => literal: 2
+
+
+            __SOLVED TYPES__
+            6: int
+            10: (1 | 2)
+            14: Small
+            20: int
+            23: int"#]],
+    );
+}
+
+#[test]
+fn non_exhaustive_match_on_constant_literals() {
+    check(
+        r#"
+        type Small = Big value 'int | 1 | 2
+
+        fn to_int(s in 'Small) returns 'int
+            match s
+            | Big value => value
+            | Small 1 => 1
+        "#,
+        expect![[r#"
+            type Small: Small
+
+            fn Big: (int → Small)
+            type constructor: Small
+
+            fn Small: ((1 | 2) → Small)
+            type constructor: Small
+
+            fn to_int: (Small → int)
+            match variable: symbolid6 | variant 0 of typeid0 symbolid3 => variable: symbolid3 | variant 1 of typeid0 1 => literal: 1
+
+            __ERRORS__
+
+            SpannedItem NonExhaustiveMatch(["Small 2"]) [Span { source: SourceId(0), span: SourceSpan { offset: SourceOffset(102), length: 67 } }]"#]],
+    );
+}
+
+#[test]
+fn non_exhaustive_match_on_bool() {
+    check(
+        r#"
+        fn to_int(b in 'bool) returns 'int
+            match b
+            | true => 1
+        "#,
+        expect![[r#"
+            fn to_int: (bool → int)
+            match variable: symbolid2 | true => literal: 1
+
+            __ERRORS__
+
+            SpannedItem NonExhaustiveMatch(["false"]) [Span { source: SourceId(0), span: SourceSpan { offset: SourceOffset(56), length: 31 } }]"#]],
+    );
+}
+
+#[test]
+fn non_exhaustive_match_span_ends_at_the_last_arm() {
+    check(
+        r#"
+        fn to_int(b in 'bool) returns 'int
+            match b
+            | true => 1
+
+        fn main() returns 'int ~to_int true
+        "#,
+        expect![[r#"
+            fn to_int: (bool → int)
+            match variable: symbolid2 | true => literal: 1
+
+            fn main: int
+            function call to functionid0 with args: b: true, returns int
+
+            __MONOMORPHIZED FUNCTIONS__
+            fn to_int(["bool"]) -> int
+            fn main([]) -> int
+            __ERRORS__
+
+            SpannedItem NonExhaustiveMatch(["false"]) [Span { source: SourceId(0), span: SourceSpan { offset: SourceOffset(56), length: 31 } }]"#]],
+    );
+}
+
+#[test]
+fn non_exhaustive_match_on_int() {
+    check(
+        r#"
+        fn to_int(i in 'int) returns 'int
+            match i
+            | 0 => 1
+            | 1 => 2
+        "#,
+        expect![[r#"
+            fn to_int: (int → int)
+            match variable: symbolid2 | 0 => literal: 1 | 1 => literal: 2
+
+            __ERRORS__
+
+            SpannedItem NonExhaustiveMatch(["_"]) [Span { source: SourceId(0), span: SourceSpan { offset: SourceOffset(55), length: 49 } }]"#]],
+    );
+}
+
+#[test]
+fn unreachable_match_arm() {
+    check(
+        r#"
+        type Shape = Square side 'int | Rect width 'int height 'int
+
+        fn area(s in 'Shape) returns 'int
+            match s
+            | Square side => side
+            | Rect w h => w
+            | Rect 0 _ => 0
+            | _ => 1
+        "#,
+        expect![[r#"
+            type Shape: Shape
+
+            fn Square: (int → Shape)
+            type constructor: Shape
+
+            fn Rect: (int → int → Shape)
+            type constructor: Shape
+
+            fn area: (Shape → int)
+            match variable: symbolid9 | variant 0 of typeid0 symbolid3 => variable: symbolid3 | variant 1 of typeid0 symbolid10 symbolid11 => variable: symbolid10 | variant 1 of typeid0 0 _ => literal: 0 | _ => literal: 1
+
+
+            __SOLVED TYPES__
+            6: int
+            10: int
+            11: int
+            16: Shape
+            26: int
+            27: int
+
+            __WARNINGS__
+            SpannedItem UnreachableMatchArm [Span { source: SourceId(0), span: SourceSpan { offset: SourceOffset(207), length: 9 } }]
+            SpannedItem UnreachableMatchArm [Span { source: SourceId(0), span: SourceSpan { offset: SourceOffset(235), length: 2 } }]"#]],
+    );
+}
+
+#[test]
+fn lambda_and_closure_call() {
+    check(
+        r#"
+        fn apply(f in 'fn('int) returns 'int, x in 'int) returns 'int ~f x
+
+        fn main() returns 'int
+            let a = 1
+            ~apply(\(x in 'int) => a, 2)
+        "#,
+        expect![[r#"
+            fn apply: ((int → int) → int → int)
+            closure call to variable: symbolid2 with args: variable: symbolid4, 
+
+            fn main: int
+            a: literal: 1 (1),
+            "function call to functionid0 with args: f: (int → 1), x: 2, returns int" (int)
+
+            __MONOMORPHIZED FUNCTIONS__
+            fn apply(["(int → 1)", "int"]) -> int
+            fn main([]) -> int
+
+            __SOLVED TYPES__
+            7: (int → int)
+            8: int
+            17: int"#]],
+    );
+}
+
+#[test]
+fn generic_function_value() {
+    check(
+        r#"
+        fn id(x in 'A) returns 'A x
+
+        fn apply(f in 'fn('int) returns 'int) returns 'int ~f 1
+
+        fn main() returns 'int ~apply id
+        "#,
+        expect![[r#"
+            fn id: (infer t5 → infer t5)
+            variable x: infer t5
+
+            fn apply: ((int → int) → int)
+            closure call to variable: symbolid5 with args: literal: 1, 
+
+            fn main: int
+            function call to functionid1 with args: f: error recovery, returns int
+
+            __MONOMORPHIZED FUNCTIONS__
+            fn apply(["error recovery"]) -> int
+            fn main([]) -> int
+            __ERRORS__
+
+            SpannedItem GenericFunctionValue("id") [Span { source: SourceId(0), span: SourceSpan { offset: SourceOffset(141), length: 2 } }]"#]],
+    );
+}
+
+#[test]
+fn record_field_access_and_update() {
+    check(
+        r#"
+        type Point = Point x 'int y 'bool
+
+        fn main() returns 'bool
+            let p = ~Point y = true, x = 1;
+                q = p with x = 2
+            q.y
+        "#,
+        expect![[r#"
+            type Point: Point
+
+            fn Point: (int → bool → Point)
+            type constructor: Point
+
+            fn main: bool
+            p: function call to functionid0 with args: symbolid2: literal: 1, symbolid4: literal: true,  (Point),
+            q: variable: symbolid7 with fields: 0: literal: 2,  (Point),
+            "field 1 of variable: symbolid8" (bool)
+
+            __MONOMORPHIZED FUNCTIONS__
+            fn Point(["int", "bool"]) -> Point
+            fn main([]) -> bool
+
+            __SOLVED TYPES__
+            6: int
+            7: bool"#]],
+    );
+}
+
+#[test]
+fn record_field_errors() {
+    check(
+        r#"
+        type Point = Point x 'int y 'int
+        type Shape = Circle radius 'int | Square side 'int
+
+        fn no_field(p in 'Point) returns 'int p.z
+
+        fn not_a_record(s in 'Shape) returns 'int s.radius
+
+        fn wrong_type(p in 'Point) returns 'Point p with x = true
+        "#,
+        expect![[r#"
+            type Point: Point
+
+            type Shape: Shape
+
+            fn Point: (int → int → Point)
+            type constructor: Point
+
+            fn Circle: (int → Shape)
+            type constructor: Shape
+
+            fn Square: (int → Shape)
+            type constructor: Shape
+
+            fn no_field: (Point → int)
+            error recovery Span { source: SourceId(0), span: SourceSpan { offset: SourceOffset(148), length: 3 } }
+
+            fn not_a_record: (Shape → int)
+            error recovery Span { source: SourceId(0), span: SourceSpan { offset: SourceOffset(203), length: 8 } }
+
+            fn wrong_type: (Point → Point)
+            variable: symbolid11 with fields: 0: literal: true, 
+
+            __ERRORS__
+
+            SpannedItem NoSuchField { ty: "Point", field: "z" } [Span { source: SourceId(0), span: SourceSpan { offset: SourceOffset(150), length: 1 } }]
+            SpannedItem NotARecord("Shape") [Span { source: SourceId(0), span: SourceSpan { offset: SourceOffset(203), length: 1 } }]
+            SpannedItem FailedToSatisfy { expected: "int", found: "true", annotation: None, inferred_at: None, found_at: SourceSpan { offset: SourceOffset(273), length: 5 }, help: None } [Span { source: SourceId(0), span: SourceSpan { offset: SourceOffset(273), length: 5 } }]"#]],
+    );
+}
+
+#[test]
+fn generic_type_declarations() {
+    check(
+        r#"
+        type Option 'A = None | Some value 'A
+
+        fn unwrap_or(opt in 'Option 'A, default in 'A) returns 'A
+            match opt
+            | Some value => value
+            | None => default
+
+        fn main() returns 'bool
+            let a = ~unwrap_or(~Some(1), 2)
+            ~unwrap_or(~Some(true), false)
+        "#,
+        expect![[r#"
+            type Option: Option
+
+            fn None: Option
+            type constructor: Option
+
+            fn Some: (infer t14 → Option)
+            type constructor: Option
+
+            fn unwrap_or: (Option → infer t24 → infer t24)
+            match variable: symbolid7 | variant 1 of typeid0 symbolid5 => variable: symbolid5 | variant 0 of typeid0 => variable: symbolid8
+
+            fn main: bool
+            a: function call to functionid2 with args: symbolid7: function call to functionid1 with args: symbolid5: literal: 1, , symbolid8: literal: 2,  (2),
+            "function call to functionid2 with args: opt: Option, default: false, returns false" (false)
+
+            __MONOMORPHIZED FUNCTIONS__
+            fn Some(["int"]) -> Option
+            fn Some(["bool"]) -> Option
+            fn unwrap_or(["Option", "int"]) -> infer t24
+            fn unwrap_or(["Option", "bool"]) -> infer t24
+            fn main([]) -> bool
+
+            __SOLVED TYPES__
+            15: infer t14
+            28: Option
+            29: infer t24
+            39: 2
+            46: (1 | 2)
+            55: false
+            62: (false | true)"#]],
+    );
+}
+
+#[test]
+fn generic_type_argument_mismatch() {
+    check(
+        r#"
+        type Option 'A = None | Some value 'A
+
+        fn main() returns 'Option 'int ~Some true
+        "#,
+        expect![[r#"
+            type Option: Option
+
+            fn None: Option
+            type constructor: Option
+
+            fn Some: (infer t14 → Option)
+            type constructor: Option
+
+            fn main: Option
+            function call to functionid1 with args: value: true, returns Option
+
+            __MONOMORPHIZED FUNCTIONS__
+            fn Some(["bool"]) -> Option
+            fn main([]) -> Option
+            __ERRORS__
+
+            SpannedItem FailedToSatisfy { expected: "int", found: "true", annotation: None, inferred_at: None, found_at: SourceSpan { offset: SourceOffset(87), length: 10 }, help: None } [Span { source: SourceId(0), span: SourceSpan { offset: SourceOffset(87), length: 10 } }]"#]],
+    );
+}
+
+#[test]
+fn interface_method_call_with_implementation() {
+    check(
+        r#"
+        interface Size 'A with size(x in 'A) returns 'int
+
+        implement Size for 'bool with size = bool_size
+
+        fn bool_size(x in 'bool) returns 'int 1
+
+        fn main() returns 'int ~size true
+        "#,
+        expect![[r#"
+            fn size: ('A → int)
+            method 0 of interface interfaceid0
+
+            fn bool_size: (bool → int)
+            literal: 1
+
+            fn main: int
+            function call to functionid0 with args: x: true, returns int
+
+            __MONOMORPHIZED FUNCTIONS__
+            fn size(["bool"]) -> int
+            fn bool_size(["bool"]) -> int
+            fn main([]) -> int
+
+            __SOLVED TYPES__
+            6: infer t5
+            10: bool
+            14: bool"#]],
+    );
+}
+
+#[test]
+fn conflicting_implementations() {
+    check(
+        r#"
+        interface Area 'A with area(shape in 'A) returns 'int
+
+        type Square = Square side 'int
+
+        implement Area for 'Square with area = sq
+
+        implement Area for 'Square with area = sq2
+
+        fn sq(s in 'Square) returns 'int 1
+
+        fn sq2(s in 'Square) returns 'int 2
+        "#,
+        expect![[r#"
+            type Square: Square
+
+            fn area: ('A → int)
+            method 0 of interface interfaceid0
+
+            fn Square: (int → Square)
+            type constructor: Square
+
+            fn sq: (Square → int)
+            literal: 1
+
+            fn sq2: (Square → int)
+            literal: 2
+
+            __MONOMORPHIZED FUNCTIONS__
+            fn sq(["Square"]) -> int
+            __ERRORS__
+
+            SpannedItem ConflictingImplementation { interface: "Area", ty: "Square", previous: Some(SourceSpan { offset: SourceOffset(112), length: 41 }) } [Span { source: SourceId(0), span: SourceSpan { offset: SourceOffset(163), length: 42 } }]"#]],
+    );
+}
+
+#[test]
+fn conflicting_implementation_in_another_file() {
+    check_errors(
+        vec![
+            (
+                "ops",
+                r#"
+                export interface Add 'A with add(lhs in 'A, rhs in 'A) returns 'A
+
+                implement Add for 'int with add = add_int
+
+                export fn add_int(lhs in 'int, rhs in 'int) returns 'int @add lhs, rhs
+                "#,
+            ),
+            (
+                "main",
+                r#"
+                implement ops.Add for 'int with add = my_add
+
+                fn my_add(lhs in 'int, rhs in 'int) returns 'int 0
+                "#,
+            ),
+        ],
+        expect![[r#"
+            SpannedItem ConflictingImplementation { interface: "Add", ty: "int", previous: None } [Span { source: SourceId(1), span: SourceSpan { offset: SourceOffset(17), length: 44 } }]
+            SpannedItem RelatedLocation("`Add` is first implemented for `int` here") [Span { source: SourceId(0), span: SourceSpan { offset: SourceOffset(100), length: 41 } }]"#]],
+    );
+}
+
+#[test]
+fn interface_method_call_without_implementation() {
+    check(
+        r#"
+        interface Size 'A with size(x in 'A) returns 'int
+
+        implement Size for 'bool with size = bool_size
+
+        fn bool_size(x in 'bool) returns 'int 1
+
+        fn main() returns 'int ~size "hello"
+        "#,
+        expect![[r#"
+            fn size: ('A → int)
+            method 0 of interface interfaceid0
+
+            fn bool_size: (bool → int)
+            literal: 1
+
+            fn main: int
+            function call to functionid0 with args: x: "hello", returns int
+
+            __MONOMORPHIZED FUNCTIONS__
+            fn size(["string"]) -> int
+            fn bool_size(["bool"]) -> int
+            fn main([]) -> int
+            __ERRORS__
+
+            SpannedItem MissingImplementation { interface: "Size", ty: "string" } [Span { source: SourceId(0), span: SourceSpan { offset: SourceOffset(196), length: 13 } }]"#]],
+    );
+}
+
+#[test]
+fn constrained_generic_requires_where_clause() {
+    check(
+        r#"
+        interface Size 'A with size(x in 'A) returns 'int
+
+        fn measure(x in 'A) returns 'int where 'A: Size
+            ~size x
+
+        fn forgot_constraint(x in 'A) returns 'int
+            ~measure x
+
+        fn main() returns 'int ~forgot_constraint true
+        "#,
+        expect![[r#"
+            fn size: ('A → int)
+            method 0 of interface interfaceid0
+
+            fn measure: (infer t10 → int)
+            function call to functionid0 with args: x: infer t10, returns int
+
+            fn forgot_constraint: (infer t17 → int)
+            function call to functionid1 with args: x: infer t17, returns int
+
+            fn main: int
+            function call to functionid2 with args: x: true, returns int
+
+            __MONOMORPHIZED FUNCTIONS__
+            fn forgot_constraint(["bool"]) -> int
+            fn main([]) -> int
+            __ERRORS__
+
+            SpannedItem UnprovenConstraint("Size") [Span { source: SourceId(0), span: SourceSpan { offset: SourceOffset(200), length: 10 } }]"#]],
+    );
+}
+
+#[test]
+fn let_bound_lambda_is_generalized() {
+    check(
+        r#"
+        fn main() returns 'int
+            let id = \(x in 'A) => x;
+                b = ~id true
+            ~id 42
+        "#,
+        expect![[r#"
+            fn main: int
+            id: generalized lambda(symbolid4, ) captures [] => variable: symbolid4 ((infer t5 → infer t5)),
+            b: closure call to instance of variable: symbolid3 with args: literal: true,  (infer t9),
+            "closure call to instance of variable: symbolid3 with args: literal: 42, " (infer t15)
+
+            __MONOMORPHIZED FUNCTIONS__
+            fn main([]) -> int
+
+            __SOLVED TYPES__
+            6: infer t5
+            9: true
+            10: infer t9
+            15: 42
+            16: infer t15"#]],
+    );
+}
+
+#[test]
+fn recursive_type() {
+    check(
+        r#"
+        type IntList = Nil | Cons head 'int tail 'IntList
+
+        fn head(list in 'IntList) returns 'int
+            match list
+              | Cons head tail => head
+              | Nil => 0
+
+        fn main() returns 'int
+            ~head ~Cons(1, ~Nil)
+        "#,
+        expect![[r#"
+            type IntList: IntList
+
+            fn Nil: IntList
+            type constructor: IntList
+
+            fn Cons: (int → IntList → IntList)
+            type constructor: IntList
+
+            fn head: (IntList → int)
+            match variable: symbolid7 | variant 1 of typeid0 symbolid4 symbolid6 => variable: symbolid4 | variant 0 of typeid0 => literal: 0
+
+            fn main: int
+            function call to functionid2 with args: list: IntList, returns int
+
+            __MONOMORPHIZED FUNCTIONS__
+            fn Nil([]) -> IntList
+            fn Cons(["int", "IntList"]) -> IntList
+            fn head(["IntList"]) -> int
+            fn main([]) -> int
+
+            __SOLVED TYPES__
+            7: int
+            13: IntList
+            18: int"#]],
+    );
+}
+
+#[test]
+fn recursive_type_without_base_case() {
+    check(
+        r#"
+        type Loop = Loop next 'Loop
+        type Tree = Leaf value 'int | Node left 'Tree right 'Tree
+        "#,
+        expect![[r#"
+            type Loop: Loop
+
+            type Tree: Tree
+
+            fn Loop: (Loop → Loop)
+            type constructor: Loop
+
+            fn Leaf: (int → Tree)
+            type constructor: Tree
+
+            fn Node: (Tree → Tree → Tree)
+            type constructor: Tree
+
+            __ERRORS__
+
+            SpannedItem UnconstructibleType("Loop") [Span { source: SourceId(0), span: SourceSpan { offset: SourceOffset(14), length: 4 } }]"#]],
+    );
+}
+
+#[test]
+fn if_narrows_variable_compared_to_literal() {
+    check(
+        r#"
+        fn one_or_two(x in 'sum 1 | 2) returns 'sum 1 | 2 x
+        fn three(x in 'sum 3 | 4) returns 'sum 3 | 4 x
+
+        fn f(x in 'sum 1 | 2 | 3) returns 'int
+            if @equals x, 3 then ~three x else ~one_or_two x
+        "#,
+        expect![[r#"
+            fn one_or_two: ((1 | 2) → (1 | 2))
+            variable x: (1 | 2)
+
+            fn three: ((3 | 4) → (3 | 4))
+            variable x: (3 | 4)
+
+            fn f: ((1 | 2 | 3) → int)
+            if intrinsic: @equal(variable: symbolid2, literal: 3) then function call to functionid1 with args: symbolid2: variable: symbolid2,  else function call to functionid0 with args: symbolid2: variable: symbolid2, 
+
+            __MONOMORPHIZED FUNCTIONS__
+            fn one_or_two(["int"]) -> (1 | 2)
+            fn three(["int"]) -> (3 | 4)
+
+            __SOLVED TYPES__
+            5: (1 | 2)
+            6: (1 | 2)
+            7: (3 | 4)
+            9: (3 | 4)
+            10: (3 | 4)
+            11: (1 | 2 | 3 | 4)
+            13: (1 | 2 | 3)
+            15: (1 | 2 | 3)
+            17: 3
+            20: (1 | 2)"#]],
+    );
+}
+
+#[test]
+fn narrowed_variable_is_checked_against_its_narrowed_type() {
+    check(
+        r#"
+        fn one_or_two(x in 'sum 1 | 2) returns 'sum 1 | 2 x
+
+        fn f(x in 'sum 1 | 2 | 3) returns 'int
+            if @equals x, 3 then ~one_or_two x else 0
+        "#,
+        expect![[r#"
+            fn one_or_two: ((1 | 2) → (1 | 2))
+            variable x: (1 | 2)
+
+            fn f: ((1 | 2 | 3) → int)
+            if intrinsic: @equal(variable: symbolid2, literal: 3) then function call to functionid0 with args: symbolid2: variable: symbolid2,  else literal: 0
+
+            __MONOMORPHIZED FUNCTIONS__
+            fn one_or_two(["int"]) -> (1 | 2)
+            __ERRORS__
+
+            SpannedItem NotSubtype { sum: "1 | 2", found: "3", annotation: Some(SourceSpan { offset: SourceOffset(23), length: 1 }), inferred_at: None, found_at: SourceSpan { offset: SourceOffset(154), length: 1 } } [Span { source: SourceId(0), span: SourceSpan { offset: SourceOffset(154), length: 1 } }]"#]],
+    );
+}
+
+#[test]
+fn sized_integers() {
+    check(
+        r#"
+        fn add(a in 'u8, b in 'u8) returns 'u8 @add a, b
+        fn small() returns 'u8 255
+        fn too_big() returns 'u8 ~add(1, 256)
+        fn widen(a in 'i8) returns 'i32 @to_i32 a
+        "#,
+        expect![[r#"
+            fn add: (u8 → u8 → u8)
+            intrinsic: @add(variable: symbolid2, variable: symbolid4)
+
+            fn small: u8
+            literal: 255
+
+            fn too_big: u8
+            function call to functionid0 with args: a: 1, b: 256, returns u8
+
+            fn widen: (i8 → i32)
+            intrinsic: @to_i32(variable: symbolid2)
+
+            __MONOMORPHIZED FUNCTIONS__
+            fn add(["u8", "u8"]) -> u8
+            __ERRORS__
+
+            SpannedItem FailedToSatisfy { expected: "u8", found: "256", annotation: Some(SourceSpan { offset: SourceOffset(26), length: 1 }), inferred_at: None, found_at: SourceSpan { offset: SourceOffset(133), length: 4 }, help: None } [Span { source: SourceId(0), span: SourceSpan { offset: SourceOffset(133), length: 4 } }]"#]],
+    );
+}
+
+#[test]
+fn floats() {
+    check(
+        r#"
+        fn half(x in 'float) returns 'float @multiply x, 0.5
+        fn root(x in 'float) returns 'float @sqrt x
+        fn truncate(x in 'float) returns 'u8 @to_u8 x
+        fn widen(x in 'int) returns 'float @to_float x
+        fn less(x in 'float) returns 'bool @less_than x, 1.5
+        fn mixed(x in 'float) returns 'float @add x, 1
+        "#,
+        expect![[r#"
+            fn half: (float → float)
+            intrinsic: @multiply(variable: symbolid2, literal: 0.5)
+
+            fn root: (float → float)
+            intrinsic: @sqrt(variable: symbolid2)
+
+            fn truncate: (float → u8)
+            intrinsic: @to_u8(variable: symbolid2)
+
+            fn widen: (int → float)
+            intrinsic: @to_float(variable: symbolid2)
+
+            fn less: (float → bool)
+            intrinsic: @less_than(variable: symbolid2, literal: 1.5)
+
+            fn mixed: (float → float)
+            intrinsic: @add(variable: symbolid2, literal: 1)
+
+            __ERRORS__
+
+            SpannedItem UnificationFailure { expected: "float", found: "1", annotation: None, inferred_at: None, found_at: SourceSpan { offset: SourceOffset(336), length: 2 }, help: None } [Span { source: SourceId(0), span: SourceSpan { offset: SourceOffset(336), length: 2 } }]"#]],
+    );
+}
+
+#[test]
+fn tuples() {
+    check(
+        r#"
+        fn pair() returns '(int, string) (1, "one")
+        fn first() returns 'int
+          let (a, b) = ~pair()
+          a
+        fn too_many() returns 'int
+          let (a, b, c) = ~pair()
+          a
+        "#,
+        expect![[r#"
+            fn pair: (int, string)
+            tuple: (literal: 1, literal: "one", )
+
+            fn first: int
+            (a, b): function call to functionid0 with args:  ((int, string)),
+            "variable a: infer t11" (infer t11)
+
+            fn too_many: int
+            (a, b, c): function call to functionid0 with args:  ((int, string)),
+            "variable a: infer t16" (infer t16)
+
+            __MONOMORPHIZED FUNCTIONS__
+            fn pair([]) -> (int, string)
+            __ERRORS__
+
+            SpannedItem UnificationFailure { expected: "(infer t16, infer t17, infer t18)", found: "(int, string)", annotation: None, inferred_at: None, found_at: SourceSpan { offset: SourceOffset(188), length: 8 }, help: None } [Span { source: SourceId(0), span: SourceSpan { offset: SourceOffset(188), length: 8 } }]"#]],
+    );
+}
+
+#[test]
+fn type_aliases() {
+    check(
+        r#"
+        type alias Id = 'int
+        type alias Digit = 'sum 0 | 1 | 2
+        type Point = Point x 'int y 'int
+        type alias Position = 'Point
+
+        fn next(id in 'Id) returns 'Id @add(id, 1)
+        fn same(digit in 'Digit) returns 'Digit digit
+        fn x_of(position in 'Position) returns 'int position.x
+
+        fn wrong_id() returns 'Id ~next "one"
+        fn two() returns 'Digit ~same 2
+        fn wrong_digit() returns 'Digit ~same 3
+        fn wrong_position() returns 'int ~x_of 5
+        "#,
+        expect![[r#"
+            type Point: Point
+
+            fn Point: (int → int → Point)
+            type constructor: Point
+
+            fn next: (int → int)
+            intrinsic: @add(variable: symbolid9, literal: 1)
+
+            fn same: ((0 | 1 | 2) → (0 | 1 | 2))
+            variable digit: (0 | 1 | 2)
+
+            fn x_of: (Point → int)
+            field 0 of variable: symbolid13
+
+            fn wrong_id: int
+            function call to functionid1 with args: id: "one", returns int
+
+            fn two: (0 | 1 | 2)
+            function call to functionid2 with args: digit: 2, returns (0 | 1 | 2)
+
+            fn wrong_digit: (0 | 1 | 2)
+            function call to functionid2 with args: digit: 3, returns (0 | 1 | 2)
+
+            fn wrong_position: int
+            function call to functionid3 with args: position: 5, returns int
+
+            __MONOMORPHIZED FUNCTIONS__
+            fn next(["string"]) -> int
+            fn same(["int"]) -> (0 | 1 | 2)
+            fn x_of(["int"]) -> int
+            __ERRORS__
+
+            SpannedItem FailedToSatisfy { expected: "Id", found: "\"one\"", annotation: Some(SourceSpan { offset: SourceOffset(167), length: 2 }), inferred_at: None, found_at: SourceSpan { offset: SourceOffset(360), length: 5 }, help: None } [Span { source: SourceId(0), span: SourceSpan { offset: SourceOffset(360), length: 5 } }]
+            SpannedItem NotSubtype { sum: "Digit", found: "3", annotation: Some(SourceSpan { offset: SourceOffset(218), length: 5 }), inferred_at: None, found_at: SourceSpan { offset: SourceOffset(452), length: 1 } } [Span { source: SourceId(0), span: SourceSpan { offset: SourceOffset(452), length: 1 } }]
+            SpannedItem FailedToSatisfy { expected: "Position", found: "5", annotation: Some(SourceSpan { offset: SourceOffset(272), length: 8 }), inferred_at: None, found_at: SourceSpan { offset: SourceOffset(501), length: 1 }, help: None } [Span { source: SourceId(0), span: SourceSpan { offset: SourceOffset(501), length: 1 } }]"#]],
+    );
+}
+
+#[test]
+fn typed_holes() {
+    check(
+        r#"
+fn add(a in 'int, b in 'int) returns 'int @add(a, b)
+
+fn total(count in 'int, label in 'string) returns 'int
+  let doubled = ~add(count, count)
+  ~add(doubled, ?rest)
+
+fn describe(n in 'int) returns 'string
+  let half = @divide(n, 2)
+  _
+"#,
+        expect![[r#"
+            fn add: (int → int → int)
+            intrinsic: @add(variable: symbolid2, variable: symbolid4)
+
+            fn total: (int → string → int)
+            doubled: function call to functionid0 with args: symbolid2: variable: symbolid6, symbolid4: variable: symbolid6,  (int),
+            "function call to functionid0 with args: a: int, b: infer t19, returns int" (int)
+
+            fn describe: (int → string)
+            half: intrinsic: @divide(variable: symbolid12, literal: 2) (int),
+            "hole _" (infer t26)
+
+            __MONOMORPHIZED FUNCTIONS__
+            fn add(["int", "int"]) -> int
+
+            __SOLVED TYPES__
+            5: int
+            6: int
+            12: int
+            13: string
+            19: int
+            22: int
+            24: int
+            26: string
+
+            __WARNINGS__
+            SpannedItem TypedHole { hole: "?rest", expected: "a value of type `int`", in_scope: "in scope: `count: int`, `label: string`, `doubled: int`" } [Span { source: SourceId(0), span: SourceSpan { offset: SourceOffset(160), length: 6 } }]
+            SpannedItem TypedHole { hole: "_", expected: "a value of type `string`", in_scope: "in scope: `n: int`, `half: int`" } [Span { source: SourceId(0), span: SourceSpan { offset: SourceOffset(237), length: 1 } }]"#]],
+    );
+}
+
+#[test]
+fn mismatches_point_to_where_the_expected_type_came_from() {
+    check(
+        r#"
+fn double(x in 'int) returns 'int @add(x, x)
+
+fn one_or_two(b in 'bool) returns 'sum 1 | 2 if b then 1 else 2
+
+fn flip(c in 'bool) returns 'bool if c then false else true
+
+fn main() returns 'int
+  let a = ~double "two";
+      b = ~flip ~one_or_two true
+  a
+"#,
+        expect![[r#"
+            fn double: (int → int)
+            intrinsic: @add(variable: symbolid2, variable: symbolid2)
+
+            fn one_or_two: (bool → (1 | 2))
+            if variable: symbolid5 then literal: 1 else literal: 2
+
+            fn flip: (bool → bool)
+            if variable: symbolid8 then literal: false else literal: true
+
+            fn main: int
+            a: function call to functionid0 with args: symbolid2: literal: "two",  (int),
+            b: function call to functionid2 with args: symbolid8: function call to functionid1 with args: symbolid5: literal: true, ,  (bool),
+            "variable a: int" (int)
+
+            __MONOMORPHIZED FUNCTIONS__
+            fn double(["string"]) -> int
+            fn one_or_two(["bool"]) -> (1 | 2)
+            fn flip(["int"]) -> bool
+            fn main([]) -> int
+            __ERRORS__
+
+            SpannedItem FailedToSatisfy { expected: "int", found: "\"two\"", annotation: Some(SourceSpan { offset: SourceOffset(11), length: 1 }), inferred_at: None, found_at: SourceSpan { offset: SourceOffset(214), length: 5 }, help: None } [Span { source: SourceId(0), span: SourceSpan { offset: SourceOffset(214), length: 5 } }]
+            SpannedItem FailedToSatisfy { expected: "bool", found: "(1 | 2)", annotation: Some(SourceSpan { offset: SourceOffset(120), length: 1 }), inferred_at: None, found_at: SourceSpan { offset: SourceOffset(237), length: 16 }, help: Some("`(1 | 2)` is a sum type, so each of the types in it must be a `bool`") } [Span { source: SourceId(0), span: SourceSpan { offset: SourceOffset(237), length: 16 } }]"#]],
+    );
+}
+
+#[test]
+fn mismatches_point_to_an_expected_type_from_another_file() {
+    check_errors(
+        vec![
+            (
+                "math",
+                r#"
+                export fn double(x in 'int) returns 'int @add(x, x)
+                "#,
+            ),
+            (
+                "main",
+                r#"
+                fn main() returns 'int ~math.double "two"
+                "#,
+            ),
+        ],
+        expect![[r#"
+            SpannedItem FailedToSatisfy { expected: "int", found: "\"two\"", annotation: None, inferred_at: None, found_at: SourceSpan { offset: SourceOffset(53), length: 5 }, help: None } [Span { source: SourceId(1), span: SourceSpan { offset: SourceOffset(53), length: 5 } }]
+            SpannedItem RelatedLocation("expected `int` because of this type annotation") [Span { source: SourceId(0), span: SourceSpan { offset: SourceOffset(34), length: 1 } }]"#]],
+    );
+}
+
+fn check_trace(
+    input: impl Into<String>,
+    expect: Expect,
+) {
+    let parser = petr_parse::Parser::new(vec![("test", input.into())]);
+    let (ast, errs, interner, source_map) = parser.into_result();
+    assert!(errs.is_empty(), "test failed: code didn't parse");
+    let (errs, resolved) = resolve_symbols(ast, interner, Default::default());
+    assert!(errs.is_empty(), "unresolved symbols in test");
+    let (_, trace) = crate::type_check_with_trace(resolved);
+
+    expect.assert_eq(&trace.render_text(&source_map));
+}
+
+#[test]
+fn trace_solver() {
+    check_trace(
+        r#"
+fn pick(b in 'bool) returns 'int
+  if b then 1 else 2
+
+fn main() returns 'int ~pick true
+"#,
+        expect![[r#"
+            axiom t5 at test:2:9
+              update t5: bool => bool
+            unify t2 t6 at test:3:5
+              rule equal types: bool, bool
+            unify t5 t6 at test:3:6
+              rule equal types: bool, bool
+            unify t7 t8 at test:3:19
+              rule different literals: 1, 2
+              update t7: 1 => (1 | 2)
+              update t8: 2 => t7
+            satisfies t5 t11 at test:5:30
+              rule supertype: bool, true
+            satisfies t9 t7 at test:3:3
+              rule supertype: int, (1 | 2)
+            satisfies t13 t9 at test:5:24
+              rule equal types: int, int
+        "#]],
+    );
+}
+
+#[test]
+fn trace_solver_as_json() {
+    let parser = petr_parse::Parser::new(vec![("test", "fn main() returns 'int @add(1, 2)")]);
+    let (ast, _, interner, source_map) = parser.into_result();
+    let (_, resolved) = resolve_symbols(ast, interner, Default::default());
+    let (_, trace) = crate::type_check_with_trace(resolved);
+
+    expect![[r#"{"steps":[{"constraint":"unify","variables":[3,5],"span":{"file":"test","start":28,"end":29,"line":1,"column":29},"events":[{"kind":"rule","rule":"supertype","lhs":"int","rhs":"1"},{"kind":"update","variable":5,"from":"1","to":"t3"}]},{"constraint":"unify","variables":[3,6],"span":{"file":"test","start":30,"end":32,"line":1,"column":31},"events":[{"kind":"rule","rule":"supertype","lhs":"int","rhs":"2"},{"kind":"update","variable":6,"from":"2","to":"t3"}]},{"constraint":"satisfies","variables":[7,3],"span":{"file":"test","start":23,"end":33,"line":1,"column":24},"events":[{"kind":"rule","rule":"equal types","lhs":"int","rhs":"int"}]}]}"#]].assert_eq(&trace.render_json(&source_map));
+}
@@ -0,0 +1,146 @@
+//! A record of what the constraint solver did, for debugging inference. Tracing is opt-in, see
+//! [`crate::type_check_with_trace`]. Each step of the trace is one constraint, followed by the
+//! rules the solver applied to it and the type variables those rules updated, in order.
+
+use petr_utils::{json_string, IndexMap, SourceId, Span};
+
+use crate::TypeVariable;
+
+/// The constraints the solver applied, in the order it applied them
+#[derive(Default, Debug, Clone)]
+pub struct SolverTrace {
+    steps: Vec<TraceStep>,
+}
+
+/// A constraint, and what applying it did
+#[derive(Debug, Clone)]
+pub struct TraceStep {
+    /// Which kind of constraint this is, like `unify` or `satisfies`
+    pub constraint: &'static str,
+    /// The type variables the constraint is about, in the order the constraint takes them
+    pub variables:  Vec<TypeVariable>,
+    /// The span from which the constraint originated
+    pub span:       Span,
+    /// What applying the constraint did, in order
+    pub events:     Vec<TraceEvent>,
+}
+
+#[derive(Debug, Clone)]
+pub enum TraceEvent {
+    /// The solver matched two types against one of its rules. Rules which recurse into the types'
+    /// components, like the parameters of two function types, are followed by the rules applied
+    /// to those.
+    Rule { rule: &'static str, lhs: String, rhs: String },
+    /// A type variable was given a new type. Types which are references to other type variables
+    /// are written as the variable, like `t3`.
+    Update {
+        variable: TypeVariable,
+        from:     String,
+        to:       String,
+    },
+}
+
+impl SolverTrace {
+    pub fn steps(&self) -> &[TraceStep] {
+        &self.steps
+    }
+
+    pub(crate) fn start_step(
+        &mut self,
+        constraint: &'static str,
+        variables: Vec<TypeVariable>,
+        span: Span,
+    ) {
+        self.steps.push(TraceStep {
+            constraint,
+            variables,
+            span,
+            events: Vec::new(),
+        });
+    }
+
+    /// Records an event in the latest step. Events that happen outside of a step, like the
+    /// exhaustiveness checks after solving, aren't part of the trace.
+    pub(crate) fn record(
+        &mut self,
+        event: TraceEvent,
+    ) {
+        if let Some(step) = self.steps.last_mut() {
+            step.events.push(event);
+        }
+    }
+
+    /// Renders the trace as indented text, one line per constraint, rule and update
+    pub fn render_text(
+        &self,
+        sources: &IndexMap<SourceId, (&'static str, &'static str)>,
+    ) -> String {
+        let mut s = String::new();
+        for step in &self.steps {
+            let (file, line, column) = location(step.span, sources);
+            let variables = step.variables.iter().map(|var| format!("t{}", usize::from(*var))).collect::<Vec<_>>();
+            s.push_str(&format!("{} {} at {file}:{line}:{column}\n", step.constraint, variables.join(" ")));
+            for event in &step.events {
+                match event {
+                    TraceEvent::Rule { rule, lhs, rhs } => s.push_str(&format!("  rule {rule}: {lhs}, {rhs}\n")),
+                    TraceEvent::Update { variable, from, to } => s.push_str(&format!("  update t{}: {from} => {to}\n", usize::from(*variable))),
+                }
+            }
+        }
+        s
+    }
+
+    /// Renders the trace as a JSON object with a `steps` array, each with the constraint, its
+    /// variables, its location and its events
+    pub fn render_json(
+        &self,
+        sources: &IndexMap<SourceId, (&'static str, &'static str)>,
+    ) -> String {
+        let steps = self
+            .steps
+            .iter()
+            .map(|step| {
+                let (file, line, column) = location(step.span, sources);
+                let variables = step.variables.iter().map(|var| usize::from(*var).to_string()).collect::<Vec<_>>();
+                let events = step
+                    .events
+                    .iter()
+                    .map(|event| match event {
+                        TraceEvent::Rule { rule, lhs, rhs } => format!(
+                            r#"{{"kind":"rule","rule":{},"lhs":{},"rhs":{}}}"#,
+                            json_string(rule),
+                            json_string(lhs),
+                            json_string(rhs)
+                        ),
+                        TraceEvent::Update { variable, from, to } => format!(
+                            r#"{{"kind":"update","variable":{},"from":{},"to":{}}}"#,
+                            usize::from(*variable),
+                            json_string(from),
+                            json_string(to)
+                        ),
+                    })
+                    .collect::<Vec<_>>();
+                let start = step.span.span().offset();
+                format!(
+                    r#"{{"constraint":{},"variables":[{}],"span":{{"file":{},"start":{start},"end":{},"line":{line},"column":{column}}},"events":[{}]}}"#,
+                    json_string(step.constraint),
+                    variables.join(","),
+                    json_string(file),
+                    start + step.span.span().len(),
+                    events.join(",")
+                )
+            })
+            .collect::<Vec<_>>();
+        format!(r#"{{"steps":[{}]}}"#, steps.join(","))
+    }
+}
+
+/// The name of the file `span` is in, and the line and column it starts at
+fn location(
+    span: Span,
+    sources: &IndexMap<SourceId, (&'static str, &'static str)>,
+) -> (&'static str, usize, usize) {
+    let (name, source) = sources.get(span.source());
+    let (line, column) = span.line_and_column(source);
+    (name, line, column)
+}
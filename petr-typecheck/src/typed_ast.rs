@@ -1,9 +1,9 @@
 use petr_bind::FunctionId;
-use petr_resolve::{Expr, ExprKind, Literal};
-use petr_utils::{Identifier, Span};
+use petr_resolve::{BindingPattern, Expr, ExprKind, FieldPattern, Literal, Pattern};
+use petr_utils::{Identifier, IntegerType, InterfaceId, Span, SpannedItem};
 
 use crate::{
-    constraint_generation::{TypeCheck, TypeChecker},
+    constraint_generation::{FunctionSignature, Hole, TypeCheck, TypeChecker},
     types::SpecificType,
     TypeVariable,
 };
@@ -18,6 +18,20 @@ pub enum Intrinsic {
     Malloc(Box<TypedExpr>),
     SizeOf(Box<TypedExpr>),
     Equals(Box<TypedExpr>, Box<TypedExpr>),
+    NotEquals(Box<TypedExpr>, Box<TypedExpr>),
+    LessThan(Box<TypedExpr>, Box<TypedExpr>),
+    GreaterThan(Box<TypedExpr>, Box<TypedExpr>),
+    LessThanOrEquals(Box<TypedExpr>, Box<TypedExpr>),
+    GreaterThanOrEquals(Box<TypedExpr>, Box<TypedExpr>),
+    And(Box<TypedExpr>, Box<TypedExpr>),
+    Or(Box<TypedExpr>, Box<TypedExpr>),
+    Not(Box<TypedExpr>),
+    /// Converts a number to the given integer type, see [`petr_resolve::IntrinsicName::ConvertTo`]
+    ConvertTo(IntegerType, Box<TypedExpr>),
+    /// Converts a number to a `'float`
+    ToFloat(Box<TypedExpr>),
+    Sqrt(Box<TypedExpr>),
+    Floor(Box<TypedExpr>),
 }
 
 impl std::fmt::Debug for Intrinsic {
@@ -34,6 +48,18 @@ impl std::fmt::Debug for Intrinsic {
             Intrinsic::Malloc(size) => write!(f, "@malloc({:?})", size),
             Intrinsic::SizeOf(expr) => write!(f, "@sizeof({:?})", expr),
             Intrinsic::Equals(lhs, rhs) => write!(f, "@equal({:?}, {:?})", lhs, rhs),
+            Intrinsic::NotEquals(lhs, rhs) => write!(f, "@not_equal({:?}, {:?})", lhs, rhs),
+            Intrinsic::LessThan(lhs, rhs) => write!(f, "@less_than({:?}, {:?})", lhs, rhs),
+            Intrinsic::GreaterThan(lhs, rhs) => write!(f, "@greater_than({:?}, {:?})", lhs, rhs),
+            Intrinsic::LessThanOrEquals(lhs, rhs) => write!(f, "@less_than_or_equal({:?}, {:?})", lhs, rhs),
+            Intrinsic::GreaterThanOrEquals(lhs, rhs) => write!(f, "@greater_than_or_equal({:?}, {:?})", lhs, rhs),
+            Intrinsic::And(lhs, rhs) => write!(f, "@and({:?}, {:?})", lhs, rhs),
+            Intrinsic::Or(lhs, rhs) => write!(f, "@or({:?}, {:?})", lhs, rhs),
+            Intrinsic::Not(expr) => write!(f, "@not({:?})", expr),
+            Intrinsic::ConvertTo(ty, expr) => write!(f, "@to_{ty}({:?})", expr),
+            Intrinsic::ToFloat(expr) => write!(f, "@to_float({:?})", expr),
+            Intrinsic::Sqrt(expr) => write!(f, "@sqrt({:?})", expr),
+            Intrinsic::Floor(expr) => write!(f, "@floor({:?})", expr),
         }
     }
 }
@@ -66,6 +92,10 @@ pub enum TypedExprKind {
         ty:       TypeVariable,
     },
     Unit,
+    Tuple {
+        elements: Vec<TypedExpr>,
+        ty:       TypeVariable,
+    },
     Variable {
         ty:   TypeVariable,
         name: Identifier,
@@ -76,18 +106,86 @@ pub enum TypedExprKind {
     },
     ErrorRecovery(Span),
     ExprWithBindings {
-        bindings:   Vec<(Identifier, TypedExpr)>,
+        bindings:   Vec<(BindingPattern, TypedExpr)>,
         expression: Box<TypedExpr>,
     },
     TypeConstructor {
-        ty:   TypeVariable,
-        args: Box<[TypedExpr]>,
+        ty:      TypeVariable,
+        /// the index of the variant being constructed
+        variant: usize,
+        args:    Box<[TypedExpr]>,
+    },
+    /// The body of an interface method, which calls the implementation of the method for the
+    /// type that `self_ty` is monomorphized to
+    InterfaceMethod {
+        interface: InterfaceId,
+        method:    usize,
+        /// the type parameter of the interface
+        self_ty:   TypeVariable,
+        ty:        TypeVariable,
     },
     If {
         condition:   Box<TypedExpr>,
         then_branch: Box<TypedExpr>,
         else_branch: Box<TypedExpr>,
     },
+    Match {
+        scrutinee: Box<TypedExpr>,
+        arms:      Vec<MatchArm>,
+        ty:        TypeVariable,
+    },
+    /// A named function used as a value. The signature is the monomorphization of the function
+    /// that the value refers to.
+    FunctionValue {
+        signature: FunctionSignature,
+        ty:        TypeVariable,
+    },
+    Lambda {
+        params:   Vec<(Identifier, TypeVariable)>,
+        captures: Vec<Identifier>,
+        body:     Box<TypedExpr>,
+        ty:       TypeVariable,
+    },
+    ClosureCall {
+        callee: Box<TypedExpr>,
+        args:   Vec<TypedExpr>,
+        ty:     TypeVariable,
+    },
+    /// A lambda bound by `let`, whose type is generalized. Its uses are each an
+    /// [`TypedExprKind::Instantiation`] of it, so the lambda is only used for the values it
+    /// captures.
+    GeneralizedLambda {
+        lambda: Box<TypedExpr>,
+    },
+    /// A use of a generalized `let` binding. `instance` is the binding's lambda, type checked
+    /// again for the types it is used at here.
+    Instantiation {
+        name:     Identifier,
+        instance: Box<TypedExpr>,
+    },
+    FieldAccess {
+        target:      Box<TypedExpr>,
+        /// the index of the field in the record's only variant
+        field_index: usize,
+        ty:          TypeVariable,
+    },
+    RecordUpdate {
+        target: Box<TypedExpr>,
+        /// the indices of the replaced fields, and their new values
+        fields: Vec<(usize, TypedExpr)>,
+        ty:     TypeVariable,
+    },
+    /// An expression that hasn't been written yet. Its type is whatever its surroundings expect.
+    Hole {
+        name: Option<Identifier>,
+        ty:   TypeVariable,
+    },
+}
+
+#[derive(Clone, Debug)]
+pub struct MatchArm {
+    pub pattern: SpannedItem<Pattern>,
+    pub body:    TypedExpr,
 }
 
 impl std::fmt::Debug for TypedExpr {
@@ -113,6 +211,13 @@ impl std::fmt::Debug for TypedExpr {
                 write!(f, "]")
             },
             Unit => write!(f, "unit"),
+            Tuple { elements, .. } => {
+                write!(f, "tuple: (")?;
+                for elem in elements {
+                    write!(f, "{:?}, ", elem)?;
+                }
+                write!(f, ")")
+            },
             Variable { name, .. } => write!(f, "variable: {}", name.id),
             Intrinsic { intrinsic, .. } => write!(f, "intrinsic: {:?}", intrinsic),
             ErrorRecovery(span) => {
@@ -120,12 +225,22 @@ impl std::fmt::Debug for TypedExpr {
             },
             ExprWithBindings { bindings, expression } => {
                 write!(f, "bindings: ")?;
-                for (name, expr) in bindings {
-                    write!(f, "{}: {:?}, ", name.id, expr)?;
+                for (pattern, expr) in bindings {
+                    match pattern {
+                        BindingPattern::Name(name) => write!(f, "{}: {:?}, ", name.id, expr)?,
+                        BindingPattern::Tuple(names) => {
+                            write!(f, "(")?;
+                            for name in names.iter() {
+                                write!(f, "{}, ", name.id)?;
+                            }
+                            write!(f, "): {:?}, ", expr)?;
+                        },
+                    }
                 }
                 write!(f, "expression: {:?}", expression)
             },
             TypeConstructor { ty, .. } => write!(f, "type constructor: {:?}", ty),
+            InterfaceMethod { interface, method, .. } => write!(f, "method {method} of interface {interface}"),
             If {
                 condition,
                 then_branch,
@@ -133,6 +248,59 @@ impl std::fmt::Debug for TypedExpr {
             } => {
                 write!(f, "if {:?} then {:?} else {:?}", condition, then_branch, else_branch)
             },
+            Match { scrutinee, arms, .. } => {
+                write!(f, "match {:?}", scrutinee)?;
+                for arm in arms {
+                    write!(f, " | ")?;
+                    match arm.pattern.item() {
+                        Pattern::Wildcard => write!(f, "_")?,
+                        Pattern::Literal(lit) => write!(f, "{lit}")?,
+                        Pattern::Variant { ty, variant, fields } => {
+                            write!(f, "variant {variant} of {ty}")?;
+                            for field in fields.iter() {
+                                match field.item() {
+                                    FieldPattern::Binding(name) => write!(f, " {}", name.id)?,
+                                    FieldPattern::Wildcard => write!(f, " _")?,
+                                    FieldPattern::Literal(lit) => write!(f, " {lit}")?,
+                                }
+                            }
+                        },
+                    }
+                    write!(f, " => {:?}", arm.body)?;
+                }
+                Ok(())
+            },
+            FunctionValue { signature, .. } => write!(f, "function value: {}", signature.0),
+            Lambda { params, captures, body, .. } => {
+                write!(f, "lambda(")?;
+                for (name, _) in params {
+                    write!(f, "{}, ", name.id)?;
+                }
+                write!(f, ") captures [")?;
+                for name in captures {
+                    write!(f, "{}, ", name.id)?;
+                }
+                write!(f, "] => {:?}", body)
+            },
+            GeneralizedLambda { lambda } => write!(f, "generalized {:?}", lambda),
+            Instantiation { name, .. } => write!(f, "instance of variable: {}", name.id),
+            ClosureCall { callee, args, .. } => {
+                write!(f, "closure call to {:?} with args: ", callee)?;
+                for arg in args {
+                    write!(f, "{:?}, ", arg)?;
+                }
+                Ok(())
+            },
+            FieldAccess { target, field_index, .. } => write!(f, "field {field_index} of {:?}", target),
+            RecordUpdate { target, fields, .. } => {
+                write!(f, "{:?} with fields: ", target)?;
+                for (field_index, value) in fields {
+                    write!(f, "{field_index}: {:?}, ", value)?;
+                }
+                Ok(())
+            },
+            Hole { name: Some(name), .. } => write!(f, "hole ?{}", name.id),
+            Hole { name: None, .. } => write!(f, "hole _"),
         }
     }
 }
@@ -170,29 +338,81 @@ impl TypeCheck for Expr {
             },
             ExprKind::FunctionCall(call) => (*call).type_check(ctx),
             ExprKind::Unit => TypedExprKind::Unit,
+            ExprKind::Tuple(exprs) => {
+                let elements = exprs.iter().map(|expr| expr.type_check(ctx)).collect::<Vec<_>>();
+                let element_tys = elements.iter().map(|element| SpecificType::Ref(ctx.expr_ty(element))).collect();
+                let ty = ctx.insert_type::<SpecificType>(&SpecificType::Tuple(element_tys));
+                TypedExprKind::Tuple { elements, ty }
+            },
             ExprKind::ErrorRecovery => TypedExprKind::ErrorRecovery(self.span),
+            ExprKind::Hole { name, locals } => {
+                let ty = ctx.fresh_ty_var(self.span);
+                // the locals are reported with the types they have where the hole is, which may be
+                // narrowed
+                let locals = locals.iter().filter_map(|local| Some((*local, ctx.find_variable(*local)?))).collect();
+                ctx.insert_hole(Hole {
+                    span: self.span,
+                    name: *name,
+                    ty,
+                    locals,
+                });
+                TypedExprKind::Hole { name: *name, ty }
+            },
             ExprKind::Variable { name, ty } => {
-                // look up variable in scope
-                // find its expr return type
-                let var_ty = ctx.find_variable(*name).expect("variable not found in scope");
-                let ty = ctx.to_type_var(ty);
+                // each use of a generalized binding gets its own instance of it. The declared type
+                // of a `let` bound variable refers to the type of the binding, so it isn't used.
+                if let Some(instance) = ctx.instantiate_variable(*name) {
+                    TypedExprKind::Instantiation {
+                        name:     *name,
+                        instance: Box::new(instance),
+                    }
+                } else if let Some(ty) = ctx.narrowed_variable(*name) {
+                    // a narrowed variable has its narrowed type, rather than the one it was
+                    // declared with
+                    TypedExprKind::Variable { ty, name: *name }
+                } else {
+                    // look up variable in scope
+                    // find its expr return type
+                    let var_ty = ctx.find_variable(*name).expect("variable not found in scope");
+                    let ty = ctx.to_type_var(ty);
 
-                ctx.unify(var_ty, ty, name.span());
+                    ctx.unify(var_ty, ty, name.span());
 
-                TypedExprKind::Variable { ty, name: *name }
+                    TypedExprKind::Variable { ty, name: *name }
+                }
             },
             ExprKind::Intrinsic(intrinsic) => return self.span.with_item(intrinsic.clone()).type_check(ctx),
-            ExprKind::TypeConstructor(parent_type_id, args) => {
+            ExprKind::TypeConstructor(parent_type_id, variant, args) => {
                 // This ExprKind only shows up in the body of type constructor functions, and
                 // is basically a noop. The surrounding function decl will handle type checking for
                 // the type constructor.
                 let args = args.iter().map(|arg| arg.type_check(ctx)).collect::<Vec<_>>();
-                let ty = ctx.get_type(*parent_type_id);
+                // the constructor function is generic over the type's parameters, so the constructed
+                // value is an instance of the type with them
+                let type_params = ctx.resolved().get_type(*parent_type_id).type_params.clone();
+                let type_args = type_params.iter().map(|param| ctx.generic_type(param)).collect();
+                let ty = ctx.instantiate_type(*parent_type_id, Some(type_args));
                 TypedExprKind::TypeConstructor {
-                    ty:   *ty,
+                    ty,
+                    variant: *variant,
                     args: args.into_boxed_slice(),
                 }
             },
+            ExprKind::InterfaceMethod(interface, method) => {
+                // like a type constructor, this only shows up in the body of the functions which
+                // the binder creates for an interface's methods, which are generic over the
+                // interface's type parameter
+                let declaration = ctx.resolved().get_interface(*interface).clone();
+                let self_ty = ctx.generic_type(&declaration.type_param);
+                let method_declaration = ctx.resolved().get_function(declaration.methods[*method]).clone();
+                let ty = ctx.to_type_var(&method_declaration.return_type);
+                TypedExprKind::InterfaceMethod {
+                    interface: *interface,
+                    method: *method,
+                    self_ty,
+                    ty,
+                }
+            },
             ExprKind::ExpressionWithBindings { bindings, expression } => {
                 // for each binding, type check the rhs
                 ctx.with_type_scope(|ctx| {
@@ -200,8 +420,34 @@ impl TypeCheck for Expr {
                     for binding in bindings {
                         let binding_ty = binding.expression.type_check(ctx);
                         let binding_expr_return_ty = ctx.expr_ty(&binding_ty);
-                        ctx.insert_variable(binding.name, binding_expr_return_ty);
-                        type_checked_bindings.push((binding.name, binding_ty));
+                        match &binding.pattern {
+                            // only lambdas are generalized, as they are values whose type can be
+                            // instantiated without evaluating them again
+                            BindingPattern::Name(name) if matches!(binding.expression.kind, ExprKind::Lambda { .. }) => {
+                                ctx.insert_generalized_variable(*name, binding_expr_return_ty, &binding.expression);
+                                let lambda = Box::new(binding_ty);
+                                let generalized = TypedExpr {
+                                    span: lambda.span,
+                                    kind: TypedExprKind::GeneralizedLambda { lambda },
+                                };
+                                type_checked_bindings.push((binding.pattern.clone(), generalized));
+                            },
+                            BindingPattern::Name(name) => {
+                                ctx.insert_variable(*name, binding_expr_return_ty);
+                                type_checked_bindings.push((binding.pattern.clone(), binding_ty));
+                            },
+                            // the bound expression must be a tuple with one element for each name
+                            BindingPattern::Tuple(names) => {
+                                let element_tys = names.iter().map(|name| ctx.fresh_ty_var(name.span)).collect::<Vec<_>>();
+                                let tuple_ty = ctx
+                                    .insert_type::<SpecificType>(&SpecificType::Tuple(element_tys.iter().map(|ty| SpecificType::Ref(*ty)).collect()));
+                                ctx.unify(tuple_ty, binding_expr_return_ty, binding_ty.span());
+                                for (name, ty) in names.iter().zip(element_tys) {
+                                    ctx.insert_variable(*name, ty);
+                                }
+                                type_checked_bindings.push((binding.pattern.clone(), binding_ty));
+                            },
+                        }
                     }
 
                     TypedExprKind::ExprWithBindings {
@@ -215,14 +461,21 @@ impl TypeCheck for Expr {
                 then_branch,
                 else_branch,
             } => {
+                let narrowing = ctx.narrowing_condition(condition);
                 let condition = condition.type_check(ctx);
                 let condition_ty = ctx.expr_ty(&condition);
                 ctx.unify(ctx.bool(), condition_ty, condition.span());
 
-                let then_branch = then_branch.type_check(ctx);
+                // if the condition checks whether a variable is equal to a literal, then the
+                // variable's type is narrowed in each branch
+                let (then_branch, else_branch) = match narrowing {
+                    Some((variable, literal, equal)) => (
+                        ctx.with_narrowed_variable(variable, &literal, equal, condition.span(), |ctx| then_branch.type_check(ctx)),
+                        ctx.with_narrowed_variable(variable, &literal, !equal, condition.span(), |ctx| else_branch.type_check(ctx)),
+                    ),
+                    None => (then_branch.type_check(ctx), else_branch.type_check(ctx)),
+                };
                 let then_ty = ctx.expr_ty(&then_branch);
-
-                let else_branch = else_branch.type_check(ctx);
                 let else_ty = ctx.expr_ty(&else_branch);
 
                 ctx.unify(then_ty, else_ty, else_branch.span());
@@ -233,6 +486,106 @@ impl TypeCheck for Expr {
                     else_branch: Box::new(else_branch),
                 }
             },
+            ExprKind::Match { scrutinee, arms } => {
+                let scrutinee = scrutinee.type_check(ctx);
+                let scrutinee_ty = ctx.known_expr_ty(&scrutinee);
+
+                let arms = arms
+                    .iter()
+                    .map(|arm| {
+                        // the bindings introduced by a pattern are only in scope for that arm
+                        ctx.with_type_scope(|ctx| {
+                            ctx.type_check_pattern(&arm.pattern, scrutinee_ty);
+                            MatchArm {
+                                pattern: arm.pattern.clone(),
+                                body:    arm.body.type_check(ctx),
+                            }
+                        })
+                    })
+                    .collect::<Vec<_>>();
+
+                // every arm must return the same type
+                let ty = match arms.first() {
+                    Some(first_arm) => ctx.expr_ty(&first_arm.body),
+                    None => ctx.unit(),
+                };
+                for arm in arms.iter().skip(1) {
+                    let arm_ty = ctx.expr_ty(&arm.body);
+                    ctx.unify(ty, arm_ty, arm.body.span());
+                }
+
+                TypedExprKind::Match {
+                    scrutinee: Box::new(scrutinee),
+                    arms,
+                    ty,
+                }
+            },
+            ExprKind::FunctionValue(function) => ctx.type_check_function_value(*function, self.span),
+            ExprKind::Lambda { params, captures, body } => ctx.with_type_scope(|ctx| {
+                let params = params.iter().map(|(name, ty)| (*name, ctx.to_type_var(ty))).collect::<Vec<_>>();
+                for (name, ty) in &params {
+                    ctx.insert_variable(*name, *ty);
+                    ctx.axiom(*ty, name.span);
+                }
+                let body = body.type_check(ctx);
+                let body_ty = ctx.expr_ty(&body);
+                let ty = ctx.function_value_type(params.iter().map(|(_, ty)| *ty).chain(std::iter::once(body_ty)).collect());
+                TypedExprKind::Lambda {
+                    params,
+                    captures: captures.clone(),
+                    body: Box::new(body),
+                    ty,
+                }
+            }),
+            ExprKind::ClosureCall { callee, args } => {
+                let callee = callee.type_check(ctx);
+                let args = args.iter().map(|arg| arg.type_check(ctx)).collect::<Vec<_>>();
+                let callee_ty = ctx.expr_ty(&callee);
+                // if the callee's type is already known, then so is the return type. Otherwise, it
+                // will be inferred.
+                let ty = match ctx.known_function_value_type(callee_ty) {
+                    Some(tys) if tys.len() == args.len() + 1 => tys[args.len()],
+                    _ => ctx.fresh_ty_var(self.span),
+                };
+                let call_tys = args.iter().map(|arg| ctx.expr_ty(arg)).chain(std::iter::once(ty)).collect();
+                let call_ty = ctx.function_value_type(call_tys);
+                ctx.unify(callee_ty, call_ty, self.span);
+                TypedExprKind::ClosureCall {
+                    callee: Box::new(callee),
+                    args,
+                    ty,
+                }
+            },
+            ExprKind::FieldAccess { target, field } => {
+                let target = target.type_check(ctx);
+                match ctx.record_field(&target, *field) {
+                    Some((field_index, ty)) => TypedExprKind::FieldAccess {
+                        target: Box::new(target),
+                        field_index,
+                        ty,
+                    },
+                    None => TypedExprKind::ErrorRecovery(self.span),
+                }
+            },
+            ExprKind::RecordUpdate { target, fields } => {
+                let target = target.type_check(ctx);
+                let ty = ctx.expr_ty(&target);
+                let mut updated_fields = Vec::with_capacity(fields.len());
+                for (name, value) in fields {
+                    let value = value.type_check(ctx);
+                    let Some((field_index, field_ty)) = ctx.record_field(&target, *name) else {
+                        continue;
+                    };
+                    let value_ty = ctx.expr_ty(&value);
+                    ctx.satisfies(field_ty, value_ty, value.span());
+                    updated_fields.push((field_index, value));
+                }
+                TypedExprKind::RecordUpdate {
+                    target: Box::new(target),
+                    fields: updated_fields,
+                    ty,
+                }
+            },
         };
 
         TypedExpr { kind, span: self.span }
@@ -245,4 +598,6 @@ pub struct Function {
     pub params:    Vec<(Identifier, TypeVariable)>,
     pub body:      TypedExpr,
     pub return_ty: TypeVariable,
+    /// The effects the function is declared to have, if it has an effect annotation
+    pub effects:   Option<Vec<Identifier>>,
 }
@@ -1,7 +1,7 @@
 use std::collections::BTreeSet;
 
 use petr_resolve::Literal;
-use petr_utils::{Identifier, IndexMap, Span};
+use petr_utils::{Identifier, IndexMap, IntegerType, Span};
 
 use crate::TypeVariable;
 
@@ -13,6 +13,8 @@ use crate::TypeVariable;
 pub enum GeneralType {
     Unit,
     Integer,
+    SizedInteger(IntegerType),
+    Float,
     Boolean,
     String,
     UserDefined {
@@ -26,15 +28,42 @@ pub enum GeneralType {
     List(Box<GeneralType>),
     Infer(usize, Span),
     Sum(BTreeSet<GeneralType>),
+    Tuple(Vec<GeneralType>),
+    /// A field of a recursive type which refers back to an enclosing user-defined type. Like
+    /// [`GeneralType::Arrow`], this keeps the type variable, as generalizing it would never end.
+    Recursive(TypeVariable),
 }
 
 impl GeneralType {
+    /// Whether any part of this type is still to be inferred, like a type parameter of the
+    /// function that the type appears in
+    pub fn contains_infer(&self) -> bool {
+        match self {
+            GeneralType::Infer(..) => true,
+            GeneralType::UserDefined { variants, .. } => variants.iter().any(|variant| variant.fields.iter().any(GeneralType::contains_infer)),
+            GeneralType::List(ty) => ty.contains_infer(),
+            GeneralType::Sum(tys) => tys.iter().any(GeneralType::contains_infer),
+            GeneralType::Tuple(tys) => tys.iter().any(GeneralType::contains_infer),
+            GeneralType::Unit
+            | GeneralType::Integer
+            | GeneralType::SizedInteger(_)
+            | GeneralType::Float
+            | GeneralType::Boolean
+            | GeneralType::String
+            | GeneralType::Arrow(_)
+            | GeneralType::ErrorRecovery
+            | GeneralType::Recursive(_) => false,
+        }
+    }
+
     /// Because [`GeneralType`]'s type info is less detailed (specific) than [`SpecificType`],
     /// we can losslessly cast any [`GeneralType`] into an instance of [`SpecificType`].
     pub fn safely_upcast(&self) -> SpecificType {
         match self {
             GeneralType::Unit => SpecificType::Unit,
             GeneralType::Integer => SpecificType::Integer,
+            GeneralType::SizedInteger(ty) => SpecificType::SizedInteger(*ty),
+            GeneralType::Float => SpecificType::Float,
             GeneralType::Boolean => SpecificType::Boolean,
             GeneralType::String => SpecificType::String,
             GeneralType::ErrorRecovery => SpecificType::ErrorRecovery,
@@ -60,6 +89,8 @@ impl GeneralType {
             GeneralType::Arrow(tys) => SpecificType::Arrow(tys.clone()),
             GeneralType::List(ty) => SpecificType::List(Box::new(ty.safely_upcast())),
             GeneralType::Sum(tys) => SpecificType::Sum(tys.iter().map(|ty| ty.safely_upcast()).collect()),
+            GeneralType::Tuple(tys) => SpecificType::Tuple(tys.iter().map(|ty| ty.safely_upcast()).collect()),
+            GeneralType::Recursive(ty) => SpecificType::Ref(*ty),
         }
     }
 }
@@ -70,7 +101,12 @@ impl GeneralType {
 #[derive(Clone, PartialEq, Debug, Eq, PartialOrd, Ord)]
 pub enum SpecificType {
     Unit,
+    /// `'int`, which is the same as the fixed-width integer type `'i64`
     Integer,
+    /// A fixed-width integer type other than `'i64`, see [`SpecificType::integer`]
+    SizedInteger(IntegerType),
+    /// `'float`, a 64-bit floating point number
+    Float,
     Boolean,
     /// a static length string known at compile time
     String,
@@ -93,6 +129,8 @@ pub enum SpecificType {
     Infer(usize, Span),
     Sum(BTreeSet<SpecificType>),
     Literal(Literal),
+    /// An anonymous tuple type, like `'(int, string)`
+    Tuple(Vec<SpecificType>),
 }
 
 #[derive(Clone, PartialEq, Debug, Eq, PartialOrd, Ord)]
@@ -101,49 +139,84 @@ pub struct GeneralizedTypeVariant {
 }
 
 impl SpecificType {
-    fn generalize_inner(
+    /// Generalizes this type, looking up the types that [`SpecificType::Ref`]s refer to with
+    /// `look_up`.
+    pub(crate) fn generalize_with(
         &self,
-        types: &IndexMap<TypeVariable, SpecificType>,
+        look_up: &impl Fn(TypeVariable) -> SpecificType,
+    ) -> GeneralType {
+        self.generalize_within(look_up, &mut Vec::new())
+    }
+
+    /// `enclosing` are the user-defined types that this type is a field of. A reference back to
+    /// one of them is a recursive type, and is not generalized again.
+    fn generalize_within(
+        &self,
+        look_up: &impl Fn(TypeVariable) -> SpecificType,
+        enclosing: &mut Vec<SpecificType>,
     ) -> GeneralType {
         match self {
             SpecificType::Unit => GeneralType::Unit,
             SpecificType::Integer => GeneralType::Integer,
+            SpecificType::SizedInteger(ty) => GeneralType::SizedInteger(*ty),
+            SpecificType::Float => GeneralType::Float,
             SpecificType::Boolean => GeneralType::Boolean,
             SpecificType::String => GeneralType::String,
-            SpecificType::Ref(ty) => types.get(*ty).generalize(types),
+            SpecificType::Ref(ty) => {
+                let mut ty = *ty;
+                let mut resolved = look_up(ty);
+                while let SpecificType::Ref(next) = resolved {
+                    ty = next;
+                    resolved = look_up(ty);
+                }
+                if enclosing.contains(&resolved) {
+                    return GeneralType::Recursive(ty);
+                }
+                resolved.generalize_within(look_up, enclosing)
+            },
             SpecificType::UserDefined {
                 name,
                 variants,
                 constant_literal_types,
-            } => GeneralType::UserDefined {
-                name: *name,
-                variants: variants
+            } => {
+                enclosing.push(self.clone());
+                let variants = variants
                     .iter()
                     .map(|variant| {
-                        let generalized_fields = variant.fields.iter().map(|field| field.generalize(types)).collect::<Vec<_>>();
+                        let generalized_fields = variant
+                            .fields
+                            .iter()
+                            .map(|field| field.generalize_within(look_up, enclosing))
+                            .collect::<Vec<_>>();
 
                         GeneralizedTypeVariant {
                             fields: generalized_fields.into_boxed_slice(),
                         }
                     })
-                    .collect(),
-                constant_literal_types: constant_literal_types.clone(),
+                    .collect();
+                enclosing.pop();
+                GeneralType::UserDefined {
+                    name: *name,
+                    variants,
+                    constant_literal_types: constant_literal_types.clone(),
+                }
             },
             SpecificType::Arrow(tys) => GeneralType::Arrow(tys.clone()),
             SpecificType::ErrorRecovery => GeneralType::ErrorRecovery,
             SpecificType::List(ty) => {
-                let ty = ty.generalize(types);
+                let ty = ty.generalize_within(look_up, enclosing);
                 GeneralType::List(Box::new(ty))
             },
             SpecificType::Infer(u, s) => GeneralType::Infer(*u, *s),
             SpecificType::Literal(l) => match l {
                 Literal::Integer(_) => GeneralType::Integer,
+                Literal::Float(_) => GeneralType::Float,
                 Literal::Boolean(_) => GeneralType::Boolean,
                 Literal::String(_) => GeneralType::String,
             },
             SpecificType::Sum(tys) => {
                 // generalize all types, fold if possible
-                let all_generalized: BTreeSet<_> = tys.iter().map(|ty| ty.generalize(types)).collect();
+                let all_generalized: BTreeSet<_> = tys.iter().map(|ty| ty.generalize_within(look_up, enclosing)).collect();
                 if all_generalized.len() == 1 {
                     // in this case, all specific types generalized to the same type
                     all_generalized.into_iter().next().expect("invariant")
@@ -151,6 +224,33 @@ impl SpecificType {
                     GeneralType::Sum(all_generalized.into_iter().collect())
                 }
             },
+            SpecificType::Tuple(tys) => GeneralType::Tuple(tys.iter().map(|ty| ty.generalize_within(look_up, enclosing)).collect()),
+        }
+    }
+
+    /// The type of integers of the given type. `'i64` is `'int`, which is [`SpecificType::Integer`].
+    pub(crate) fn integer(ty: IntegerType) -> SpecificType {
+        match ty {
+            IntegerType::I64 => SpecificType::Integer,
+            ty => SpecificType::SizedInteger(ty),
+        }
+    }
+
+    /// The integer type of values of this type, if they are integers of a known type
+    pub(crate) fn integer_type(&self) -> Option<IntegerType> {
+        match self {
+            SpecificType::Integer => Some(IntegerType::I64),
+            SpecificType::SizedInteger(ty) => Some(*ty),
+            _ => None,
+        }
+    }
+
+    /// The type of numbers of this type, if they are integers of a known type or floats. Float
+    /// literals are floats, but integer literals may be any integer type, so they are unknown.
+    pub(crate) fn numeric_type(&self) -> Option<SpecificType> {
+        match self {
+            SpecificType::Float | SpecificType::Literal(Literal::Float(_)) => Some(SpecificType::Float),
+            ty => ty.integer_type().map(SpecificType::integer),
         }
     }
 
@@ -188,7 +288,7 @@ impl Type for SpecificType {
         &self,
         types: &IndexMap<TypeVariable, SpecificType>,
     ) -> GeneralType {
-        self.generalize_inner(types)
+        self.generalize_with(&|ty| types.get(ty).clone())
     }
 }
 
@@ -204,6 +304,8 @@ impl Type for GeneralType {
         match self {
             GeneralType::Unit => SpecificType::Unit,
             GeneralType::Integer => SpecificType::Integer,
+            GeneralType::SizedInteger(ty) => SpecificType::SizedInteger(*ty),
+            GeneralType::Float => SpecificType::Float,
             GeneralType::Boolean => SpecificType::Boolean,
             GeneralType::String => SpecificType::String,
             GeneralType::UserDefined {
@@ -232,6 +334,8 @@ impl Type for GeneralType {
                 let tys = tys.iter().map(|ty| ty.as_specific_ty()).collect();
                 SpecificType::Sum(tys)
             },
+            GeneralType::Tuple(tys) => SpecificType::Tuple(tys.iter().map(|ty| ty.as_specific_ty()).collect()),
+            GeneralType::Recursive(ty) => SpecificType::Ref(*ty),
         }
     }
 }
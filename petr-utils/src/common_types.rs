@@ -16,6 +16,11 @@ idx_map_key!(
     TypeId
 );
 
+idx_map_key!(
+    /// The ID type of an interface declaration.
+    InterfaceId
+);
+
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub struct Identifier {
     pub id:   SymbolId,
@@ -74,6 +79,100 @@ impl Path {
     }
 }
 
+/// A fixed-width integer type, like `'u8` or `'i32`. `'int` is a signed 64-bit integer, so it is
+/// the same as [`IntegerType::I64`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum IntegerType {
+    I8,
+    I16,
+    I32,
+    I64,
+    U8,
+    U16,
+    U32,
+    U64,
+}
+
+impl IntegerType {
+    /// The integer type with the name used in type annotations, like `i8` for `'i8`
+    pub fn from_name(name: &str) -> Option<Self> {
+        use IntegerType::*;
+        Some(match name {
+            "i8" => I8,
+            "i16" => I16,
+            "i32" => I32,
+            "i64" => I64,
+            "u8" => U8,
+            "u16" => U16,
+            "u32" => U32,
+            "u64" => U64,
+            _ => return None,
+        })
+    }
+
+    pub fn name(&self) -> &'static str {
+        use IntegerType::*;
+        match self {
+            I8 => "i8",
+            I16 => "i16",
+            I32 => "i32",
+            I64 => "i64",
+            U8 => "u8",
+            U16 => "u16",
+            U32 => "u32",
+            U64 => "u64",
+        }
+    }
+
+    pub fn is_signed(&self) -> bool {
+        use IntegerType::*;
+        matches!(self, I8 | I16 | I32 | I64)
+    }
+
+    pub fn bits(&self) -> u32 {
+        use IntegerType::*;
+        match self {
+            I8 | U8 => 8,
+            I16 | U16 => 16,
+            I32 | U32 => 32,
+            I64 | U64 => 64,
+        }
+    }
+
+    pub fn min(&self) -> i128 {
+        if self.is_signed() {
+            -(1 << (self.bits() - 1))
+        } else {
+            0
+        }
+    }
+
+    pub fn max(&self) -> i128 {
+        if self.is_signed() {
+            (1 << (self.bits() - 1)) - 1
+        } else {
+            (1 << self.bits()) - 1
+        }
+    }
+
+    /// Whether `value` is one of the values of this type
+    pub fn contains(
+        &self,
+        value: i128,
+    ) -> bool {
+        (self.min()..=self.max()).contains(&value)
+    }
+}
+
+impl std::fmt::Display for IntegerType {
+    fn fmt(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+    ) -> std::fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
 #[cfg(not(feature = "debug"))]
 idx_map_key!(
     /// The ID of an ident in the symbol interner
@@ -0,0 +1,440 @@
+//! A sink which every stage of the compiler reports its diagnostics to, so that they can be
+//! rendered together. Warnings are reported under the name of a lint, like
+//! `unreachable_match_arm`, and each lint can be allowed, which drops its warnings, or denied,
+//! which turns them into errors.
+
+use std::collections::BTreeMap;
+
+use miette::{Diagnostic, LabeledSpan, Report};
+
+use crate::{json_optional_string, json_string, IndexMap, SourceId, Span, SpannedItem};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    /// The program can't be compiled
+    Error,
+    /// The program compiles, but probably doesn't do what was intended
+    Warning,
+    /// Information which helps with understanding the program or other diagnostics
+    Note,
+}
+
+/// How the warnings of a lint are reported
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LintLevel {
+    /// The warnings are dropped
+    Allow,
+    /// The warnings are reported as warnings, unless all warnings are denied
+    #[default]
+    Warn,
+    /// The warnings are reported as errors
+    Deny,
+}
+
+/// A diagnostic which was reported to [`Diagnostics`], with the severity it was given
+#[derive(Debug)]
+pub struct ReportedDiagnostic {
+    severity:   Severity,
+    /// The lint this diagnostic was reported under, if it is a warning or a denied warning
+    lint:       Option<&'static str>,
+    /// The span the diagnostic is about, if it is about a particular part of the program
+    span:       Option<Span>,
+    diagnostic: Box<dyn Diagnostic + Send + Sync>,
+}
+
+impl ReportedDiagnostic {
+    pub fn severity(&self) -> Severity {
+        self.severity
+    }
+
+    pub fn lint(&self) -> Option<&'static str> {
+        self.lint
+    }
+
+    pub fn span(&self) -> Option<Span> {
+        self.span
+    }
+
+    pub fn diagnostic(&self) -> &(dyn Diagnostic + Send + Sync) {
+        self.diagnostic.as_ref()
+    }
+
+    /// The help of the diagnostic. Warnings also say which lint they belong to, so that it can be
+    /// configured.
+    pub fn help(&self) -> Option<String> {
+        let help = self.diagnostic.help().map(|help| help.to_string());
+        let lint = self.lint.map(|lint| match self.severity {
+            Severity::Error => format!("`{lint}` is denied, it can be allowed in the [lints] table of pete.toml"),
+            _ => format!("`{lint}` can be allowed or denied in the [lints] table of pete.toml"),
+        });
+        match (help, lint) {
+            (Some(help), Some(lint)) => Some(format!("{help}\n{lint}")),
+            (help, lint) => help.or(lint),
+        }
+    }
+
+    /// The diagnostic as a single line JSON object, with its severity, code, lint, message, the
+    /// file, byte range, line and column of its span, its labels, and its help. Lines and
+    /// columns start at 1. Diagnostics which aren't about a particular part of the program have
+    /// `null` for the fields of their span, and no labels.
+    pub fn to_json(
+        &self,
+        sources: &IndexMap<SourceId, (&'static str, &'static str)>,
+    ) -> String {
+        let severity = match self.severity {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+            Severity::Note => "note",
+        };
+        let code = self.diagnostic.code().map(|code| code.to_string());
+        let help = self.help();
+        let (file, location, labels) = match self.span {
+            Some(span) => {
+                let (file, source) = sources.get(span.source());
+                let labels = self
+                    .diagnostic
+                    .labels()
+                    .into_iter()
+                    .flatten()
+                    .map(|label| {
+                        let location = json_location(Span::new(span.source(), *label.inner()), source);
+                        format!(r#"{{{location},"message":{}}}"#, json_optional_string(label.label()))
+                    })
+                    .collect::<Vec<_>>();
+                (json_string(file), json_location(span, source), labels)
+            },
+            None => (
+                "null".to_string(),
+                r#""start":null,"end":null,"line":null,"column":null"#.to_string(),
+                vec![],
+            ),
+        };
+        format!(
+            r#"{{"severity":"{severity}","code":{},"lint":{},"message":{},"file":{file},{location},"labels":[{}],"help":{}}}"#,
+            json_optional_string(code.as_deref()),
+            json_optional_string(self.lint),
+            json_string(&self.diagnostic.to_string()),
+            labels.join(","),
+            json_optional_string(help.as_deref())
+        )
+    }
+}
+
+/// The byte range of `span` and the line and column it starts at in `source`, as the fields of
+/// a JSON object
+fn json_location(
+    span: Span,
+    source: &str,
+) -> String {
+    let (line, column) = span.line_and_column(source);
+    let start = span.span().offset();
+    format!(r#""start":{start},"end":{},"line":{line},"column":{column}"#, start + span.span().len())
+}
+
+#[derive(Default, Debug)]
+pub struct Diagnostics {
+    lint_levels:   BTreeMap<String, LintLevel>,
+    deny_warnings: bool,
+    reported:      Vec<ReportedDiagnostic>,
+    /// How many errors have been reported, including those which were already taken
+    error_count:   usize,
+}
+
+impl Diagnostics {
+    /// A sink which reports the warnings of the lints in `lint_levels` at their level, and the
+    /// warnings of every other lint as warnings. If `deny_warnings` is set, warnings which would
+    /// be reported as warnings are reported as errors instead.
+    pub fn new(
+        lint_levels: BTreeMap<String, LintLevel>,
+        deny_warnings: bool,
+    ) -> Self {
+        Self {
+            lint_levels,
+            deny_warnings,
+            ..Default::default()
+        }
+    }
+
+    /// Reports an error. Errors which are advice, like those which point out a place in another
+    /// file than the error before them, are reported as notes.
+    pub fn error<T>(
+        &mut self,
+        error: SpannedItem<T>,
+    ) where
+        T: Diagnostic + Send + Sync + 'static,
+    {
+        let severity = match error.item().severity() {
+            Some(miette::Severity::Advice) => Severity::Note,
+            _ => Severity::Error,
+        };
+        self.push(severity, None, Some(error.span()), Box::new(error));
+    }
+
+    /// Reports an error which isn't about any particular part of the program, like a failure to
+    /// read the project
+    pub fn unspanned_error<T>(
+        &mut self,
+        error: T,
+    ) where
+        T: Diagnostic + Send + Sync + 'static,
+    {
+        self.push(Severity::Error, None, None, Box::new(error));
+    }
+
+    pub fn errors<T>(
+        &mut self,
+        errors: impl IntoIterator<Item = SpannedItem<T>>,
+    ) where
+        T: Diagnostic + Send + Sync + 'static,
+    {
+        for error in errors {
+            self.error(error);
+        }
+    }
+
+    /// Reports a warning of the lint `lint`, at the level that lint is configured to
+    pub fn warning<T>(
+        &mut self,
+        lint: &'static str,
+        warning: SpannedItem<T>,
+    ) where
+        T: Diagnostic + Send + Sync + 'static,
+    {
+        self.push_lint(lint, Some(warning.span()), Box::new(warning));
+    }
+
+    /// Reports a warning of the lint `lint` which isn't about any particular part of the program,
+    /// like a program without an entry point
+    pub fn unspanned_warning<T>(
+        &mut self,
+        lint: &'static str,
+        warning: T,
+    ) where
+        T: Diagnostic + Send + Sync + 'static,
+    {
+        self.push_lint(lint, None, Box::new(warning));
+    }
+
+    pub fn note<T>(
+        &mut self,
+        note: SpannedItem<T>,
+    ) where
+        T: Diagnostic + Send + Sync + 'static,
+    {
+        self.push(Severity::Note, None, Some(note.span()), Box::new(note));
+    }
+
+    fn push_lint(
+        &mut self,
+        lint: &'static str,
+        span: Option<Span>,
+        diagnostic: Box<dyn Diagnostic + Send + Sync>,
+    ) {
+        let severity = match self.lint_levels.get(lint).copied().unwrap_or_default() {
+            LintLevel::Allow => return,
+            LintLevel::Warn if !self.deny_warnings => Severity::Warning,
+            LintLevel::Warn | LintLevel::Deny => Severity::Error,
+        };
+        self.push(severity, Some(lint), span, diagnostic);
+    }
+
+    fn push(
+        &mut self,
+        severity: Severity,
+        lint: Option<&'static str>,
+        span: Option<Span>,
+        diagnostic: Box<dyn Diagnostic + Send + Sync>,
+    ) {
+        if severity == Severity::Error {
+            self.error_count += 1;
+        }
+        self.reported.push(ReportedDiagnostic {
+            severity,
+            lint,
+            span,
+            diagnostic,
+        });
+    }
+
+    /// Whether any errors have been reported, including denied warnings
+    pub fn has_errors(&self) -> bool {
+        self.error_count > 0
+    }
+
+    /// The diagnostics which have been reported since they were last taken, in the order they
+    /// were reported
+    pub fn reported(&self) -> &[ReportedDiagnostic] {
+        &self.reported
+    }
+
+    /// Takes the diagnostics which have been reported so far, so that later stages only add new
+    /// ones. Whether errors were reported is remembered.
+    pub fn take(&mut self) -> Vec<ReportedDiagnostic> {
+        std::mem::take(&mut self.reported)
+    }
+
+    /// Takes the diagnostics which have been reported so far, see [`Diagnostics::take`], and
+    /// renders them with the sources they point into
+    pub fn take_reports(
+        &mut self,
+        sources: &IndexMap<SourceId, (&'static str, &'static str)>,
+    ) -> Vec<Report> {
+        self.take()
+            .into_iter()
+            .map(|reported| {
+                let source = reported.span.map(|span| {
+                    let (name, source) = sources.get(span.source());
+                    miette::NamedSource::new(*name, *source)
+                });
+                Report::new(Rendered { source, reported })
+            })
+            .collect()
+    }
+}
+
+/// A reported diagnostic with the source it points into, rendered at the severity it was
+/// reported with
+#[derive(Debug)]
+struct Rendered {
+    source:   Option<miette::NamedSource>,
+    reported: ReportedDiagnostic,
+}
+
+impl std::fmt::Display for Rendered {
+    fn fmt(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+    ) -> std::fmt::Result {
+        write!(f, "{}", self.reported.diagnostic)
+    }
+}
+
+impl std::error::Error for Rendered {}
+
+impl Diagnostic for Rendered {
+    fn code<'a>(&'a self) -> Option<Box<dyn std::fmt::Display + 'a>> {
+        self.reported.diagnostic.code()
+    }
+
+    fn severity(&self) -> Option<miette::Severity> {
+        Some(match self.reported.severity {
+            Severity::Error => miette::Severity::Error,
+            Severity::Warning => miette::Severity::Warning,
+            Severity::Note => miette::Severity::Advice,
+        })
+    }
+
+    fn help<'a>(&'a self) -> Option<Box<dyn std::fmt::Display + 'a>> {
+        self.reported.help().map(|help| Box::new(help) as Box<dyn std::fmt::Display>)
+    }
+
+    fn url<'a>(&'a self) -> Option<Box<dyn std::fmt::Display + 'a>> {
+        self.reported.diagnostic.url()
+    }
+
+    fn source_code(&self) -> Option<&dyn miette::SourceCode> {
+        self.source.as_ref().map(|source| source as &dyn miette::SourceCode)
+    }
+
+    fn labels(&self) -> Option<Box<dyn Iterator<Item = LabeledSpan> + '_>> {
+        self.reported.diagnostic.labels()
+    }
+
+    fn related<'a>(&'a self) -> Option<Box<dyn Iterator<Item = &'a dyn Diagnostic> + 'a>> {
+        self.reported.diagnostic.related()
+    }
+
+    fn diagnostic_source(&self) -> Option<&dyn Diagnostic> {
+        self.reported.diagnostic.diagnostic_source()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug)]
+    struct Suspicious;
+
+    impl std::fmt::Display for Suspicious {
+        fn fmt(
+            &self,
+            f: &mut std::fmt::Formatter<'_>,
+        ) -> std::fmt::Result {
+            write!(f, "something suspicious")
+        }
+    }
+
+    impl std::error::Error for Suspicious {}
+
+    impl Diagnostic for Suspicious {}
+
+    fn severities(diagnostics: &Diagnostics) -> Vec<Severity> {
+        diagnostics.reported().iter().map(ReportedDiagnostic::severity).collect()
+    }
+
+    #[test]
+    fn warnings_follow_their_lint_level() {
+        let lint_levels = BTreeMap::from([("allowed".to_string(), LintLevel::Allow), ("denied".to_string(), LintLevel::Deny)]);
+        let mut diagnostics = Diagnostics::new(lint_levels, false);
+        diagnostics.unspanned_warning("allowed", Suspicious);
+        diagnostics.unspanned_warning("unconfigured", Suspicious);
+        assert!(!diagnostics.has_errors());
+        diagnostics.unspanned_warning("denied", Suspicious);
+        assert_eq!(severities(&diagnostics), vec![Severity::Warning, Severity::Error]);
+        assert!(diagnostics.has_errors());
+    }
+
+    #[test]
+    fn deny_warnings_only_denies_warned_lints() {
+        let lint_levels = BTreeMap::from([("allowed".to_string(), LintLevel::Allow)]);
+        let mut diagnostics = Diagnostics::new(lint_levels, true);
+        diagnostics.unspanned_warning("allowed", Suspicious);
+        assert!(!diagnostics.has_errors());
+        diagnostics.unspanned_warning("unconfigured", Suspicious);
+        assert_eq!(severities(&diagnostics), vec![Severity::Error]);
+    }
+
+    #[test]
+    fn taking_diagnostics_remembers_errors() {
+        let mut diagnostics = Diagnostics::new(Default::default(), true);
+        diagnostics.unspanned_warning("unconfigured", Suspicious);
+        assert_eq!(diagnostics.take().len(), 1);
+        assert!(diagnostics.reported().is_empty());
+        assert!(diagnostics.has_errors());
+    }
+
+    #[test]
+    fn diagnostics_as_json() {
+        let mut sources = IndexMap::default();
+        let source: SourceId = sources.insert(("src/main.pt", "fn main() returns 'int\n  \"oops\""));
+        let mut diagnostics = Diagnostics::default();
+        diagnostics.error(Span::new(source, (25, 6).into()).with_item(Suspicious));
+        diagnostics.unspanned_warning("unconfigured", Suspicious);
+        diagnostics.unspanned_error(Suspicious);
+        let json = diagnostics.take().iter().map(|reported| reported.to_json(&sources)).collect::<Vec<_>>();
+        assert_eq!(
+            json,
+            vec![
+                r#"{"severity":"error","code":null,"lint":null,"message":"something suspicious","file":"src/main.pt","start":25,"end":31,"line":2,"column":3,"labels":[{"start":25,"end":31,"line":2,"column":3,"message":"something suspicious"}],"help":null}"#,
+                r#"{"severity":"warning","code":null,"lint":"unconfigured","message":"something suspicious","file":null,"start":null,"end":null,"line":null,"column":null,"labels":[],"help":"`unconfigured` can be allowed or denied in the [lints] table of pete.toml"}"#,
+                r#"{"severity":"error","code":null,"lint":null,"message":"something suspicious","file":null,"start":null,"end":null,"line":null,"column":null,"labels":[],"help":null}"#,
+            ]
+        );
+    }
+
+    #[test]
+    fn lint_warnings_have_the_same_help_in_json() {
+        let sources = IndexMap::default();
+        let mut diagnostics = Diagnostics::new(BTreeMap::from([("denied".to_string(), LintLevel::Deny)]), false);
+        diagnostics.unspanned_warning("denied", Suspicious);
+        let json = diagnostics.reported()[0].to_json(&sources);
+        let reports = diagnostics.take_reports(&sources);
+        let help = reports[0].help().map(|help| help.to_string());
+        assert_eq!(
+            help.as_deref(),
+            Some("`denied` is denied, it can be allowed in the [lints] table of pete.toml")
+        );
+        assert!(json.ends_with(r#""help":"`denied` is denied, it can be allowed in the [lints] table of pete.toml"}"#));
+    }
+}
@@ -3,12 +3,16 @@
 //! For production uses, these IDs are turned into usizes.
 
 pub use common_types::*;
+pub use diagnostics::{Diagnostics, LintLevel, ReportedDiagnostic, Severity};
 pub use index_map::IndexMap;
+pub use json::{json_optional_string, json_string};
 pub use pretty_print::PrettyPrint;
 pub use sources::{error_printing::render as render_error, SourceId, Span, SpannedItem};
 
 mod common_types;
+mod diagnostics;
 mod index_map;
+mod json;
 mod sources;
 mod pretty_print {
     //! Trait for pretty-printing compiler internals
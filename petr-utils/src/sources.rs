@@ -84,11 +84,15 @@ impl<T: Diagnostic + std::error::Error> Diagnostic for SpannedItem<T> {
     fn labels(&self) -> Option<Box<dyn Iterator<Item = miette::LabeledSpan> + '_>> {
         // get any inner labels
         let item = self.item();
-        let labels = item.labels().unwrap_or_else(|| Box::new(std::iter::empty()));
+        let labels = item.labels().map(|labels| labels.collect::<Vec<_>>()).unwrap_or_default();
         let span = self.span().span();
+        // an inner label for the item's own span says more about it than the error message does
+        if labels.iter().any(|label| *label.inner() == span) {
+            return Some(Box::new(labels.into_iter()));
+        }
         let label = self.item().to_string();
         let labeled_span = LabeledSpan::new_with_span(Some(label), span);
-        Some(Box::new(labels.chain(std::iter::once(labeled_span))))
+        Some(Box::new(labels.into_iter().chain(std::iter::once(labeled_span))))
     }
 
     fn related<'a>(&'a self) -> Option<Box<dyn Iterator<Item = &'a dyn Diagnostic> + 'a>> {
@@ -234,6 +238,18 @@ impl Span {
         self.source
     }
 
+    /// The line and column that this span starts at in `source`, the text of its file. Both
+    /// start at 1, and columns are counted in characters.
+    pub fn line_and_column(
+        &self,
+        source: &str,
+    ) -> (usize, usize) {
+        let before = &source[..self.span.offset().min(source.len())];
+        let line = before.matches('\n').count() + 1;
+        let line_start = before.rfind('\n').map(|ix| ix + 1).unwrap_or(0);
+        (line, before[line_start..].chars().count() + 1)
+    }
+
     pub fn zero_length(&self) -> Self {
         Span {
             source: self.source,
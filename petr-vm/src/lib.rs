@@ -6,8 +6,8 @@
 
 use std::collections::BTreeMap;
 
-use petr_ir::{DataLabel, DataSectionEntry, Intrinsic, IrOpcode, LabelId, Reg, ReservedRegister};
-use petr_utils::{idx_map_key, IndexMap};
+use petr_ir::{DataLabel, DataSectionEntry, Intrinsic, IrOpcode, IrTy, LabelId, MonomorphizedFunctionId, Reg, ReservedRegister};
+use petr_utils::{idx_map_key, IndexMap, IntegerType};
 use thiserror::Error;
 
 #[cfg(test)]
@@ -46,6 +46,32 @@ impl Value {
     pub fn inner(&self) -> u64 {
         self.0
     }
+
+    /// `'float`s are stored as the bits of a 64-bit float
+    pub fn from_float(value: f64) -> Self {
+        Value(value.to_bits())
+    }
+
+    /// Reads this value as a `'float`
+    pub fn as_float(&self) -> f64 {
+        f64::from_bits(self.0)
+    }
+
+    /// Renders this value as a value of type `ty`. Values which are pointers, like strings and
+    /// user-defined types, are rendered as the address they point to.
+    pub fn render(
+        &self,
+        ty: &IrTy,
+    ) -> String {
+        match ty {
+            // `{:?}` keeps the decimal point of whole floats
+            IrTy::Float64 => format!("{:?}", self.as_float()),
+            IrTy::Int64 => (self.0 as i64).to_string(),
+            IrTy::SizedInt(ty) if ty.is_signed() => (self.0 as i64).to_string(),
+            IrTy::Boolean => (self.0 != 0).to_string(),
+            _ => self.0.to_string(),
+        }
+    }
 }
 
 #[derive(Debug, Error)]
@@ -62,8 +88,14 @@ pub enum VmError {
     PoppedEmptyCallStack(IrOpcode),
     #[error("Attempted to write to memory at index {0} but memory only has length {1}")]
     OutOfBoundsMemoryWrite(usize, usize),
+    #[error("Attempted to read from memory at index {0} but memory only has length {1}")]
+    OutOfBoundsMemoryRead(usize, usize),
     #[error("Label not found when executing opcode {0}")]
     LabelNotFound(IrOpcode),
+    #[error("Integer overflow when executing opcode {0}")]
+    IntegerOverflow(IrOpcode),
+    #[error("Division by zero when executing opcode {0}")]
+    DivisionByZero(IrOpcode),
 }
 
 type Result<T> = std::result::Result<T, VmError>;
@@ -122,38 +154,67 @@ impl Vm {
         self.state.program_counter = (self.state.program_counter.0 + 1).into();
         match opcode {
             IrOpcode::JumpImmediateFunction(label) => {
-                let Some(offset) = self
-                    .instructions
-                    .iter()
-                    .find_map(|(position, op)| if *op == IrOpcode::FunctionLabel(label) { Some(position) } else { None })
-                else {
-                    return Err(VmError::FunctionLabelNotFound(opcode));
-                };
-                self.state.program_counter = offset;
+                self.jump_to_function(label, &opcode)?;
                 Ok(Continue)
             },
-            IrOpcode::Add(dest, lhs, rhs) => {
-                let lhs = self.get_register(lhs)?;
-                let rhs = self.get_register(rhs)?;
-                self.set_register(dest, Value(lhs.0.wrapping_add(rhs.0)));
+            IrOpcode::JumpFunction(function) => {
+                let label = (self.get_register(function)?.0 as usize).into();
+                self.jump_to_function(label, &opcode)?;
                 Ok(Continue)
             },
-            IrOpcode::Multiply(dest, lhs, rhs) => {
-                let lhs = self.get_register(lhs)?;
-                let rhs = self.get_register(rhs)?;
-                self.set_register(dest, Value(lhs.0.wrapping_mul(rhs.0)));
+            IrOpcode::LoadFunction(dest, function) => {
+                self.set_register(dest, Value(Into::<usize>::into(function) as u64));
                 Ok(Continue)
             },
-            IrOpcode::Subtract(dest, lhs, rhs) => {
-                let lhs = self.get_register(lhs)?;
-                let rhs = self.get_register(rhs)?;
-                self.set_register(dest, Value(lhs.0.wrapping_sub(rhs.0)));
+            IrOpcode::Add(dest, lhs, rhs) => self.arithmetic(IntegerType::I64, dest, lhs, rhs, |lhs, rhs| Some(lhs + rhs), &opcode),
+            IrOpcode::Multiply(dest, lhs, rhs) => self.arithmetic(IntegerType::I64, dest, lhs, rhs, |lhs, rhs| Some(lhs * rhs), &opcode),
+            IrOpcode::Subtract(dest, lhs, rhs) => self.arithmetic(IntegerType::I64, dest, lhs, rhs, |lhs, rhs| Some(lhs - rhs), &opcode),
+            IrOpcode::Divide(dest, lhs, rhs) => self.arithmetic(IntegerType::I64, dest, lhs, rhs, i128::checked_div, &opcode),
+            IrOpcode::SizedAdd(ty, dest, lhs, rhs) => self.arithmetic(ty, dest, lhs, rhs, |lhs, rhs| Some(lhs + rhs), &opcode),
+            IrOpcode::SizedMultiply(ty, dest, lhs, rhs) => self.arithmetic(ty, dest, lhs, rhs, |lhs, rhs| Some(lhs * rhs), &opcode),
+            IrOpcode::SizedSubtract(ty, dest, lhs, rhs) => self.arithmetic(ty, dest, lhs, rhs, |lhs, rhs| Some(lhs - rhs), &opcode),
+            IrOpcode::SizedDivide(ty, dest, lhs, rhs) => self.arithmetic(ty, dest, lhs, rhs, i128::checked_div, &opcode),
+            IrOpcode::SizedLessThan(ty, dest, lhs, rhs) => self.compare_integers(ty, dest, lhs, rhs, |lhs, rhs| lhs < rhs),
+            IrOpcode::SizedGreaterThan(ty, dest, lhs, rhs) => self.compare_integers(ty, dest, lhs, rhs, |lhs, rhs| lhs > rhs),
+            IrOpcode::SizedLessThanOrEqual(ty, dest, lhs, rhs) => self.compare_integers(ty, dest, lhs, rhs, |lhs, rhs| lhs <= rhs),
+            IrOpcode::SizedGreaterThanOrEqual(ty, dest, lhs, rhs) => self.compare_integers(ty, dest, lhs, rhs, |lhs, rhs| lhs >= rhs),
+            IrOpcode::Convert(from, to, dest, src) => {
+                let value = self.get_integer(from, src)?;
+                let value = self.fit_integer(to, value, &opcode)?;
+                self.set_register(dest, value);
                 Ok(Continue)
             },
-            IrOpcode::Divide(dest, lhs, rhs) => {
-                let lhs = self.get_register(lhs)?;
-                let rhs = self.get_register(rhs)?;
-                self.set_register(dest, Value(lhs.0 / rhs.0));
+            IrOpcode::FloatAdd(dest, lhs, rhs) => self.float_arithmetic(dest, lhs, rhs, |lhs, rhs| lhs + rhs),
+            IrOpcode::FloatSubtract(dest, lhs, rhs) => self.float_arithmetic(dest, lhs, rhs, |lhs, rhs| lhs - rhs),
+            IrOpcode::FloatMultiply(dest, lhs, rhs) => self.float_arithmetic(dest, lhs, rhs, |lhs, rhs| lhs * rhs),
+            IrOpcode::FloatDivide(dest, lhs, rhs) => self.float_arithmetic(dest, lhs, rhs, |lhs, rhs| lhs / rhs),
+            IrOpcode::FloatEqual(dest, lhs, rhs) => self.compare_floats(dest, lhs, rhs, |lhs, rhs| lhs == rhs),
+            IrOpcode::FloatNotEqual(dest, lhs, rhs) => self.compare_floats(dest, lhs, rhs, |lhs, rhs| lhs != rhs),
+            IrOpcode::FloatLessThan(dest, lhs, rhs) => self.compare_floats(dest, lhs, rhs, |lhs, rhs| lhs < rhs),
+            IrOpcode::FloatGreaterThan(dest, lhs, rhs) => self.compare_floats(dest, lhs, rhs, |lhs, rhs| lhs > rhs),
+            IrOpcode::FloatLessThanOrEqual(dest, lhs, rhs) => self.compare_floats(dest, lhs, rhs, |lhs, rhs| lhs <= rhs),
+            IrOpcode::FloatGreaterThanOrEqual(dest, lhs, rhs) => self.compare_floats(dest, lhs, rhs, |lhs, rhs| lhs >= rhs),
+            IrOpcode::FloatSqrt(dest, src) => {
+                let value = self.get_register(src)?.as_float();
+                self.set_register(dest, Value::from_float(value.sqrt()));
+                Ok(Continue)
+            },
+            IrOpcode::FloatFloor(dest, src) => {
+                let value = self.get_register(src)?.as_float();
+                self.set_register(dest, Value::from_float(value.floor()));
+                Ok(Continue)
+            },
+            IrOpcode::IntToFloat(from, dest, src) => {
+                let value = self.get_integer(from, src)?;
+                self.set_register(dest, Value::from_float(value as f64));
+                Ok(Continue)
+            },
+            IrOpcode::FloatToInt(to, dest, src) => {
+                // `as` rounds towards zero, and saturates at the bounds of an `i128`, which no
+                // integer type fits anyway
+                let value = self.get_register(src)?.as_float() as i128;
+                let value = self.fit_integer(to, value, &opcode)?;
+                self.set_register(dest, value);
                 Ok(Continue)
             },
             IrOpcode::LoadData(dest, data_label) => {
@@ -253,6 +314,15 @@ impl Vm {
                 self.state.memory[dest_ptr] = val;
                 Ok(Continue)
             },
+            IrOpcode::ReadMemoryToRegister(reg, src_ptr) => {
+                let src_ptr = self.get_register(src_ptr)?.0 as usize;
+
+                let Some(val) = self.state.memory.get(src_ptr) else {
+                    return Err(VmError::OutOfBoundsMemoryRead(src_ptr, self.state.memory.len()));
+                };
+                self.set_register(reg, Value(*val));
+                Ok(Continue)
+            },
             IrOpcode::Comment(_) => Ok(Continue),
             IrOpcode::JumpIfFalseImmediate(condition, dest) => {
                 let condition = self.get_register(condition)?;
@@ -271,9 +341,150 @@ impl Vm {
                 self.set_register(dest, Value(if lhs.0 == rhs.0 { 1 } else { 0 }));
                 Ok(Continue)
             },
+            IrOpcode::StringEqual(dest, lhs, rhs) => {
+                let lhs = self.read_string(lhs)?;
+                let rhs = self.read_string(rhs)?;
+                self.set_register(dest, Value(if lhs == rhs { 1 } else { 0 }));
+                Ok(Continue)
+            },
+            IrOpcode::NotEqual(dest, lhs, rhs) => self.compare(dest, lhs, rhs, |lhs, rhs| lhs != rhs),
+            IrOpcode::LessThan(dest, lhs, rhs) => self.compare(dest, lhs, rhs, |lhs, rhs| lhs < rhs),
+            IrOpcode::GreaterThan(dest, lhs, rhs) => self.compare(dest, lhs, rhs, |lhs, rhs| lhs > rhs),
+            IrOpcode::LessThanOrEqual(dest, lhs, rhs) => self.compare(dest, lhs, rhs, |lhs, rhs| lhs <= rhs),
+            IrOpcode::GreaterThanOrEqual(dest, lhs, rhs) => self.compare(dest, lhs, rhs, |lhs, rhs| lhs >= rhs),
+            IrOpcode::Not(dest, src) => {
+                let val = self.get_register(src)?;
+                self.set_register(dest, Value(if val.0 == 0 { 1 } else { 0 }));
+                Ok(Continue)
+            },
         }
     }
 
+    /// Writes `1` to `dest` if `cmp` holds for the signed values of `lhs` and `rhs`, and `0` otherwise
+    fn compare(
+        &mut self,
+        dest: Reg,
+        lhs: Reg,
+        rhs: Reg,
+        cmp: fn(i64, i64) -> bool,
+    ) -> Result<VmControlFlow> {
+        let lhs = self.get_register(lhs)?.0 as i64;
+        let rhs = self.get_register(rhs)?.0 as i64;
+        self.set_register(dest, Value(if cmp(lhs, rhs) { 1 } else { 0 }));
+        Ok(VmControlFlow::Continue)
+    }
+
+    /// Writes the result of `op` on the integers of type `ty` in `lhs` and `rhs` to `dest`. `op`
+    /// returns `None` if it divides by zero.
+    fn arithmetic(
+        &mut self,
+        ty: IntegerType,
+        dest: Reg,
+        lhs: Reg,
+        rhs: Reg,
+        op: fn(i128, i128) -> Option<i128>,
+        opcode: &IrOpcode,
+    ) -> Result<VmControlFlow> {
+        let lhs = self.get_integer(ty, lhs)?;
+        let rhs = self.get_integer(ty, rhs)?;
+        let result = op(lhs, rhs).ok_or_else(|| VmError::DivisionByZero(opcode.clone()))?;
+        let result = self.fit_integer(ty, result, opcode)?;
+        self.set_register(dest, result);
+        Ok(VmControlFlow::Continue)
+    }
+
+    /// Writes `1` to `dest` if `cmp` holds for the integers of type `ty` in `lhs` and `rhs`, and
+    /// `0` otherwise
+    fn compare_integers(
+        &mut self,
+        ty: IntegerType,
+        dest: Reg,
+        lhs: Reg,
+        rhs: Reg,
+        cmp: fn(i128, i128) -> bool,
+    ) -> Result<VmControlFlow> {
+        let lhs = self.get_integer(ty, lhs)?;
+        let rhs = self.get_integer(ty, rhs)?;
+        self.set_register(dest, Value(if cmp(lhs, rhs) { 1 } else { 0 }));
+        Ok(VmControlFlow::Continue)
+    }
+
+    /// Writes the result of `op` on the floats in `lhs` and `rhs` to `dest`
+    fn float_arithmetic(
+        &mut self,
+        dest: Reg,
+        lhs: Reg,
+        rhs: Reg,
+        op: fn(f64, f64) -> f64,
+    ) -> Result<VmControlFlow> {
+        let lhs = self.get_register(lhs)?.as_float();
+        let rhs = self.get_register(rhs)?.as_float();
+        self.set_register(dest, Value::from_float(op(lhs, rhs)));
+        Ok(VmControlFlow::Continue)
+    }
+
+    /// Writes `1` to `dest` if `cmp` holds for the floats in `lhs` and `rhs`, and `0` otherwise
+    fn compare_floats(
+        &mut self,
+        dest: Reg,
+        lhs: Reg,
+        rhs: Reg,
+        cmp: fn(f64, f64) -> bool,
+    ) -> Result<VmControlFlow> {
+        let lhs = self.get_register(lhs)?.as_float();
+        let rhs = self.get_register(rhs)?.as_float();
+        self.set_register(dest, Value(if cmp(lhs, rhs) { 1 } else { 0 }));
+        Ok(VmControlFlow::Continue)
+    }
+
+    /// Reads the integer of type `ty` in `reg`. It is wide enough to hold the result of any
+    /// arithmetic on two of them.
+    fn get_integer(
+        &self,
+        ty: IntegerType,
+        reg: Reg,
+    ) -> Result<i128> {
+        let value = self.get_register(reg)?.0;
+        Ok(if ty.is_signed() { value as i64 as i128 } else { value as i128 })
+    }
+
+    /// Stores `value` as an integer of type `ty`. If it doesn't fit, it wraps around, or is an
+    /// error with the `checked_arithmetic` feature.
+    fn fit_integer(
+        &self,
+        ty: IntegerType,
+        value: i128,
+        opcode: &IrOpcode,
+    ) -> Result<Value> {
+        if cfg!(feature = "checked_arithmetic") && !ty.contains(value) {
+            return Err(VmError::IntegerOverflow(opcode.clone()));
+        }
+        // keep the low bits, then sign-extend or zero-extend them back to 64 bits
+        let unused_bits = 64 - ty.bits();
+        let value = (value as u64) << unused_bits;
+        Ok(Value(if ty.is_signed() {
+            ((value as i64) >> unused_bits) as u64
+        } else {
+            value >> unused_bits
+        }))
+    }
+
+    fn jump_to_function(
+        &mut self,
+        label: MonomorphizedFunctionId,
+        opcode: &IrOpcode,
+    ) -> Result<()> {
+        let Some(offset) = self
+            .instructions
+            .iter()
+            .find_map(|(position, op)| if *op == IrOpcode::FunctionLabel(label) { Some(position) } else { None })
+        else {
+            return Err(VmError::FunctionLabelNotFound(opcode.clone()));
+        };
+        self.state.program_counter = offset;
+        Ok(())
+    }
+
     fn jump_to_label(
         &mut self,
         label: LabelId,
@@ -304,6 +515,20 @@ impl Vm {
         self.state.registers.insert(dest, val);
     }
 
+    /// The words of the string that `reg` points to, after the word which holds its length
+    fn read_string(
+        &self,
+        reg: Reg,
+    ) -> Result<&[u64]> {
+        let ptr = self.get_register(reg)?.0 as usize;
+        let memory = &self.state.memory;
+        let Some(len) = memory.get(ptr) else {
+            return Err(VmError::OutOfBoundsMemoryRead(ptr, memory.len()));
+        };
+        let end = ptr + 1 + *len as usize;
+        memory.get(ptr + 1..end).ok_or(VmError::OutOfBoundsMemoryRead(end - 1, memory.len()))
+    }
+
     // TODO things larger than a register
     fn data_section_to_val(
         &mut self,
@@ -311,6 +536,7 @@ impl Vm {
     ) -> Value {
         match data {
             DataSectionEntry::Int64(x) => Value(*x as u64),
+            DataSectionEntry::Float64(x) => Value::from_float(*x),
             DataSectionEntry::String(val) => {
                 let str_as_bytes = val.as_bytes();
                 let bytes_compressed_as_u64s = str_as_bytes